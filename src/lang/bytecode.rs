@@ -0,0 +1,1065 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::lang::generator::Error;
+use crate::lang::interner::Symbol;
+use crate::lang::lexer::{Literal, Token, TokenType};
+use crate::lang::parser::{Expression, Statement};
+use crate::lang::resolver::Resolver;
+
+/// An instruction of a stack machine, operating on the values on top of the stack.
+///
+/// Every function has a frame on the stack, starting with its arguments, followed by its variables
+/// in the order they are declared, so a variable is known by its slot, its position within the frame.
+/// Addresses are positions in the chunk's code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    /// Pushes the constant with the given index in the chunk's constants.
+    Constant(usize),
+    /// Pushes the value of the variable in the given slot.
+    LoadLocal(usize),
+    /// Sets the variable in the given slot to the value on top of the stack, leaving the value there.
+    StoreLocal(usize),
+    /// Pops the right side, then the left side, pushing their sum.
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    /// Pops a number, pushing its negation.
+    Negate,
+    /// Pops a boolean, pushing its opposite.
+    Not,
+    /// Pops a value, pushing the name of its type.
+    TypeOf,
+    /// Pops the right side, then the left side, pushing whether they are equal.
+    Equal,
+    NotEqual,
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    /// Continues at the given address.
+    Jump(usize),
+    /// Pops a condition, continuing at the given address if it is false or none.
+    JumpIfFalse(usize),
+    /// Calls the function at the given address with the given number of arguments,
+    /// which are on top of the stack and become the start of the function's frame.
+    Call {
+        address: usize,
+        arguments: usize,
+    },
+    /// Pops the return value, drops the function's frame and pushes the value for the caller.
+    /// Returning from the program itself ends it.
+    Return,
+    /// Pops a value and prints it.
+    Print,
+    /// Pops a value, discarding it.
+    Pop,
+}
+
+impl Instruction {
+    /// Gets the name the disassembler writes the instruction with.
+    pub fn name(self) -> &'static str {
+        match self {
+            Instruction::Constant(_) => "CONSTANT",
+            Instruction::LoadLocal(_) => "LOAD_LOCAL",
+            Instruction::StoreLocal(_) => "STORE_LOCAL",
+            Instruction::Add => "ADD",
+            Instruction::Subtract => "SUBTRACT",
+            Instruction::Multiply => "MULTIPLY",
+            Instruction::Divide => "DIVIDE",
+            Instruction::Modulo => "MODULO",
+            Instruction::Negate => "NEGATE",
+            Instruction::Not => "NOT",
+            Instruction::TypeOf => "TYPEOF",
+            Instruction::Equal => "EQUAL",
+            Instruction::NotEqual => "NOT_EQUAL",
+            Instruction::Greater => "GREATER",
+            Instruction::GreaterEqual => "GREATER_EQUAL",
+            Instruction::Less => "LESS",
+            Instruction::LessEqual => "LESS_EQUAL",
+            Instruction::Jump(_) => "JUMP",
+            Instruction::JumpIfFalse(_) => "JUMP_IF_FALSE",
+            Instruction::Call { .. } => "CALL",
+            Instruction::Return => "RETURN",
+            Instruction::Print => "PRINT",
+            Instruction::Pop => "POP",
+        }
+    }
+}
+
+/// Compiled code, with the constants it refers to and the line each instruction was compiled from.
+#[derive(Debug, Default, PartialEq)]
+pub struct Chunk {
+    pub code: Vec<Instruction>,
+    /// The literals of the program, each only once.
+    pub constants: Vec<Literal>,
+    /// The source line of each instruction, for reporting errors that happen while running it.
+    pub lines: Vec<usize>,
+}
+
+impl Chunk {
+    /// Writes the chunk as text, one instruction per line, for debugging the compiler.
+    ///
+    /// Each line has the instruction's address, its source line, or `|` if it is the same as the previous
+    /// instruction's, its name and its operands.
+    /// Constants are followed by their values, and jumps and calls by the address they continue at.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the chunk, written above its instructions.
+    ///
+    /// # Examples
+    /// ```
+    /// use cpl::lang::bytecode::Compiler;
+    /// use cpl::lang::parse;
+    ///
+    /// let statements = parse("print 1 + 2;").unwrap();
+    /// let chunk = Compiler::new(&statements).compile().unwrap();
+    ///
+    /// assert!(chunk.disassemble("main").starts_with("== main ==\n0000    1 CONSTANT            0 '1'\n"));
+    /// ```
+    pub fn disassemble(&self, name: &str) -> String {
+        let mut text = format!("== {} ==\n", name);
+
+        for (address, (instruction, line)) in self.code.iter().zip(&self.lines).enumerate() {
+            write!(text, "{:04} ", address).expect("Writing to a string cannot fail.");
+            if address > 0 && self.lines[address - 1] == *line {
+                text.push_str("   | ");
+            } else {
+                write!(text, "{:4} ", line).expect("Writing to a string cannot fail.");
+            }
+
+            let name = instruction.name();
+            let written = match *instruction {
+                Instruction::Constant(index) => {
+                    writeln!(text, "{:<16} {:4} '{}'", name, index, self.constants[index])
+                }
+                Instruction::LoadLocal(slot) | Instruction::StoreLocal(slot) => {
+                    writeln!(text, "{:<16} {:4}", name, slot)
+                }
+                Instruction::Jump(target) | Instruction::JumpIfFalse(target) => {
+                    writeln!(text, "{:<16} -> {:04}", name, target)
+                }
+                Instruction::Call { address, arguments } => writeln!(
+                    text,
+                    "{:<16} -> {:04} ({} {})",
+                    name,
+                    address,
+                    arguments,
+                    if arguments == 1 {
+                        "argument"
+                    } else {
+                        "arguments"
+                    }
+                ),
+                _ => writeln!(text, "{}", name),
+            };
+            written.expect("Writing to a string cannot fail.");
+        }
+
+        text
+    }
+}
+
+/// What a name in scope stands for.
+#[derive(Debug, Clone, Copy)]
+enum Binding {
+    /// A variable in the given slot of its function's frame.
+    Slot(usize),
+    /// A function, with the index of its address.
+    Function(usize),
+}
+
+/// A name declared in a scope.
+#[derive(Debug)]
+struct Local {
+    name: Symbol,
+    binding: Binding,
+    /// How deeply nested the scope it is declared in is, counting from the program's top level.
+    depth: usize,
+    /// How deeply nested the function it is declared in is, the program itself being 0.
+    frame: usize,
+}
+
+/// The jumps of a loop, which `break` and `continue` leave it with.
+#[derive(Debug, Default)]
+struct Loop {
+    /// The depth of the scope enclosing the loop, whose variables are kept when leaving it.
+    depth: usize,
+    /// Where `continue` jumps to, if it is known already.
+    next: Option<usize>,
+    /// The jumps to patch with the address after the loop.
+    breaks: Vec<usize>,
+    /// The jumps to patch with the address of the next iteration, if it was not known when they were compiled.
+    continues: Vec<usize>,
+}
+
+/// Compiles a syntax tree to a chunk of bytecode for a stack machine.
+///
+/// Only variables, functions, blocks, if statements, loops, `print` and expression statements of literals,
+/// arithmetic, comparisons, `!`, `&&`, `||` and calls to declared functions are supported so far.
+/// The program's own code comes first and ends by returning, and each function is compiled where it is declared,
+/// behind a jump over it.
+#[derive(Debug)]
+pub struct Compiler<'a> {
+    statements: &'a [Statement],
+    chunk: Chunk,
+    /// The names in scope, innermost last.
+    locals: Vec<Local>,
+    depth: usize,
+    frame: usize,
+    /// The loops being compiled within the current function, innermost last.
+    loops: Vec<Loop>,
+    /// The ids of the declared functions, keyed by the byte offset of their names.
+    functions: HashMap<usize, usize>,
+    /// The address of each function, once it has been compiled.
+    addresses: Vec<Option<usize>>,
+    /// The calls to patch with the address of their function, once every function has been compiled.
+    calls: Vec<(usize, usize)>,
+}
+
+impl<'a> Compiler<'a> {
+    /// Creates a compiler for a program.
+    ///
+    /// # Arguments
+    /// * `statements` - The program to compile, which must already have been analyzed.
+    pub fn new(statements: &'a [Statement]) -> Self {
+        Self {
+            statements,
+            chunk: Chunk::default(),
+            locals: Vec::new(),
+            depth: 0,
+            frame: 0,
+            loops: Vec::new(),
+            functions: HashMap::new(),
+            addresses: Vec::new(),
+            calls: Vec::new(),
+        }
+    }
+
+    /// Compiles the program.
+    ///
+    /// # Returns
+    /// The chunk, or the first statement or expression that cannot be compiled.
+    ///
+    /// # Examples
+    /// ```
+    /// use cpl::lang::bytecode::{Compiler, Instruction};
+    /// use cpl::lang::parse;
+    ///
+    /// let statements = parse("print 1 + 2;").unwrap();
+    /// let chunk = Compiler::new(&statements).compile().unwrap();
+    ///
+    /// assert_eq!(chunk.code[..4], [
+    ///     Instruction::Constant(0),
+    ///     Instruction::Constant(1),
+    ///     Instruction::Add,
+    ///     Instruction::Print,
+    /// ]);
+    /// ```
+    pub fn compile(mut self) -> Result<Chunk, Error> {
+        Resolver::resolve(self.statements).map_err(|errors| {
+            let (line, column) = errors[0].position();
+
+            Error::Unresolved(errors[0].to_string(), line, column)
+        })?;
+
+        self.statements(self.statements)?;
+
+        let line = self.statements.last().map_or(1, |last| last.span().line);
+        self.return_none(line);
+
+        for (call, function) in std::mem::take(&mut self.calls) {
+            let Instruction::Call { address, .. } = &mut self.chunk.code[call] else {
+                unreachable!("Only calls are patched with the address of a function.");
+            };
+            *address = self.addresses[function].expect("Every declared function is compiled.");
+        }
+
+        Ok(self.chunk)
+    }
+
+    /// Compiles the statements of a scope.
+    /// The functions declared among them are declared first, so they can call each other in any order.
+    fn statements(&mut self, statements: &[Statement]) -> Result<(), Error> {
+        for statement in statements {
+            if let Statement::Function { name, .. } = statement {
+                let id = self.addresses.len();
+                self.addresses.push(None);
+                self.functions.insert(name.start, id);
+                self.declare(name, Binding::Function(id));
+            }
+        }
+
+        statements
+            .iter()
+            .try_for_each(|statement| self.statement(statement))
+    }
+
+    /// Compiles a statement.
+    fn statement(&mut self, statement: &Statement) -> Result<(), Error> {
+        let line = statement.span().line;
+
+        match statement {
+            Statement::Expression(expression, _) => {
+                self.expression(expression)?;
+                self.emit(Instruction::Pop, line);
+            }
+            Statement::Print(expression, _) => {
+                self.expression(expression)?;
+                self.emit(Instruction::Print, line);
+            }
+            Statement::Variable {
+                name, initializer, ..
+            } => {
+                // The variable's slot is the value of its initializer, left on the stack.
+                match initializer {
+                    Some(initializer) => self.expression(initializer)?,
+                    None => self.constant(Literal::None, line),
+                }
+
+                let slot = self.slots();
+                self.declare(name, Binding::Slot(slot));
+            }
+            Statement::Block(statements, _) => {
+                self.begin_scope();
+                self.statements(statements)?;
+                self.end_scope(line);
+            }
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.expression(condition)?;
+                let otherwise = self.emit(Instruction::JumpIfFalse(0), line);
+                self.statement(then_branch)?;
+
+                match else_branch {
+                    Some(else_branch) => {
+                        let end = self.emit(Instruction::Jump(0), line);
+                        self.patch(otherwise);
+                        self.statement(else_branch)?;
+                        self.patch(end);
+                    }
+                    None => self.patch(otherwise),
+                }
+            }
+            Statement::While {
+                condition, body, ..
+            } => {
+                let start = self.chunk.code.len();
+                self.expression(condition)?;
+                let exit = self.emit(Instruction::JumpIfFalse(0), line);
+
+                self.body(body, Some(start))?;
+                self.emit(Instruction::Jump(start), line);
+                self.patch(exit);
+                self.end_loop();
+            }
+            Statement::Loop { body, .. } => {
+                let start = self.chunk.code.len();
+
+                self.body(body, Some(start))?;
+                self.emit(Instruction::Jump(start), line);
+                self.end_loop();
+            }
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            } => {
+                // The initializer's variable is only visible within the loop.
+                self.begin_scope();
+                if let Some(initializer) = initializer {
+                    self.statement(initializer)?;
+                }
+
+                let start = self.chunk.code.len();
+                let exit = match condition {
+                    Some(condition) => {
+                        self.expression(condition)?;
+                        Some(self.emit(Instruction::JumpIfFalse(0), line))
+                    }
+                    None => None,
+                };
+
+                self.body(body, None)?;
+
+                // 'continue' runs the increment before the next iteration.
+                for jump in std::mem::take(&mut self.current_loop().continues) {
+                    self.patch(jump);
+                }
+                if let Some(increment) = increment {
+                    self.expression(increment)?;
+                    self.emit(Instruction::Pop, line);
+                }
+                self.emit(Instruction::Jump(start), line);
+
+                if let Some(exit) = exit {
+                    self.patch(exit);
+                }
+                self.end_loop();
+                self.end_scope(line);
+            }
+            Statement::Function {
+                name,
+                parameters,
+                body,
+                ..
+            } => self.function(name, parameters, body)?,
+            Statement::Return { value, .. } => {
+                match value {
+                    Some(value) => self.expression(value)?,
+                    None => self.constant(Literal::None, line),
+                }
+                self.emit(Instruction::Return, line);
+            }
+            Statement::Break { keyword, .. } | Statement::Continue { keyword, .. } => {
+                let Some(depth) = self.loops.last().map(|current| current.depth) else {
+                    return Err(Error::UnsupportedStatement(keyword.line, keyword.column));
+                };
+
+                // The variables declared within the loop are dropped when leaving an iteration.
+                for _ in 0..self.slots_deeper_than(depth) {
+                    self.emit(Instruction::Pop, line);
+                }
+
+                let next = self.current_loop().next;
+                match (statement, next) {
+                    (Statement::Continue { .. }, Some(next)) => {
+                        self.emit(Instruction::Jump(next), line);
+                    }
+                    (Statement::Continue { .. }, None) => {
+                        let jump = self.emit(Instruction::Jump(0), line);
+                        self.current_loop().continues.push(jump);
+                    }
+                    _ => {
+                        let jump = self.emit(Instruction::Jump(0), line);
+                        self.current_loop().breaks.push(jump);
+                    }
+                }
+            }
+            Statement::Comment { .. } => {}
+            statement => {
+                let span = statement.span();
+
+                return Err(Error::UnsupportedStatement(span.line, span.column));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Compiles the body of a loop, starting the loop.
+    ///
+    /// # Arguments
+    /// * `body` - The body of the loop.
+    /// * `next` - Where `continue` jumps to, or `None` if it is not known until the body has been compiled.
+    fn body(&mut self, body: &Statement, next: Option<usize>) -> Result<(), Error> {
+        self.loops.push(Loop {
+            depth: self.depth,
+            next,
+            ..Loop::default()
+        });
+
+        self.statement(body)
+    }
+
+    fn current_loop(&mut self) -> &mut Loop {
+        self.loops
+            .last_mut()
+            .expect("Only loops have 'break' and 'continue'.")
+    }
+
+    /// Ends the innermost loop, pointing its breaks to the next instruction.
+    fn end_loop(&mut self) {
+        let current = self.loops.pop().expect("The loop has been started.");
+
+        for jump in current.breaks {
+            self.patch(jump);
+        }
+    }
+
+    /// Compiles a function where it is declared, behind a jump over it.
+    fn function(
+        &mut self,
+        name: &Token,
+        parameters: &[(Token, Token)],
+        body: &Statement,
+    ) -> Result<(), Error> {
+        let over = self.emit(Instruction::Jump(0), name.line);
+        let id = self.functions[&name.start];
+        self.addresses[id] = Some(self.chunk.code.len());
+
+        // The parameters are the first slots of the frame, and share the scope of the body.
+        let loops = std::mem::take(&mut self.loops);
+        self.frame += 1;
+        self.begin_scope();
+        for (slot, (parameter, _)) in parameters.iter().enumerate() {
+            self.declare(parameter, Binding::Slot(slot));
+        }
+
+        match body {
+            Statement::Block(statements, _) => self.statements(statements)?,
+            body => self.statement(body)?,
+        }
+        self.return_none(body.span().line);
+
+        // The frame is dropped by returning, so its variables are not popped.
+        self.depth -= 1;
+        self.locals.retain(|local| local.depth <= self.depth);
+        self.frame -= 1;
+        self.loops = loops;
+
+        self.patch(over);
+
+        Ok(())
+    }
+
+    /// Compiles an expression, leaving its value on the stack.
+    fn expression(&mut self, expression: &Expression) -> Result<(), Error> {
+        match expression {
+            Expression::Literal { value, token, .. } => self.constant(value.clone(), token.line),
+            Expression::Grouping(expression, _) => self.expression(expression)?,
+            Expression::Variable(name, _) => {
+                let slot = self.slot(name)?;
+                self.emit(Instruction::LoadLocal(slot), name.line);
+            }
+            Expression::Assign { name, value, .. } => {
+                self.expression(value)?;
+
+                let slot = self.slot(name)?;
+                self.emit(Instruction::StoreLocal(slot), name.line);
+            }
+            Expression::Unary {
+                operator, right, ..
+            } => {
+                self.expression(right)?;
+
+                let instruction = match operator.token_type {
+                    TokenType::Minus => Instruction::Negate,
+                    TokenType::Bang => Instruction::Not,
+                    _ => return Err(unsupported(operator)),
+                };
+                self.emit(instruction, operator.line);
+            }
+            Expression::TypeOf(expression, span) => {
+                self.expression(expression)?;
+                self.emit(Instruction::TypeOf, span.line);
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => match operator.token_type {
+                TokenType::LogicalAnd | TokenType::LogicalOr => {
+                    self.logical(left, operator, right)?
+                }
+                _ => {
+                    self.expression(left)?;
+                    self.expression(right)?;
+
+                    let instruction = match operator.token_type {
+                        TokenType::Plus => Instruction::Add,
+                        TokenType::Minus => Instruction::Subtract,
+                        TokenType::Star => Instruction::Multiply,
+                        TokenType::Slash => Instruction::Divide,
+                        TokenType::Percent => Instruction::Modulo,
+                        TokenType::EqualEqual => Instruction::Equal,
+                        TokenType::BangEqual => Instruction::NotEqual,
+                        TokenType::GreaterThan => Instruction::Greater,
+                        TokenType::GreaterThanOrEqual => Instruction::GreaterEqual,
+                        TokenType::LessThan => Instruction::Less,
+                        TokenType::LessThanOrEqual => Instruction::LessEqual,
+                        _ => return Err(unsupported(operator)),
+                    };
+                    self.emit(instruction, operator.line);
+                }
+            },
+            Expression::Call {
+                callee,
+                parenthesis,
+                arguments,
+                ..
+            } => {
+                let Expression::Variable(name, _) = callee.as_ref() else {
+                    return Err(unsupported(callee.first_token()));
+                };
+                let Some(Binding::Function(function)) = self.lookup(name) else {
+                    return Err(unsupported(name));
+                };
+
+                for argument in arguments {
+                    self.expression(argument)?;
+                }
+
+                let call = self.emit(
+                    Instruction::Call {
+                        address: 0,
+                        arguments: arguments.len(),
+                    },
+                    parenthesis.line,
+                );
+                self.calls.push((call, function));
+            }
+            expression => return Err(unsupported(expression.first_token())),
+        }
+
+        Ok(())
+    }
+
+    /// Compiles `&&` or `||`, only evaluating the right side if the left side does not decide the result.
+    fn logical(
+        &mut self,
+        left: &Expression,
+        operator: &Token,
+        right: &Expression,
+    ) -> Result<(), Error> {
+        let line = operator.line;
+        self.expression(left)?;
+        let otherwise = self.emit(Instruction::JumpIfFalse(0), line);
+
+        if operator.token_type == TokenType::LogicalAnd {
+            self.expression(right)?;
+            let end = self.emit(Instruction::Jump(0), line);
+            self.patch(otherwise);
+            self.constant(Literal::Boolean(false), line);
+            self.patch(end);
+        } else {
+            self.constant(Literal::Boolean(true), line);
+            let end = self.emit(Instruction::Jump(0), line);
+            self.patch(otherwise);
+            self.expression(right)?;
+            self.patch(end);
+        }
+
+        Ok(())
+    }
+
+    /// Appends an instruction to the chunk.
+    ///
+    /// # Returns
+    /// The address of the instruction, for patching jumps.
+    fn emit(&mut self, instruction: Instruction, line: usize) -> usize {
+        self.chunk.code.push(instruction);
+        self.chunk.lines.push(line);
+
+        self.chunk.code.len() - 1
+    }
+
+    /// Pushes a constant, adding it to the chunk's constants unless an equal one is there already.
+    fn constant(&mut self, literal: Literal, line: usize) {
+        let index = match self
+            .chunk
+            .constants
+            .iter()
+            .position(|constant| *constant == literal)
+        {
+            Some(index) => index,
+            None => {
+                self.chunk.constants.push(literal);
+                self.chunk.constants.len() - 1
+            }
+        };
+
+        self.emit(Instruction::Constant(index), line);
+    }
+
+    /// Returns none, for the end of the program or a function without a return at its end.
+    fn return_none(&mut self, line: usize) {
+        self.constant(Literal::None, line);
+        self.emit(Instruction::Return, line);
+    }
+
+    /// Points the jump at the given address to the next instruction.
+    fn patch(&mut self, jump: usize) {
+        let target = self.chunk.code.len();
+
+        match &mut self.chunk.code[jump] {
+            Instruction::Jump(address) | Instruction::JumpIfFalse(address) => *address = target,
+            instruction => unreachable!("Only jumps are patched, not {:?}.", instruction),
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.depth += 1;
+    }
+
+    /// Ends the innermost scope, popping its variables off the stack.
+    fn end_scope(&mut self, line: usize) {
+        self.depth -= 1;
+
+        for _ in 0..self.slots_deeper_than(self.depth) {
+            self.emit(Instruction::Pop, line);
+        }
+        self.locals.retain(|local| local.depth <= self.depth);
+    }
+
+    /// Declares a name in the innermost scope.
+    fn declare(&mut self, name: &Token, binding: Binding) {
+        self.locals.push(Local {
+            name: name.name(),
+            binding,
+            depth: self.depth,
+            frame: self.frame,
+        });
+    }
+
+    /// Counts the variables of the current function, which is the slot of the next one declared.
+    fn slots(&self) -> usize {
+        self.locals
+            .iter()
+            .filter(|local| local.frame == self.frame)
+            .filter(|local| matches!(local.binding, Binding::Slot(_)))
+            .count()
+    }
+
+    /// Counts the variables of the current function declared in scopes nested deeper than the given depth.
+    fn slots_deeper_than(&self, depth: usize) -> usize {
+        self.locals
+            .iter()
+            .filter(|local| local.frame == self.frame && local.depth > depth)
+            .filter(|local| matches!(local.binding, Binding::Slot(_)))
+            .count()
+    }
+
+    /// Finds the innermost declaration of a name.
+    ///
+    /// # Returns
+    /// What the name stands for, or `None` if it is a native function.
+    fn lookup(&self, name: &Token) -> Option<Binding> {
+        let symbol = name.name();
+
+        self.locals
+            .iter()
+            .rev()
+            .find(|local| local.name == symbol)
+            .map(|local| local.binding)
+    }
+
+    /// Finds the slot of a variable, which must be declared in the current function.
+    /// The program has been resolved, so a name that is not declared is a native function.
+    fn slot(&self, name: &Token) -> Result<usize, Error> {
+        let symbol = name.name();
+        let local = self.locals.iter().rev().find(|local| local.name == symbol);
+
+        match local {
+            Some(Local {
+                binding: Binding::Slot(slot),
+                frame,
+                ..
+            }) if *frame == self.frame => Ok(*slot),
+            Some(Local {
+                binding: Binding::Slot(_),
+                ..
+            }) => Err(Error::Captured(
+                name.lexeme.to_string(),
+                name.line,
+                name.column,
+            )),
+            Some(_) | None => Err(unsupported(name)),
+        }
+    }
+}
+
+/// Creates the error for a token starting something that cannot be compiled yet.
+fn unsupported(token: &Token) -> Error {
+    Error::Unsupported(token.lexeme.to_string(), token.line, token.column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::parse;
+    use Instruction::*;
+
+    fn compile(source: &str) -> Result<Chunk, Error> {
+        let statements = parse(source).expect("Parsing should succeed.");
+
+        Compiler::new(&statements).compile()
+    }
+
+    /// Compiles the source code, leaving out the return every program ends with.
+    fn code(source: &str) -> Vec<Instruction> {
+        let mut code = compile(source).expect("Compiling should succeed.").code;
+        assert_eq!(code.split_off(code.len() - 2)[1], Return);
+
+        code
+    }
+
+    #[test]
+    fn test_compile_expressions() {
+        let chunk =
+            compile("print -(1 + 2) * 3 == 1 && !false;").expect("Compiling should succeed.");
+
+        assert_eq!(
+            chunk.code,
+            [
+                Constant(0),
+                Constant(1),
+                Add,
+                Negate,
+                Constant(2),
+                Multiply,
+                Constant(0),
+                Equal,
+                JumpIfFalse(12),
+                Constant(3),
+                Not,
+                Jump(13),
+                Constant(3),
+                Print,
+                Constant(4),
+                Return,
+            ]
+        );
+        // Equal constants are only added once.
+        assert_eq!(
+            chunk.constants,
+            [
+                Literal::Int(1),
+                Literal::Int(2),
+                Literal::Int(3),
+                Literal::Boolean(false),
+                Literal::None,
+            ]
+        );
+        assert_eq!(
+            code("print 1 || 2 > 3;"),
+            [
+                Constant(0),
+                JumpIfFalse(4),
+                Constant(1),
+                Jump(7),
+                Constant(2),
+                Constant(3),
+                Greater,
+                Print,
+            ]
+        );
+        assert_eq!(
+            code("print typeof -1;"),
+            [Constant(0), Negate, TypeOf, Print]
+        );
+    }
+
+    #[test]
+    fn test_compile_if() {
+        // Variables are popped at the end of their block.
+        assert_eq!(
+            code("let a = 1; if (a < 2) print a; else { let b = a; print b; }"),
+            [
+                Constant(0),
+                LoadLocal(0),
+                Constant(1),
+                Less,
+                JumpIfFalse(8),
+                LoadLocal(0),
+                Print,
+                Jump(12),
+                LoadLocal(0),
+                LoadLocal(1),
+                Print,
+                Pop,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compile_loops() {
+        let chunk = compile(
+            "let i = 0;\nwhile (i < 10) {\n    let j = i;\n    i = i + 1;\n    if (j == 3) continue;\n    if (j > 8) break;\n}",
+        )
+        .expect("Compiling should succeed.");
+
+        // Leaving an iteration pops the variables declared in it.
+        assert_eq!(
+            chunk.code[..25],
+            [
+                Constant(0),
+                LoadLocal(0),
+                Constant(1),
+                Less,
+                JumpIfFalse(25),
+                LoadLocal(0),
+                LoadLocal(0),
+                Constant(2),
+                Add,
+                StoreLocal(0),
+                Pop,
+                LoadLocal(1),
+                Constant(3),
+                Equal,
+                JumpIfFalse(17),
+                Pop,
+                Jump(1),
+                LoadLocal(1),
+                Constant(4),
+                Greater,
+                JumpIfFalse(23),
+                Pop,
+                Jump(25),
+                Pop,
+                Jump(1),
+            ]
+        );
+        assert_eq!(chunk.lines[..12], [1, 2, 2, 2, 2, 3, 4, 4, 4, 4, 4, 5]);
+
+        // 'continue' runs the increment of a 'for' loop, whose variable is popped after it.
+        assert_eq!(
+            code("for (let i = 0; i < 3; i = i + 1) { if (i == 1) continue; print i; }"),
+            [
+                Constant(0),
+                LoadLocal(0),
+                Constant(1),
+                Less,
+                JumpIfFalse(18),
+                LoadLocal(0),
+                Constant(2),
+                Equal,
+                JumpIfFalse(10),
+                Jump(12),
+                LoadLocal(0),
+                Print,
+                LoadLocal(0),
+                Constant(2),
+                Add,
+                StoreLocal(0),
+                Pop,
+                Jump(1),
+                Pop,
+            ]
+        );
+        assert_eq!(code("loop { break; }"), [Jump(2), Jump(0)]);
+    }
+
+    #[test]
+    fn test_compile_functions() {
+        // Functions are jumped over, and their arguments are the first slots of their frame.
+        assert_eq!(
+            code("fn add(a: int, b: int) -> int { return a + b; }\nprint add(1, add(2, 3));"),
+            [
+                Jump(7),
+                LoadLocal(0),
+                LoadLocal(1),
+                Add,
+                Return,
+                Constant(0),
+                Return,
+                Constant(1),
+                Constant(2),
+                Constant(3),
+                Call {
+                    address: 1,
+                    arguments: 2
+                },
+                Call {
+                    address: 1,
+                    arguments: 2
+                },
+                Print,
+            ]
+        );
+
+        // Functions can be called before they are declared.
+        assert_eq!(
+            code("fn f() { g(); }\nfn g() {}"),
+            [
+                Jump(5),
+                Call {
+                    address: 6,
+                    arguments: 0
+                },
+                Pop,
+                Constant(0),
+                Return,
+                Jump(8),
+                Constant(0),
+                Return,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compile_unsupported() {
+        assert_eq!(
+            compile("assert(true);"),
+            Err(Error::UnsupportedStatement(1, 1))
+        );
+        assert_eq!(
+            compile("print clock();"),
+            Err(Error::Unsupported("clock".to_string(), 1, 7))
+        );
+        assert_eq!(
+            compile("let a = 1; fn f() -> int { return a; }"),
+            Err(Error::Captured("a".to_string(), 1, 35))
+        );
+        assert!(matches!(
+            compile("print a;"),
+            Err(Error::Unresolved(_, 1, 7))
+        ));
+    }
+
+    /// Compiles and disassembles the source code.
+    fn disassemble(source: &str) -> String {
+        compile(source)
+            .expect("Compiling should succeed.")
+            .disassemble("test")
+    }
+
+    #[test]
+    fn test_disassemble_expressions() {
+        assert_eq!(
+            disassemble("let a = 1.5;\nprint -a == \"a\";"),
+            "== test ==\n\
+             0000    1 CONSTANT            0 '1.5'\n\
+             0001    2 LOAD_LOCAL          0\n\
+             0002    | NEGATE\n\
+             0003    | CONSTANT            1 'a'\n\
+             0004    | EQUAL\n\
+             0005    | PRINT\n\
+             0006    | CONSTANT            2 'none'\n\
+             0007    | RETURN\n"
+        );
+    }
+
+    #[test]
+    fn test_disassemble_jumps() {
+        assert_eq!(
+            disassemble(
+                "fn half(n: int) -> int {\n    return n / 2;\n}\n\n\
+                 let i = 0;\nwhile (i < 2) { i = i + 1; }\nprint half(i);"
+            ),
+            "== test ==\n\
+             0000    1 JUMP             -> 0007\n\
+             0001    2 LOAD_LOCAL          0\n\
+             0002    | CONSTANT            0 '2'\n\
+             0003    | DIVIDE\n\
+             0004    | RETURN\n\
+             0005    1 CONSTANT            1 'none'\n\
+             0006    | RETURN\n\
+             0007    5 CONSTANT            2 '0'\n\
+             0008    6 LOAD_LOCAL          0\n\
+             0009    | CONSTANT            0 '2'\n\
+             0010    | LESS\n\
+             0011    | JUMP_IF_FALSE    -> 0018\n\
+             0012    | LOAD_LOCAL          0\n\
+             0013    | CONSTANT            3 '1'\n\
+             0014    | ADD\n\
+             0015    | STORE_LOCAL         0\n\
+             0016    | POP\n\
+             0017    | JUMP             -> 0008\n\
+             0018    7 LOAD_LOCAL          0\n\
+             0019    | CALL             -> 0001 (1 argument)\n\
+             0020    | PRINT\n\
+             0021    | CONSTANT            1 'none'\n\
+             0022    | RETURN\n"
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use crate::lang::errors::{Error, Severity};
+use crate::lang::lexer::{Scanner, Token, TokenType};
+use crate::lang::parser::Parser;
+use crate::lang::semantic_analyzer::Analyzer;
+
+/// A position in the source code, in the shape of a Language Server Protocol `Position`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    /// The zero-based line.
+    pub line: usize,
+    /// The zero-based offset within the line, in UTF-16 code units.
+    pub character: usize,
+}
+
+/// A range in the source code, in the shape of a Language Server Protocol `Range`.
+/// The end is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+/// A problem found in the source code, in the shape of a Language Server Protocol `Diagnostic`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub range: Range,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Finds the problems in the given source code by scanning, parsing and analyzing it.
+/// Later stages only run if the earlier ones succeeded.
+///
+/// # Arguments
+/// * `source` - The source code to check.
+///
+/// # Returns
+/// The problems found, in the order they were reported.
+pub fn diagnostics(source: &str) -> Vec<Diagnostic> {
+    let mut scanner = Scanner::new(source);
+    let tokens = scanner.scan_tokens();
+
+    let errors = if scanner.had_error() {
+        scanner.errors().to_vec()
+    } else {
+        match Parser::new(&tokens).parse() {
+            Ok(statements) => Analyzer::new().analyze(&statements),
+            Err(errors) => errors,
+        }
+    };
+
+    let lines = LineIndex::new(source);
+
+    errors
+        .into_iter()
+        .map(|error| Diagnostic {
+            range: lines.range(&tokens, &error),
+            severity: error.severity,
+            message: error.message,
+        })
+        .collect()
+}
+
+/// The byte offsets lines start at, for converting between offsets and positions.
+struct LineIndex<'a> {
+    source: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(source: &'a str) -> Self {
+        let starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+
+        Self { source, starts }
+    }
+
+    /// Converts a byte offset to a position.
+    fn position(&self, offset: usize) -> Position {
+        let line = self.starts.partition_point(|start| *start <= offset) - 1;
+        let character = self.source[self.starts[line]..offset]
+            .encode_utf16()
+            .count();
+
+        Position { line, character }
+    }
+
+    /// Converts a one-based line and column, counted in characters, to a byte offset.
+    fn offset(&self, line: usize, column: usize) -> usize {
+        let Some(start) = line.checked_sub(1).and_then(|line| self.starts.get(line)) else {
+            return self.source.len();
+        };
+
+        self.source[*start..]
+            .char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(self.source.len(), |(offset, _)| start + offset)
+    }
+
+    /// Gets the range an error covers.
+    /// Errors only record where they start, so the range covers the token starting there, if any.
+    fn range(&self, tokens: &[Token], error: &Error) -> Range {
+        let token = tokens.iter().find(|token| {
+            token.token_type != TokenType::EndOfFile
+                && token.line == error.line
+                && token.column == error.column
+        });
+
+        let (start, end) = match token {
+            Some(token) => (token.start, token.end),
+            None => {
+                let offset = self.offset(error.line, error.column);
+
+                (offset, offset)
+            }
+        };
+
+        Range {
+            start: self.position(start),
+            end: self.position(end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: (usize, usize), end: (usize, usize)) -> Range {
+        Range {
+            start: Position {
+                line: start.0,
+                character: start.1,
+            },
+            end: Position {
+                line: end.0,
+                character: end.1,
+            },
+        }
+    }
+
+    #[test]
+    fn test_parser_diagnostics() {
+        let diagnostics = diagnostics("let größe = 1\nlet y = 2;\nprint größe y;");
+
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.range)
+                .collect::<Vec<_>>(),
+            vec![range((1, 0), (1, 3)), range((2, 12), (2, 13))]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == Severity::Error));
+        assert!(diagnostics[0].message.starts_with("Expected ';'"));
+    }
+
+    #[test]
+    fn test_unsupported_syntax_diagnostics() {
+        let diagnostics = diagnostics("let a = 1;\nswitch (a) {}");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range, range((1, 0), (1, 6)));
+        assert_eq!(
+            diagnostics[0].message,
+            "Switch statements are not supported"
+        );
+    }
+
+    #[test]
+    fn test_analyzer_diagnostics() {
+        let diagnostics = diagnostics("let count = 1;\nprint \"ü\" + counter;");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range, range((1, 12), (1, 19)));
+        assert_eq!(
+            diagnostics[0].message,
+            "Variable 'counter' is not defined, did you mean 'count'?"
+        );
+    }
+
+    #[test]
+    fn test_scanner_diagnostics() {
+        let diagnostics = diagnostics("let a = 1;\nlet b = @;");
+
+        assert_eq!(diagnostics[0].range, range((1, 8), (1, 8)));
+    }
+
+    #[test]
+    fn test_warning_diagnostics() {
+        let diagnostics = diagnostics("fn main() {\n    let unused = 1;\n}");
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Warning);
+        assert_eq!(diagnostics[0].range, range((1, 8), (1, 14)));
+    }
+}
@@ -0,0 +1,174 @@
+use crate::lang::bytecode::Compiler;
+use crate::lang::errors::Error;
+use crate::lang::{lex, parse};
+
+/// A representation of a program for external tools or debugging, printed instead of running the program.
+///
+/// The tokens and syntax tree are JSON, with every enum variant tagged by its name,
+/// such as `{"Print":[...]}` or `"Identifier"`, and every token carrying its lexeme, literal, line, column and byte offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dump {
+    /// The tokens of the program, ending with the end of the file.
+    Tokens,
+    /// The syntax tree of the program, as parsed, without its included files.
+    Ast,
+    /// The disassembled bytecode of the program, see `Chunk::disassemble`.
+    ///
+    /// [`Chunk::disassemble`]: crate::lang::bytecode::Chunk::disassemble
+    Bytecode,
+}
+
+impl Dump {
+    /// The names of every dump, as returned by `name`.
+    pub const NAMES: [&'static str; 3] = ["tokens-json", "ast-json", "bytecode"];
+
+    /// Gets a dump by its name, as given to `--emit`.
+    ///
+    /// # Returns
+    /// The dump, or `None` if there is no dump with the name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "tokens-json" => Some(Dump::Tokens),
+            "ast-json" => Some(Dump::Ast),
+            "bytecode" => Some(Dump::Bytecode),
+            _ => None,
+        }
+    }
+
+    /// Gets the stable name of the dump.
+    pub fn name(self) -> &'static str {
+        match self {
+            Dump::Tokens => "tokens-json",
+            Dump::Ast => "ast-json",
+            Dump::Bytecode => "bytecode",
+        }
+    }
+
+    /// Dumps a program.
+    ///
+    /// # Arguments
+    /// * `source` - The source code of the program.
+    ///
+    /// # Returns
+    /// The text, ending with a line break, or the errors found while scanning, parsing or compiling.
+    ///
+    /// # Examples
+    /// ```
+    /// use cpl::lang::dump::Dump;
+    ///
+    /// let json = Dump::Ast.dump("print 1;").unwrap();
+    ///
+    /// assert!(json.starts_with(r#"[{"Print":[{"Literal":{"value":{"Int":1}"#));
+    /// ```
+    pub fn dump(self, source: &str) -> Result<String, Vec<Error>> {
+        let json = match self {
+            Dump::Tokens => serde_json::to_string(&lex(source)?),
+            Dump::Ast => serde_json::to_string(&parse(source)?),
+            Dump::Bytecode => {
+                let statements = parse(source)?;
+                let chunk = Compiler::new(&statements)
+                    .compile()
+                    .map_err(|error| vec![error.into()])?;
+
+                return Ok(chunk.disassemble("main"));
+            }
+        };
+
+        Ok(json.expect("The tokens and syntax tree only have string keys.") + "\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// Dumps the source code, reading the JSON back.
+    fn dump(dump: Dump, source: &str) -> Value {
+        let json = dump.dump(source).expect("Dumping should succeed.");
+
+        serde_json::from_str(&json).expect("The dump should be valid JSON.")
+    }
+
+    #[test]
+    fn test_dump_names() {
+        for name in Dump::NAMES {
+            let dump = Dump::from_name(name).expect("Every name should have a dump.");
+
+            assert_eq!(dump.name(), name);
+        }
+        assert_eq!(Dump::from_name("asm"), None);
+    }
+
+    #[test]
+    fn test_dump_tokens() {
+        assert_eq!(
+            dump(Dump::Tokens, "a 2.5"),
+            json!([
+                {
+                    "token_type": "Identifier",
+                    "lexeme": "a",
+                    "literal": null,
+                    "line": 1,
+                    "column": 1,
+                    "start": 0,
+                    "end": 1,
+                },
+                {
+                    "token_type": "Number",
+                    "lexeme": "2.5",
+                    "literal": { "Float": 2.5 },
+                    "line": 1,
+                    "column": 3,
+                    "start": 2,
+                    "end": 5,
+                },
+                {
+                    "token_type": "EndOfFile",
+                    "lexeme": "",
+                    "literal": null,
+                    "line": 1,
+                    "column": 6,
+                    "start": 5,
+                    "end": 5,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_dump_ast() {
+        let ast = dump(Dump::Ast, "let a = -1;");
+        let variable = &ast[0]["Variable"];
+
+        assert_eq!(variable["name"]["lexeme"], "a");
+        assert_eq!(variable["declared_type"], Value::Null);
+        assert_eq!(variable["mutable"], true);
+        assert_eq!(
+            variable["span"],
+            json!({ "start": 0, "end": 11, "line": 1, "column": 1 })
+        );
+
+        let unary = &variable["initializer"]["Unary"];
+        assert_eq!(unary["operator"]["token_type"], "Minus");
+        assert_eq!(unary["right"]["Literal"]["value"], json!({ "Int": 1 }));
+
+        assert!(Dump::Ast.dump("let a = ;").is_err());
+    }
+
+    #[test]
+    fn test_dump_bytecode() {
+        assert_eq!(
+            Dump::Bytecode
+                .dump("print 1;")
+                .expect("Dumping should succeed."),
+            "== main ==\n\
+             0000    1 CONSTANT            0 '1'\n\
+             0001    | PRINT\n\
+             0002    | CONSTANT            1 'none'\n\
+             0003    | RETURN\n"
+        );
+        assert!(Dump::Bytecode.dump("assert(true);").is_err());
+    }
+}
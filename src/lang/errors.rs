@@ -1,12 +1,62 @@
+use std::path::Path;
+
+/// How severe an error is, numbered like the Language Server Protocol's `DiagnosticSeverity`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Stops the program from running.
+    Error = 1,
+    /// Is reported, but does not stop the program from running.
+    Warning = 2,
+}
+
 /// A struct representing an error.
 #[derive(Debug, Clone)]
 pub struct Error {
     pub line: usize,
     pub column: usize,
     pub message: String,
+    pub severity: Severity,
+}
+
+/// The name diagnostics give to source code that is not read from a file, such as the REPL's input.
+pub const NO_FILE: &str = "<repl>";
+
+/// Formats a diagnostic as `file:line:column: message`, which editors recognize as a location to jump to.
+///
+/// # Arguments
+/// * `file` - The file the diagnostic is about, or `None` for source code not read from a file.
+/// * `line` - The line of the diagnostic.
+/// * `column` - The column of the diagnostic.
+/// * `message` - What the diagnostic says.
+pub fn format(file: Option<&Path>, line: usize, column: usize, message: &str) -> String {
+    let file = file.map_or_else(|| NO_FILE.into(), Path::to_string_lossy);
+
+    format!("{}:{}:{}: {}", file, line, column, message)
+}
+
+/// Prints an error message to the `stderr` file descriptor, prefixed with the file it is in.
+pub fn report(file: Option<&Path>, line: usize, column: usize, message: &str) {
+    eprintln!("{}", format(file, line, column, message));
 }
 
-/// Prints an error message to the `stderr` file descriptor.
-pub fn report(line: usize, column: usize, message: &str) {
-    eprintln!("[line {}:{}]: {}", line, column, message);
+/// Prints a warning message to the `stderr` file descriptor, prefixed with the file it is in.
+pub fn warn(file: Option<&Path>, line: usize, column: usize, message: &str) {
+    eprintln!(
+        "{}",
+        format(file, line, column, &format!("warning: {}", message))
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        assert_eq!(
+            format(Some(Path::new("src/main.cpl")), 3, 7, "Expected ';'"),
+            "src/main.cpl:3:7: Expected ';'"
+        );
+        assert_eq!(format(None, 1, 1, "Oops"), "<repl>:1:1: Oops");
+    }
 }
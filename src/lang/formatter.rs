@@ -0,0 +1,180 @@
+use crate::lang::errors::Error;
+use crate::lang::lexer::{Scanner, Token, TokenType};
+use crate::lang::parser::{Parser, Statement};
+
+/// Formats the given source code.
+/// Statements are placed one per line, indented with four spaces per level,
+/// and top-level functions are separated from their neighbours by a blank line.
+/// Comments are kept, either on their own line or trailing the statement they follow.
+///
+/// # Arguments
+/// * `source` - The source code to format.
+///
+/// # Returns
+/// The formatted source code, or the errors found while parsing it.
+pub fn format(source: &str) -> Result<String, Vec<Error>> {
+    let mut scanner = Scanner::new(source).with_comments(true);
+    let tokens = scanner.scan_tokens();
+    if scanner.had_error() {
+        return Err(scanner.errors().to_vec());
+    }
+
+    let statements = Parser::new(&hoist_comments(tokens)).parse()?;
+
+    let mut formatted = String::new();
+    for (i, statement) in statements.iter().enumerate() {
+        if i > 0 {
+            let previous = &statements[i - 1];
+
+            if statement.is_trailing_comment() {
+                formatted.pop();
+                formatted.push(' ');
+            } else if (is_function(previous) || is_function(statement))
+                && !matches!(
+                    previous,
+                    Statement::Comment {
+                        trailing: false,
+                        ..
+                    }
+                )
+            {
+                formatted.push('\n');
+            }
+        }
+
+        formatted.push_str(&statement.to_source());
+        formatted.push('\n');
+    }
+
+    Ok(formatted)
+}
+
+/// Moves comments that appear inside a statement to just before that statement,
+/// as the parser only accepts comments between statements.
+///
+/// # Arguments
+/// * `tokens` - The tokens, including comments.
+fn hoist_comments(tokens: Vec<Token>) -> Vec<Token> {
+    let mut hoisted = Vec::with_capacity(tokens.len());
+    // The position after the last token that ended a statement.
+    let mut boundary = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        if token.token_type != TokenType::Comment {
+            if matches!(
+                token.token_type,
+                TokenType::Semicolon | TokenType::LeftCurlyBrace | TokenType::RightCurlyBrace
+            ) {
+                boundary = hoisted.len() + 1;
+            }
+
+            hoisted.push(token.clone());
+            continue;
+        }
+
+        let previous = hoisted
+            .iter()
+            .rev()
+            .find(|token: &&Token| token.token_type != TokenType::Comment)
+            .map(|token| &token.token_type);
+        let next = tokens[i + 1..]
+            .iter()
+            .find(|token| token.token_type != TokenType::Comment)
+            .map(|token| &token.token_type);
+
+        // Moved comments are placed on a line of their own, which line 0 marks to the parser.
+        let moved = Token {
+            line: 0,
+            ..token.clone()
+        };
+
+        if previous == Some(&TokenType::RightCurlyBrace) && next == Some(&TokenType::Else) {
+            // Between a branch and its 'else', so move it to the end of the branch.
+            let closing_brace = hoisted
+                .iter()
+                .rposition(|token: &Token| token.token_type == TokenType::RightCurlyBrace)
+                .expect("A closing brace precedes the comment.");
+            hoisted.insert(closing_brace, moved);
+            boundary += 1;
+        } else if matches!(
+            previous,
+            None | Some(TokenType::Semicolon)
+                | Some(TokenType::LeftCurlyBrace)
+                | Some(TokenType::RightCurlyBrace)
+        ) {
+            hoisted.push(token.clone());
+        } else {
+            hoisted.insert(boundary, moved);
+            boundary += 1;
+        }
+    }
+
+    hoisted
+}
+
+/// Checks if the given statement is a function declaration.
+fn is_function(statement: &Statement) -> bool {
+    matches!(statement, Statement::Function { .. })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format() {
+        let source = "let a=1;let b   =a+2*3;\n\n\nfn add(a:i32,b:i32){return a+b;}\nif(a<b){print add( a,b );}else print\"no\";";
+
+        assert_eq!(
+            format(source).expect("Formatting should succeed."),
+            "let a = 1;\nlet b = a + 2 * 3;\n\nfn add(a: i32, b: i32) {\n    return a + b;\n}\n\nif (a < b) {\n    print add(a, b);\n} else print \"no\";\n"
+        );
+    }
+
+    #[test]
+    fn test_format_is_idempotent() {
+        let sources = [
+            "fn main(){let a=1;while(a<10){a=a+1;if(a==5){break;}}print a;}",
+            "  loop{print 1;{}}\n\n\n  let x ;x=-  -1;",
+            "fn a(){}fn b(){}let c=a()??b();",
+            "// Leading.\nfn a(){ // Trailing.\nprint 1; // Also trailing.\n// Before '}'.\n}",
+        ];
+
+        for source in sources {
+            let once = format(source).expect("Formatting should succeed.");
+            let twice = format(&once).expect("Formatting should succeed.");
+
+            assert_eq!(once, twice);
+        }
+    }
+
+    #[test]
+    fn test_format_keeps_comments() {
+        let source = "// Adds two numbers.\nfn add(a:i32,b:i32){\n  // Sum them.\n  return a+b;   // Done.\n}\nlet c=add(1, // One.\n2);";
+
+        assert_eq!(
+            format(source).expect("Formatting should succeed."),
+            "// Adds two numbers.\nfn add(a: i32, b: i32) {\n    // Sum them.\n    return a + b; // Done.\n}\n\n// One.\nlet c = add(1, 2);\n"
+        );
+    }
+
+    #[test]
+    fn test_format_keeps_block_comments() {
+        let source = "/* Adds\n   two numbers. */\nfn add(a:i32,b:i32){return a+b; /* a*b */}\nprint add(1,/* One. */2);";
+
+        assert_eq!(
+            format(source).expect("Formatting should succeed."),
+            "/* Adds\n   two numbers. */\nfn add(a: i32, b: i32) {\n    return a + b; /* a*b */\n}\n\n/* One. */\nprint add(1, 2);\n"
+        );
+    }
+
+    #[test]
+    fn test_format_comment_before_else() {
+        let source = "if (a) { print 1; } // Then.\nelse { print 2; }";
+
+        assert_eq!(
+            format(source).expect("Formatting should succeed."),
+            "if (a) {\n    print 1;\n    // Then.\n} else {\n    print 2;\n}\n"
+        );
+    }
+}
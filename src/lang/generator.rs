@@ -1 +1,1723 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Write};
 
+use crate::lang::errors;
+use crate::lang::lexer::{Literal, Token, TokenType};
+use crate::lang::natives;
+use crate::lang::parser::{Expression, Statement};
+use crate::lang::resolver::{Resolution, Resolver};
+use crate::lang::semantic_analyzer::{self, Type};
+use crate::lang::span::Span;
+
+/// An error found while generating code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A statement that cannot be compiled yet, with the line and column of its first token.
+    UnsupportedStatement(usize, usize),
+    /// An expression or operator that cannot be compiled yet, with the lexeme of its first token, line and column.
+    Unsupported(String, usize, usize),
+    /// A kind of statement or expression the backend cannot compile at all yet, such as tuples or modules,
+    /// with the name of the construct and its span.
+    UnsupportedConstruct(&'static str, Span),
+    /// A function uses a variable declared outside of it, which cannot be compiled yet,
+    /// with the variable's name, line and column.
+    Captured(String, usize, usize),
+    /// A function takes more arguments than can be passed in registers,
+    /// with the number of arguments and the line and column of its name or call.
+    TooManyArguments(usize, usize, usize),
+    /// The program's variables could not be resolved, so it has not been analyzed,
+    /// with an error found by the resolver, line and column.
+    Unresolved(String, usize, usize),
+}
+
+impl Error {
+    /// Gets the line and column the error occurred at.
+    pub fn position(&self) -> (usize, usize) {
+        match self {
+            Error::UnsupportedStatement(line, column)
+            | Error::Unsupported(_, line, column)
+            | Error::Captured(_, line, column)
+            | Error::TooManyArguments(_, line, column)
+            | Error::Unresolved(_, line, column) => (*line, *column),
+            Error::UnsupportedConstruct(_, span) => (span.line, span.column),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UnsupportedStatement(..) => write!(
+                f,
+                "Cannot compile this statement yet, only expression statements are supported"
+            ),
+            Error::Unsupported(lexeme, ..) => write!(
+                f,
+                "Cannot compile '{}' yet, only integer arithmetic is supported",
+                lexeme
+            ),
+            Error::UnsupportedConstruct(construct, _) => {
+                write!(f, "{} are not supported by this backend yet", construct)
+            }
+            Error::Captured(name, ..) => write!(
+                f,
+                "Cannot compile '{}' yet, functions cannot use variables declared outside of them",
+                name
+            ),
+            Error::TooManyArguments(count, ..) => write!(
+                f,
+                "Functions with more than {} arguments are not supported yet, found {}",
+                ARGUMENT_REGISTERS.len(),
+                count
+            ),
+            Error::Unresolved(message, ..) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<Error> for errors::Error {
+    fn from(error: Error) -> Self {
+        let (line, column) = error.position();
+
+        Self {
+            line,
+            column,
+            message: error.to_string(),
+            severity: errors::Severity::Error,
+        }
+    }
+}
+
+/// Generates x86_64 assembly in AT&T syntax, for the GNU assembler, from a syntax tree.
+///
+/// Only variables, functions, blocks, if statements, loops, `print` and expression statements of integer
+/// and boolean literals, `+`, `-`, `*`, `/`, `%`, unary `-`, comparisons and calls are supported so far.
+/// Booleans are 1 for true and 0 for false.
+/// Strings can only be printed as literals, as nothing can be done with their addresses yet.
+/// Values are printed with `printf` from the C runtime, so the program must be linked against it,
+/// which `gcc` does by default.
+/// The program becomes a `main` function returning the value of its last expression statement,
+/// or 0 if it has none, so the value is the exit status of the compiled program.
+/// The value is kept in the first stack slot of `main`, as `rax` is overwritten by every other statement.
+///
+/// Every variable has a stack slot of its own below the base pointer of its function,
+/// in the order they are declared, so shadowed variables keep their values.
+/// Functions follow the System V AMD64 calling convention, taking their arguments in registers
+/// and returning their value in `rax`, or 0 if they do not return one.
+#[derive(Debug)]
+pub struct Generator<'a> {
+    statements: &'a [Statement],
+    /// Where each variable reference is declared.
+    resolution: Resolution,
+    /// The variables and functions in each scope, in the order the resolver numbers them.
+    /// Like the resolver's, the outermost scope holds the native functions, which cannot be compiled yet.
+    scopes: Vec<Vec<Binding>>,
+    /// The function being generated, starting with `main`.
+    frame: Frame,
+    /// The number of functions generated, so every function has a frame of its own.
+    frames: usize,
+    /// The number of labels given out, so every label is unique.
+    labels: usize,
+    /// The signatures of the declared functions, keyed by the byte offset of their names.
+    signatures: HashMap<usize, Signature>,
+    /// The functions generated so far, after `main` in the assembly.
+    functions: String,
+    /// The read-only data of the program, such as format strings and string literals.
+    data: String,
+    /// The labels of the data, keyed by the directive defining it, so equal data is only defined once.
+    constants: HashMap<String, String>,
+    /// The statements and expressions that cannot be compiled, as generating goes on past them to find the rest.
+    errors: Vec<Error>,
+}
+
+/// What a name in scope refers to.
+#[derive(Debug, Clone)]
+enum Binding {
+    /// A variable, with the frame of the function it belongs to, its stack slot there and its kind.
+    Variable {
+        frame: usize,
+        slot: usize,
+        kind: Kind,
+    },
+    /// A function.
+    Function(Signature),
+    /// A native function, which cannot be compiled yet.
+    Native,
+}
+
+/// The kind of a value, which decides how it is printed and which operators apply to it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Kind {
+    #[default]
+    Int,
+    Boolean,
+    /// The address of a string.
+    String,
+}
+
+/// The label and types of a function.
+#[derive(Debug, Clone)]
+struct Signature {
+    label: String,
+    parameters: Vec<Kind>,
+    /// The kind of value the function returns, which is an integer if it is not annotated with one.
+    returns: Kind,
+}
+
+/// The state of the function being generated.
+#[derive(Debug, Default)]
+struct Frame {
+    /// The number of the frame, unique within the program.
+    id: usize,
+    /// The instructions of the function's body, without its prologue.
+    assembly: String,
+    /// The number of stack slots given out.
+    slots: usize,
+    /// The number of values pushed onto the stack while evaluating expressions,
+    /// to keep the stack aligned for calls.
+    temporaries: usize,
+    /// The labels 'continue' and 'break' jump to in each enclosing loop, innermost last.
+    loops: Vec<LoopLabels>,
+}
+
+/// The stack slot of `main` holding the program's exit status.
+const STATUS_SLOT: usize = 0;
+
+/// The registers the first arguments of a call are passed in, in order.
+const ARGUMENT_REGISTERS: [&str; 6] = ["%rdi", "%rsi", "%rdx", "%rcx", "%r8", "%r9"];
+
+/// Where the statements ending an iteration of a loop jump to.
+#[derive(Debug)]
+struct LoopLabels {
+    /// Where 'continue' jumps to, the condition or increment of the loop.
+    next: String,
+    /// Where 'break' jumps to, just past the loop.
+    end: String,
+}
+
+impl<'a> Generator<'a> {
+    /// Creates a new generator.
+    ///
+    /// # Arguments
+    /// * `statements` - The program to generate code for, which must already have been analyzed.
+    pub fn new(statements: &'a [Statement]) -> Self {
+        let natives = vec![Binding::Native; natives::standard_library().len()];
+
+        Self {
+            statements,
+            resolution: Resolution::default(),
+            scopes: vec![natives, Vec::new()],
+            frame: Frame::default(),
+            frames: 1,
+            labels: 0,
+            signatures: HashMap::new(),
+            functions: String::new(),
+            data: String::new(),
+            constants: HashMap::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// Generates the assembly for the program.
+    ///
+    /// # Returns
+    /// The assembly, or an error for every statement that cannot be compiled,
+    /// at the first part of it that cannot be compiled.
+    ///
+    /// # Examples
+    /// ```
+    /// use cpl::lang::generator::Generator;
+    /// use cpl::lang::parse;
+    ///
+    /// let statements = parse("-(1 + 2);").unwrap();
+    /// let assembly = Generator::new(&statements).generate().unwrap();
+    ///
+    /// assert!(assembly.contains("main:"));
+    /// assert!(assembly.contains("neg %rax"));
+    /// ```
+    pub fn generate(mut self) -> Result<String, Vec<Error>> {
+        self.resolution = Resolver::resolve(self.statements).map_err(unresolved)?;
+
+        // Without any expression statements, the program exits successfully.
+        self.frame.slots = STATUS_SLOT + 1;
+        self.emit(&format!("movq $0, {}(%rbp)", offset(STATUS_SLOT)));
+        self.statements(self.statements);
+        if !self.errors.is_empty() {
+            return Err(self.errors);
+        }
+        self.emit(&format!("mov {}(%rbp), %rax", offset(STATUS_SLOT)));
+
+        let mut assembly = String::from("    .data\n\n");
+        if !self.data.is_empty() {
+            assembly.push_str("    .section .rodata\n");
+            assembly.push_str(&self.data);
+            assembly.push('\n');
+        }
+        assembly.push_str("    .text\n    .globl main\n");
+        assembly.push_str(&routine("main", &self.frame));
+        assembly.push_str(&self.functions);
+        // Marks the stack as not executable, which linkers otherwise warn about.
+        assembly.push_str("\n    .section .note.GNU-stack,\"\",@progbits\n");
+
+        Ok(assembly)
+    }
+
+    /// Generates the code for the statements of a scope, keeping the errors of the ones that cannot be compiled.
+    fn statements(&mut self, statements: &[Statement]) {
+        // The resolver declares the functions of a scope before its variables,
+        // so they can call each other in any order.
+        for statement in statements {
+            if let Statement::Function {
+                name,
+                parameters,
+                return_type,
+                ..
+            } = statement
+            {
+                let label = format!("cpl_{}_{}", name.lexeme, self.labels);
+                self.labels += 1;
+
+                let kinds = parameters
+                    .iter()
+                    .map(|(_, annotation)| kind(annotation))
+                    .collect::<Result<Vec<_>, _>>();
+                let returns = return_type.as_ref().map_or(Ok(Kind::Int), kind);
+                let signature = match (kinds, returns) {
+                    (Ok(parameters), Ok(returns)) => Signature {
+                        label,
+                        parameters,
+                        returns,
+                    },
+                    // The function is still declared, so the names declared after it are found.
+                    (Err(error), _) | (_, Err(error)) => {
+                        self.errors.push(error);
+
+                        Signature {
+                            label,
+                            parameters: vec![Kind::Int; parameters.len()],
+                            returns: Kind::Int,
+                        }
+                    }
+                };
+
+                self.signatures.insert(name.start, signature.clone());
+                self.bind(Binding::Function(signature));
+            }
+        }
+
+        for statement in statements {
+            if let Err(error) = self.statement(statement) {
+                self.errors.push(error);
+            }
+        }
+    }
+
+    /// Generates the code for a statement.
+    fn statement(&mut self, statement: &Statement) -> Result<(), Error> {
+        match statement {
+            Statement::Expression(expression, _) => {
+                let kind = self.expression(expression)?;
+
+                // Only the program's own expression statements decide its exit status.
+                if self.frame.id == 0 && kind != Kind::String {
+                    self.emit(&format!("mov %rax, {}(%rbp)", offset(STATUS_SLOT)));
+                }
+
+                Ok(())
+            }
+            Statement::Print(expression, _) => self.print(expression),
+            Statement::Variable {
+                declared_type,
+                initializer,
+                ..
+            } => {
+                let value = match initializer {
+                    Some(initializer) => self.expression(initializer),
+                    // Variables declared without a value start out as 0.
+                    None => {
+                        self.emit("mov $0, %rax");
+                        Ok(Kind::Int)
+                    }
+                };
+                // Values are never converted, so annotations only decide the kind of variables declared without a value,
+                // but their kind must still be supported.
+                let value = match declared_type {
+                    Some(annotation) => value.and_then(|value| match initializer {
+                        Some(_) => kind(annotation).map(|_| value),
+                        None => kind(annotation),
+                    }),
+                    None => value,
+                };
+
+                // The variable is declared even if its value cannot be compiled,
+                // so the names declared after it are still found.
+                let offset = offset(self.declare(*value.as_ref().unwrap_or(&Kind::Int)));
+                self.emit(&format!("mov %rax, {}(%rbp)", offset));
+
+                value.map(|_| ())
+            }
+            Statement::Function {
+                name,
+                parameters,
+                body,
+                ..
+            } => self.function(name, parameters, body),
+            Statement::Return { value, .. } => {
+                match value {
+                    Some(value) => {
+                        self.expression(value)?;
+                    }
+                    None => self.emit("mov $0, %rax"),
+                }
+                self.emit_return();
+
+                Ok(())
+            }
+            Statement::Block(statements, _) => {
+                self.scopes.push(Vec::new());
+                self.statements(statements);
+                self.scopes.pop();
+
+                Ok(())
+            }
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                let end = self.label();
+
+                match else_branch {
+                    Some(else_branch) => {
+                        let otherwise = self.label();
+
+                        self.jump_unless(condition, &otherwise)?;
+                        self.statement(then_branch)?;
+                        self.emit(&format!("jmp {}", end));
+                        self.place(&otherwise);
+                        self.statement(else_branch)?;
+                    }
+                    None => {
+                        self.jump_unless(condition, &end)?;
+                        self.statement(then_branch)?;
+                    }
+                }
+
+                self.place(&end);
+
+                Ok(())
+            }
+            Statement::While {
+                condition, body, ..
+            } => {
+                let start = self.label();
+                let end = self.label();
+
+                self.place(&start);
+                self.jump_unless(condition, &end)?;
+                self.loop_body(body, &start, &end)?;
+                self.emit(&format!("jmp {}", start));
+                self.place(&end);
+
+                Ok(())
+            }
+            Statement::Loop { body, .. } => {
+                let start = self.label();
+                let end = self.label();
+
+                self.place(&start);
+                self.loop_body(body, &start, &end)?;
+                self.emit(&format!("jmp {}", start));
+                self.place(&end);
+
+                Ok(())
+            }
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            } => {
+                // The initializer's variable is only visible within the loop.
+                self.scopes.push(Vec::new());
+                let result = self.for_loop(
+                    initializer.as_deref(),
+                    condition.as_ref(),
+                    increment.as_ref(),
+                    body,
+                );
+                self.scopes.pop();
+
+                result
+            }
+            Statement::Break { keyword, .. } | Statement::Continue { keyword, .. } => {
+                let Some(labels) = self.frame.loops.last() else {
+                    return Err(Error::UnsupportedStatement(keyword.line, keyword.column));
+                };
+
+                let label = match statement {
+                    Statement::Break { .. } => &labels.end,
+                    _ => &labels.next,
+                };
+                let jump = format!("jmp {}", label);
+                self.emit(&jump);
+
+                Ok(())
+            }
+            Statement::Comment { .. } => Ok(()),
+            Statement::Destructure { names, span, .. } => {
+                for _ in names {
+                    self.declare(Kind::Int);
+                }
+
+                Err(Error::UnsupportedConstruct(
+                    "Destructuring declarations",
+                    *span,
+                ))
+            }
+            Statement::Assert { span, .. } => Err(Error::UnsupportedConstruct("Assertions", *span)),
+            Statement::Include { span, .. } => Err(Error::UnsupportedConstruct("Includes", *span)),
+            Statement::Module { span, .. } => Err(Error::UnsupportedConstruct("Modules", *span)),
+        }
+    }
+
+    /// Generates the code for an expression, leaving its value in `rax`.
+    /// Operands are kept on the stack while the other side is evaluated.
+    ///
+    /// # Returns
+    /// The kind of the value.
+    fn expression(&mut self, expression: &Expression) -> Result<Kind, Error> {
+        match expression {
+            Expression::Literal {
+                value: Literal::Int(value),
+                ..
+            } => {
+                self.emit(&format!("mov ${}, %rax", value));
+
+                Ok(Kind::Int)
+            }
+            Expression::Literal {
+                value: Literal::Boolean(value),
+                ..
+            } => {
+                self.emit(&format!("mov ${}, %rax", u8::from(*value)));
+
+                Ok(Kind::Boolean)
+            }
+            Expression::Literal {
+                value: Literal::String(string),
+                ..
+            } => {
+                let label = self.string(string);
+                self.emit(&format!("lea {}(%rip), %rax", label));
+
+                Ok(Kind::String)
+            }
+            Expression::Grouping(expression, _) => self.expression(expression),
+            Expression::Variable(name, _) => {
+                let (offset, kind) = self.lookup(name)?;
+                // Strings are only supported as literals.
+                if kind == Kind::String {
+                    return Err(unsupported(name));
+                }
+                self.emit(&format!("mov {}(%rbp), %rax", offset));
+
+                Ok(kind)
+            }
+            Expression::Assign { name, value, .. } => {
+                let value = self.expression(value)?;
+
+                let (offset, kind) = self.lookup(name)?;
+                // Variables keep the kind they are declared with, as values are never converted.
+                if value != kind {
+                    return Err(unsupported(name));
+                }
+                self.emit(&format!("mov %rax, {}(%rbp)", offset));
+
+                Ok(kind)
+            }
+            Expression::Unary {
+                operator, right, ..
+            } if operator.token_type == TokenType::Minus => {
+                if self.expression(right)? != Kind::Int {
+                    return Err(unsupported(operator));
+                }
+                self.emit("neg %rax");
+
+                Ok(Kind::Int)
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } if condition_codes(&operator.token_type).is_some() => {
+                let (condition, _) = self.compare(left, operator, right)?;
+
+                // Only the lowest byte is set, so the rest of the register is cleared.
+                self.emit(&format!("set{} %al", condition));
+                self.emit("movzbq %al, %rax");
+
+                Ok(Kind::Boolean)
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                let instructions: &[&str] = match operator.token_type {
+                    TokenType::Plus => &["add %rcx, %rax"],
+                    TokenType::Minus => &["sub %rcx, %rax"],
+                    TokenType::Star => &["imul %rcx, %rax"],
+                    // The dividend is sign-extended into 'rdx:rax', leaving the quotient in 'rax'.
+                    TokenType::Slash => &["cqo", "idiv %rcx"],
+                    // The remainder is left in 'rdx'.
+                    TokenType::Percent => &["cqo", "idiv %rcx", "mov %rdx, %rax"],
+                    _ => return Err(unsupported(operator)),
+                };
+
+                // Only integers can be computed with, as strings would be added as addresses.
+                if self.operands(left, right)? != (Kind::Int, Kind::Int) {
+                    return Err(unsupported(operator));
+                }
+
+                for instruction in instructions {
+                    self.emit(instruction);
+                }
+
+                Ok(Kind::Int)
+            }
+            Expression::Call {
+                callee,
+                parenthesis,
+                arguments,
+                ..
+            } => self.call(callee, parenthesis, arguments),
+            Expression::Literal { token, .. } => Err(unsupported(token)),
+            Expression::Unary { operator, .. } => Err(unsupported(operator)),
+            Expression::Coalesce { span, .. } => Err(Error::UnsupportedConstruct(
+                "Null-coalescing expressions",
+                *span,
+            )),
+            Expression::Qualified { span, .. } => {
+                Err(Error::UnsupportedConstruct("Module members", *span))
+            }
+            Expression::Tuple(_, span) => Err(Error::UnsupportedConstruct("Tuples", *span)),
+            Expression::TypeOf(_, span) => {
+                Err(Error::UnsupportedConstruct("'typeof' expressions", *span))
+            }
+        }
+    }
+
+    /// Evaluates the operands of a binary operator, leaving the left one in `rax` and the right one in `rcx`.
+    /// The left one is evaluated first, as the interpreter does, so calls happen in the same order.
+    ///
+    /// # Returns
+    /// The kinds of the left and right operands.
+    fn operands(&mut self, left: &Expression, right: &Expression) -> Result<(Kind, Kind), Error> {
+        let left = self.expression(left)?;
+        self.push();
+        let right = self.expression(right)?;
+        self.emit("mov %rax, %rcx");
+        self.pop("%rax");
+
+        Ok((left, right))
+    }
+
+    /// Generates the code for a 'for' loop, whose scope has already been opened.
+    fn for_loop(
+        &mut self,
+        initializer: Option<&Statement>,
+        condition: Option<&Expression>,
+        increment: Option<&Expression>,
+        body: &Statement,
+    ) -> Result<(), Error> {
+        if let Some(initializer) = initializer {
+            self.statement(initializer)?;
+        }
+
+        let start = self.label();
+        let next = self.label();
+        let end = self.label();
+
+        self.place(&start);
+        if let Some(condition) = condition {
+            self.jump_unless(condition, &end)?;
+        }
+        self.loop_body(body, &next, &end)?;
+        self.place(&next);
+        if let Some(increment) = increment {
+            self.expression(increment)?;
+        }
+        self.emit(&format!("jmp {}", start));
+        self.place(&end);
+
+        Ok(())
+    }
+
+    /// Generates the code for the body of a loop.
+    ///
+    /// # Arguments
+    /// * `body` - The body of the loop.
+    /// * `next` - The label 'continue' jumps to.
+    /// * `end` - The label 'break' jumps to.
+    fn loop_body(&mut self, body: &Statement, next: &str, end: &str) -> Result<(), Error> {
+        self.frame.loops.push(LoopLabels {
+            next: next.to_string(),
+            end: end.to_string(),
+        });
+        let result = self.statement(body);
+        self.frame.loops.pop();
+
+        result
+    }
+
+    /// Generates a function as a routine of its own, after the ones generated so far.
+    /// Its parameters are moved from their registers to stack slots, as the registers are used by calls.
+    fn function(
+        &mut self,
+        name: &Token,
+        parameters: &[(Token, Token)],
+        body: &Statement,
+    ) -> Result<(), Error> {
+        if parameters.len() > ARGUMENT_REGISTERS.len() {
+            return Err(Error::TooManyArguments(
+                parameters.len(),
+                name.line,
+                name.column,
+            ));
+        }
+
+        let signature = self.signatures[&name.start].clone();
+        let frame = Frame {
+            id: self.frames,
+            ..Frame::default()
+        };
+        self.frames += 1;
+        let enclosing = std::mem::replace(&mut self.frame, frame);
+
+        // The parameters share the scope of the body, like they do in the resolver.
+        self.scopes.push(Vec::new());
+        for (register, kind) in ARGUMENT_REGISTERS.iter().zip(signature.parameters) {
+            let offset = offset(self.declare(kind));
+            self.emit(&format!("mov {}, {}(%rbp)", register, offset));
+        }
+        match body {
+            Statement::Block(statements, _) => self.statements(statements),
+            body => self.statements(std::slice::from_ref(body)),
+        }
+        self.scopes.pop();
+        // Functions without a 'return' at their end return none, which is 0.
+        self.emit("mov $0, %rax");
+
+        let frame = std::mem::replace(&mut self.frame, enclosing);
+
+        self.functions.push('\n');
+        self.functions.push_str(&routine(&signature.label, &frame));
+
+        Ok(())
+    }
+
+    /// Calls a function, passing the arguments in registers, leaving its return value in `rax`.
+    ///
+    /// # Returns
+    /// The kind of the return value.
+    fn call(
+        &mut self,
+        callee: &Expression,
+        parenthesis: &Token,
+        arguments: &[Expression],
+    ) -> Result<Kind, Error> {
+        let name = match callee {
+            Expression::Variable(name, _) => name,
+            callee => return Err(unsupported(callee.first_token())),
+        };
+        let signature = match self.binding(name)? {
+            // Returned strings are not literals, which are the only strings supported.
+            Binding::Function(signature) if signature.returns != Kind::String => signature.clone(),
+            _ => return Err(unsupported(name)),
+        };
+
+        if arguments.len() > ARGUMENT_REGISTERS.len() {
+            return Err(Error::TooManyArguments(
+                arguments.len(),
+                parenthesis.line,
+                parenthesis.column,
+            ));
+        }
+
+        // Evaluating an argument may call a function, which would overwrite the registers,
+        // so they are all evaluated onto the stack first.
+        for argument in arguments {
+            self.expression(argument)?;
+            self.push();
+        }
+        for register in ARGUMENT_REGISTERS[..arguments.len()].iter().rev() {
+            self.pop(register);
+        }
+
+        self.emit_call(&signature.label);
+
+        Ok(signature.returns)
+    }
+
+    /// Prints a value with `printf`, choosing the format by the kind of value.
+    /// Only integers, booleans, and string and float literals can be printed so far.
+    /// Other strings and floats cannot be computed yet, as their literals are only supported here.
+    /// Values are printed the way the interpreter prints them.
+    fn print(&mut self, value: &Expression) -> Result<(), Error> {
+        let format = match literal(value) {
+            Some(Literal::String(string)) => {
+                let label = self.string(string);
+                self.emit(&format!("lea {}(%rip), %rsi", label));
+
+                "%s\n"
+            }
+            // The text of a float is known before running, so it is printed as a string.
+            Some(Literal::Float(float)) => {
+                let label = self.string(&format!("{:?}", float));
+                self.emit(&format!("lea {}(%rip), %rsi", label));
+
+                "%s\n"
+            }
+            _ => match self.expression(value)? {
+                Kind::Int => {
+                    self.emit("mov %rax, %rsi");
+
+                    "%ld\n"
+                }
+                Kind::Boolean => {
+                    let (yes, no) = (self.string("true"), self.string("false"));
+                    self.emit(&format!("lea {}(%rip), %rsi", yes));
+                    self.emit(&format!("lea {}(%rip), %rcx", no));
+                    self.emit("cmp $0, %rax");
+                    self.emit("cmove %rcx, %rsi");
+
+                    "%s\n"
+                }
+                Kind::String => return Err(unsupported(value.first_token())),
+            },
+        };
+
+        // A variadic call takes the number of vector registers it uses in 'al', which is none.
+        self.emit("mov $0, %rax");
+        let label = self.string(format);
+        self.emit(&format!("lea {}(%rip), %rdi", label));
+        self.emit_call("printf@plt");
+
+        Ok(())
+    }
+
+    /// Defines read-only data, unless the same data has been defined already.
+    ///
+    /// # Arguments
+    /// * `directive` - The directive defining the data, such as `.string "a"`.
+    ///
+    /// # Returns
+    /// The label of the data.
+    fn constant(&mut self, directive: &str) -> String {
+        if let Some(label) = self.constants.get(directive) {
+            return label.clone();
+        }
+
+        let label = self.label();
+        write!(self.data, "{}:\n    {}\n", label, directive)
+            .expect("Writing to a string cannot fail.");
+        self.constants.insert(directive.to_string(), label.clone());
+
+        label
+    }
+
+    /// Defines a string as read-only data, terminated by a null byte.
+    ///
+    /// # Returns
+    /// The label of the string's first byte.
+    fn string(&mut self, string: &str) -> String {
+        self.constant(&format!(".string {}", quote(string)))
+    }
+
+    /// Jumps to a label if a condition is false.
+    /// Comparisons jump on their flags directly, instead of turning them into a boolean first.
+    fn jump_unless(&mut self, condition: &Expression, label: &str) -> Result<(), Error> {
+        match condition {
+            Expression::Grouping(condition, _) => self.jump_unless(condition, label),
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } if condition_codes(&operator.token_type).is_some() => {
+                let (_, negated) = self.compare(left, operator, right)?;
+                self.emit(&format!("j{} {}", negated, label));
+
+                Ok(())
+            }
+            condition => {
+                self.expression(condition)?;
+                self.emit("cmp $0, %rax");
+                self.emit(&format!("je {}", label));
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Compares two integers, or two booleans for equality, setting the flags for the condition codes it returns.
+    ///
+    /// # Returns
+    /// The condition codes that are true when the comparison is true and when it is false,
+    /// such as `l` and `ge` for `<`.
+    fn compare(
+        &mut self,
+        left: &Expression,
+        operator: &Token,
+        right: &Expression,
+    ) -> Result<(&'static str, &'static str), Error> {
+        let codes = condition_codes(&operator.token_type).ok_or_else(|| unsupported(operator))?;
+
+        // Strings would be compared by their addresses.
+        let comparable = match self.operands(left, right)? {
+            (Kind::Int, Kind::Int) => true,
+            (Kind::Boolean, Kind::Boolean) => matches!(
+                operator.token_type,
+                TokenType::EqualEqual | TokenType::BangEqual
+            ),
+            _ => false,
+        };
+        if !comparable {
+            return Err(unsupported(operator));
+        }
+        self.emit("cmp %rcx, %rax");
+
+        Ok(codes)
+    }
+
+    /// Creates a new label, unique within the program.
+    fn label(&mut self) -> String {
+        let label = format!(".L{}", self.labels);
+        self.labels += 1;
+
+        label
+    }
+
+    /// Places a label at the current position in the assembly.
+    fn place(&mut self, label: &str) {
+        writeln!(self.frame.assembly, "{}:", label).expect("Writing to a string cannot fail.");
+    }
+
+    /// Gives the next stack slot of the current function to a variable declared in the innermost scope.
+    ///
+    /// # Returns
+    /// The stack slot.
+    fn declare(&mut self, kind: Kind) -> usize {
+        let slot = self.frame.slots;
+        self.frame.slots += 1;
+        self.bind(Binding::Variable {
+            frame: self.frame.id,
+            slot,
+            kind,
+        });
+
+        slot
+    }
+
+    /// Adds a name to the innermost scope.
+    fn bind(&mut self, binding: Binding) {
+        self.scopes
+            .last_mut()
+            .expect("There is always a scope.")
+            .push(binding);
+    }
+
+    /// Finds what a name refers to.
+    ///
+    /// # Returns
+    /// The binding, or an error if the name was not resolved.
+    fn binding(&self, name: &Token) -> Result<&Binding, Error> {
+        self.resolution
+            .get(name)
+            .and_then(|slot| {
+                let scope = self.scopes.len().checked_sub(slot.depth + 1)?;
+
+                self.scopes[scope].get(slot.index)
+            })
+            .ok_or_else(|| unsupported(name))
+    }
+
+    /// Finds the stack slot of the variable a name refers to.
+    ///
+    /// # Returns
+    /// The slot's offset from the base pointer and the variable's kind,
+    /// or an error if the name is not a variable of the current function.
+    fn lookup(&self, name: &Token) -> Result<(isize, Kind), Error> {
+        match *self.binding(name)? {
+            Binding::Variable { frame, slot, kind } if frame == self.frame.id => {
+                Ok((offset(slot), kind))
+            }
+            Binding::Variable { .. } => Err(Error::Captured(
+                name.lexeme.to_string(),
+                name.line,
+                name.column,
+            )),
+            _ => Err(unsupported(name)),
+        }
+    }
+
+    /// Pushes `rax` onto the stack.
+    fn push(&mut self) {
+        self.emit("push %rax");
+        self.frame.temporaries += 1;
+    }
+
+    /// Pops the value on top of the stack into a register.
+    fn pop(&mut self, register: &str) {
+        self.emit(&format!("pop {}", register));
+        self.frame.temporaries -= 1;
+    }
+
+    /// Calls a routine, aligning the stack to 16 bytes as calls require.
+    fn emit_call(&mut self, label: &str) {
+        let misaligned = self.frame.temporaries % 2 == 1;
+        if misaligned {
+            self.emit("sub $8, %rsp");
+        }
+        self.emit(&format!("call {}", label));
+        if misaligned {
+            self.emit("add $8, %rsp");
+        }
+    }
+
+    /// Returns from the current function with the value in `rax`.
+    fn emit_return(&mut self) {
+        self.emit("mov %rbp, %rsp");
+        self.emit("pop %rbp");
+        self.emit("ret");
+    }
+
+    /// Adds an indented instruction to the assembly.
+    fn emit(&mut self, instruction: &str) {
+        writeln!(self.frame.assembly, "    {}", instruction)
+            .expect("Writing to a string cannot fail.");
+    }
+}
+
+/// Assembles a function from its label and body, adding the prologue and an epilogue returning `rax`.
+fn routine(label: &str, frame: &Frame) -> String {
+    let mut assembly = format!("{}:\n    push %rbp\n    mov %rsp, %rbp\n", label);
+
+    if frame.slots > 0 {
+        // The stack pointer is kept aligned to 16 bytes, as calls require.
+        writeln!(
+            assembly,
+            "    sub ${}, %rsp",
+            (frame.slots * 8).next_multiple_of(16)
+        )
+        .expect("Writing to a string cannot fail.");
+    }
+
+    assembly.push_str(&frame.assembly);
+    assembly.push_str("    mov %rbp, %rsp\n    pop %rbp\n    ret\n");
+
+    assembly
+}
+
+/// Gets the offset of a stack slot from the base pointer.
+fn offset(slot: usize) -> isize {
+    -8 * (slot as isize + 1)
+}
+
+/// Gets the condition codes of a comparison operator, which are signed as integers are.
+///
+/// # Returns
+/// The condition codes that are true when the comparison is true and when it is false,
+/// or `None` if the operator is not a comparison.
+fn condition_codes(operator: &TokenType) -> Option<(&'static str, &'static str)> {
+    match operator {
+        TokenType::EqualEqual => Some(("e", "ne")),
+        TokenType::BangEqual => Some(("ne", "e")),
+        TokenType::LessThan => Some(("l", "ge")),
+        TokenType::LessThanOrEqual => Some(("le", "g")),
+        TokenType::GreaterThan => Some(("g", "le")),
+        TokenType::GreaterThanOrEqual => Some(("ge", "l")),
+        _ => None,
+    }
+}
+
+/// Gets the kind of the values a type annotation allows.
+///
+/// # Returns
+/// The kind, or an error if values of the type cannot be compiled yet.
+fn kind(annotation: &Token) -> Result<Kind, Error> {
+    match Type::from_annotation(annotation) {
+        Ok(Type::Int) => Ok(Kind::Int),
+        Ok(Type::Boolean) => Ok(Kind::Boolean),
+        Ok(Type::String) => Ok(Kind::String),
+        _ => Err(unsupported(annotation)),
+    }
+}
+
+/// Gets the literal an expression consists of, looking through parentheses.
+fn literal(expression: &Expression) -> Option<&Literal> {
+    match expression {
+        Expression::Grouping(expression, _) => literal(expression),
+        Expression::Literal { value, .. } => Some(value),
+        _ => None,
+    }
+}
+
+/// Quotes a string for the assembler, escaping quotes, backslashes and every byte that is not printable ASCII.
+fn quote(string: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for byte in string.bytes() {
+        match byte {
+            b'"' => quoted.push_str("\\\""),
+            b'\\' => quoted.push_str("\\\\"),
+            b' '..=b'~' => quoted.push(char::from(byte)),
+            byte => write!(quoted, "\\{:03o}", byte).expect("Writing to a string cannot fail."),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+/// Creates the error for a token starting something that cannot be compiled yet.
+fn unsupported(token: &Token) -> Error {
+    Error::Unsupported(token.lexeme.to_string(), token.line, token.column)
+}
+
+/// Creates the errors for a program whose variables could not be resolved, one for each error of the resolver.
+pub(crate) fn unresolved(errors: Vec<semantic_analyzer::Error>) -> Vec<Error> {
+    errors
+        .iter()
+        .map(|error| {
+            let (line, column) = error.position();
+
+            Error::Unresolved(error.to_string(), line, column)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::parse;
+
+    /// Generates the assembly for the source code, without the parts every program has,
+    /// such as setting and returning its exit status, leaving only the body of `main`.
+    fn generate(source: &str) -> Result<Vec<String>, Vec<Error>> {
+        let statements = parse(source).expect("Parsing should succeed.");
+        let assembly = Generator::new(&statements).generate()?;
+
+        let lines = assembly.lines().map(str::trim).collect::<Vec<_>>();
+        let body = lines
+            .iter()
+            .position(|line| *line == "movq $0, -8(%rbp)")
+            .expect("The program should start with its default exit status.");
+        let end = lines
+            .iter()
+            .position(|line| *line == "mov -8(%rbp), %rax")
+            .expect("The program should end by returning its exit status.");
+        assert_eq!(lines[end + 1], "mov %rbp, %rsp");
+
+        Ok(lines[body + 1..end]
+            .iter()
+            .map(|line| line.to_string())
+            .collect())
+    }
+
+    #[test]
+    fn test_generate_skeleton() {
+        let statements = parse("").expect("Parsing should succeed.");
+
+        assert_eq!(
+            Generator::new(&statements).generate(),
+            Ok(concat!(
+                "    .data\n",
+                "\n",
+                "    .text\n",
+                "    .globl main\n",
+                "main:\n",
+                "    push %rbp\n",
+                "    mov %rsp, %rbp\n",
+                "    sub $16, %rsp\n",
+                "    movq $0, -8(%rbp)\n",
+                "    mov -8(%rbp), %rax\n",
+                "    mov %rbp, %rsp\n",
+                "    pop %rbp\n",
+                "    ret\n",
+                "\n",
+                "    .section .note.GNU-stack,\"\",@progbits\n",
+            )
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn test_generate_arithmetic() {
+        // The value of every expression statement is kept as the exit status.
+        assert_eq!(
+            generate("42;"),
+            Ok(vec![
+                "mov $42, %rax".to_string(),
+                "mov %rax, -8(%rbp)".to_string()
+            ])
+        );
+        assert_eq!(
+            generate("-(7);"),
+            Ok(["mov $7, %rax", "neg %rax", "mov %rax, -8(%rbp)"]
+                .map(String::from)
+                .to_vec())
+        );
+        assert_eq!(
+            generate("1 + 2 * 3;"),
+            Ok([
+                "mov $1, %rax",
+                "push %rax",
+                "mov $2, %rax",
+                "push %rax",
+                "mov $3, %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "imul %rcx, %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "add %rcx, %rax",
+                "mov %rax, -8(%rbp)",
+            ]
+            .map(String::from)
+            .to_vec())
+        );
+        assert_eq!(
+            generate("(10 - 4) / 3;"),
+            Ok([
+                "mov $10, %rax",
+                "push %rax",
+                "mov $4, %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "sub %rcx, %rax",
+                "push %rax",
+                "mov $3, %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "cqo",
+                "idiv %rcx",
+                "mov %rax, -8(%rbp)",
+            ]
+            .map(String::from)
+            .to_vec())
+        );
+    }
+
+    #[test]
+    fn test_generate_variables() {
+        let statements = parse("let a = 2; let b; { let a = a * 3; b = a; } a + b;")
+            .expect("Parsing should succeed.");
+        let assembly = Generator::new(&statements)
+            .generate()
+            .expect("Generating should succeed.");
+
+        // The exit status and three variables take 8 bytes each.
+        assert!(assembly.contains("    mov %rsp, %rbp\n    sub $32, %rsp\n"));
+        assert_eq!(
+            generate("let a = 2; let b; { let a = a * 3; b = a; } a + b;"),
+            Ok([
+                "mov $2, %rax",
+                "mov %rax, -16(%rbp)",
+                "mov $0, %rax",
+                "mov %rax, -24(%rbp)",
+                // The shadowing 'a' has a slot of its own.
+                "mov -16(%rbp), %rax",
+                "push %rax",
+                "mov $3, %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "imul %rcx, %rax",
+                "mov %rax, -32(%rbp)",
+                "mov -32(%rbp), %rax",
+                "mov %rax, -24(%rbp)",
+                "mov %rax, -8(%rbp)",
+                "mov -16(%rbp), %rax",
+                "push %rax",
+                "mov -24(%rbp), %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "add %rcx, %rax",
+                "mov %rax, -8(%rbp)",
+            ]
+            .map(String::from)
+            .to_vec())
+        );
+    }
+
+    #[test]
+    fn test_generate_comparisons() {
+        assert_eq!(
+            generate("2 <= 1;"),
+            Ok([
+                "mov $2, %rax",
+                "push %rax",
+                "mov $1, %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "cmp %rcx, %rax",
+                "setle %al",
+                "movzbq %al, %rax",
+                "mov %rax, -8(%rbp)",
+            ]
+            .map(String::from)
+            .to_vec())
+        );
+        assert_eq!(
+            generate("if (true) 1;"),
+            Ok([
+                "mov $1, %rax",
+                "cmp $0, %rax",
+                "je .L0",
+                "mov $1, %rax",
+                "mov %rax, -8(%rbp)",
+                ".L0:",
+            ]
+            .map(String::from)
+            .to_vec())
+        );
+    }
+
+    #[test]
+    fn test_generate_if() {
+        let lines = generate(
+            r#"
+            let a = 5;
+            if (a < 3) {
+                1;
+            } else if (a == 5) {
+                if (a != 0) 2; else 3;
+            } else {
+                4;
+            }
+            "#,
+        )
+        .expect("Generating should succeed.");
+
+        // Comparisons jump on the opposite condition to the else branch.
+        let jumps = lines
+            .iter()
+            .filter(|line| line.starts_with('j'))
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            jumps,
+            ["jge .L1", "jmp .L0", "jne .L3", "je .L5", "jmp .L4", "jmp .L2"]
+        );
+
+        let labels = lines
+            .iter()
+            .filter_map(|line| line.strip_suffix(':'))
+            .collect::<Vec<_>>();
+        assert_eq!(labels, [".L1", ".L5", ".L4", ".L3", ".L2", ".L0"]);
+
+        // Every label is placed once and jumped to.
+        for label in &labels {
+            assert_eq!(labels.iter().filter(|other| *other == label).count(), 1);
+            assert!(jumps
+                .iter()
+                .any(|jump| jump.ends_with(&format!(" {}", label))));
+        }
+    }
+
+    /// Gets the jumps and the labels placed, in order.
+    fn control_flow(source: &str) -> Vec<String> {
+        generate(source)
+            .expect("Generating should succeed.")
+            .into_iter()
+            .filter(|line| line.starts_with('j') || line.ends_with(':'))
+            .collect()
+    }
+
+    #[test]
+    fn test_generate_counting_loop() {
+        assert_eq!(
+            generate("let sum = 0; for (let i = 0; i < 3; i = i + 1) sum = sum + i;"),
+            Ok([
+                "mov $0, %rax",
+                "mov %rax, -16(%rbp)",
+                "mov $0, %rax",
+                "mov %rax, -24(%rbp)",
+                ".L0:",
+                "mov -24(%rbp), %rax",
+                "push %rax",
+                "mov $3, %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "cmp %rcx, %rax",
+                "jge .L2",
+                "mov -16(%rbp), %rax",
+                "push %rax",
+                "mov -24(%rbp), %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "add %rcx, %rax",
+                "mov %rax, -16(%rbp)",
+                "mov %rax, -8(%rbp)",
+                ".L1:",
+                "mov -24(%rbp), %rax",
+                "push %rax",
+                "mov $1, %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "add %rcx, %rax",
+                "mov %rax, -24(%rbp)",
+                "jmp .L0",
+                ".L2:",
+            ]
+            .map(String::from)
+            .to_vec())
+        );
+    }
+
+    #[test]
+    fn test_generate_break_and_continue() {
+        // 'continue' goes to the condition of a 'while' loop and the increment of a 'for' loop.
+        assert_eq!(
+            control_flow(
+                "let i = 0; while (i < 10) { i = i + 1; if (i == 2) continue; if (i == 5) break; }"
+            ),
+            [
+                ".L0:", "jge .L1", "jne .L2", "jmp .L0", ".L2:", "jne .L3", "jmp .L1", ".L3:",
+                "jmp .L0", ".L1:",
+            ]
+        );
+        assert_eq!(
+            control_flow("for (let i = 0; ; i = i + 1) { if (i == 2) continue; break; }"),
+            [".L0:", "jne .L3", "jmp .L1", ".L3:", "jmp .L2", ".L1:", "jmp .L0", ".L2:"]
+        );
+    }
+
+    #[test]
+    fn test_generate_nested_loops() {
+        // Each 'break' leaves its own loop only.
+        assert_eq!(
+            control_flow("loop { while (true) { break; } loop { break; } break; }"),
+            [
+                ".L0:", ".L2:", "je .L3", "jmp .L3", "jmp .L2", ".L3:", ".L4:", "jmp .L5",
+                "jmp .L4", ".L5:", "jmp .L1", "jmp .L0", ".L1:",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_functions() {
+        let source = "fn add(a: int, b: int) -> int { return a + b; }\n\
+                      fn nothing() {}\n\
+                      let x = add(1, 2);";
+        let statements = parse(source).expect("Parsing should succeed.");
+        let assembly = Generator::new(&statements)
+            .generate()
+            .expect("Generating should succeed.");
+
+        // The arguments are evaluated onto the stack, then popped into their registers.
+        assert_eq!(
+            generate(source),
+            Ok([
+                "mov $1, %rax",
+                "push %rax",
+                "mov $2, %rax",
+                "push %rax",
+                "pop %rsi",
+                "pop %rdi",
+                "call cpl_add_0",
+                "mov %rax, -16(%rbp)",
+            ]
+            .map(String::from)
+            .to_vec())
+        );
+
+        let lines = assembly.lines().map(str::trim).collect::<Vec<_>>();
+        let start = lines
+            .iter()
+            .position(|line| *line == "cpl_add_0:")
+            .expect("The function should have a routine.");
+        assert_eq!(
+            lines[start..],
+            [
+                "cpl_add_0:",
+                "push %rbp",
+                "mov %rsp, %rbp",
+                "sub $16, %rsp",
+                "mov %rdi, -8(%rbp)",
+                "mov %rsi, -16(%rbp)",
+                "mov -8(%rbp), %rax",
+                "push %rax",
+                "mov -16(%rbp), %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "add %rcx, %rax",
+                "mov %rbp, %rsp",
+                "pop %rbp",
+                "ret",
+                "mov $0, %rax",
+                "mov %rbp, %rsp",
+                "pop %rbp",
+                "ret",
+                "",
+                "cpl_nothing_1:",
+                "push %rbp",
+                "mov %rsp, %rbp",
+                "mov $0, %rax",
+                "mov %rbp, %rsp",
+                "pop %rbp",
+                "ret",
+                "",
+                ".section .note.GNU-stack,\"\",@progbits",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_call_alignment() {
+        assert_eq!(
+            generate("fn one() -> int { return 1; } one() + 2;"),
+            Ok([
+                "call cpl_one_0",
+                "push %rax",
+                "mov $2, %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "add %rcx, %rax",
+                "mov %rax, -8(%rbp)",
+            ]
+            .map(String::from)
+            .to_vec())
+        );
+        // The call happens with one value pushed, so the stack is realigned around it.
+        assert_eq!(
+            generate("fn one() -> int { return 1; } 2 + one();")
+                .expect("Generating should succeed.")[2..5],
+            ["sub $8, %rsp", "call cpl_one_0", "add $8, %rsp"]
+        );
+    }
+
+    #[test]
+    fn test_generate_print() {
+        // Nothing is left on the stack around the calls, so it is already aligned for them.
+        assert_eq!(
+            generate("print 1 + 2; print \"a\"; print (2.5); print 3; print 1 < 2;").unwrap(),
+            [
+                "mov $1, %rax",
+                "push %rax",
+                "mov $2, %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "add %rcx, %rax",
+                "mov %rax, %rsi",
+                "mov $0, %rax",
+                "lea .L0(%rip), %rdi",
+                "call printf@plt",
+                "lea .L1(%rip), %rsi",
+                "mov $0, %rax",
+                "lea .L2(%rip), %rdi",
+                "call printf@plt",
+                // Floats are printed as strings, and booleans choose between two.
+                "lea .L3(%rip), %rsi",
+                "mov $0, %rax",
+                "lea .L2(%rip), %rdi",
+                "call printf@plt",
+                "mov $3, %rax",
+                "mov %rax, %rsi",
+                "mov $0, %rax",
+                "lea .L0(%rip), %rdi",
+                "call printf@plt",
+                "mov $1, %rax",
+                "push %rax",
+                "mov $2, %rax",
+                "mov %rax, %rcx",
+                "pop %rax",
+                "cmp %rcx, %rax",
+                "setl %al",
+                "movzbq %al, %rax",
+                "lea .L4(%rip), %rsi",
+                "lea .L5(%rip), %rcx",
+                "cmp $0, %rax",
+                "cmove %rcx, %rsi",
+                "mov $0, %rax",
+                "lea .L2(%rip), %rdi",
+                "call printf@plt",
+            ]
+        );
+
+        let statements =
+            parse("print 2.0; print 1e16; print true;").expect("Parsing should succeed.");
+        let assembly = Generator::new(&statements)
+            .generate()
+            .expect("Generating should succeed.");
+        for text in ["2.0", "1e16", "true", "false"] {
+            assert!(assembly.contains(&format!("    .string \"{}\"\n", text)));
+        }
+    }
+
+    #[test]
+    fn test_generate_print_data() {
+        let statements = parse(r#"print "tab\t \"quoted\" \\ é"; print "tab\t \"quoted\" \\ é";"#)
+            .expect("Parsing should succeed.");
+        let assembly = Generator::new(&statements)
+            .generate()
+            .expect("Generating should succeed.");
+
+        // Equal strings are only defined once, and bytes that are not printable ASCII are escaped.
+        assert!(assembly.contains(concat!(
+            "    .section .rodata\n",
+            ".L0:\n",
+            r#"    .string "tab\011 \"quoted\" \\ \303\251""#,
+            "\n",
+            ".L1:\n",
+            "    .string \"%s\\012\"\n",
+            "\n",
+            "    .text\n",
+        )));
+    }
+
+    #[test]
+    fn test_generate_strings() {
+        let statements = parse(r#"let a = "line\n\"quoted\""; let b = "line\n\"quoted\"";"#)
+            .expect("Parsing should succeed.");
+        let assembly = Generator::new(&statements)
+            .generate()
+            .expect("Generating should succeed.");
+
+        // The string is only defined once, but its address is loaded by both declarations.
+        assert!(assembly.contains(concat!(
+            "    .section .rodata\n",
+            ".L0:\n",
+            r#"    .string "line\012\"quoted\"""#,
+            "\n",
+            "\n",
+            "    .text\n",
+        )));
+        assert_eq!(assembly.matches("    lea .L0(%rip), %rax\n").count(), 2);
+    }
+
+    #[test]
+    fn test_generate_unsupported() {
+        assert_eq!(
+            generate("1;\nassert(true);"),
+            Err(vec![Error::UnsupportedConstruct(
+                "Assertions",
+                Span {
+                    start: 3,
+                    end: 16,
+                    line: 2,
+                    column: 1
+                }
+            )])
+        );
+        assert_eq!(
+            generate("print 1.5 * 2;"),
+            Err(vec![Error::Unsupported("1.5".to_string(), 1, 7)])
+        );
+        assert_eq!(
+            generate("1 + 2.5;"),
+            Err(vec![Error::Unsupported("2.5".to_string(), 1, 5)])
+        );
+        assert_eq!(
+            generate("1 && 2;"),
+            Err(vec![Error::Unsupported("&&".to_string(), 1, 3)])
+        );
+        assert_eq!(
+            generate("!true;"),
+            Err(vec![Error::Unsupported("!".to_string(), 1, 1)])
+        );
+        assert_eq!(
+            generate("let a = clock;"),
+            Err(vec![Error::Unsupported("clock".to_string(), 1, 9)])
+        );
+        assert_eq!(
+            generate("let a = 1; fn f() -> int { return a; }"),
+            Err(vec![Error::Captured("a".to_string(), 1, 35)])
+        );
+        assert_eq!(
+            generate("fn f(a: int, b: int, c: int, d: int, e: int, f: int, g: int) {}"),
+            Err(vec![Error::TooManyArguments(7, 1, 4)])
+        );
+        assert_eq!(
+            generate("a;"),
+            Err(vec![Error::Unresolved(
+                "Variable 'a' is not defined".to_string(),
+                1,
+                1
+            )])
+        );
+    }
+
+    #[test]
+    fn test_generate_unsupported_kinds() {
+        assert_eq!(
+            generate("let s = \"hi\"; print s;"),
+            Err(vec![Error::Unsupported("s".to_string(), 1, 21)])
+        );
+        assert_eq!(
+            generate("print \"a\" + \"b\";"),
+            Err(vec![Error::Unsupported("+".to_string(), 1, 11)])
+        );
+        assert_eq!(
+            generate("fn f() -> string { return \"x\"; } print f();"),
+            Err(vec![Error::Unsupported("f".to_string(), 1, 40)])
+        );
+        assert_eq!(
+            generate("\"b\" < \"a\";"),
+            Err(vec![Error::Unsupported("<".to_string(), 1, 5)])
+        );
+        assert_eq!(
+            generate("true < false;"),
+            Err(vec![Error::Unsupported("<".to_string(), 1, 6)])
+        );
+        assert_eq!(
+            generate("let a = 1; a = true;"),
+            Err(vec![Error::Unsupported("a".to_string(), 1, 12)])
+        );
+        assert_eq!(
+            generate("let x: float = 1;"),
+            Err(vec![Error::Unsupported("float".to_string(), 1, 8)])
+        );
+        assert_eq!(
+            generate("fn f(c: char) {}"),
+            Err(vec![Error::Unsupported("char".to_string(), 1, 9)])
+        );
+        assert!(generate("let s = \"hi\"; let b: bool; true == (1 < 2);").is_ok());
+    }
+
+    #[test]
+    fn test_generate_reports_every_unsupported_statement() {
+        let statements =
+            parse("let t = (1, 2);\nprint 1;\nlet a = none ?? 1;\nfn f() { assert(true); }")
+                .expect("Parsing should succeed.");
+        let errors = Generator::new(&statements)
+            .generate()
+            .expect_err("Generating should fail.");
+
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| (error.position(), error.to_string()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    (1, 9),
+                    "Tuples are not supported by this backend yet".to_string()
+                ),
+                (
+                    (3, 9),
+                    "Null-coalescing expressions are not supported by this backend yet".to_string()
+                ),
+                (
+                    (4, 10),
+                    "Assertions are not supported by this backend yet".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::lang::errors::{Error, Severity};
+use crate::lang::lexer::{Literal, Token};
+use crate::lang::parse;
+use crate::lang::parser::Statement;
+use crate::lang::span::Span;
+
+/// Replaces the includes of a program with the statements of the files they include, recursively.
+/// The included statements come before the program's own, in the order they are included,
+/// and a file that has already been included is skipped.
+/// A file included with 'as' is placed in a module of that name instead, every time it is included.
+///
+/// The lines of each included file are numbered on from the last line of the files before it,
+/// so the positions of the errors found in the program can be traced back to their files with the returned `Sources`.
+///
+/// # Arguments
+/// * `statements` - The program.
+/// * `file` - The file the program was read from, or `None` to resolve paths from the current directory.
+///
+/// # Returns
+/// The program without includes and the lines its included files were moved to,
+/// or the errors found reading, scanning or parsing the included files.
+///
+/// # Examples
+/// ```
+/// use cpl::lang::includes;
+/// use cpl::lang::parse;
+///
+/// let statements = parse("include \"missing.cpl\";").unwrap();
+/// let errors = includes::resolve(statements, None).unwrap_err();
+///
+/// assert!(errors[0].message.starts_with("Cannot include 'missing.cpl'"));
+/// ```
+pub fn resolve(
+    mut statements: Vec<Statement>,
+    file: Option<&Path>,
+) -> Result<(Vec<Statement>, Sources), Vec<Error>> {
+    let mut last_line = 0;
+    for statement in &mut statements {
+        statement.update_positions(
+            &mut |token| last_line = last_line.max(token.line),
+            &mut |_| {},
+        );
+    }
+
+    let mut includer = Includer {
+        stack: Vec::new(),
+        included: HashSet::new(),
+        sources: Sources::default(),
+        next_line: last_line + 1,
+        errors: Vec::new(),
+    };
+
+    // The program's own file is included already, so including it again is a cycle.
+    if let Some((file, Ok(canonical))) = file.map(|file| (file, file.canonicalize())) {
+        includer.included.insert(canonical.clone());
+        includer.stack.push((canonical, file.display().to_string()));
+    }
+
+    let directory = file.and_then(Path::parent).unwrap_or(Path::new(""));
+    let statements = includer.resolve_statements(statements, directory);
+
+    if includer.errors.is_empty() {
+        Ok((statements, includer.sources))
+    } else {
+        Err(includer.sources.locate(includer.errors))
+    }
+}
+
+/// The lines the statements of the included files were moved to.
+#[derive(Debug, Clone, Default)]
+pub struct Sources {
+    /// The first line of each included file, in increasing order, with the path it was included by.
+    files: Vec<(usize, String)>,
+}
+
+impl Sources {
+    /// Moves errors in included files back to their lines in those files, naming the files in their messages.
+    /// The other errors are in the program's own file, and are left as they are.
+    pub fn locate(&self, errors: Vec<Error>) -> Vec<Error> {
+        errors
+            .into_iter()
+            .map(|error| {
+                let Some((first, name)) = self
+                    .files
+                    .iter()
+                    .rev()
+                    .find(|(first, _)| error.line >= *first)
+                else {
+                    return error;
+                };
+
+                Error {
+                    line: error.line - first + 1,
+                    message: format!("In '{}': {}", name, error.message),
+                    ..error
+                }
+            })
+            .collect()
+    }
+}
+
+/// Resolves the includes of a program and the files it includes.
+#[derive(Debug)]
+struct Includer {
+    /// The files being included, outermost first, with the paths they were included by.
+    stack: Vec<(PathBuf, String)>,
+    /// Every file included so far.
+    included: HashSet<PathBuf>,
+    /// The lines the included files were moved to.
+    sources: Sources,
+    /// The line the next included file starts at.
+    next_line: usize,
+    errors: Vec<Error>,
+}
+
+impl Includer {
+    /// Moves the statements of the files included by the given statements in front of them.
+    ///
+    /// # Arguments
+    /// * `statements` - The statements of a file.
+    /// * `directory` - The directory containing the file.
+    fn resolve_statements(
+        &mut self,
+        statements: Vec<Statement>,
+        directory: &Path,
+    ) -> Vec<Statement> {
+        let (includes, statements): (Vec<_>, Vec<_>) = statements
+            .into_iter()
+            .partition(|statement| matches!(statement, Statement::Include { .. }));
+
+        let mut resolved = Vec::new();
+        for include in includes {
+            if let Statement::Include {
+                path, alias, span, ..
+            } = include
+            {
+                resolved.extend(self.include(&path, alias, span, directory));
+            }
+        }
+        resolved.extend(statements);
+
+        resolved
+    }
+
+    /// Reads and parses an included file, along with the files it includes.
+    ///
+    /// # Arguments
+    /// * `path` - The string literal holding the path of the file.
+    /// * `alias` - The name of the module to place the statements in, if any.
+    /// * `span` - The span of the include statement, which the module is given.
+    /// * `directory` - The directory the path is relative to.
+    ///
+    /// # Returns
+    /// The statements of the file, or none if it could not be included or already has been.
+    fn include(
+        &mut self,
+        path: &Token,
+        alias: Option<Token>,
+        span: Span,
+        directory: &Path,
+    ) -> Vec<Statement> {
+        let Some(Literal::String(name)) = &path.literal else {
+            unreachable!("Included paths are string literals.");
+        };
+
+        let file = match directory.join(name).canonicalize() {
+            Ok(file) => file,
+            Err(error) => {
+                self.error(path, format!("Cannot include '{}': {}", name, error));
+                return Vec::new();
+            }
+        };
+
+        if let Some(start) = self
+            .stack
+            .iter()
+            .position(|(including, _)| *including == file)
+        {
+            let cycle = self.stack[start..]
+                .iter()
+                .map(|(_, including)| including.as_str())
+                .chain([name.as_str()])
+                .collect::<Vec<_>>();
+            self.error(path, format!("Include cycle: {}", cycle.join(" -> ")));
+
+            return Vec::new();
+        }
+
+        // Modules are namespaces of their own, so the same file can be placed in several.
+        if alias.is_none() && !self.included.insert(file.clone()) {
+            return Vec::new();
+        }
+
+        let source = match std::fs::read_to_string(&file) {
+            Ok(source) => source,
+            Err(error) => {
+                self.error(path, format!("Cannot include '{}': {}", name, error));
+                return Vec::new();
+            }
+        };
+
+        // The lines of the file follow the ones of the files before it, so its errors can be told apart from theirs.
+        let shift = self.next_line - 1;
+        self.sources.files.push((self.next_line, name.clone()));
+        self.next_line += source.lines().count() + 1;
+
+        let mut statements = match parse(&source) {
+            Ok(statements) => statements,
+            Err(errors) => {
+                self.errors.extend(errors.into_iter().map(|error| Error {
+                    line: error.line + shift,
+                    ..error
+                }));
+
+                return Vec::new();
+            }
+        };
+        for statement in &mut statements {
+            statement.update_positions(&mut |token| token.line += shift, &mut |span| {
+                span.line += shift
+            });
+        }
+
+        let directory = file.parent().unwrap_or(Path::new("")).to_path_buf();
+        self.stack.push((file, name.clone()));
+        let statements = self.resolve_statements(statements, &directory);
+        self.stack.pop();
+
+        match alias {
+            Some(name) => vec![Statement::Module {
+                name,
+                statements,
+                span,
+            }],
+            None => statements,
+        }
+    }
+
+    fn error(&mut self, path: &Token, message: String) {
+        self.errors.push(Error {
+            line: path.line,
+            column: path.column,
+            message,
+            severity: Severity::Error,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::semantic_analyzer::Analyzer;
+
+    /// Writes the files to a new directory, named after the test so tests running at once do not clash.
+    fn write_files(test: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("cpl-includes-{}-{}", test, std::process::id()));
+        std::fs::create_dir_all(&directory).expect("Creating the directory should succeed.");
+
+        for (name, source) in files {
+            let file = directory.join(name);
+            std::fs::create_dir_all(file.parent().unwrap())
+                .expect("Creating the directory should succeed.");
+            std::fs::write(file, source).expect("Writing the file should succeed.");
+        }
+
+        directory
+    }
+
+    /// Parses a file and resolves its includes.
+    fn resolve_file(file: &Path) -> Result<Vec<String>, Vec<String>> {
+        let source = std::fs::read_to_string(file).expect("Reading the file should succeed.");
+        let statements = parse(&source).expect("Parsing should succeed.");
+
+        resolve(statements, Some(file))
+            .map(|(statements, _)| statements.iter().map(Statement::to_string).collect())
+            .map_err(|errors| errors.into_iter().map(|error| error.message).collect())
+    }
+
+    #[test]
+    fn test_include() {
+        let directory = write_files(
+            "include",
+            &[
+                (
+                    "main.cpl",
+                    "print add(1, 2);\ninclude \"lib/math.cpl\";\ninclude \"lib/math.cpl\";",
+                ),
+                (
+                    "lib/math.cpl",
+                    "include \"../consts.cpl\";\nfn add(a: i32, b: i32) -> int { return a + b; }",
+                ),
+                ("consts.cpl", "const one = 1;"),
+            ],
+        );
+
+        // Included files are relative to the including file, and only included once.
+        assert_eq!(
+            resolve_file(&directory.join("main.cpl")),
+            Ok(vec![
+                "(const one 1)".to_string(),
+                "(fn add(a, b) -> int (block (ret return (+ a b))))".to_string(),
+                "(print (add(1, 2)))".to_string(),
+            ])
+        );
+
+        std::fs::remove_dir_all(directory).expect("Removing the directory should succeed.");
+    }
+
+    #[test]
+    fn test_include_as_module() {
+        let directory = write_files(
+            "include-as-module",
+            &[
+                (
+                    "main.cpl",
+                    "include \"math.cpl\" as math;\ninclude \"math.cpl\";\ninclude \"math.cpl\" as other;",
+                ),
+                ("math.cpl", "const pi = 3;"),
+            ],
+        );
+
+        assert_eq!(
+            resolve_file(&directory.join("main.cpl")),
+            Ok(vec![
+                "(module math (const pi 3))".to_string(),
+                "(const pi 3)".to_string(),
+                "(module other (const pi 3))".to_string(),
+            ])
+        );
+
+        std::fs::remove_dir_all(directory).expect("Removing the directory should succeed.");
+    }
+
+    #[test]
+    fn test_include_errors() {
+        let directory = write_files(
+            "include-errors",
+            &[
+                ("self.cpl", "include \"self.cpl\";"),
+                ("a.cpl", "include \"b.cpl\";"),
+                ("b.cpl", "include \"a.cpl\";"),
+                ("missing.cpl", "print 1;\ninclude \"nothing.cpl\";"),
+                ("broken.cpl", "include \"syntax.cpl\";"),
+                ("syntax.cpl", "let = 1;"),
+            ],
+        );
+
+        let errors = |file: &str| resolve_file(&directory.join(file)).unwrap_err();
+        let self_cycle = format!(
+            "Include cycle: {} -> self.cpl",
+            directory.join("self.cpl").display()
+        );
+        assert_eq!(errors("self.cpl"), [self_cycle]);
+        let cycle = format!(
+            "In 'b.cpl': Include cycle: {} -> b.cpl -> a.cpl",
+            directory.join("a.cpl").display()
+        );
+        assert_eq!(errors("a.cpl"), [cycle]);
+
+        let missing = errors("missing.cpl");
+        assert_eq!(missing.len(), 1);
+        assert!(
+            missing[0].starts_with("Cannot include 'nothing.cpl': "),
+            "{}",
+            missing[0]
+        );
+
+        let syntax = errors("broken.cpl");
+        assert_eq!(syntax.len(), 1);
+        assert!(
+            syntax[0].starts_with("In 'syntax.cpl': Expected"),
+            "{}",
+            syntax[0]
+        );
+
+        std::fs::remove_dir_all(directory).expect("Removing the directory should succeed.");
+    }
+
+    #[test]
+    fn test_errors_in_included_files() {
+        let directory = write_files(
+            "errors-in-included-files",
+            &[
+                (
+                    "main.cpl",
+                    "include \"a.cpl\";\ninclude \"b.cpl\";\nprint nothere;",
+                ),
+                ("a.cpl", "// a\n// b\n\nprint missing;"),
+                ("b.cpl", "include \"c.cpl\";\nprint also_missing;"),
+                ("c.cpl", "\nprint gone;"),
+            ],
+        );
+
+        let file = directory.join("main.cpl");
+        let source = std::fs::read_to_string(&file).expect("Reading the file should succeed.");
+        let (statements, sources) = resolve(
+            parse(&source).expect("Parsing should succeed."),
+            Some(&file),
+        )
+        .expect("Including the files should succeed.");
+        let errors = sources.locate(Analyzer::new().analyze(&statements));
+
+        // The errors are at their positions in the files they are in, which the messages name.
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| (error.line, error.column, error.message.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (3, 7, "Variable 'nothere' is not defined"),
+                (4, 7, "In 'a.cpl': Variable 'missing' is not defined"),
+                (2, 7, "In 'b.cpl': Variable 'also_missing' is not defined"),
+                (2, 7, "In 'c.cpl': Variable 'gone' is not defined"),
+            ]
+        );
+
+        std::fs::remove_dir_all(directory).expect("Removing the directory should succeed.");
+    }
+}
@@ -0,0 +1,109 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// A handle to an interned string, which is cheap to copy and compare.
+/// Equal strings are interned as the same symbol, but symbols are only valid on the thread that created them.
+/// The marker keeps symbols from being sent to or shared with other threads, which do not know them.
+///
+/// # Examples
+/// ```compile_fail
+/// use cpl::lang::interner::Symbol;
+///
+/// fn send<T: Send>(_: T) {}
+///
+/// send(Symbol::intern("count"));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32, PhantomData<Rc<()>>);
+
+impl Symbol {
+    /// Interns a string.
+    ///
+    /// # Arguments
+    /// * `string` - The string to intern.
+    ///
+    /// # Returns
+    /// The symbol of the string, which is the same for every call with an equal string.
+    ///
+    /// # Examples
+    /// ```
+    /// use cpl::lang::interner::Symbol;
+    ///
+    /// let symbol = Symbol::intern("count");
+    ///
+    /// assert_eq!(symbol, Symbol::intern("count"));
+    /// assert_ne!(symbol, Symbol::intern("total"));
+    /// assert_eq!(&*symbol.as_str(), "count");
+    /// ```
+    pub fn intern(string: &str) -> Self {
+        INTERNER.with(|interner| interner.borrow_mut().intern(string))
+    }
+
+    /// Gets the string the symbol was interned from.
+    pub fn as_str(self) -> Rc<str> {
+        INTERNER.with(|interner| interner.borrow().resolve(self))
+    }
+}
+
+impl Debug for Symbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Symbol").field(&self.0).finish()
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+thread_local! {
+    static INTERNER: RefCell<Interner> = RefCell::new(Interner::default());
+}
+
+/// The strings interned so far, stored once each.
+#[derive(Debug, Default)]
+struct Interner {
+    symbols: HashMap<Rc<str>, Symbol>,
+    strings: Vec<Rc<str>>,
+}
+
+impl Interner {
+    fn intern(&mut self, string: &str) -> Symbol {
+        if let Some(symbol) = self.symbols.get(string) {
+            return *symbol;
+        }
+
+        let symbol = Symbol(
+            u32::try_from(self.strings.len()).expect("Interned more than u32::MAX strings."),
+            PhantomData,
+        );
+        let string: Rc<str> = Rc::from(string);
+        self.strings.push(Rc::clone(&string));
+        self.symbols.insert(string, symbol);
+
+        symbol
+    }
+
+    fn resolve(&self, symbol: Symbol) -> Rc<str> {
+        Rc::clone(&self.strings[symbol.0 as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interned_strings_are_shared() {
+        let first = Symbol::intern("shared");
+        let second = Symbol::intern("shared");
+
+        assert_eq!(first, second);
+        assert!(Rc::ptr_eq(&first.as_str(), &second.as_str()));
+        assert_eq!(Symbol::intern("other").to_string(), "other");
+    }
+}
@@ -0,0 +1,1379 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::io::{Stdout, Write};
+use std::rc::Rc;
+
+use crate::lang::errors;
+use crate::lang::lexer::{Literal, Token, TokenType};
+use crate::lang::natives::{self, NativeFunction};
+use crate::lang::parser::{Expression, Statement};
+use crate::lang::semantic_analyzer::Type;
+use crate::lang::{MAX_CALL_DEPTH, MAX_NESTING_DEPTH};
+
+/// An error that occurred while running a program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A variable that does not exist was accessed, with its name, line and column.
+    UndefinedVariable(String, usize, usize),
+    /// An operation was applied to values of the wrong type, with a description, line and column.
+    TypeError(String, usize, usize),
+    /// A value that is not a function was called, with the line and column of the call.
+    NotCallable(usize, usize),
+    /// A function was called with the wrong number of arguments,
+    /// with the expected and given counts, line and column.
+    ArgumentCount(usize, usize, usize, usize),
+    /// The maximum call or nesting depth was exceeded, with the function's name and the line and column of the call.
+    StackOverflow(String, usize, usize),
+    /// A native function failed, with its name, the reason, and the line and column of the call.
+    NativeFunction(String, String, usize, usize),
+    /// An integer was divided by zero, with the line and column of the operator.
+    DivisionByZero(usize, usize),
+    /// The result of integer arithmetic does not fit in an integer,
+    /// with the operator, line and column.
+    IntegerOverflow(String, usize, usize),
+    /// An assertion's condition was falsy, with its message if it has one,
+    /// and the line and column of the 'assert' keyword.
+    AssertionFailed(Option<String>, usize, usize),
+    /// A value could not be printed, such as when the output has been closed,
+    /// with the reason, and the line and column of the 'print' statement.
+    Output(String, usize, usize),
+}
+
+impl Error {
+    /// Gets the line and column the error occurred at.
+    pub fn position(&self) -> (usize, usize) {
+        match self {
+            Error::UndefinedVariable(_, line, column)
+            | Error::TypeError(_, line, column)
+            | Error::NotCallable(line, column)
+            | Error::ArgumentCount(_, _, line, column)
+            | Error::StackOverflow(_, line, column)
+            | Error::NativeFunction(_, _, line, column)
+            | Error::DivisionByZero(line, column)
+            | Error::IntegerOverflow(_, line, column)
+            | Error::AssertionFailed(_, line, column)
+            | Error::Output(_, line, column) => (*line, *column),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::UndefinedVariable(name, ..) => write!(f, "Undefined variable '{}'", name),
+            Error::TypeError(message, ..) => write!(f, "{}", message),
+            Error::NotCallable(..) => write!(f, "Can only call functions"),
+            Error::ArgumentCount(expected, found, ..) => {
+                write!(f, "Expected {} arguments but got {}", expected, found)
+            }
+            Error::StackOverflow(name, ..) => {
+                write!(f, "Stack overflow while calling '{}'", name)
+            }
+            Error::NativeFunction(name, message, ..) => write!(f, "{} in '{}'", message, name),
+            Error::DivisionByZero(..) => write!(f, "Cannot divide an integer by zero"),
+            Error::IntegerOverflow(operator, ..) => {
+                write!(f, "Integer overflow in '{}'", operator)
+            }
+            Error::AssertionFailed(Some(message), ..) => {
+                write!(f, "Assertion failed: {}", message)
+            }
+            Error::AssertionFailed(None, ..) => write!(f, "Assertion failed"),
+            Error::Output(reason, ..) => write!(f, "Cannot print the value: {}", reason),
+        }
+    }
+}
+
+impl From<Error> for errors::Error {
+    fn from(error: Error) -> Self {
+        let (line, column) = error.position();
+
+        Self {
+            line,
+            column,
+            message: error.to_string(),
+            severity: errors::Severity::Error,
+        }
+    }
+}
+
+/// A function defined in CPL.
+#[derive(Debug)]
+pub struct Function {
+    name: String,
+    /// The names of the parameters, with their type annotations.
+    parameters: Vec<(Token, Token)>,
+    /// Whether the function is declared to return a float, so integers it returns are converted.
+    returns_float: bool,
+    body: Statement,
+    /// The environment the function was declared in.
+    closure: Rc<RefCell<Environment>>,
+}
+
+/// A module declared in the program, holding the variables declared within it.
+#[derive(Debug)]
+pub struct Module {
+    name: String,
+    environment: Rc<RefCell<Environment>>,
+}
+
+/// A value produced by running a program.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    String(String),
+    Char(char),
+    Boolean(bool),
+    None,
+    Function(Rc<Function>),
+    Native(Rc<NativeFunction>),
+    Module(Rc<Module>),
+    Tuple(Rc<[Value]>),
+}
+
+impl Value {
+    /// Checks if the value counts as true in a condition.
+    /// Only `false` and `none` are falsy.
+    pub fn is_truthy(&self) -> bool {
+        !matches!(self, Value::Boolean(false) | Value::None)
+    }
+
+    /// Gets the name of the value's type, as the analyzer names it.
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::String(_) => "string",
+            Value::Char(_) => "char",
+            Value::Boolean(_) => "boolean",
+            Value::None => "none",
+            Value::Function(_) | Value::Native(_) => "function",
+            Value::Module(_) => "module",
+            Value::Tuple(_) => "tuple",
+        }
+    }
+
+    /// Checks if the value can be compared with another one for equality,
+    /// which requires them to have the same type, to both be numbers, or for either to be none.
+    fn is_comparable(&self, other: &Self) -> bool {
+        self.type_name() == other.type_name()
+            || (self.as_float().is_some() && other.as_float().is_some())
+            || matches!(self, Value::None)
+            || matches!(other, Value::None)
+    }
+
+    /// Gets the value of a number as a float, converting integers.
+    ///
+    /// # Returns
+    /// The float, or `None` if the value is not a number.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Int(int) => Some(*int as f64),
+            Value::Float(float) => Some(*float),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Int(left), Value::Int(right)) => left == right,
+            // Integers are converted to floats when compared with them.
+            (Value::Int(_) | Value::Float(_), Value::Int(_) | Value::Float(_)) => {
+                self.as_float() == other.as_float()
+            }
+            (Value::String(left), Value::String(right)) => left == right,
+            (Value::Char(left), Value::Char(right)) => left == right,
+            (Value::Boolean(left), Value::Boolean(right)) => left == right,
+            (Value::None, Value::None) => true,
+            (Value::Function(left), Value::Function(right)) => Rc::ptr_eq(left, right),
+            (Value::Native(left), Value::Native(right)) => Rc::ptr_eq(left, right),
+            (Value::Module(left), Value::Module(right)) => Rc::ptr_eq(left, right),
+            (Value::Tuple(left), Value::Tuple(right)) => left == right,
+            _ => false,
+        }
+    }
+}
+
+/// Numbers are written in the shortest form that reads back as the same number.
+/// Floats always have a decimal point or an exponent, so they can be told apart from integers:
+/// `3.0` and `3.5` are written as such, and floats from 1e16 upwards or below 1e-4 use an exponent, like `1e16`.
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Int(int) => write!(f, "{}", int),
+            Value::Float(float) => write!(f, "{:?}", float),
+            Value::String(string) => write!(f, "{}", string),
+            Value::Char(c) => write!(f, "{}", c),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::None => write!(f, "none"),
+            Value::Function(function) => write!(f, "<fn {}>", function.name),
+            Value::Native(function) => write!(f, "<native fn {}>", function.name),
+            Value::Module(module) => write!(f, "<module {}>", module.name),
+            // A tuple of one value is written with a trailing comma, like in the source code.
+            Value::Tuple(values) => match values.as_ref() {
+                [value] => write!(f, "({},)", value),
+                values => {
+                    write!(f, "(")?;
+                    for (i, value) in values.iter().enumerate() {
+                        if i > 0 {
+                            write!(f, ", ")?;
+                        }
+
+                        write!(f, "{}", value)?;
+                    }
+                    write!(f, ")")
+                }
+            },
+        }
+    }
+}
+
+impl From<&Literal> for Value {
+    fn from(literal: &Literal) -> Self {
+        match literal {
+            Literal::String(string) => Value::String(string.clone()),
+            Literal::Char(c) => Value::Char(*c),
+            Literal::Int(int) => Value::Int(*int),
+            Literal::Float(float) => Value::Float(*float),
+            Literal::Boolean(boolean) => Value::Boolean(*boolean),
+            Literal::None => Value::None,
+        }
+    }
+}
+
+/// The variables of one scope, linked to the scope enclosing it.
+#[derive(Debug, Default)]
+pub struct Environment {
+    values: HashMap<String, Value>,
+    /// The variables that hold floats, so integers assigned to them are converted like in the generated code.
+    floats: HashSet<String>,
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    /// Creates a new scope inside the given one.
+    fn new_enclosed(enclosing: &Rc<RefCell<Environment>>) -> Rc<RefCell<Self>> {
+        Rc::new(RefCell::new(Self {
+            values: HashMap::new(),
+            floats: HashSet::new(),
+            enclosing: Some(Rc::clone(enclosing)),
+        }))
+    }
+
+    fn define(&mut self, name: &str, value: Value) {
+        self.define_as(name, value, false);
+    }
+
+    /// Defines a variable, which holds floats if its value is one or it is declared as one.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the variable.
+    /// * `value` - The value of the variable, which is converted to a float if the variable holds floats.
+    /// * `float` - Whether the variable is annotated as a float.
+    fn define_as(&mut self, name: &str, value: Value, float: bool) {
+        if float || matches!(value, Value::Float(_)) {
+            self.floats.insert(name.to_string());
+        } else {
+            self.floats.remove(name);
+        }
+
+        let value = if float { to_float(value) } else { value };
+        self.values.insert(name.to_string(), value);
+    }
+
+    fn get(&self, name: &Token) -> Result<Value, Error> {
+        match self.values.get(&*name.lexeme) {
+            Some(value) => Ok(value.clone()),
+            None => match &self.enclosing {
+                Some(enclosing) => enclosing.borrow().get(name),
+                None => Err(Error::UndefinedVariable(
+                    name.lexeme.to_string(),
+                    name.line,
+                    name.column,
+                )),
+            },
+        }
+    }
+
+    /// Assigns a value to a variable, converting integers to floats if the variable holds floats.
+    ///
+    /// # Returns
+    /// The value that was assigned, or an error if the variable is not defined.
+    fn assign(&mut self, name: &Token, value: Value) -> Result<Value, Error> {
+        match self.values.get_mut(&*name.lexeme) {
+            Some(slot) => {
+                *slot = if self.floats.contains(&*name.lexeme) {
+                    to_float(value)
+                } else {
+                    value
+                };
+
+                Ok(slot.clone())
+            }
+            None => match &self.enclosing {
+                Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+                None => Err(Error::UndefinedVariable(
+                    name.lexeme.to_string(),
+                    name.line,
+                    name.column,
+                )),
+            },
+        }
+    }
+}
+
+/// How control leaves a statement.
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+    Return(Value),
+}
+
+/// Runs a syntax tree directly.
+pub struct Interpreter<W: Write = Stdout> {
+    environment: Rc<RefCell<Environment>>,
+    /// The number of function calls currently being run.
+    call_depth: usize,
+    max_call_depth: usize,
+    /// The number of statements and expressions currently being run, across every call.
+    /// Each of them takes some of the stack, so calls nested too deeply in them overflow it as well.
+    depth: usize,
+    max_depth: usize,
+    /// Where printed values are written to.
+    output: W,
+}
+
+impl Interpreter<Stdout> {
+    /// Creates a new interpreter that prints to the standard output.
+    pub fn new() -> Self {
+        Self::with_output(std::io::stdout())
+    }
+}
+
+impl Default for Interpreter<Stdout> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W: Write> Interpreter<W> {
+    /// Creates a new interpreter that prints to the given writer.
+    ///
+    /// # Arguments
+    /// * `output` - Where printed values are written to.
+    pub fn with_output(output: W) -> Self {
+        let mut interpreter = Self {
+            environment: Rc::new(RefCell::new(Environment::default())),
+            call_depth: 0,
+            max_call_depth: MAX_CALL_DEPTH,
+            depth: 0,
+            max_depth: MAX_NESTING_DEPTH,
+            output,
+        };
+
+        for function in natives::standard_library() {
+            interpreter.define_native(function);
+        }
+
+        interpreter
+    }
+
+    /// Defines a native function in the global scope.
+    ///
+    /// # Arguments
+    /// * `function` - The function to define.
+    pub fn define_native(&mut self, function: NativeFunction) {
+        let name = function.name.clone();
+
+        self.environment
+            .borrow_mut()
+            .define(&name, Value::Native(Rc::new(function)));
+    }
+
+    /// Sets the maximum number of nested function calls.
+    ///
+    /// # Arguments
+    /// * `max_call_depth` - The maximum call depth.
+    pub fn with_max_call_depth(mut self, max_call_depth: usize) -> Self {
+        self.max_call_depth = max_call_depth;
+
+        self
+    }
+
+    /// Sets the maximum number of statements and expressions that calls can be nested in,
+    /// counting the ones of every function being called.
+    ///
+    /// # Arguments
+    /// * `max_depth` - The maximum nesting depth.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+
+        self
+    }
+
+    /// Gets the writer printed values are written to.
+    pub fn output(&self) -> &W {
+        &self.output
+    }
+
+    /// Runs the given statements.
+    ///
+    /// # Arguments
+    /// * `statements` - The statements to run.
+    ///
+    /// # Returns
+    /// The error that stopped the program, if any.
+    pub fn interpret(&mut self, statements: &[Statement]) -> Result<(), Error> {
+        self.declare_functions(statements);
+
+        for statement in statements {
+            self.execute(statement)?;
+        }
+
+        Ok(())
+    }
+
+    /// Defines the functions declared among the statements in the current scope,
+    /// so they can be called before their declaration like the analyzer allows.
+    fn declare_functions(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            if let Statement::Function {
+                name,
+                parameters,
+                return_type,
+                body,
+                ..
+            } = statement
+            {
+                let function = Function {
+                    name: name.lexeme.to_string(),
+                    parameters: parameters.clone(),
+                    returns_float: is_float(return_type.as_ref()),
+                    body: *body.clone(),
+                    closure: Rc::clone(&self.environment),
+                };
+
+                self.environment
+                    .borrow_mut()
+                    .define(&name.lexeme, Value::Function(Rc::new(function)));
+            }
+        }
+    }
+
+    fn execute(&mut self, statement: &Statement) -> Result<Flow, Error> {
+        self.depth += 1;
+        let flow = self.execute_inner(statement);
+        self.depth -= 1;
+
+        flow
+    }
+
+    fn execute_inner(&mut self, statement: &Statement) -> Result<Flow, Error> {
+        match statement {
+            Statement::Expression(expression, _) => {
+                self.evaluate(expression)?;
+            }
+            Statement::Print(expression, span) => {
+                let value = self.evaluate(expression)?;
+
+                writeln!(self.output, "{}", value)
+                    .map_err(|error| Error::Output(error.to_string(), span.line, span.column))?;
+            }
+            Statement::Variable {
+                name,
+                declared_type,
+                initializer,
+                ..
+            } => {
+                let value = match initializer {
+                    Some(initializer) => self.evaluate(initializer)?,
+                    None => Value::None,
+                };
+
+                self.environment.borrow_mut().define_as(
+                    &name.lexeme,
+                    value,
+                    is_float(declared_type.as_ref()),
+                );
+            }
+            Statement::Destructure {
+                names, initializer, ..
+            } => {
+                let position = initializer.first_token();
+                let values = match self.evaluate(initializer)? {
+                    Value::Tuple(values) if values.len() == names.len() => values,
+                    Value::Tuple(values) => {
+                        return Err(Error::TypeError(
+                            format!(
+                                "Cannot destructure a tuple of {} values into {} variables",
+                                values.len(),
+                                names.len()
+                            ),
+                            position.line,
+                            position.column,
+                        ));
+                    }
+                    value => {
+                        return Err(Error::TypeError(
+                            format!(
+                                "Cannot destructure {}, only tuples can be destructured",
+                                value.type_name()
+                            ),
+                            position.line,
+                            position.column,
+                        ));
+                    }
+                };
+
+                for (name, value) in names.iter().zip(values.iter()) {
+                    self.environment
+                        .borrow_mut()
+                        .define(&name.lexeme, value.clone());
+                }
+            }
+            Statement::Block(statements, _) => {
+                let environment = Environment::new_enclosed(&self.environment);
+
+                return self.execute_block(statements, environment);
+            }
+            Statement::Module {
+                name, statements, ..
+            } => {
+                // The module's variables outlive its statements, so they can be accessed through its name.
+                let environment = Environment::new_enclosed(&self.environment);
+                self.execute_block(statements, Rc::clone(&environment))?;
+
+                let module = Module {
+                    name: name.lexeme.to_string(),
+                    environment,
+                };
+                self.environment
+                    .borrow_mut()
+                    .define(&name.lexeme, Value::Module(Rc::new(module)));
+            }
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                if self.evaluate(condition)?.is_truthy() {
+                    return self.execute(then_branch);
+                } else if let Some(else_branch) = else_branch {
+                    return self.execute(else_branch);
+                }
+            }
+            Statement::While {
+                condition, body, ..
+            } => {
+                while self.evaluate(condition)?.is_truthy() {
+                    match self.execute(body)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Normal | Flow::Continue => {}
+                    }
+                }
+            }
+            Statement::Loop { body, .. } => loop {
+                match self.execute(body)? {
+                    Flow::Break => break,
+                    Flow::Return(value) => return Ok(Flow::Return(value)),
+                    Flow::Normal | Flow::Continue => {}
+                }
+            },
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            } => {
+                let environment = Environment::new_enclosed(&self.environment);
+                let previous = std::mem::replace(&mut self.environment, environment);
+                let result = self.execute_for(
+                    initializer.as_deref(),
+                    condition.as_ref(),
+                    increment.as_ref(),
+                    body,
+                );
+                self.environment = previous;
+
+                return result;
+            }
+            // Functions are defined when the scope they are declared in is entered,
+            // and includes are replaced by the included statements before running.
+            Statement::Function { .. } | Statement::Include { .. } | Statement::Comment { .. } => {}
+            Statement::Return { value, .. } => {
+                let value = match value {
+                    Some(value) => self.evaluate(value)?,
+                    None => Value::None,
+                };
+
+                return Ok(Flow::Return(value));
+            }
+            Statement::Assert {
+                keyword,
+                condition,
+                message,
+                ..
+            } => {
+                if !self.evaluate(condition)?.is_truthy() {
+                    // The message is only evaluated when the assertion fails.
+                    let message = match message {
+                        Some(message) => Some(self.evaluate(message)?.to_string()),
+                        None => None,
+                    };
+
+                    return Err(Error::AssertionFailed(
+                        message,
+                        keyword.line,
+                        keyword.column,
+                    ));
+                }
+            }
+            Statement::Break { .. } => return Ok(Flow::Break),
+            Statement::Continue { .. } => return Ok(Flow::Continue),
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    /// Runs the statements in the given environment, restoring the current one afterwards.
+    fn execute_block(
+        &mut self,
+        statements: &[Statement],
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<Flow, Error> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+        self.declare_functions(statements);
+
+        let mut result = Ok(Flow::Normal);
+        for statement in statements {
+            result = self.execute(statement);
+
+            if !matches!(result, Ok(Flow::Normal)) {
+                break;
+            }
+        }
+
+        self.environment = previous;
+
+        result
+    }
+
+    fn execute_for(
+        &mut self,
+        initializer: Option<&Statement>,
+        condition: Option<&Expression>,
+        increment: Option<&Expression>,
+        body: &Statement,
+    ) -> Result<Flow, Error> {
+        if let Some(initializer) = initializer {
+            self.execute(initializer)?;
+        }
+
+        loop {
+            if let Some(condition) = condition {
+                if !self.evaluate(condition)?.is_truthy() {
+                    break;
+                }
+            }
+
+            match self.execute(body)? {
+                Flow::Break => break,
+                Flow::Return(value) => return Ok(Flow::Return(value)),
+                Flow::Normal | Flow::Continue => {}
+            }
+
+            if let Some(increment) = increment {
+                self.evaluate(increment)?;
+            }
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    fn evaluate(&mut self, expression: &Expression) -> Result<Value, Error> {
+        self.depth += 1;
+        let value = self.evaluate_inner(expression);
+        self.depth -= 1;
+
+        value
+    }
+
+    fn evaluate_inner(&mut self, expression: &Expression) -> Result<Value, Error> {
+        match expression {
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => self.evaluate_binary(left, operator, right),
+            Expression::Grouping(expression, _) => self.evaluate(expression),
+            Expression::TypeOf(expression, _) => {
+                // Booleans are named like in type annotations, rather than like in error messages.
+                let name = match self.evaluate(expression)? {
+                    Value::Boolean(_) => "bool",
+                    value => value.type_name(),
+                };
+
+                Ok(Value::String(name.to_string()))
+            }
+            Expression::Tuple(elements, _) => Ok(Value::Tuple(
+                elements
+                    .iter()
+                    .map(|element| self.evaluate(element))
+                    .collect::<Result<_, _>>()?,
+            )),
+            Expression::Literal { value, .. } => Ok(Value::from(value)),
+            Expression::Unary {
+                operator, right, ..
+            } => {
+                let right = self.evaluate(right)?;
+
+                match (&operator.token_type, right) {
+                    (TokenType::Bang, right) => Ok(Value::Boolean(!right.is_truthy())),
+                    (TokenType::Minus, Value::Int(int)) => {
+                        int.checked_neg().map(Value::Int).ok_or_else(|| {
+                            Error::IntegerOverflow(
+                                operator.lexeme.to_string(),
+                                operator.line,
+                                operator.column,
+                            )
+                        })
+                    }
+                    (TokenType::Minus, Value::Float(float)) => Ok(Value::Float(-float)),
+                    _ => Err(Error::TypeError(
+                        format!("Operand of '{}' must be a number", operator.lexeme),
+                        operator.line,
+                        operator.column,
+                    )),
+                }
+            }
+            Expression::Variable(name, _) => self.environment.borrow().get(name),
+            Expression::Qualified { module, name, .. } => {
+                let value = self.environment.borrow().get(module)?;
+
+                match value {
+                    Value::Module(value) => value
+                        .environment
+                        .borrow()
+                        .values
+                        .get(&*name.lexeme)
+                        .cloned()
+                        .ok_or_else(|| {
+                            Error::UndefinedVariable(
+                                format!("{}.{}", module.lexeme, name.lexeme),
+                                name.line,
+                                name.column,
+                            )
+                        }),
+                    value => Err(Error::TypeError(
+                        format!(
+                            "'{}' is a {}, not a module",
+                            module.lexeme,
+                            value.type_name()
+                        ),
+                        module.line,
+                        module.column,
+                    )),
+                }
+            }
+            Expression::Assign { name, value, .. } => {
+                let value = self.evaluate(value)?;
+
+                self.environment.borrow_mut().assign(name, value)
+            }
+            Expression::Call {
+                callee,
+                parenthesis,
+                arguments,
+                ..
+            } => {
+                let callee = self.evaluate(callee)?;
+                let arguments = arguments
+                    .iter()
+                    .map(|argument| self.evaluate(argument))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                self.call(callee, parenthesis, arguments)
+            }
+            Expression::Coalesce { left, right, .. } => match self.evaluate(left)? {
+                Value::None => self.evaluate(right),
+                left => Ok(left),
+            },
+        }
+    }
+
+    fn evaluate_binary(
+        &mut self,
+        left: &Expression,
+        operator: &Token,
+        right: &Expression,
+    ) -> Result<Value, Error> {
+        let left = self.evaluate(left)?;
+
+        // The logical operators only evaluate their right side when needed.
+        match operator.token_type {
+            TokenType::LogicalAnd if !left.is_truthy() => return Ok(Value::Boolean(false)),
+            TokenType::LogicalOr if left.is_truthy() => return Ok(Value::Boolean(true)),
+            TokenType::LogicalAnd | TokenType::LogicalOr => {
+                return Ok(Value::Boolean(self.evaluate(right)?.is_truthy()));
+            }
+            _ => {}
+        }
+
+        let right = self.evaluate(right)?;
+
+        match (&operator.token_type, left, right) {
+            (TokenType::EqualEqual | TokenType::BangEqual, left, right)
+                if !left.is_comparable(&right) =>
+            {
+                Err(Error::TypeError(
+                    format!(
+                        "Cannot compare {} and {} with '{}'",
+                        left.type_name(),
+                        right.type_name(),
+                        operator.lexeme
+                    ),
+                    operator.line,
+                    operator.column,
+                ))
+            }
+            (TokenType::EqualEqual, left, right) => Ok(Value::Boolean(left == right)),
+            (TokenType::BangEqual, left, right) => Ok(Value::Boolean(left != right)),
+            (TokenType::Plus, Value::String(left), Value::String(right)) => {
+                Ok(Value::String(left + &right))
+            }
+            // Strings are ordered lexicographically, by their characters' code points.
+            (token_type, Value::String(left), Value::String(right)) if is_ordering(token_type) => {
+                Ok(Value::Boolean(match token_type {
+                    TokenType::GreaterThan => left > right,
+                    TokenType::GreaterThanOrEqual => left >= right,
+                    TokenType::LessThan => left < right,
+                    _ => left <= right,
+                }))
+            }
+            // Powers are always floats, so integers can have negative exponents.
+            (
+                TokenType::StarStar,
+                left @ (Value::Int(_) | Value::Float(_)),
+                right @ (Value::Int(_) | Value::Float(_)),
+            ) => Ok(Value::Float(
+                left.as_float()
+                    .unwrap_or_default()
+                    .powf(right.as_float().unwrap_or_default()),
+            )),
+            (_, Value::Int(left), Value::Int(right)) => integer_binary(operator, left, right),
+            // Mixing integers and floats converts the integers to floats.
+            (
+                token_type,
+                left @ (Value::Int(_) | Value::Float(_)),
+                right @ (Value::Int(_) | Value::Float(_)),
+            ) => {
+                let (left, right) = (
+                    left.as_float().unwrap_or_default(),
+                    right.as_float().unwrap_or_default(),
+                );
+
+                match token_type {
+                    TokenType::Plus => Ok(Value::Float(left + right)),
+                    TokenType::Minus => Ok(Value::Float(left - right)),
+                    TokenType::Star => Ok(Value::Float(left * right)),
+                    TokenType::Slash => Ok(Value::Float(left / right)),
+                    TokenType::Percent => Ok(Value::Float(left % right)),
+                    TokenType::GreaterThan => Ok(Value::Boolean(left > right)),
+                    TokenType::GreaterThanOrEqual => Ok(Value::Boolean(left >= right)),
+                    TokenType::LessThan => Ok(Value::Boolean(left < right)),
+                    TokenType::LessThanOrEqual => Ok(Value::Boolean(left <= right)),
+                    _ => unreachable!("The parser only produces known binary operators."),
+                }
+            }
+            (TokenType::Plus, Value::String(_), other) | (TokenType::Plus, other, Value::String(_)) => {
+                Err(Error::TypeError(
+                    format!(
+                        "Operands of '+' must be two numbers or two strings, convert the {} with 'str' first",
+                        other.type_name()
+                    ),
+                    operator.line,
+                    operator.column,
+                ))
+            }
+            (token_type, ..) if *token_type == TokenType::Plus || is_ordering(token_type) => {
+                Err(Error::TypeError(
+                    format!(
+                        "Operands of '{}' must be two numbers or two strings",
+                        operator.lexeme
+                    ),
+                    operator.line,
+                    operator.column,
+                ))
+            }
+            _ => Err(Error::TypeError(
+                format!("Operands of '{}' must be numbers", operator.lexeme),
+                operator.line,
+                operator.column,
+            )),
+        }
+    }
+
+    /// Calls a function.
+    ///
+    /// # Arguments
+    /// * `callee` - The value being called.
+    /// * `parenthesis` - The call's closing parenthesis, used for error positions.
+    /// * `arguments` - The evaluated arguments.
+    fn call(
+        &mut self,
+        callee: Value,
+        parenthesis: &Token,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Error> {
+        let function = match callee {
+            Value::Function(function) => function,
+            Value::Native(function) => {
+                if arguments.len() != function.arity {
+                    return Err(Error::ArgumentCount(
+                        function.arity,
+                        arguments.len(),
+                        parenthesis.line,
+                        parenthesis.column,
+                    ));
+                }
+
+                return (function.function)(&arguments).map_err(|message| {
+                    Error::NativeFunction(
+                        function.name.clone(),
+                        message,
+                        parenthesis.line,
+                        parenthesis.column,
+                    )
+                });
+            }
+            _ => return Err(Error::NotCallable(parenthesis.line, parenthesis.column)),
+        };
+
+        if arguments.len() != function.parameters.len() {
+            return Err(Error::ArgumentCount(
+                function.parameters.len(),
+                arguments.len(),
+                parenthesis.line,
+                parenthesis.column,
+            ));
+        }
+
+        // The syntax tree is only so deep, so checking at every call bounds the stack used between them.
+        if self.call_depth >= self.max_call_depth || self.depth >= self.max_depth {
+            return Err(Error::StackOverflow(
+                function.name.clone(),
+                parenthesis.line,
+                parenthesis.column,
+            ));
+        }
+
+        // The parameters share the scope of the body, like in the analyzer.
+        let environment = Environment::new_enclosed(&function.closure);
+        for ((parameter, annotation), argument) in function.parameters.iter().zip(arguments) {
+            environment.borrow_mut().define_as(
+                &parameter.lexeme,
+                argument,
+                is_float(Some(annotation)),
+            );
+        }
+
+        let body = match &function.body {
+            Statement::Block(statements, _) => statements.as_slice(),
+            body => std::slice::from_ref(body),
+        };
+
+        self.call_depth += 1;
+        let result = self.execute_block(body, environment);
+        self.call_depth -= 1;
+
+        match result? {
+            Flow::Return(value) if function.returns_float => Ok(to_float(value)),
+            Flow::Return(value) => Ok(value),
+            _ => Ok(Value::None),
+        }
+    }
+}
+
+/// Checks if a type annotation names a float.
+fn is_float(annotation: Option<&Token>) -> bool {
+    annotation.is_some_and(|annotation| Type::from_annotation(annotation) == Ok(Type::Float))
+}
+
+/// Converts an integer to a float, leaving other values as they are.
+fn to_float(value: Value) -> Value {
+    match value {
+        Value::Int(int) => Value::Float(int as f64),
+        value => value,
+    }
+}
+
+/// Checks if an operator orders its operands, which works on numbers and strings.
+fn is_ordering(token_type: &TokenType) -> bool {
+    matches!(
+        token_type,
+        TokenType::GreaterThan
+            | TokenType::GreaterThanOrEqual
+            | TokenType::LessThan
+            | TokenType::LessThanOrEqual
+    )
+}
+
+/// Applies an arithmetic or comparison operator to two integers.
+/// Division rounds towards zero, so the remainder has the sign of the dividend,
+/// and results that do not fit in an integer are errors.
+fn integer_binary(operator: &Token, left: i64, right: i64) -> Result<Value, Error> {
+    let result = match operator.token_type {
+        TokenType::Plus => left.checked_add(right),
+        TokenType::Minus => left.checked_sub(right),
+        TokenType::Star => left.checked_mul(right),
+        TokenType::Slash | TokenType::Percent if right == 0 => {
+            return Err(Error::DivisionByZero(operator.line, operator.column));
+        }
+        TokenType::Slash => left.checked_div(right),
+        TokenType::Percent => left.checked_rem(right),
+        TokenType::GreaterThan => return Ok(Value::Boolean(left > right)),
+        TokenType::GreaterThanOrEqual => return Ok(Value::Boolean(left >= right)),
+        TokenType::LessThan => return Ok(Value::Boolean(left < right)),
+        TokenType::LessThanOrEqual => return Ok(Value::Boolean(left <= right)),
+        _ => unreachable!("The parser only produces known binary operators."),
+    };
+
+    result.map(Value::Int).ok_or_else(|| {
+        Error::IntegerOverflow(operator.lexeme.to_string(), operator.line, operator.column)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::{parse, with_stack};
+
+    /// Runs the source code and returns what it printed.
+    fn run(source: &str) -> Result<String, Error> {
+        let statements = parse(source).expect("Parsing should succeed.");
+        let mut interpreter = Interpreter::with_output(Vec::new());
+        interpreter.interpret(&statements)?;
+
+        Ok(String::from_utf8(interpreter.output().clone()).expect("Output should be UTF-8."))
+    }
+
+    #[test]
+    fn test_interpret() {
+        assert_eq!(
+            run(r#"
+                fn fib(n: i32) {
+                    if (n < 2) return n;
+                    return fib(n - 1) + fib(n - 2);
+                }
+
+                let i = 0;
+                loop {
+                    i = i + 1;
+                    if (i == 2) continue;
+                    if (i > 4) break;
+                    print fib(i * 3);
+                }
+                print "done" + "!";
+                "#),
+            Ok("2\n34\n144\ndone!\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_loop_break_and_continue() {
+        // 'continue' restarts the loop, and 'break' only exits the innermost loop.
+        assert_eq!(
+            run(r#"
+                let i = 0;
+                loop {
+                    i = i + 1;
+                    if (i == 2 || i == 4) continue;
+                    if (i > 5) break;
+
+                    let j = 0;
+                    loop {
+                        j = j + 1;
+                        if (j > i) break;
+                    }
+                    print i + j;
+                }
+                print i;
+                "#),
+            Ok("3\n7\n11\n6\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_for_continue_runs_increment() {
+        // Skipping the increment on 'continue' would never let the loop finish.
+        assert_eq!(
+            run(r#"
+                let count = 0;
+                for (let i = 0; i < 10; i = i + 1) {
+                    if (i == 3 || i == 7) continue;
+                    count = count + 1;
+                }
+                print count;
+                "#),
+            Ok("8\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_int_and_float() {
+        assert_eq!(
+            run("print 5 / 2; print -7 / 2; print 5 / 2.0; print 1 + 0.5; print 2 == 2.0; print 1 < 1.5;"),
+            Ok("2\n-3\n2.5\n1.5\ntrue\ntrue\n".to_string())
+        );
+        assert_eq!(run("print 1 / 0;"), Err(Error::DivisionByZero(1, 9)));
+        assert_eq!(
+            run("print 7 % 3; print -7 % 3; print 7 % -3; print 7.5 % 2; print 7 % 2.5; print 2 + 7 % 4 * 2;"),
+            Ok("1\n-1\n1\n1.5\n2.0\n8\n".to_string())
+        );
+        assert_eq!(run("print 1 % 0;"), Err(Error::DivisionByZero(1, 9)));
+        assert_eq!(
+            run("print -9223372036854775807 - 1 % -1;"),
+            Ok("-9223372036854775807\n".to_string())
+        );
+        assert_eq!(
+            run("let min = -9223372036854775807 - 1; print min % -1;"),
+            Err(Error::IntegerOverflow("%".to_string(), 1, 47))
+        );
+        assert_eq!(
+            run("print 9223372036854775807 + 1;"),
+            Err(Error::IntegerOverflow("+".to_string(), 1, 27))
+        );
+    }
+
+    #[test]
+    fn test_int_to_float_conversion() {
+        // Integers become floats where floats are declared, like in the generated code.
+        assert_eq!(
+            run("let x: float = 5; print x / 2; let y: float; y = 3; print y;"),
+            Ok("2.5\n3.0\n".to_string())
+        );
+        assert_eq!(
+            run("fn half(x: float) -> float { return x / 2; } print half(5);"),
+            Ok("2.5\n".to_string())
+        );
+        assert_eq!(
+            run("fn one() -> float { return 1; } print one(); print typeof one();"),
+            Ok("1.0\nfloat\n".to_string())
+        );
+        // A variable holding a float keeps holding floats.
+        assert_eq!(
+            run("let x = 1.5; x = 2; print x; print (x = 3) / 2;"),
+            Ok("2.0\n1.5\n".to_string())
+        );
+        assert_eq!(run("let x = 1; print x / 2;"), Ok("0\n".to_string()));
+    }
+
+    #[test]
+    fn test_power() {
+        assert_eq!(
+            run("print 2 ** 3 ** 2; print 2 ** 9; print -2 ** 2; print 2 ** -1; print 1.5 ** 2;"),
+            Ok("512.0\n512.0\n-4.0\n0.5\n2.25\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_print_numbers() {
+        assert_eq!(
+            run("print 3; print -3; print 3.0; print 3.5; print 0.1 + 0.2; print 2.5e-3; print 9223372036854775807;"),
+            Ok("3\n-3\n3.0\n3.5\n0.30000000000000004\n0.0025\n9223372036854775807\n".to_string())
+        );
+        assert_eq!(
+            run("print 1e15; print 1e16; print 1.5e300; print 0.0001; print 0.00001; print -1.5e-7;"),
+            Ok("1000000000000000.0\n1e16\n1.5e300\n0.0001\n1e-5\n-1.5e-7\n".to_string())
+        );
+        assert_eq!(
+            run("print 1.0 / 0; print -1.0 / 0; print 0.0 / 0; print str(2.0) + str(2);"),
+            Ok("inf\n-inf\nNaN\n2.02\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_string_operators() {
+        assert_eq!(
+            run(
+                r#"print "a" + "b"; print "a" < "b"; print "b" <= "a"; print "Z" < "a"; print "ab" > "a"; print "a" != "a";"#
+            ),
+            Ok("ab\ntrue\nfalse\ntrue\ntrue\nfalse\n".to_string())
+        );
+        assert_eq!(
+            run(r#"print "a" + 1;"#),
+            Err(Error::TypeError(
+                "Operands of '+' must be two numbers or two strings, convert the int with 'str' first"
+                    .to_string(),
+                1,
+                11
+            ))
+        );
+        assert_eq!(
+            run(r#"print "a" == 1;"#),
+            Err(Error::TypeError(
+                "Cannot compare string and int with '=='".to_string(),
+                1,
+                11
+            ))
+        );
+        assert_eq!(
+            run(r#"print "a" * "b";"#),
+            Err(Error::TypeError(
+                "Operands of '*' must be numbers".to_string(),
+                1,
+                11
+            ))
+        );
+        assert_eq!(run(r#"print "a" == none;"#), Ok("false\n".to_string()));
+    }
+
+    #[test]
+    fn test_assert() {
+        assert_eq!(
+            run("let a = 1; assert(a == 1); assert(a, \"a is falsy\"); print a;"),
+            Ok("1\n".to_string())
+        );
+        assert_eq!(
+            run("let a = 1;\nassert(a == 2, \"a is \" + str(a));"),
+            Err(Error::AssertionFailed(Some("a is 1".to_string()), 2, 1))
+        );
+        assert_eq!(
+            run("assert(none);"),
+            Err(Error::AssertionFailed(None, 1, 1))
+        );
+        assert_eq!(
+            Error::AssertionFailed(Some("a is 1".to_string()), 2, 1).to_string(),
+            "Assertion failed: a is 1"
+        );
+    }
+
+    #[test]
+    fn test_modules() {
+        let source = "module math {\n    const two = 2;\n    fn double(x: int) -> int { return x * two; }\n}\nfn double(x: int) -> int { return x + x + 1; }\nprint math.double(5);\nprint double(5);\nprint math;";
+        assert_eq!(run(source), Ok("10\n11\n<module math>\n".to_string()));
+
+        assert_eq!(
+            run("module m {}\nprint m.missing;"),
+            Err(Error::UndefinedVariable("m.missing".to_string(), 2, 9))
+        );
+        assert_eq!(
+            run("let m = 1;\nprint m.a;"),
+            Err(Error::TypeError(
+                "'m' is a int, not a module".to_string(),
+                2,
+                7
+            ))
+        );
+    }
+
+    #[test]
+    fn test_tuples() {
+        assert_eq!(
+            run("let (a, b) = (1, \"b\"); print a; print b; print (a + 1, b, (none,)); print (1, 2) == (1, 2);"),
+            Ok("1\nb\n(2, b, (none,))\ntrue\n".to_string())
+        );
+        assert_eq!(
+            run("let t = (1, 2);\nlet (a, b, c) = t;"),
+            Err(Error::TypeError(
+                "Cannot destructure a tuple of 2 values into 3 variables".to_string(),
+                2,
+                17
+            ))
+        );
+    }
+
+    #[test]
+    fn test_typeof() {
+        assert_eq!(run("print typeof 5;"), Ok("int\n".to_string()));
+        assert_eq!(run("print typeof \"x\";"), Ok("string\n".to_string()));
+        assert_eq!(
+            run("fn f() {} print typeof 1.5; print typeof true; print typeof none; print typeof f; print typeof len;"),
+            Ok("float\nbool\nnone\nfunction\nfunction\n".to_string())
+        );
+        assert_eq!(
+            run("print typeof typeof 'c'; print typeof (1,) == \"tuple\";"),
+            Ok("string\ntrue\n".to_string())
+        );
+    }
+
+    /// An output that has been closed, like a pipe whose reader has exited.
+    struct Closed;
+
+    impl Write for Closed {
+        fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_closed_output() {
+        let statements = parse("let a = 1;\nprint a;").expect("Parsing should succeed.");
+        let mut interpreter = Interpreter::with_output(Closed);
+
+        assert!(matches!(
+            interpreter.interpret(&statements),
+            Err(Error::Output(_, 2, 1))
+        ));
+    }
+
+    #[test]
+    fn test_stack_overflow() {
+        // Run with the stack the command line uses, as the default limits are meant for it.
+        let result = with_stack(|| run("fn f() {\n    return f();\n}\nf();"));
+        assert_eq!(result, Err(Error::StackOverflow("f".to_string(), 2, 14)));
+
+        let result = with_stack(|| run("fn f(n: int) -> int { return f(n + 1); }\nprint f(0);"));
+        assert_eq!(result, Err(Error::StackOverflow("f".to_string(), 1, 37)));
+
+        // Deeply nested bodies reach the nesting depth before the call depth.
+        let source = format!(
+            "fn f(n: int) -> int {{ {}return f(n + 1);{} }}\nprint f(0);",
+            "if (true) { ".repeat(100),
+            " }".repeat(100)
+        );
+        let result = with_stack(|| run(&source));
+        assert!(matches!(result, Err(Error::StackOverflow(name, 1, _)) if name == "f"));
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let statements =
+            parse("fn count(n: i32) { if (n > 0) { count(n - 1); } } count(3);").unwrap();
+
+        let mut interpreter = Interpreter::with_output(Vec::new()).with_max_depth(10);
+        assert_eq!(
+            interpreter.interpret(&statements),
+            Err(Error::StackOverflow("count".to_string(), 1, 44))
+        );
+
+        let mut interpreter = Interpreter::with_output(Vec::new()).with_max_depth(20);
+        assert_eq!(interpreter.interpret(&statements), Ok(()));
+    }
+
+    #[test]
+    fn test_max_call_depth() {
+        let statements = parse("fn count(n: i32) { if (n > 0) count(n - 1); } count(10);").unwrap();
+
+        let mut interpreter = Interpreter::with_output(Vec::new()).with_max_call_depth(10);
+        assert_eq!(
+            interpreter.interpret(&statements),
+            Err(Error::StackOverflow("count".to_string(), 1, 42))
+        );
+
+        let mut interpreter = Interpreter::with_output(Vec::new()).with_max_call_depth(11);
+        assert_eq!(interpreter.interpret(&statements), Ok(()));
+    }
+}
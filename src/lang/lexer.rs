@@ -1,14 +1,21 @@
 use std::fmt::{Display, Formatter};
+use std::rc::Rc;
+
+use serde::Serialize;
+use unicode_xid::UnicodeXID;
+
+use crate::lang::errors::{Error, Severity};
+use crate::lang::interner::Symbol;
 
 /// An enumeration of all the possible tokens in the language.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum TokenType {
     // Single-character tokens.
     /// A left parenthesis token.
     /// '('
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// // Left parenthesis token is '('.
     /// test_function();
     LeftParenthesis,
@@ -16,7 +23,7 @@ pub enum TokenType {
     /// ')'
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// // Right parenthesis token is ')'.
     /// test_function();
     RightParenthesis,
@@ -24,7 +31,7 @@ pub enum TokenType {
     /// '{'
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// // Left curly brace token is '{'.
     /// fn main() {}
     LeftCurlyBrace,
@@ -32,7 +39,7 @@ pub enum TokenType {
     /// '}'
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// // Right curly brace token is '}'.
     /// fn main() {}
     /// ```
@@ -41,7 +48,7 @@ pub enum TokenType {
     /// ';'
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// // Semicolon token is ';'.
     /// let a = 6;
     Semicolon,
@@ -49,7 +56,7 @@ pub enum TokenType {
     /// ','
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// let b = 4;
     /// // Comma token is ','.
@@ -60,16 +67,26 @@ pub enum TokenType {
     /// Used for type annotations.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// // Colon token is ':'.
     /// let a: i32 = 6;
     /// ```
     Colon,
+    /// A dot token.
+    /// '.'
+    /// Used for accessing the members of modules.
+    ///
+    /// # Example
+    /// ```cpl
+    /// // Dot token is '.'.
+    /// print math.pi;
+    /// ```
+    Dot,
     /// A plus token.
     /// '+'
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// let b = 4;
     /// // Plus token is '+'.
@@ -79,7 +96,7 @@ pub enum TokenType {
     /// '-'
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// let b = 4;
     /// // Minus token is '-'.
@@ -89,17 +106,28 @@ pub enum TokenType {
     /// '*'
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// let b = 4;
     /// // Star token is '*'.
     /// let c = a * b;
     Star,
+    /// A star star token.
+    /// '**'
+    /// Used for the power operator.
+    ///
+    /// # Example
+    /// ```cpl
+    /// let a = 2;
+    /// // Star star token is '**'.
+    /// let b = a ** 10;
+    /// ```
+    StarStar,
     /// A slash token.
     /// '/'
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// let b = 4;
     /// // Slash token is '/'.
@@ -110,7 +138,7 @@ pub enum TokenType {
     /// Used for the remainder operator.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// let b = 4;
     /// // Percent token is '%'.
@@ -122,7 +150,7 @@ pub enum TokenType {
     /// Used for bitwise XOR expressions.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 0b1010 ^ 0b1100;
     /// ```
     BitwiseXor,
@@ -131,7 +159,7 @@ pub enum TokenType {
     /// Used for bitwise AND expressions.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 0b1010 & 0b1100;
     /// ```
     BitwiseAnd,
@@ -140,7 +168,7 @@ pub enum TokenType {
     /// Used for bitwise OR expressions.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 0b1010 | 0b1100;
     /// ```
     BitwiseOr,
@@ -149,7 +177,7 @@ pub enum TokenType {
     /// Used for logical AND expressions.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = true && false;
     /// ```
     LogicalAnd,
@@ -158,16 +186,35 @@ pub enum TokenType {
     /// Used for logical OR expressions.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = true || false;
     /// ```
     LogicalOr,
+    /// A question mark token.
+    /// '?'
+    /// Reserved for the ternary operator.
+    ///
+    /// # Example
+    /// ```cpl
+    /// let a = b ? 1 : 2;
+    /// ```
+    Question,
+    /// A double question mark token.
+    /// '??'
+    /// Used for null-coalescing expressions.
+    ///
+    /// # Example
+    /// ```cpl
+    /// // 'a' is 6 if 'b' is none.
+    /// let a = b ?? 6;
+    /// ```
+    QuestionQuestion,
     // One or two character tokens.
     /// A bang token.
     /// '!'
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = false;
     ///
     /// // Bang token is '!'.
@@ -178,7 +225,7 @@ pub enum TokenType {
     /// A bang-equal. '!='
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// let b = 4;
     ///
@@ -190,7 +237,7 @@ pub enum TokenType {
     /// An equal token. '='
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// // Equal token is '='.
     /// let a = 6;
     /// ```
@@ -199,7 +246,7 @@ pub enum TokenType {
     /// '=='
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// let b = 4;
     ///
@@ -212,7 +259,7 @@ pub enum TokenType {
     /// '>'
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// let b = 4;
     ///
@@ -225,7 +272,7 @@ pub enum TokenType {
     /// '>='
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// let b = 4;
     ///
@@ -238,7 +285,7 @@ pub enum TokenType {
     /// '<'
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// let b = 4;
     ///
@@ -251,7 +298,7 @@ pub enum TokenType {
     /// '<='
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// let b = 4;
     ///
@@ -265,7 +312,7 @@ pub enum TokenType {
     /// Used for incrementing variables.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// a++; // a is now 7.
     /// ```
@@ -275,7 +322,7 @@ pub enum TokenType {
     /// Used for decrementing variables.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// a--; // a is now 5.
     /// ```
@@ -285,7 +332,7 @@ pub enum TokenType {
     /// Used for bitwise left shift expressions.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 0b1010 << 2;
     /// ```
     BitwiseLeftShift,
@@ -294,7 +341,7 @@ pub enum TokenType {
     /// Used for bitwise right shift expressions.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 0b1010 >> 2;
     /// ```
     BitwiseRightShift,
@@ -303,7 +350,7 @@ pub enum TokenType {
     /// Used for bitwise right shift expressions, and assigning the result to the variable.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 0b1010;
     /// a >>= 2; // a is now 0b10.
     /// ```
@@ -313,7 +360,7 @@ pub enum TokenType {
     /// Used for bitwise left shift expressions, and assigning the result to the variable.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 0b1010;
     /// a <<= 2; // a is now 0b101000.
     /// ```
@@ -323,7 +370,7 @@ pub enum TokenType {
     /// Used for adding a value to a variable, and assigning the result to the variable.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// a += 4; // a is now 10.
     /// ```
@@ -333,7 +380,7 @@ pub enum TokenType {
     /// Used for subtracting a value from a variable, and assigning the result to the variable.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// a -= 4; // a is now 2.
     /// ```
@@ -343,7 +390,7 @@ pub enum TokenType {
     /// Used for multiplying a variable by a value, and assigning the result to the variable.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// a *= 4; // a is now 24.
     /// ```
@@ -353,7 +400,7 @@ pub enum TokenType {
     /// Used for dividing a variable by a value, and assigning the result to the variable.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// a /= 4; // a is now 1.5.
     /// ```
@@ -365,7 +412,7 @@ pub enum TokenType {
     /// Also known as the modulo operator.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// a %= 4; // a is now 2.
     /// ```
@@ -375,7 +422,7 @@ pub enum TokenType {
     /// Used for bitwise AND-ing a variable with a value, and assigning the result to the variable.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 0b1010;
     /// a &= 0b1100; // a is now 0b1000.
     /// ```
@@ -385,7 +432,7 @@ pub enum TokenType {
     /// Used for bitwise OR-ing a variable with a value, and assigning the result to the variable.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 0b1010;
     /// a |= 0b1100; // a is now 0b1110.
     /// ```
@@ -395,7 +442,7 @@ pub enum TokenType {
     /// Used for bitwise XOR-ing a variable with a value, and assigning the result to the variable.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 0b1010;
     /// a ^= 0b1100; // a is now 0b0110.
     /// ```
@@ -405,7 +452,7 @@ pub enum TokenType {
     /// Used for variable names, function names, etc.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// // Identifier is 'a'.
     /// let a = 6;
     ///
@@ -417,15 +464,24 @@ pub enum TokenType {
     /// Used for strings.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let text = "Hello, world!";
     /// ```
     String,
+    /// A character literal.
+    /// Used for single characters.
+    ///
+    /// # Example
+    /// ```cpl
+    /// let letter = 'a';
+    /// let newline = '\n';
+    /// ```
+    Char,
     /// A number literal.
     /// Used for numbers.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6.9;
     /// let b = 4.2;
     /// let c = a + b;
@@ -437,7 +493,7 @@ pub enum TokenType {
     /// Used for conditional statements.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// if true {
     ///     print("Hello, world!");
     /// }
@@ -447,7 +503,7 @@ pub enum TokenType {
     /// Used for conditional statements.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// if true {
     ///     print("Hello, world!");
     /// } else {
@@ -455,6 +511,18 @@ pub enum TokenType {
     /// }
     /// ```
     Else,
+    /// The 'elif' keyword.
+    /// Used for conditional statements, as a shorthand for 'else if'.
+    ///
+    /// # Example
+    /// ```cpl
+    /// if a < 0 {
+    ///     print("Negative");
+    /// } elif a == 0 {
+    ///     print("Zero");
+    /// }
+    /// ```
+    Elif,
     /// The 'switch' keyword.
     /// Used for conditional statements.
     /// The switch statement is a multi-way branch statement.
@@ -464,7 +532,7 @@ pub enum TokenType {
     /// then executes all statements that follow the matching case label.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// switch <expression> {
     ///     case <expression>:
     ///         <statements>
@@ -479,7 +547,7 @@ pub enum TokenType {
     /// Used for conditional switch statements.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// switch <expression> {
     ///     case <expression>:
     ///         <statements>
@@ -494,7 +562,7 @@ pub enum TokenType {
     /// Used for conditional switch statements.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// switch <expression> {
     ///     case <expression>:
     ///         <statements>
@@ -510,7 +578,7 @@ pub enum TokenType {
     /// The arrow operator is used to separate the expression from the statements.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// switch <expression> {
     ///     case <expression> => <statement>,
     ///     case <expression> => {
@@ -524,7 +592,7 @@ pub enum TokenType {
     /// Used for boolean values.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = true;
     /// ```
     True,
@@ -532,7 +600,7 @@ pub enum TokenType {
     /// Used for boolean values.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = false;
     /// ```
     False,
@@ -540,7 +608,7 @@ pub enum TokenType {
     /// Used for null values.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = none;
     /// ```
     None,
@@ -548,15 +616,58 @@ pub enum TokenType {
     /// Used for printing to the console.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// print("Hello, world!");
     /// ```
     Print,
+    /// The 'assert' keyword.
+    /// Used for stopping the program when a condition does not hold.
+    ///
+    /// # Example
+    /// ```cpl
+    /// assert(1 + 1 == 2, "Math is broken!");
+    /// ```
+    Assert,
+    /// The 'include' keyword.
+    /// Used for splicing the top-level statements of another file into the program.
+    ///
+    /// # Example
+    /// ```cpl
+    /// include "utils.cpl";
+    /// ```
+    Include,
+    /// The 'as' keyword.
+    /// Used for naming the module an included file is placed in.
+    ///
+    /// # Example
+    /// ```cpl
+    /// include "math.cpl" as math;
+    /// ```
+    As,
+    /// The 'module' keyword.
+    /// Used for declaring a module, whose members are accessed through its name.
+    ///
+    /// # Example
+    /// ```cpl
+    /// module math {
+    ///     const pi = 3.14;
+    /// }
+    /// print math.pi;
+    /// ```
+    Module,
+    /// The 'typeof' keyword.
+    /// Used for getting the name of a value's type.
+    ///
+    /// # Example
+    /// ```cpl
+    /// print typeof 6.9;
+    /// ```
+    TypeOf,
     /// The '->' keyword.
     /// Used for function return types.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// fn test_function() -> f32 {
     ///     return 6.9;
     /// }
@@ -566,7 +677,7 @@ pub enum TokenType {
     /// Used for returning values from functions.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// fn test_function() -> f32 {
     ///    return 6.9;
     /// }
@@ -576,18 +687,28 @@ pub enum TokenType {
     /// Used for loops.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 0;
     /// while a < 10 {
     ///     print(a++);
     /// }
     /// ```
     While,
+    /// The 'loop' keyword.
+    /// Used for infinite loops, which only exit through 'break'.
+    ///
+    /// # Example
+    /// ```cpl
+    /// loop {
+    ///     print("Hello, world!");
+    /// }
+    /// ```
+    Loop,
     /// The 'for' keyword.
     /// Used for loops.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// for i in 0 to 10 {
     ///     print(i);
     /// }
@@ -597,7 +718,7 @@ pub enum TokenType {
     /// Used in range-based expressions.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// for i in 0 to 10 {
     ///     print(i);
     /// }
@@ -607,7 +728,7 @@ pub enum TokenType {
     /// Used in range-based expressions.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// for i in 0 to 10 {
     ///     print(i);
     /// }
@@ -617,7 +738,7 @@ pub enum TokenType {
     /// Used for breaking out of loops.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// for i in 0 to 10 {
     ///     if i == 5 {
     ///         break;
@@ -628,7 +749,7 @@ pub enum TokenType {
     /// Used for skipping to the next iteration of a loop.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// for i in 0 to 10 {
     ///     if i == 5 {
     ///         continue;
@@ -640,7 +761,7 @@ pub enum TokenType {
     /// Used for function declarations.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// fn test_function() {
     ///     print("Hello, world!");
     /// }
@@ -650,7 +771,7 @@ pub enum TokenType {
     /// Used for variable declarations.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// let a = 6;
     /// ```
     Variable,
@@ -658,37 +779,226 @@ pub enum TokenType {
     /// Used for constant declarations.
     ///
     /// # Example
-    /// ```
+    /// ```cpl
     /// const a = 6;
     /// ```
     Constant,
 
+    /// A single-line comment.
+    /// Only produced when the scanner is asked to keep comments, e.g. for the formatter.
+    ///
+    /// # Example
+    /// ```cpl
+    /// // This is a comment.
+    /// ```
+    Comment,
+
     /// Used to represent the end of a file.
     EndOfFile,
 }
 
+impl TokenType {
+    /// Gets the source text every token of this type is spelled with.
+    ///
+    /// # Returns
+    /// The spelling, or `None` for identifiers, literals, comments and the end of file, whose text varies.
+    pub fn spelling(&self) -> Option<&'static str> {
+        let spelling = match self {
+            TokenType::LeftParenthesis => "(",
+            TokenType::RightParenthesis => ")",
+            TokenType::LeftCurlyBrace => "{",
+            TokenType::RightCurlyBrace => "}",
+            TokenType::Semicolon => ";",
+            TokenType::Comma => ",",
+            TokenType::Colon => ":",
+            TokenType::Dot => ".",
+            TokenType::Plus => "+",
+            TokenType::Minus => "-",
+            TokenType::Star => "*",
+            TokenType::StarStar => "**",
+            TokenType::Slash => "/",
+            TokenType::Percent => "%",
+            TokenType::BitwiseXor => "^",
+            TokenType::BitwiseAnd => "&",
+            TokenType::BitwiseOr => "|",
+            TokenType::LogicalAnd => "&&",
+            TokenType::LogicalOr => "||",
+            TokenType::Question => "?",
+            TokenType::QuestionQuestion => "??",
+            TokenType::Bang => "!",
+            TokenType::BangEqual => "!=",
+            TokenType::Equal => "=",
+            TokenType::EqualEqual => "==",
+            TokenType::GreaterThan => ">",
+            TokenType::GreaterThanOrEqual => ">=",
+            TokenType::LessThan => "<",
+            TokenType::LessThanOrEqual => "<=",
+            TokenType::Increment => "++",
+            TokenType::Decrement => "--",
+            TokenType::BitwiseLeftShift => "<<",
+            TokenType::BitwiseRightShift => ">>",
+            TokenType::BitwiseRightShiftEqual => ">>=",
+            TokenType::BitwiseLeftShiftEqual => "<<=",
+            TokenType::PlusEqual => "+=",
+            TokenType::MinusEqual => "-=",
+            TokenType::StarEqual => "*=",
+            TokenType::SlashEqual => "/=",
+            TokenType::PercentEqual => "%=",
+            TokenType::BitwiseAndEqual => "&=",
+            TokenType::BitwiseOrEqual => "|=",
+            TokenType::BitwiseXorEqual => "^=",
+            TokenType::If => "if",
+            TokenType::Else => "else",
+            TokenType::Elif => "elif",
+            TokenType::Switch => "switch",
+            TokenType::Case => "case",
+            TokenType::Default => "default",
+            TokenType::ExpressionArrow => "=>",
+            TokenType::True => "true",
+            TokenType::False => "false",
+            TokenType::None => "none",
+            TokenType::Print => "print",
+            TokenType::Assert => "assert",
+            TokenType::Include => "include",
+            TokenType::As => "as",
+            TokenType::Module => "module",
+            TokenType::TypeOf => "typeof",
+            TokenType::Arrow => "->",
+            TokenType::Return => "return",
+            TokenType::While => "while",
+            TokenType::Loop => "loop",
+            TokenType::For => "for",
+            TokenType::In => "in",
+            TokenType::To => "to",
+            TokenType::Break => "break",
+            TokenType::Continue => "continue",
+            TokenType::Function => "fn",
+            TokenType::Variable => "let",
+            TokenType::Constant => "const",
+            TokenType::Identifier
+            | TokenType::String
+            | TokenType::Char
+            | TokenType::Number
+            | TokenType::Comment
+            | TokenType::EndOfFile => return None,
+        };
+
+        Some(spelling)
+    }
+}
+
+impl Display for TokenType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TokenType::LeftParenthesis => "left parenthesis",
+            TokenType::RightParenthesis => "right parenthesis",
+            TokenType::LeftCurlyBrace => "left curly brace",
+            TokenType::RightCurlyBrace => "right curly brace",
+            TokenType::Semicolon => "semicolon",
+            TokenType::Comma => "comma",
+            TokenType::Colon => "colon",
+            TokenType::Dot => "dot",
+            TokenType::Plus => "plus",
+            TokenType::Minus => "minus",
+            TokenType::Star => "star",
+            TokenType::StarStar => "star star",
+            TokenType::Slash => "slash",
+            TokenType::Percent => "percent",
+            TokenType::BitwiseXor => "caret",
+            TokenType::BitwiseAnd => "ampersand",
+            TokenType::BitwiseOr => "pipe",
+            TokenType::LogicalAnd => "double ampersand",
+            TokenType::LogicalOr => "double pipe",
+            TokenType::Question => "question mark",
+            TokenType::QuestionQuestion => "double question mark",
+            TokenType::Bang => "bang",
+            TokenType::BangEqual => "bang-equal",
+            TokenType::Equal => "equal",
+            TokenType::EqualEqual => "equal-equal",
+            TokenType::GreaterThan => "greater-than",
+            TokenType::GreaterThanOrEqual => "greater-than-or-equal",
+            TokenType::LessThan => "less-than",
+            TokenType::LessThanOrEqual => "less-than-or-equal",
+            TokenType::Increment => "increment",
+            TokenType::Decrement => "decrement",
+            TokenType::BitwiseLeftShift => "left shift",
+            TokenType::BitwiseRightShift => "right shift",
+            TokenType::BitwiseRightShiftEqual => "right shift-equal",
+            TokenType::BitwiseLeftShiftEqual => "left shift-equal",
+            TokenType::PlusEqual => "plus-equal",
+            TokenType::MinusEqual => "minus-equal",
+            TokenType::StarEqual => "star-equal",
+            TokenType::SlashEqual => "slash-equal",
+            TokenType::PercentEqual => "percent-equal",
+            TokenType::BitwiseAndEqual => "ampersand-equal",
+            TokenType::BitwiseOrEqual => "pipe-equal",
+            TokenType::BitwiseXorEqual => "caret-equal",
+            TokenType::Identifier => "identifier",
+            TokenType::String => "string",
+            TokenType::Char => "character",
+            TokenType::Number => "number",
+            TokenType::If => "if keyword",
+            TokenType::Else => "else keyword",
+            TokenType::Elif => "elif keyword",
+            TokenType::Switch => "switch keyword",
+            TokenType::Case => "case keyword",
+            TokenType::Default => "default keyword",
+            TokenType::ExpressionArrow => "fat arrow",
+            TokenType::True => "true keyword",
+            TokenType::False => "false keyword",
+            TokenType::None => "none keyword",
+            TokenType::Print => "print keyword",
+            TokenType::Assert => "assert keyword",
+            TokenType::Include => "include keyword",
+            TokenType::As => "as keyword",
+            TokenType::Module => "module keyword",
+            TokenType::TypeOf => "typeof keyword",
+            TokenType::Arrow => "arrow",
+            TokenType::Return => "return keyword",
+            TokenType::While => "while keyword",
+            TokenType::Loop => "loop keyword",
+            TokenType::For => "for keyword",
+            TokenType::In => "in keyword",
+            TokenType::To => "to keyword",
+            TokenType::Break => "break keyword",
+            TokenType::Continue => "continue keyword",
+            TokenType::Function => "fn keyword",
+            TokenType::Variable => "let keyword",
+            TokenType::Constant => "const keyword",
+            TokenType::Comment => "comment",
+            TokenType::EndOfFile => "end of file",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
 /// Representation of a literal.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum Literal {
     /// A string literal.
     String(String),
-    /// A number literal.
-    Number(f64),
+    /// A character literal.
+    Char(char),
+    /// An integer literal, written without a decimal point.
+    Int(i64),
+    /// A floating-point literal, written with a decimal point.
+    Float(f64),
     /// A boolean literal.
     Boolean(bool),
     /// A null literal.
     None,
 }
 
-impl Eq for Literal {
-    fn assert_receiver_is_total_eq(&self) {}
-}
+impl Eq for Literal {}
 
 impl Display for Literal {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Literal::String(string) => write!(f, "{}", string),
-            Literal::Number(number) => write!(f, "{}", number),
+            Literal::Char(c) => write!(f, "{}", c),
+            Literal::Int(int) => write!(f, "{}", int),
+            Literal::Float(float) => write!(f, "{:?}", float),
             Literal::Boolean(boolean) => write!(f, "{}", boolean),
             Literal::None => write!(f, "none"),
         }
@@ -696,14 +1006,23 @@ impl Display for Literal {
 }
 
 /// Representation of a token, with its type, lexeme, literal, line, and column.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Token {
     pub token_type: TokenType,
-    pub lexeme: String,
+    /// The source text of the token, shared so cloning a token does not copy it.
+    pub lexeme: Rc<str>,
+    /// The interned lexeme, for identifiers and keywords.
+    /// It is left out of the JSON, as symbols only mean something to the process that interned them.
+    #[serde(skip)]
+    pub symbol: Option<Symbol>,
     pub literal: Option<Literal>,
 
     pub line: usize,
     pub column: usize,
+    /// The byte offset of the token's first character in the source code.
+    pub start: usize,
+    /// The byte offset just past the token's last character in the source code.
+    pub end: usize,
 }
 
 impl Token {
@@ -717,23 +1036,60 @@ impl Token {
     ) -> Self {
         Self {
             token_type,
-            lexeme: lexeme.to_string(),
+            lexeme: Rc::from(lexeme),
+            symbol: None,
             literal,
             line,
             column,
+            start: 0,
+            end: 0,
         }
     }
+
+    /// Sets the byte range the token covers in the source code.
+    ///
+    /// # Arguments
+    /// * `start` - The byte offset of the token's first character.
+    /// * `end` - The byte offset just past the token's last character.
+    pub fn with_span(mut self, start: usize, end: usize) -> Self {
+        self.start = start;
+        self.end = end;
+
+        self
+    }
+
+    /// Gets the interned lexeme, interning it if the scanner did not.
+    pub fn name(&self) -> Symbol {
+        self.symbol.unwrap_or_else(|| Symbol::intern(&self.lexeme))
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:?} \"{}\" @ {}:{}",
+            self.token_type, self.lexeme, self.line, self.column
+        )
+    }
 }
 
 /// Representation of a scanner.
 pub struct Scanner {
     source: String,
     tokens: Vec<Token>,
+    errors: Vec<Error>,
+    keep_comments: bool,
 
+    /// The byte offset of the start of the current token.
     start: usize,
+    /// The byte offset of the next character.
     current: usize,
     line: usize,
     column: usize,
+    /// The position of the start of the current token, which may span multiple lines.
+    start_line: usize,
+    start_column: usize,
 }
 
 impl Scanner {
@@ -742,31 +1098,54 @@ impl Scanner {
         Self {
             source: source.to_string(),
             tokens: Vec::new(),
+            errors: Vec::new(),
+            keep_comments: false,
 
             start: 0,
             current: 0,
             line: 1,
             column: 1,
+            start_line: 1,
+            start_column: 1,
         }
     }
 
+    /// Sets whether comments are kept as tokens instead of being discarded.
+    ///
+    /// # Arguments
+    /// * `keep_comments` - Whether to keep comments.
+    pub fn with_comments(mut self, keep_comments: bool) -> Self {
+        self.keep_comments = keep_comments;
+
+        self
+    }
+
     /// Scans the source code and returns a vector of tokens.
     pub fn scan_tokens(&mut self) -> Vec<Token> {
         while !self.is_at_end() {
             self.start = self.current;
+            self.start_line = self.line;
+            self.start_column = self.column;
             self.scan_token();
         }
 
-        self.tokens.push(Token::new(
-            TokenType::EndOfFile,
-            "",
-            None,
-            self.line,
-            self.column,
-        ));
+        self.tokens.push(
+            Token::new(TokenType::EndOfFile, "", None, self.line, self.column)
+                .with_span(self.current, self.current),
+        );
         self.tokens.clone()
     }
 
+    /// Checks if any errors were found while scanning.
+    pub fn had_error(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Gets the errors found while scanning.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
     /// Scans a single token.
     fn scan_token(&mut self) {
         let c = self.advance();
@@ -780,6 +1159,7 @@ impl Scanner {
             ':' => self.add_token(TokenType::Colon),
             ';' => self.add_token(TokenType::Semicolon),
             ',' => self.add_token(TokenType::Comma),
+            '.' => self.add_token(TokenType::Dot),
 
             // One or two character tokens.
             '!' => {
@@ -863,7 +1243,10 @@ impl Scanner {
                 }
             }
             '*' => {
-                if self.match_char('=') {
+                if self.match_char('*') {
+                    // Power.
+                    self.add_token(TokenType::StarStar);
+                } else if self.match_char('=') {
                     // Multiplication assignment.
                     self.add_token(TokenType::StarEqual);
                 } else {
@@ -913,18 +1296,24 @@ impl Scanner {
                     self.add_token(TokenType::BitwiseXor);
                 }
             }
+            '?' => {
+                if self.match_char('?') {
+                    // Null-coalescing.
+                    self.add_token(TokenType::QuestionQuestion);
+                } else {
+                    // Ternary.
+                    self.add_token(TokenType::Question);
+                }
+            }
 
             // Literals.
             '"' => self.string(),
+            '\'' => self.character(),
             '0'..='9' => self.number(),
-            'a'..='z' | 'A'..='Z' | '_' => self.identifier(),
+            c if is_identifier_start(c) => self.identifier(),
 
-            // Whitespace.
-            ' ' | '\r' | '\t' => (),
-            '\n' => {
-                self.line += 1;
-                self.column = 1;
-            }
+            // Whitespace, `advance` keeps track of new lines.
+            ' ' | '\r' | '\t' | '\n' => (),
 
             '/' => {
                 if self.match_char('/') {
@@ -932,22 +1321,27 @@ impl Scanner {
                     while self.peek() != '\n' && !self.is_at_end() {
                         self.advance();
                     }
+
+                    if self.keep_comments {
+                        self.add_token(TokenType::Comment);
+                    }
                 } else if self.match_char('*') {
                     // Multi-line comments.
-                    while self.peek() != '*' && self.peek_next() != '/' && !self.is_at_end() {
-                        if self.peek() == '\n' {
-                            self.line += 1;
-                            self.column = 1;
-                        }
+                    while !self.is_at_end() && (self.peek() != '*' || self.peek_next() != '/') {
                         self.advance();
                     }
 
                     if self.is_at_end() {
-                        panic!("Scanner tried to advance past the end of the source code!");
+                        self.error("Unterminated comment");
+                        return;
                     }
 
                     self.advance();
                     self.advance();
+
+                    if self.keep_comments {
+                        self.add_token(TokenType::Comment);
+                    }
                 } else if self.match_char('=') {
                     // Division assignment.
                     self.add_token(TokenType::SlashEqual);
@@ -956,7 +1350,7 @@ impl Scanner {
                     self.add_token(TokenType::Slash);
                 }
             }
-            _ => panic!("Unexpected character: {}", c),
+            _ => self.error(&format!("Unexpected character '{}'", c)),
         }
     }
 
@@ -968,13 +1362,44 @@ impl Scanner {
     /// # Panics
     /// Panics if the scanner tries to advance past the end of the source code.
     fn advance(&mut self) -> char {
-        self.current += 1;
-        self.column += 1;
-
-        self.source
+        let c = self.source[self.current..]
             .chars()
-            .nth(self.current - 1)
-            .expect("Scanner tried to advance past the end of the source code!")
+            .next()
+            .expect("Scanner tried to advance past the end of the source code!");
+
+        self.current += c.len_utf8();
+
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        c
+    }
+
+    /// Reports an error at the start of the current token.
+    ///
+    /// # Arguments
+    /// * `message` - The error message.
+    fn error(&mut self, message: &str) {
+        self.error_at(self.start_line, self.start_column, message);
+    }
+
+    /// Reports an error at the given position.
+    ///
+    /// # Arguments
+    /// * `line` - The line of the error.
+    /// * `column` - The column of the error.
+    /// * `message` - The error message.
+    fn error_at(&mut self, line: usize, column: usize, message: &str) {
+        self.errors.push(Error {
+            line,
+            column,
+            message: message.to_string(),
+            severity: Severity::Error,
+        });
     }
 
     /// Adds a token to the vector of tokens.
@@ -984,23 +1409,38 @@ impl Scanner {
     ///
     ///
     fn add_token(&mut self, token_type: TokenType) {
-        let text = self.source[self.start..self.current].to_string();
+        self.add_interned_token(token_type, None);
+    }
+
+    /// Adds a token, whose lexeme is the interned string of the symbol if there is one.
+    ///
+    /// # Arguments
+    /// * `token_type` - The type of the token.
+    /// * `symbol` - The interned lexeme, for identifiers and keywords.
+    fn add_interned_token(&mut self, token_type: TokenType, symbol: Option<Symbol>) {
+        let text = &self.source[self.start..self.current];
+        let text = match token_type {
+            TokenType::Comment => text.trim_end(),
+            _ => text,
+        };
         let literal = match token_type {
-            TokenType::String => Some(Literal::String(text.clone())),
-            TokenType::Number => Some(Literal::Number(text.parse().unwrap())),
+            TokenType::String => Some(Literal::String(text.to_string())),
             TokenType::True => Some(Literal::Boolean(true)),
             TokenType::False => Some(Literal::Boolean(false)),
             TokenType::None => Some(Literal::None),
             _ => None,
         };
 
-        self.tokens.push(Token::new(
+        self.tokens.push(Token {
             token_type,
-            text.as_str(),
+            lexeme: symbol.map_or_else(|| Rc::from(text), Symbol::as_str),
+            symbol,
             literal,
-            self.line,
-            self.column,
-        ));
+            line: self.start_line,
+            column: self.start_column,
+            start: self.start,
+            end: self.start + text.len(),
+        });
     }
 
     /// Checks if the next character matches the given character.
@@ -1015,12 +1455,11 @@ impl Scanner {
             return false;
         }
 
-        if self.source.chars().nth(self.current).unwrap() != expected {
+        if self.peek() != expected {
             return false;
         }
 
-        self.current += 1;
-        self.column += 1;
+        self.advance();
 
         true
     }
@@ -1036,58 +1475,147 @@ impl Scanner {
     /// Peeks at the next character without advancing the scanner.
     ///
     /// # Returns
-    /// The next character.
-    ///
-    /// # Panics
-    /// Panics if the scanner tries to peek past the end of the source code.
+    /// The next character, or '\0' if the scanner is at the end of the source code.
     fn peek(&self) -> char {
-        self.source
-            .chars()
-            .nth(self.current)
-            .expect("Scanner tried to peek past the end of the source code!")
+        self.source[self.current..].chars().next().unwrap_or('\0')
     }
 
     /// Peeks at the character after the next character without advancing the scanner.
     ///
     /// # Returns
-    /// The character after the next character.
-    ///
-    /// # Panics
-    /// Panics if the scanner tries to peek past the end of the source code.
+    /// The character after the next character, or '\0' if it is past the end of the source code.
     fn peek_next(&self) -> char {
-        self.source
-            .chars()
-            .nth(self.current + 1)
-            .expect("Scanner tried to peek past the end of the source code!")
+        self.source[self.current..].chars().nth(1).unwrap_or('\0')
     }
 
     fn string(&mut self) {
-        while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\\' {
-                self.advance();
-                self.column += 1;
-            }
+        let mut value = String::new();
 
-            if self.peek() == '\n' {
-                self.line += 1;
-                self.column = 1;
+        while self.peek() != '"' && !self.is_at_end() {
+            match self.advance() {
+                '\\' if !self.is_at_end() => {
+                    if let Some(escaped) = self.escape() {
+                        value.push(escaped);
+                    }
+                }
+                c => value.push(c),
             }
-
-            self.advance();
         }
 
         if self.is_at_end() {
-            panic!("Unterminated string!");
+            self.error("Unterminated string");
+            return;
         }
 
         self.advance();
-
-        let value = self.source[self.start + 1..self.current - 1].to_string();
         self.add_token(TokenType::String);
 
         self.tokens.last_mut().unwrap().literal = Some(Literal::String(value));
     }
 
+    fn character(&mut self) {
+        let mut characters = Vec::new();
+        let mut valid = true;
+
+        while self.peek() != '\'' && self.peek() != '\n' && !self.is_at_end() {
+            match self.advance() {
+                '\\' if !self.is_at_end() => match self.escape() {
+                    Some(escaped) => characters.push(escaped),
+                    None => valid = false,
+                },
+                c => characters.push(c),
+            }
+        }
+
+        if !self.match_char('\'') {
+            self.error("Unterminated character literal");
+            return;
+        }
+
+        // Invalid escape sequences have already been reported.
+        if !valid {
+            return;
+        }
+
+        match characters[..] {
+            [c] => {
+                self.add_token(TokenType::Char);
+
+                self.tokens.last_mut().unwrap().literal = Some(Literal::Char(c));
+            }
+            [] => self.error("Empty character literal"),
+            _ => self.error("Character literal must contain exactly one character"),
+        }
+    }
+
+    /// Scans an escape sequence in a string or character literal, after its backslash.
+    ///
+    /// # Returns
+    /// The escaped character, or `None` if the escape sequence is invalid.
+    fn escape(&mut self) -> Option<char> {
+        // Errors are reported at the backslash.
+        let (line, column) = (self.line, self.column - 1);
+
+        match self.advance() {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '0' => Some('\0'),
+            '\\' => Some('\\'),
+            '"' => Some('"'),
+            '\'' => Some('\''),
+            'u' => self.unicode_escape(line, column),
+            c => {
+                self.error_at(line, column, &format!("Unknown escape sequence '\\{}'", c));
+
+                None
+            }
+        }
+    }
+
+    /// Scans the code point of a `\u{...}` escape sequence, after its 'u'.
+    ///
+    /// # Arguments
+    /// * `line` - The line of the escape sequence.
+    /// * `column` - The column of the escape sequence's backslash.
+    ///
+    /// # Returns
+    /// The character, or `None` if the escape sequence is invalid.
+    fn unicode_escape(&mut self, line: usize, column: usize) -> Option<char> {
+        if !self.match_char('{') {
+            self.error_at(line, column, "Expected '{' after '\\u'");
+
+            return None;
+        }
+
+        let mut digits = String::new();
+        while self.peek().is_ascii_hexdigit() {
+            digits.push(self.advance());
+        }
+
+        if !self.match_char('}') || digits.is_empty() || digits.len() > 6 {
+            self.error_at(
+                line,
+                column,
+                "Expected 1 to 6 hex digits followed by '}' in unicode escape",
+            );
+
+            return None;
+        }
+
+        let code_point = u32::from_str_radix(&digits, 16).expect("At most 6 hex digits fit a u32.");
+        let c = char::from_u32(code_point);
+        if c.is_none() {
+            self.error_at(
+                line,
+                column,
+                &format!("Invalid unicode code point '{:X}'", code_point),
+            );
+        }
+
+        c
+    }
+
     fn number(&mut self) {
         while self.peek().is_ascii_digit() {
             self.advance();
@@ -1101,26 +1629,57 @@ impl Scanner {
             }
         }
 
+        let mut exponent = self.source[self.current..].chars();
+        if matches!(exponent.next(), Some('e' | 'E')) {
+            let digit = match exponent.next() {
+                Some('+' | '-') => exponent.next(),
+                sign => sign,
+            };
+
+            if digit.is_some_and(|digit| digit.is_ascii_digit()) {
+                self.advance();
+                self.advance();
+
+                while self.peek().is_ascii_digit() {
+                    self.advance();
+                }
+            }
+        }
+
+        // Numbers with a decimal point or an exponent are floats, all others are integers.
         let value = self.source[self.start..self.current].to_string();
-        self.add_token(TokenType::Number);
+        let literal = if value.contains(['.', 'e', 'E']) {
+            Literal::Float(value.parse().expect("A scanned float is valid."))
+        } else {
+            match value.parse() {
+                Ok(int) => Literal::Int(int),
+                Err(_) => {
+                    self.error(&format!("Integer literal '{}' is too large", value));
+                    return;
+                }
+            }
+        };
 
-        self.tokens.last_mut().unwrap().literal = Some(Literal::Number(value.parse().unwrap()));
+        self.add_token(TokenType::Number);
+        self.tokens.last_mut().unwrap().literal = Some(literal);
     }
 
     fn identifier(&mut self) {
-        while self.peek().is_alphanumeric() || self.peek() == '_' {
+        while is_identifier_continue(self.peek()) {
             self.advance();
         }
 
-        let text = self.source[self.start..self.current].to_string();
-        let token_type = match text.as_str() {
+        let text = &self.source[self.start..self.current];
+        let token_type = match text {
             "fn" => TokenType::Function,
             "if" => TokenType::If,
             "else" => TokenType::Else,
+            "elif" => TokenType::Elif,
             "switch" => TokenType::Switch,
             "case" => TokenType::Case,
             "_" => TokenType::Default,
             "while" => TokenType::While,
+            "loop" => TokenType::Loop,
             "continue" => TokenType::Continue,
             "break" => TokenType::Break,
             "for" => TokenType::For,
@@ -1130,16 +1689,34 @@ impl Scanner {
             "false" => TokenType::False,
             "none" => TokenType::None,
             "print" => TokenType::Print,
+            "assert" => TokenType::Assert,
+            "include" => TokenType::Include,
+            "as" => TokenType::As,
+            "module" => TokenType::Module,
+            "typeof" => TokenType::TypeOf,
             "return" => TokenType::Return,
             "let" => TokenType::Variable,
             "const" => TokenType::Constant,
             _ => TokenType::Identifier,
         };
 
-        self.add_token(token_type);
+        // Identifiers and keywords are interned, so every occurrence of a name shares one string.
+        let symbol = Symbol::intern(text);
+        self.add_interned_token(token_type, Some(symbol));
     }
 }
 
+/// Checks if a character can start an identifier, which is an underscore or a Unicode `XID_Start` character.
+fn is_identifier_start(c: char) -> bool {
+    c == '_' || c.is_xid_start()
+}
+
+/// Checks if a character can continue an identifier, which is a Unicode `XID_Continue` character.
+/// These include the underscore and digits.
+fn is_identifier_continue(c: char) -> bool {
+    c.is_xid_continue()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1252,4 +1829,376 @@ mod tests {
 
         assert_eq!(tokens[40].token_type, TokenType::EndOfFile);
     }
+
+    #[test]
+    fn test_unexpected_characters() {
+        let mut scanner = Scanner::new("let x @ 1;\nlet y = 2 # 3;");
+        let tokens = scanner.scan_tokens();
+
+        assert!(scanner.had_error());
+        assert_eq!(scanner.errors().len(), 2);
+
+        assert_eq!(scanner.errors()[0].message, "Unexpected character '@'");
+        assert_eq!(scanner.errors()[0].line, 1);
+        assert_eq!(scanner.errors()[0].column, 7);
+
+        assert_eq!(scanner.errors()[1].message, "Unexpected character '#'");
+        assert_eq!(scanner.errors()[1].line, 2);
+        assert_eq!(scanner.errors()[1].column, 11);
+
+        // The remaining tokens are still scanned.
+        assert_eq!(tokens.len(), 11);
+    }
+
+    #[test]
+    fn test_multi_line_token_positions() {
+        // Errors and tokens are at the start of the token, even if it spans multiple lines.
+        let mut scanner = Scanner::new(
+            "print 1;
+let a = \"first\nsecond\nthird;",
+        );
+        scanner.scan_tokens();
+
+        let error = &scanner.errors()[0];
+        assert_eq!(error.message, "Unterminated string");
+        assert_eq!((error.line, error.column), (2, 9));
+
+        let tokens = Scanner::new("let a = \"first\nsecond\";\nprint a;").scan_tokens();
+        assert_eq!((tokens[3].line, tokens[3].column), (1, 9));
+        assert_eq!((tokens[4].line, tokens[4].column), (2, 8));
+    }
+
+    #[test]
+    fn test_scan_question_marks() {
+        let token_types = |source: &str| {
+            Scanner::new(source)
+                .scan_tokens()
+                .into_iter()
+                .map(|token| token.token_type)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            token_types("a??b"),
+            vec![
+                TokenType::Identifier,
+                TokenType::QuestionQuestion,
+                TokenType::Identifier,
+                TokenType::EndOfFile,
+            ]
+        );
+        assert_eq!(
+            token_types("a ? b"),
+            vec![
+                TokenType::Identifier,
+                TokenType::Question,
+                TokenType::Identifier,
+                TokenType::EndOfFile,
+            ]
+        );
+        assert_eq!(
+            token_types("a???b"),
+            vec![
+                TokenType::Identifier,
+                TokenType::QuestionQuestion,
+                TokenType::Question,
+                TokenType::Identifier,
+                TokenType::EndOfFile,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_stars() {
+        let token_types = Scanner::new("a ** b * c *= d ***e")
+            .scan_tokens()
+            .into_iter()
+            .map(|token| token.token_type)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::Identifier,
+                TokenType::StarStar,
+                TokenType::Identifier,
+                TokenType::Star,
+                TokenType::Identifier,
+                TokenType::StarEqual,
+                TokenType::Identifier,
+                TokenType::StarStar,
+                TokenType::Star,
+                TokenType::Identifier,
+                TokenType::EndOfFile,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_typeof() {
+        let token_types = Scanner::new("typeof a typeofa")
+            .scan_tokens()
+            .into_iter()
+            .map(|token| token.token_type)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::TypeOf,
+                TokenType::Identifier,
+                TokenType::Identifier,
+                TokenType::EndOfFile,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_comments() {
+        let source = "let a = 1; // One.\n// Two.\n/* Three. */";
+
+        let tokens = Scanner::new(source).scan_tokens();
+        assert!(tokens
+            .iter()
+            .all(|token| token.token_type != TokenType::Comment));
+
+        let tokens = Scanner::new(source).with_comments(true).scan_tokens();
+        let comments = tokens
+            .iter()
+            .filter(|token| token.token_type == TokenType::Comment)
+            .map(|token| (token.lexeme.as_ref(), token.line))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            comments,
+            vec![("// One.", 1), ("// Two.", 2), ("/* Three. */", 3)]
+        );
+    }
+
+    #[test]
+    fn test_scan_block_comment_with_star() {
+        let tokens = Scanner::new("/* a*b **/ print 1;").scan_tokens();
+        let token_types = tokens
+            .iter()
+            .map(|token| token.token_type.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            token_types,
+            vec![
+                TokenType::Print,
+                TokenType::Number,
+                TokenType::Semicolon,
+                TokenType::EndOfFile,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_numbers() {
+        let tokens = Scanner::new("5 5.0 0.25 9223372036854775807 1e3 2.5E-2 3e+1").scan_tokens();
+
+        assert_eq!(tokens[0].literal, Some(Literal::Int(5)));
+        assert_eq!(tokens[1].literal, Some(Literal::Float(5.0)));
+        assert_eq!(tokens[2].literal, Some(Literal::Float(0.25)));
+        assert_eq!(tokens[3].literal, Some(Literal::Int(i64::MAX)));
+        assert_eq!(tokens[4].literal, Some(Literal::Float(1000.0)));
+        assert_eq!(tokens[5].literal, Some(Literal::Float(0.025)));
+        assert_eq!(tokens[6].literal, Some(Literal::Float(30.0)));
+
+        // Without digits after it, the dot is not a decimal point.
+        let tokens = Scanner::new("1.a").scan_tokens();
+        assert_eq!(tokens[0].literal, Some(Literal::Int(1)));
+        assert_eq!(tokens[1].token_type, TokenType::Dot);
+
+        // Without digits, the 'e' is not an exponent.
+        let tokens = Scanner::new("2e").scan_tokens();
+        assert_eq!(tokens[0].literal, Some(Literal::Int(2)));
+        assert_eq!(tokens[1].token_type, TokenType::Identifier);
+
+        let mut scanner = Scanner::new("9223372036854775808");
+        scanner.scan_tokens();
+        assert_eq!(
+            scanner.errors()[0].message,
+            "Integer literal '9223372036854775808' is too large"
+        );
+    }
+
+    #[test]
+    fn test_identifiers_are_interned() {
+        let tokens = Scanner::new("let x = x + y; let y = x;").scan_tokens();
+        let names = tokens
+            .iter()
+            .filter(|token| token.token_type == TokenType::Identifier)
+            .collect::<Vec<_>>();
+
+        assert!(Rc::ptr_eq(&names[0].lexeme, &names[1].lexeme));
+        assert!(Rc::ptr_eq(&names[0].lexeme, &names[4].lexeme));
+        assert_eq!(names[2].symbol, names[3].symbol);
+        assert_ne!(names[0].symbol, names[2].symbol);
+        assert_eq!(tokens[0].symbol, tokens[7].symbol);
+        assert_eq!(tokens[2].symbol, None);
+        assert_eq!(names[0].name().to_string(), "x");
+    }
+
+    #[test]
+    fn test_end_of_file_position() {
+        let end = |source: &str| {
+            let tokens = Scanner::new(source).scan_tokens();
+            let end = tokens
+                .last()
+                .expect("There is always an end of file token.");
+
+            (end.line, end.column)
+        };
+
+        assert_eq!(end("let a = 1;\nprint a"), (2, 8));
+        assert_eq!(end("let a = \"x\\\"\ny\";\n/* a\n */ print a;"), (4, 13));
+        assert_eq!(end("print 1;\n"), (2, 1));
+        assert_eq!(end(""), (1, 1));
+    }
+
+    #[test]
+    fn test_scan_escapes() {
+        let literal = |source: &str| {
+            let tokens = Scanner::new(source).scan_tokens();
+
+            tokens[0].literal.clone()
+        };
+
+        assert_eq!(
+            literal(r#""a\tb\n\"c\" \\ \u{1F600}\u{e9}""#),
+            Some(Literal::String(
+                "a\tb\n\"c\" \\ \u{1F600}\u{e9}".to_string()
+            ))
+        );
+
+        let errors = |source: &str| {
+            let mut scanner = Scanner::new(source);
+            scanner.scan_tokens();
+
+            scanner
+                .errors()
+                .iter()
+                .map(|error| (error.line, error.column, error.message.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            errors("print \"ok\";\nprint \"x\\u{110000}\";"),
+            [(2, 9, "Invalid unicode code point '110000'".to_string())]
+        );
+        assert_eq!(
+            errors(r#""\u{D800}" "\u{}" "\u{1234567}" "\u41" "\q""#),
+            [
+                (1, 2, "Invalid unicode code point 'D800'".to_string()),
+                (
+                    1,
+                    13,
+                    "Expected 1 to 6 hex digits followed by '}' in unicode escape".to_string()
+                ),
+                (
+                    1,
+                    20,
+                    "Expected 1 to 6 hex digits followed by '}' in unicode escape".to_string()
+                ),
+                (1, 34, "Expected '{' after '\\u'".to_string()),
+                (1, 41, "Unknown escape sequence '\\q'".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_characters() {
+        let tokens = Scanner::new(r"'a' '\n' '\'' '\u{e9}' '名' '\\'").scan_tokens();
+        let literals = tokens
+            .iter()
+            .filter(|token| token.token_type == TokenType::Char)
+            .map(|token| token.literal.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            literals,
+            ['a', '\n', '\'', '\u{e9}', '名', '\\'].map(|c| Some(Literal::Char(c)))
+        );
+
+        let errors = |source: &str| {
+            let mut scanner = Scanner::new(source);
+            scanner.scan_tokens();
+
+            scanner
+                .errors()
+                .iter()
+                .map(|error| (error.line, error.column, error.message.clone()))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            errors(
+                "let a = '';
+let b = 'ab';"
+            ),
+            [
+                (1, 9, "Empty character literal".to_string()),
+                (
+                    2,
+                    9,
+                    "Character literal must contain exactly one character".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            errors(
+                "let c = 'a;
+let d = '\\q';"
+            ),
+            [
+                (1, 9, "Unterminated character literal".to_string()),
+                (2, 10, "Unknown escape sequence '\\q'".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_display_tokens() {
+        let tokens = Scanner::new("let foo = (\"hi\");\n  foo += 1.5;").scan_tokens();
+        let rendered = tokens.iter().map(Token::to_string).collect::<Vec<_>>();
+
+        assert_eq!(
+            rendered,
+            [
+                "Variable \"let\" @ 1:1",
+                "Identifier \"foo\" @ 1:5",
+                "Equal \"=\" @ 1:9",
+                "LeftParenthesis \"(\" @ 1:11",
+                "String \"\"hi\"\" @ 1:12",
+                "RightParenthesis \")\" @ 1:16",
+                "Semicolon \";\" @ 1:17",
+                "Identifier \"foo\" @ 2:3",
+                "PlusEqual \"+=\" @ 2:7",
+                "Number \"1.5\" @ 2:10",
+                "Semicolon \";\" @ 2:13",
+                "EndOfFile \"\" @ 2:14",
+            ]
+        );
+        assert_eq!(TokenType::LeftParenthesis.to_string(), "left parenthesis");
+        assert_eq!(TokenType::Variable.to_string(), "let keyword");
+        assert_eq!(TokenType::EndOfFile.to_string(), "end of file");
+    }
+
+    #[test]
+    fn test_unicode_identifiers() {
+        let tokens = Scanner::new("let größe_2 = überall + _x1 + 名前;").scan_tokens();
+        let names = tokens
+            .iter()
+            .filter(|token| token.token_type == TokenType::Identifier)
+            .map(|token| &*token.lexeme)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["größe_2", "überall", "_x1", "名前"]);
+
+        // Emoji are not letters, so they cannot be part of identifiers.
+        let mut scanner = Scanner::new("let a😀 = 1;");
+        scanner.scan_tokens();
+        assert_eq!(scanner.errors().len(), 1);
+        assert_eq!(scanner.errors()[0].message, "Unexpected character '😀'");
+        assert_eq!(scanner.errors()[0].column, 6);
+    }
 }
@@ -0,0 +1,1527 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::lang::generator::{unresolved, Error};
+use crate::lang::interpreter;
+use crate::lang::lexer::{Literal, Token, TokenType};
+use crate::lang::natives;
+use crate::lang::parser::{Expression, Statement};
+use crate::lang::resolver::{Resolution, Resolver};
+use crate::lang::semantic_analyzer::Type;
+
+/// Generates textual LLVM IR from a syntax tree, for LLVM's own tools to optimize and compile.
+///
+/// Only variables, functions, blocks, if statements, loops, `print` and expression statements of integer,
+/// float and boolean literals, arithmetic, `!`, comparisons and calls are supported so far.
+/// Integers are `i64`, floats are `double` and booleans are `i1`, and integers are converted to floats
+/// when mixed with them.
+/// Values are printed the way the interpreter prints them, with `printf` from the C runtime,
+/// which `lli` and `clang` provide.
+/// The program becomes a `main` function returning the value of its last integer or boolean
+/// expression statement, or 0 if it has none, so the value is the exit status of the compiled program.
+/// Integer divisions check their operands first, and print the interpreter's error and exit with status 1
+/// instead of dividing by zero or overflowing.
+///
+/// Pointers are opaque, which LLVM 15 and later expect, so LLVM 14 needs the `-opaque-pointers` option.
+#[derive(Debug)]
+pub struct LlvmGenerator<'a> {
+    statements: &'a [Statement],
+    /// Where each variable reference is declared.
+    resolution: Resolution,
+    /// The variables and functions in each scope, in the order the resolver numbers them.
+    /// Like the resolver's, the outermost scope holds the native functions, which cannot be compiled yet.
+    scopes: Vec<Vec<Binding>>,
+    /// The function being generated, starting with `main`.
+    function: Function,
+    /// The number of functions generated, so every function has an id of its own.
+    functions: usize,
+    /// The signatures of the declared functions, keyed by the byte offset of their names.
+    signatures: HashMap<usize, Signature>,
+    /// The definitions of the functions generated so far, after `main` in the module.
+    definitions: String,
+    /// The global constants of the module, such as format strings and string literals.
+    globals: String,
+    /// The names of the string constants, keyed by their contents, so equal strings are only defined once.
+    strings: HashMap<String, String>,
+    /// Whether a float is printed, so the module needs the routine printing them.
+    prints_floats: bool,
+    /// Whether the program can stop at an error, so the module needs the functions reporting it.
+    fails: bool,
+    /// The statements and expressions that cannot be compiled, as generating goes on past them to find the rest.
+    errors: Vec<Error>,
+}
+
+/// The kind of a value, which decides its LLVM type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Kind {
+    #[default]
+    Int,
+    Float,
+    Boolean,
+}
+
+impl Kind {
+    /// Gets the LLVM type of the kind's values.
+    fn ir(self) -> &'static str {
+        match self {
+            Kind::Int => "i64",
+            Kind::Float => "double",
+            Kind::Boolean => "i1",
+        }
+    }
+
+    /// Gets the value every value of the kind starts out as.
+    fn zero(self) -> String {
+        match self {
+            Kind::Int => "0".to_string(),
+            Kind::Float => float(0.0),
+            Kind::Boolean => "false".to_string(),
+        }
+    }
+}
+
+/// A value computed by the generated code.
+#[derive(Debug, Clone)]
+struct Value {
+    kind: Kind,
+    /// The constant or register holding the value.
+    operand: String,
+}
+
+impl Value {
+    fn new(kind: Kind, operand: String) -> Self {
+        Self { kind, operand }
+    }
+}
+
+/// What a name in scope refers to.
+#[derive(Debug, Clone)]
+enum Binding {
+    /// A variable, with the id of the function it belongs to, the pointer to its stack slot and its kind.
+    Variable {
+        function: usize,
+        pointer: String,
+        kind: Kind,
+    },
+    /// A function.
+    Function(Signature),
+    /// A native function, which cannot be compiled yet.
+    Native,
+}
+
+/// The name and types of a function.
+#[derive(Debug, Clone)]
+struct Signature {
+    /// The global name of the function.
+    name: String,
+    parameters: Vec<Kind>,
+    /// The kind of value the function returns, which is an integer if it is not annotated with one.
+    returns: Kind,
+}
+
+/// The state of the function being generated.
+#[derive(Debug, Default)]
+struct Function {
+    /// The id of the function, unique within the module.
+    id: usize,
+    /// The kind of value the function returns.
+    returns: Kind,
+    /// The stack slots of the function's variables, allocated at its entry, so loops do not allocate them again.
+    allocations: String,
+    /// The instructions and labels of the function's body.
+    body: String,
+    /// The number of registers, labels and variables given out, so their names are unique within the function.
+    names: usize,
+    /// Whether the current basic block has ended with a branch or return.
+    terminated: bool,
+    /// The labels 'continue' and 'break' branch to in each enclosing loop, innermost last.
+    loops: Vec<LoopLabels>,
+}
+
+/// Where the statements ending an iteration of a loop branch to.
+#[derive(Debug)]
+struct LoopLabels {
+    /// Where 'continue' branches to, the condition or increment of the loop.
+    next: String,
+    /// Where 'break' branches to, just past the loop.
+    end: String,
+}
+
+impl<'a> LlvmGenerator<'a> {
+    /// Creates a new generator.
+    ///
+    /// # Arguments
+    /// * `statements` - The program to generate code for, which must already have been analyzed.
+    pub fn new(statements: &'a [Statement]) -> Self {
+        let natives = vec![Binding::Native; natives::standard_library().len()];
+
+        Self {
+            statements,
+            resolution: Resolution::default(),
+            scopes: vec![natives, Vec::new()],
+            function: Function::default(),
+            functions: 1,
+            signatures: HashMap::new(),
+            definitions: String::new(),
+            globals: String::new(),
+            strings: HashMap::new(),
+            prints_floats: false,
+            fails: false,
+            errors: Vec::new(),
+        }
+    }
+
+    /// Generates the LLVM IR module for the program.
+    ///
+    /// # Returns
+    /// The module, or an error for every statement that cannot be compiled,
+    /// at the first part of it that cannot be compiled.
+    ///
+    /// # Examples
+    /// ```
+    /// use cpl::lang::llvm::LlvmGenerator;
+    /// use cpl::lang::parse;
+    ///
+    /// let statements = parse("-(1 + 2);").unwrap();
+    /// let ir = LlvmGenerator::new(&statements).generate().unwrap();
+    ///
+    /// assert!(ir.contains("define i32 @main()"));
+    /// assert!(ir.contains("%t0 = add i64 1, 2"));
+    /// ```
+    pub fn generate(mut self) -> Result<String, Vec<Error>> {
+        self.resolution = Resolver::resolve(self.statements).map_err(unresolved)?;
+
+        // Without any expression statements, the program exits successfully.
+        self.function
+            .allocations
+            .push_str("  %status = alloca i64\n  store i64 0, ptr %status\n");
+        if let Err(error) = self.statements(self.statements) {
+            self.errors.push(error);
+        }
+        if !self.errors.is_empty() {
+            return Err(self.errors);
+        }
+
+        let status = self.assign("load i64, ptr %status");
+        let code = self.assign(&format!("trunc i64 {} to i32", status));
+        self.terminate(&format!("ret i32 {}", code));
+        let float_printer = self.prints_floats.then(|| self.float_printer());
+
+        let mut module = String::new();
+        if !self.globals.is_empty() {
+            module.push_str(&self.globals);
+            module.push('\n');
+        }
+        module.push_str("declare i32 @printf(ptr, ...)\n");
+        if float_printer.is_some() {
+            module.push_str(FLOAT_PRINTER_DECLARATIONS);
+        }
+        if self.fails {
+            module.push_str(FAILURE_DECLARATIONS);
+        }
+        module.push('\n');
+        module.push_str(&define("i32 @main()", &self.function));
+        module.push_str(&self.definitions);
+        if let Some(float_printer) = float_printer {
+            module.push('\n');
+            module.push_str(&float_printer);
+        }
+
+        Ok(module)
+    }
+
+    /// Generates the code for the statements of a scope, keeping the errors of the ones that cannot be compiled.
+    ///
+    /// # Returns
+    /// Nothing, or the error of a function whose signature cannot be compiled.
+    fn statements(&mut self, statements: &[Statement]) -> Result<(), Error> {
+        // The resolver declares the functions of a scope before its variables,
+        // so they can call each other in any order.
+        for statement in statements {
+            if let Statement::Function {
+                name,
+                parameters,
+                return_type,
+                ..
+            } = statement
+            {
+                let parameters = parameters
+                    .iter()
+                    .map(|(_, annotation)| kind(annotation))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let returns = match return_type {
+                    Some(annotation) => kind(annotation)?,
+                    None => Kind::Int,
+                };
+                let signature = Signature {
+                    name: global(&format!("cpl_{}_{}", name.lexeme, self.signatures.len())),
+                    parameters,
+                    returns,
+                };
+
+                self.signatures.insert(name.start, signature.clone());
+                self.bind(Binding::Function(signature));
+            }
+        }
+
+        for statement in statements {
+            if let Err(error) = self.statement(statement) {
+                self.errors.push(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Generates the code for a statement.
+    fn statement(&mut self, statement: &Statement) -> Result<(), Error> {
+        match statement {
+            Statement::Expression(expression, _) => {
+                let value = self.expression(expression)?;
+
+                // Only the program's own expression statements decide its exit status.
+                if self.function.id == 0 {
+                    let status = match value.kind {
+                        Kind::Int => value.operand,
+                        Kind::Boolean => self.assign(&format!("zext i1 {} to i64", value.operand)),
+                        Kind::Float => return Ok(()),
+                    };
+                    self.emit(&format!("store i64 {}, ptr %status", status));
+                }
+
+                Ok(())
+            }
+            Statement::Print(expression, _) => self.print(expression),
+            Statement::Variable {
+                name,
+                declared_type,
+                initializer,
+                ..
+            } => {
+                let value = match initializer {
+                    Some(initializer) => self.expression(initializer),
+                    // Variables declared without a value start out as 0.
+                    None => Ok(Value::new(Kind::Int, Kind::Int.zero())),
+                };
+                let value = match (value, declared_type) {
+                    (Ok(value), Some(annotation)) => {
+                        kind(annotation).and_then(|kind| self.convert(value, kind, name))
+                    }
+                    (value, _) => value,
+                };
+                let value = match value {
+                    Ok(value) => value,
+                    Err(error) => {
+                        // The variable is still declared, so the names declared after it are found.
+                        self.declare(name, Kind::Int);
+
+                        return Err(error);
+                    }
+                };
+
+                let pointer = self.declare(name, value.kind);
+                self.emit(&format!(
+                    "store {} {}, ptr {}",
+                    value.kind.ir(),
+                    value.operand,
+                    pointer
+                ));
+
+                Ok(())
+            }
+            Statement::Function {
+                name,
+                parameters,
+                body,
+                ..
+            } => self.function(name, parameters, body),
+            Statement::Return { keyword, value, .. } => {
+                let returns = self.function.returns;
+                let value = match value {
+                    Some(value) => {
+                        let value = self.expression(value)?;
+                        self.convert(value, returns, keyword)?
+                    }
+                    None => Value::new(returns, returns.zero()),
+                };
+                self.terminate(&format!("ret {} {}", returns.ir(), value.operand));
+
+                Ok(())
+            }
+            Statement::Block(statements, _) => {
+                self.scopes.push(Vec::new());
+                let result = self.statements(statements);
+                self.scopes.pop();
+
+                result
+            }
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                let condition = self.condition(condition)?;
+                let then = self.label("then");
+                let end = self.label("end");
+                let otherwise = match else_branch {
+                    Some(_) => self.label("else"),
+                    None => end.clone(),
+                };
+
+                self.terminate(&format!(
+                    "br i1 {}, label %{}, label %{}",
+                    condition, then, otherwise
+                ));
+                self.place(&then);
+                self.statement(then_branch)?;
+
+                if let Some(else_branch) = else_branch {
+                    self.branch(&end);
+                    self.place(&otherwise);
+                    self.statement(else_branch)?;
+                }
+
+                self.place(&end);
+
+                Ok(())
+            }
+            Statement::While {
+                condition, body, ..
+            } => {
+                let start = self.label("while");
+                let then = self.label("do");
+                let end = self.label("end");
+
+                self.place(&start);
+                let condition = self.condition(condition)?;
+                self.terminate(&format!(
+                    "br i1 {}, label %{}, label %{}",
+                    condition, then, end
+                ));
+                self.place(&then);
+                self.loop_body(body, &start, &end)?;
+                self.branch(&start);
+                self.place(&end);
+
+                Ok(())
+            }
+            Statement::Loop { body, .. } => {
+                let start = self.label("loop");
+                let end = self.label("end");
+
+                self.place(&start);
+                self.loop_body(body, &start, &end)?;
+                self.branch(&start);
+                self.place(&end);
+
+                Ok(())
+            }
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            } => {
+                // The initializer's variable is only visible within the loop.
+                self.scopes.push(Vec::new());
+                let result = self.for_loop(
+                    initializer.as_deref(),
+                    condition.as_ref(),
+                    increment.as_ref(),
+                    body,
+                );
+                self.scopes.pop();
+
+                result
+            }
+            Statement::Break { keyword, .. } | Statement::Continue { keyword, .. } => {
+                let Some(labels) = self.function.loops.last() else {
+                    return Err(Error::UnsupportedStatement(keyword.line, keyword.column));
+                };
+
+                let label = match statement {
+                    Statement::Break { .. } => labels.end.clone(),
+                    _ => labels.next.clone(),
+                };
+                self.branch(&label);
+
+                Ok(())
+            }
+            Statement::Comment { .. } => Ok(()),
+            Statement::Destructure { names, span, .. } => {
+                for name in names {
+                    self.declare(name, Kind::Int);
+                }
+
+                Err(Error::UnsupportedConstruct(
+                    "Destructuring declarations",
+                    *span,
+                ))
+            }
+            Statement::Assert { span, .. } => Err(Error::UnsupportedConstruct("Assertions", *span)),
+            Statement::Include { span, .. } => Err(Error::UnsupportedConstruct("Includes", *span)),
+            Statement::Module { span, .. } => Err(Error::UnsupportedConstruct("Modules", *span)),
+        }
+    }
+
+    /// Generates the code for an expression.
+    ///
+    /// # Returns
+    /// The value of the expression, or the first part of it that cannot be compiled.
+    fn expression(&mut self, expression: &Expression) -> Result<Value, Error> {
+        match expression {
+            Expression::Literal {
+                value: Literal::Int(value),
+                ..
+            } => Ok(Value::new(Kind::Int, value.to_string())),
+            Expression::Literal {
+                value: Literal::Float(value),
+                ..
+            } => Ok(Value::new(Kind::Float, float(*value))),
+            Expression::Literal {
+                value: Literal::Boolean(value),
+                ..
+            } => Ok(Value::new(Kind::Boolean, value.to_string())),
+            Expression::Grouping(expression, _) => self.expression(expression),
+            Expression::Variable(name, _) => {
+                let (pointer, kind) = self.lookup(name)?;
+                let register = self.assign(&format!("load {}, ptr {}", kind.ir(), pointer));
+
+                Ok(Value::new(kind, register))
+            }
+            Expression::Assign { name, value, .. } => {
+                let value = self.expression(value)?;
+                let (pointer, kind) = self.lookup(name)?;
+                let value = self.convert(value, kind, name)?;
+                self.emit(&format!(
+                    "store {} {}, ptr {}",
+                    kind.ir(),
+                    value.operand,
+                    pointer
+                ));
+
+                Ok(value)
+            }
+            Expression::Unary {
+                operator, right, ..
+            } => {
+                let right = self.expression(right)?;
+                let instruction = match (&operator.token_type, right.kind) {
+                    (TokenType::Minus, Kind::Int) => format!("sub i64 0, {}", right.operand),
+                    (TokenType::Minus, Kind::Float) => format!("fneg double {}", right.operand),
+                    (TokenType::Bang, Kind::Boolean) => format!("xor i1 {}, true", right.operand),
+                    _ => return Err(unsupported(operator)),
+                };
+
+                Ok(Value::new(right.kind, self.assign(&instruction)))
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => self.binary(left, operator, right),
+            Expression::Call {
+                callee, arguments, ..
+            } => self.call(callee, arguments),
+            Expression::Literal { token, .. } => Err(unsupported(token)),
+            Expression::Coalesce { span, .. } => Err(Error::UnsupportedConstruct(
+                "Null-coalescing expressions",
+                *span,
+            )),
+            Expression::Qualified { span, .. } => {
+                Err(Error::UnsupportedConstruct("Module members", *span))
+            }
+            Expression::Tuple(_, span) => Err(Error::UnsupportedConstruct("Tuples", *span)),
+            Expression::TypeOf(_, span) => {
+                Err(Error::UnsupportedConstruct("'typeof' expressions", *span))
+            }
+        }
+    }
+
+    /// Generates the code for an arithmetic operation or a comparison.
+    /// Integers are converted to floats when mixed with them, and booleans can only be compared for equality.
+    fn binary(
+        &mut self,
+        left: &Expression,
+        operator: &Token,
+        right: &Expression,
+    ) -> Result<Value, Error> {
+        let left = self.expression(left)?;
+        let right = self.expression(right)?;
+
+        let kind = match (left.kind, right.kind) {
+            (Kind::Float, _) | (_, Kind::Float) => Kind::Float,
+            (left, right) if left == right => left,
+            _ => return Err(unsupported(operator)),
+        };
+        let left = self.convert(left, kind, operator)?;
+        let right = self.convert(right, kind, operator)?;
+        if kind == Kind::Int && matches!(operator.token_type, TokenType::Slash | TokenType::Percent)
+        {
+            self.check_division(&left, operator, &right);
+        }
+
+        let instruction = match (kind, &operator.token_type) {
+            (Kind::Int, TokenType::Plus) => "add",
+            (Kind::Int, TokenType::Minus) => "sub",
+            (Kind::Int, TokenType::Star) => "mul",
+            (Kind::Int, TokenType::Slash) => "sdiv",
+            (Kind::Int, TokenType::Percent) => "srem",
+            (Kind::Float, TokenType::Plus) => "fadd",
+            (Kind::Float, TokenType::Minus) => "fsub",
+            (Kind::Float, TokenType::Star) => "fmul",
+            (Kind::Float, TokenType::Slash) => "fdiv",
+            (Kind::Float, TokenType::Percent) => "frem",
+            (Kind::Int | Kind::Boolean, TokenType::EqualEqual) => "icmp eq",
+            (Kind::Int | Kind::Boolean, TokenType::BangEqual) => "icmp ne",
+            (Kind::Int, TokenType::LessThan) => "icmp slt",
+            (Kind::Int, TokenType::LessThanOrEqual) => "icmp sle",
+            (Kind::Int, TokenType::GreaterThan) => "icmp sgt",
+            (Kind::Int, TokenType::GreaterThanOrEqual) => "icmp sge",
+            (Kind::Float, TokenType::EqualEqual) => "fcmp oeq",
+            (Kind::Float, TokenType::BangEqual) => "fcmp une",
+            (Kind::Float, TokenType::LessThan) => "fcmp olt",
+            (Kind::Float, TokenType::LessThanOrEqual) => "fcmp ole",
+            (Kind::Float, TokenType::GreaterThan) => "fcmp ogt",
+            (Kind::Float, TokenType::GreaterThanOrEqual) => "fcmp oge",
+            _ => return Err(unsupported(operator)),
+        };
+        let register = self.assign(&format!(
+            "{} {} {}, {}",
+            instruction,
+            kind.ir(),
+            left.operand,
+            right.operand
+        ));
+
+        if instruction.contains("cmp") {
+            Ok(Value::new(Kind::Boolean, register))
+        } else {
+            Ok(Value::new(kind, register))
+        }
+    }
+
+    /// Stops the program before an integer division by zero, or one overflowing as the smallest integer
+    /// divided by -1 does, with the errors the interpreter reports for them.
+    fn check_division(&mut self, left: &Value, operator: &Token, right: &Value) {
+        let zero = self.assign(&format!("icmp eq i64 {}, 0", right.operand));
+        self.fail_if(
+            &zero,
+            &interpreter::Error::DivisionByZero(operator.line, operator.column),
+        );
+
+        let minus_one = self.assign(&format!("icmp eq i64 {}, -1", right.operand));
+        let smallest = self.assign(&format!("icmp eq i64 {}, {}", left.operand, i64::MIN));
+        let overflows = self.assign(&format!("and i1 {}, {}", minus_one, smallest));
+        self.fail_if(
+            &overflows,
+            &interpreter::Error::IntegerOverflow(
+                operator.lexeme.to_string(),
+                operator.line,
+                operator.column,
+            ),
+        );
+    }
+
+    /// Prints an error to the standard error and exits with status 1 when a condition holds.
+    ///
+    /// # Arguments
+    /// * `condition` - The boolean deciding whether the program stops.
+    /// * `error` - The error the interpreter reports in the same place, printed after its line and column.
+    fn fail_if(&mut self, condition: &str, error: &interpreter::Error) {
+        self.fails = true;
+        let fail = self.label("fail");
+        let pass = self.label("pass");
+        self.terminate(&format!(
+            "br i1 {}, label %{}, label %{}",
+            condition, fail, pass
+        ));
+
+        self.place(&fail);
+        let (line, column) = error.position();
+        // The message is an argument, as it can contain '%'.
+        let format = self.string("%s\n");
+        let message = self.string(&format!("{}:{}: {}", line, column, error));
+        self.emit(&format!(
+            "call i32 (i32, ptr, ...) @dprintf(i32 2, ptr {}, ptr {})",
+            format, message
+        ));
+        self.emit("call void @exit(i32 1)");
+        self.terminate("unreachable");
+        self.place(&pass);
+    }
+
+    /// Calls a function, converting the arguments to the kinds of its parameters.
+    fn call(&mut self, callee: &Expression, arguments: &[Expression]) -> Result<Value, Error> {
+        let name = match callee {
+            Expression::Variable(name, _) => name,
+            callee => return Err(unsupported(callee.first_token())),
+        };
+        let signature = match self.binding(name)? {
+            Binding::Function(signature) => signature.clone(),
+            _ => return Err(unsupported(name)),
+        };
+
+        let mut values = Vec::new();
+        for (argument, kind) in arguments.iter().zip(&signature.parameters) {
+            let value = self.expression(argument)?;
+            let value = self.convert(value, *kind, argument.first_token())?;
+
+            values.push(format!("{} {}", kind.ir(), value.operand));
+        }
+
+        let register = self.assign(&format!(
+            "call {} {}({})",
+            signature.returns.ir(),
+            signature.name,
+            values.join(", ")
+        ));
+
+        Ok(Value::new(signature.returns, register))
+    }
+
+    /// Prints a value with `printf`, choosing the format by its kind.
+    /// Strings can only be printed as literals so far, and floats are printed by a routine of their own,
+    /// as `printf` has no format printing them the way the interpreter does.
+    fn print(&mut self, value: &Expression) -> Result<(), Error> {
+        let (format, argument) = match literal(value) {
+            Some(Literal::String(string)) => ("%s\n", format!("ptr {}", self.string(string))),
+            _ => {
+                let value = self.expression(value)?;
+
+                match value.kind {
+                    Kind::Int => ("%ld\n", format!("i64 {}", value.operand)),
+                    Kind::Float => {
+                        self.prints_floats = true;
+                        self.emit(&format!(
+                            "call void @cpl_print_float(double {})",
+                            value.operand
+                        ));
+
+                        return Ok(());
+                    }
+                    Kind::Boolean => {
+                        let (yes, no) = (self.string("true"), self.string("false"));
+                        let text = self.assign(&format!(
+                            "select i1 {}, ptr {}, ptr {}",
+                            value.operand, yes, no
+                        ));
+
+                        ("%s\n", format!("ptr {}", text))
+                    }
+                }
+            }
+        };
+
+        let format = self.string(format);
+        self.emit(&format!(
+            "call i32 (ptr, ...) @printf(ptr {}, {})",
+            format, argument
+        ));
+
+        Ok(())
+    }
+
+    /// Generates the code for a 'for' loop, whose scope has already been opened.
+    fn for_loop(
+        &mut self,
+        initializer: Option<&Statement>,
+        condition: Option<&Expression>,
+        increment: Option<&Expression>,
+        body: &Statement,
+    ) -> Result<(), Error> {
+        if let Some(initializer) = initializer {
+            self.statement(initializer)?;
+        }
+
+        let start = self.label("for");
+        let then = self.label("do");
+        let next = self.label("next");
+        let end = self.label("end");
+
+        self.place(&start);
+        if let Some(condition) = condition {
+            let condition = self.condition(condition)?;
+            self.terminate(&format!(
+                "br i1 {}, label %{}, label %{}",
+                condition, then, end
+            ));
+        }
+        self.place(&then);
+        self.loop_body(body, &next, &end)?;
+        self.place(&next);
+        if let Some(increment) = increment {
+            self.expression(increment)?;
+        }
+        self.branch(&start);
+        self.place(&end);
+
+        Ok(())
+    }
+
+    /// Generates the code for the body of a loop.
+    ///
+    /// # Arguments
+    /// * `body` - The body of the loop.
+    /// * `next` - The label 'continue' branches to.
+    /// * `end` - The label 'break' branches to.
+    fn loop_body(&mut self, body: &Statement, next: &str, end: &str) -> Result<(), Error> {
+        self.function.loops.push(LoopLabels {
+            next: next.to_string(),
+            end: end.to_string(),
+        });
+        let result = self.statement(body);
+        self.function.loops.pop();
+
+        result
+    }
+
+    /// Generates a function as a definition of its own, after the ones generated so far.
+    /// Its parameters are stored in stack slots, so they can be assigned to like other variables.
+    fn function(
+        &mut self,
+        name: &Token,
+        parameters: &[(Token, Token)],
+        body: &Statement,
+    ) -> Result<(), Error> {
+        let signature = self.signatures[&name.start].clone();
+        let function = Function {
+            id: self.functions,
+            returns: signature.returns,
+            ..Function::default()
+        };
+        self.functions += 1;
+        let enclosing = std::mem::replace(&mut self.function, function);
+
+        // The parameters share the scope of the body, like they do in the resolver.
+        self.scopes.push(Vec::new());
+        let mut arguments = Vec::new();
+        for (i, ((parameter, _), kind)) in parameters.iter().zip(&signature.parameters).enumerate()
+        {
+            let argument = format!("%p{}", i);
+            let pointer = self.declare(parameter, *kind);
+            self.emit(&format!(
+                "store {} {}, ptr {}",
+                kind.ir(),
+                argument,
+                pointer
+            ));
+
+            arguments.push(format!("{} {}", kind.ir(), argument));
+        }
+        let result = match body {
+            Statement::Block(statements, _) => self.statements(statements),
+            body => self.statements(std::slice::from_ref(body)),
+        };
+        self.scopes.pop();
+        // Functions without a 'return' at their end return 0.
+        if !self.function.terminated {
+            let returns = signature.returns;
+            self.terminate(&format!("ret {} {}", returns.ir(), returns.zero()));
+        }
+
+        let function = std::mem::replace(&mut self.function, enclosing);
+        result?;
+
+        let header = format!(
+            "{} {}({})",
+            signature.returns.ir(),
+            signature.name,
+            arguments.join(", ")
+        );
+        self.definitions.push('\n');
+        self.definitions.push_str(&define(&header, &function));
+
+        Ok(())
+    }
+
+    /// Generates the code for the condition of an if statement or loop.
+    ///
+    /// # Returns
+    /// The operand holding the condition, or an error if it is not a boolean.
+    fn condition(&mut self, condition: &Expression) -> Result<String, Error> {
+        let value = self.expression(condition)?;
+        if value.kind != Kind::Boolean {
+            return Err(unsupported(condition.first_token()));
+        }
+
+        Ok(value.operand)
+    }
+
+    /// Converts a value to another kind, which only integers can be, to floats.
+    ///
+    /// # Arguments
+    /// * `token` - Where the error is reported, if the value cannot be converted.
+    fn convert(&mut self, value: Value, kind: Kind, token: &Token) -> Result<Value, Error> {
+        match (value.kind, kind) {
+            (from, to) if from == to => Ok(value),
+            (Kind::Int, Kind::Float) => {
+                let register = self.assign(&format!("sitofp i64 {} to double", value.operand));
+
+                Ok(Value::new(Kind::Float, register))
+            }
+            _ => Err(unsupported(token)),
+        }
+    }
+
+    /// Defines the routine printing a float the way the interpreter does, with the fewest digits that read back
+    /// as the same float, the closer ones if two do, in scientific notation if it is at least 1e16 or below 1e-4,
+    /// and with a fraction otherwise.
+    /// The digits are cut from the exact expansion `printf` gives, trying one more at a time until `strtod` reads
+    /// the float back from the digits rounded down or up, so they are rounded the way the interpreter rounds them.
+    ///
+    /// # Returns
+    /// The definition of the routine.
+    fn float_printer(&mut self) -> String {
+        let not_a_number = self.string("NaN\n");
+        // Infinities are printed as 'inf' and '-inf' by every format.
+        let infinity = self.string("%g\n");
+        let expansion = self.string("%.*e");
+        let candidate = self.string("%lde%d");
+        let zero = self.string("0");
+        let zeros = self.string("0000000000000000");
+        let (minus, plus) = (self.string("-"), self.string(""));
+        let scientific_digit = self.string("%s%lde%d\n");
+        let scientific = self.string("%s%ld.%0*lde%d\n");
+        let whole = self.string("%s%ld%.*s.0\n");
+        let mixed = self.string("%s%ld.%0*ld\n");
+        let fraction = self.string("%s0.%.*s%ld\n");
+        let powers = (0..19)
+            .map(|power| format!("i64 {}", 10_i64.pow(power)))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        format!(
+            r#"@cpl_powers_of_ten = private unnamed_addr constant [19 x i64] [{powers}]
+
+define private void @cpl_print_float(double %x) {{
+entry:
+  %exact = alloca [820 x i8]
+  %candidate = alloca [32 x i8]
+  %abs = call double @llvm.fabs.f64(double %x)
+  %finite = fcmp olt double %abs, {infinite}
+  br i1 %finite, label %expand, label %special
+special:
+  %nan = fcmp uno double %x, %x
+  br i1 %nan, label %not_a_number, label %infinity
+not_a_number:
+  call i32 (ptr, ...) @printf(ptr {not_a_number})
+  ret void
+infinity:
+  call i32 (ptr, ...) @printf(ptr {infinity}, double %x)
+  ret void
+expand:
+  ; The exact digits, of which a double never has more than 767.
+  call i32 (ptr, i64, ptr, ...) @snprintf(ptr %exact, i64 820, ptr {expansion}, i32 800, double %abs)
+  %e = call ptr @strchr(ptr %exact, i32 101)
+  %after = getelementptr i8, ptr %e, i64 1
+  %exponent = call i32 @atoi(ptr %after)
+  %leading = load i8, ptr %exact
+  %leading.code = zext i8 %leading to i64
+  %leading.digit = sub i64 %leading.code, 48
+  br label %search
+search:
+  ; The digits so far, rounded down, and whether the rest of them are zeros.
+  %precision = phi i32 [ 0, %expand ], [ %more, %next ]
+  %floor = phi i64 [ %leading.digit, %expand ], [ %floor.next, %next ]
+  %position = add i32 %precision, 2
+  %position.wide = zext i32 %position to i64
+  %rest = getelementptr i8, ptr %exact, i64 %position.wide
+  %rest.zeros = call i64 @strspn(ptr %rest, ptr {zero})
+  %precision.wide = zext i32 %precision to i64
+  %rest.length = sub i64 800, %precision.wide
+  %is_exact = icmp eq i64 %rest.zeros, %rest.length
+  br i1 %is_exact, label %found, label %round
+round:
+  ; The digits rounded down or up are kept if they read back as the float, the closer ones if both do.
+  %scale = sub i32 %exponent, %precision
+  call i32 (ptr, i64, ptr, ...) @snprintf(ptr %candidate, i64 32, ptr {candidate}, i64 %floor, i32 %scale)
+  %floor.read = call double @strtod(ptr %candidate, ptr null)
+  %floor.fits = fcmp oeq double %floor.read, %abs
+  %ceiling = add i64 %floor, 1
+  call i32 (ptr, i64, ptr, ...) @snprintf(ptr %candidate, i64 32, ptr {candidate}, i64 %ceiling, i32 %scale)
+  %ceiling.read = call double @strtod(ptr %candidate, ptr null)
+  %ceiling.fits = fcmp oeq double %ceiling.read, %abs
+  %next.code = load i8, ptr %rest
+  %closer = icmp uge i8 %next.code, 53
+  %floor.fails = xor i1 %floor.fits, true
+  %ceiling.wanted = or i1 %closer, %floor.fails
+  %use.ceiling = and i1 %ceiling.fits, %ceiling.wanted
+  %chosen = select i1 %use.ceiling, i64 %ceiling, i64 %floor
+  %fits = or i1 %floor.fits, %ceiling.fits
+  br i1 %fits, label %found, label %next
+next:
+  %more = add i32 %precision, 1
+  %next.wide = zext i8 %next.code to i64
+  %next.digit = sub i64 %next.wide, 48
+  %floor.shifted = mul i64 %floor, 10
+  %floor.next = add i64 %floor.shifted, %next.digit
+  br label %search
+found:
+  ; Rounding up may carry into another digit, as in 9.99 becoming 10.
+  %digits = phi i64 [ %floor, %search ], [ %chosen, %round ]
+  %count = add i64 %precision.wide, 1
+  %limit.pointer = getelementptr [19 x i64], ptr @cpl_powers_of_ten, i64 0, i64 %count
+  %limit = load i64, ptr %limit.pointer
+  %carried = icmp eq i64 %digits, %limit
+  %significand = select i1 %carried, i64 1, i64 %digits
+  %places = select i1 %carried, i32 0, i32 %precision
+  %exponent.carried = add i32 %exponent, 1
+  %magnitude = select i1 %carried, i32 %exponent.carried, i32 %exponent
+  ; Negative zero is printed with its sign too.
+  %bits = bitcast double %x to i64
+  %negative = icmp slt i64 %bits, 0
+  %sign = select i1 %negative, ptr {minus}, ptr {plus}
+  %large = fcmp oge double %abs, {large}
+  %small = fcmp olt double %abs, {small}
+  %nonzero = fcmp one double %abs, {zero_float}
+  %tiny = and i1 %small, %nonzero
+  %exponential = or i1 %large, %tiny
+  br i1 %exponential, label %scientific, label %positional
+scientific:
+  %single = icmp eq i32 %places, 0
+  br i1 %single, label %scientific.digit, label %scientific.digits
+scientific.digit:
+  call i32 (ptr, ...) @printf(ptr {scientific_digit}, ptr %sign, i64 %significand, i32 %magnitude)
+  ret void
+scientific.digits:
+  %places.wide = zext i32 %places to i64
+  %unit.pointer = getelementptr [19 x i64], ptr @cpl_powers_of_ten, i64 0, i64 %places.wide
+  %unit = load i64, ptr %unit.pointer
+  %lead = udiv i64 %significand, %unit
+  %tail = urem i64 %significand, %unit
+  call i32 (ptr, ...) @printf(ptr {scientific}, ptr %sign, i64 %lead, i32 %places, i64 %tail, i32 %magnitude)
+  ret void
+positional:
+  %is_whole = icmp sge i32 %magnitude, %places
+  br i1 %is_whole, label %whole, label %part
+whole:
+  %padding = sub i32 %magnitude, %places
+  call i32 (ptr, ...) @printf(ptr {whole}, ptr %sign, i64 %significand, i32 %padding, ptr {zeros})
+  ret void
+part:
+  %below_one = icmp slt i32 %magnitude, 0
+  br i1 %below_one, label %fraction, label %mixed
+mixed:
+  %decimals = sub i32 %places, %magnitude
+  %decimals.wide = zext i32 %decimals to i64
+  %divisor.pointer = getelementptr [19 x i64], ptr @cpl_powers_of_ten, i64 0, i64 %decimals.wide
+  %divisor = load i64, ptr %divisor.pointer
+  %integer = udiv i64 %significand, %divisor
+  %decimal = urem i64 %significand, %divisor
+  call i32 (ptr, ...) @printf(ptr {mixed}, ptr %sign, i64 %integer, i32 %decimals, i64 %decimal)
+  ret void
+fraction:
+  %leading.zeros = sub i32 -1, %magnitude
+  call i32 (ptr, ...) @printf(ptr {fraction}, ptr %sign, i32 %leading.zeros, ptr {zeros}, i64 %significand)
+  ret void
+}}
+"#,
+            infinite = float(f64::INFINITY),
+            large = float(1e16),
+            small = float(1e-4),
+            zero_float = float(0.0),
+        )
+    }
+
+    /// Defines a string as a global constant, terminated by a null byte, unless it has been defined already.
+    ///
+    /// # Returns
+    /// The global name of the string.
+    fn string(&mut self, string: &str) -> String {
+        if let Some(name) = self.strings.get(string) {
+            return name.clone();
+        }
+
+        let name = format!("@.str.{}", self.strings.len());
+        writeln!(
+            self.globals,
+            "{} = private unnamed_addr constant [{} x i8] c\"{}\\00\"",
+            name,
+            string.len() + 1,
+            escape(string)
+        )
+        .expect("Writing to a string cannot fail.");
+        self.strings.insert(string.to_string(), name.clone());
+
+        name
+    }
+
+    /// Creates a new label, unique within the function.
+    ///
+    /// # Arguments
+    /// * `purpose` - What the label marks, such as "then", to make the IR easier to follow.
+    fn label(&mut self, purpose: &str) -> String {
+        let label = format!("{}.{}", purpose, self.function.names);
+        self.function.names += 1;
+
+        label
+    }
+
+    /// Starts a new basic block, branching to it from the current one unless that has ended already.
+    fn place(&mut self, label: &str) {
+        if !self.function.terminated {
+            self.terminate(&format!("br label %{}", label));
+        }
+
+        writeln!(self.function.body, "{}:", label).expect("Writing to a string cannot fail.");
+        self.function.terminated = false;
+    }
+
+    /// Branches to a label, unless the current basic block has ended already.
+    fn branch(&mut self, label: &str) {
+        if !self.function.terminated {
+            self.terminate(&format!("br label %{}", label));
+        }
+    }
+
+    /// Gives a stack slot of the current function to a variable declared in the innermost scope.
+    ///
+    /// # Returns
+    /// The pointer to the stack slot.
+    fn declare(&mut self, name: &Token, kind: Kind) -> String {
+        let pointer = local(&format!("{}.{}", name.lexeme, self.function.names));
+        self.function.names += 1;
+
+        writeln!(
+            self.function.allocations,
+            "  {} = alloca {}",
+            pointer,
+            kind.ir()
+        )
+        .expect("Writing to a string cannot fail.");
+        self.bind(Binding::Variable {
+            function: self.function.id,
+            pointer: pointer.clone(),
+            kind,
+        });
+
+        pointer
+    }
+
+    /// Adds a name to the innermost scope.
+    fn bind(&mut self, binding: Binding) {
+        self.scopes
+            .last_mut()
+            .expect("There is always a scope.")
+            .push(binding);
+    }
+
+    /// Finds what a name refers to.
+    ///
+    /// # Returns
+    /// The binding, or an error if the name was not resolved.
+    fn binding(&self, name: &Token) -> Result<&Binding, Error> {
+        self.resolution
+            .get(name)
+            .and_then(|slot| {
+                let scope = self.scopes.len().checked_sub(slot.depth + 1)?;
+
+                self.scopes[scope].get(slot.index)
+            })
+            .ok_or_else(|| unsupported(name))
+    }
+
+    /// Finds the stack slot of the variable a name refers to.
+    ///
+    /// # Returns
+    /// The pointer to the slot and the kind of the variable, or an error if the name is not a variable
+    /// of the current function.
+    fn lookup(&self, name: &Token) -> Result<(String, Kind), Error> {
+        match self.binding(name)? {
+            Binding::Variable {
+                function,
+                pointer,
+                kind,
+            } if *function == self.function.id => Ok((pointer.clone(), *kind)),
+            Binding::Variable { .. } => Err(Error::Captured(
+                name.lexeme.to_string(),
+                name.line,
+                name.column,
+            )),
+            _ => Err(unsupported(name)),
+        }
+    }
+
+    /// Adds an instruction producing a value to the function's body.
+    ///
+    /// # Returns
+    /// The register holding the value.
+    fn assign(&mut self, instruction: &str) -> String {
+        let register = format!("%t{}", self.function.names);
+        self.function.names += 1;
+        self.emit(&format!("{} = {}", register, instruction));
+
+        register
+    }
+
+    /// Adds an instruction ending the current basic block, such as a branch or return.
+    fn terminate(&mut self, instruction: &str) {
+        self.emit(instruction);
+        self.function.terminated = true;
+    }
+
+    /// Adds an indented instruction to the function's body.
+    /// Code after a branch or return cannot run, but still needs a basic block of its own.
+    fn emit(&mut self, instruction: &str) {
+        if self.function.terminated {
+            let label = self.label("dead");
+            self.place(&label);
+        }
+
+        writeln!(self.function.body, "  {}", instruction)
+            .expect("Writing to a string cannot fail.");
+    }
+}
+
+/// Assembles the definition of a function from its header and body.
+/// The stack slots are allocated in the entry block, before the body.
+fn define(header: &str, function: &Function) -> String {
+    format!(
+        "define {} {{\nentry:\n{}{}}}\n",
+        header, function.allocations, function.body
+    )
+}
+
+/// Gets the kind of the values a type annotation names.
+///
+/// # Returns
+/// The kind, or an error if values of the type cannot be compiled yet.
+fn kind(annotation: &Token) -> Result<Kind, Error> {
+    match Type::from_annotation(annotation) {
+        Ok(Type::Int) => Ok(Kind::Int),
+        Ok(Type::Float) => Ok(Kind::Float),
+        Ok(Type::Boolean) => Ok(Kind::Boolean),
+        _ => Err(unsupported(annotation)),
+    }
+}
+
+/// Writes a float as LLVM expects it, as the hexadecimal bits of a double,
+/// as decimal floats must be exactly representable.
+fn float(value: f64) -> String {
+    format!("0x{:016X}", value.to_bits())
+}
+
+/// Gets the literal an expression consists of, looking through parentheses.
+fn literal(expression: &Expression) -> Option<&Literal> {
+    match expression {
+        Expression::Grouping(expression, _) => literal(expression),
+        Expression::Literal { value, .. } => Some(value),
+        _ => None,
+    }
+}
+
+/// The functions of the C runtime and LLVM the routine printing floats uses.
+const FLOAT_PRINTER_DECLARATIONS: &str = "declare i32 @snprintf(ptr, i64, ptr, ...)
+declare double @strtod(ptr, ptr)
+declare ptr @strchr(ptr, i32)
+declare i32 @atoi(ptr)
+declare i64 @strspn(ptr, ptr)
+declare double @llvm.fabs.f64(double)
+";
+
+/// The functions of the C runtime a program stopping at an error uses.
+const FAILURE_DECLARATIONS: &str = "declare i32 @dprintf(i32, ptr, ...)
+declare void @exit(i32)
+";
+
+/// Creates the name of a local value.
+fn local(name: &str) -> String {
+    format!("%{}", identifier(name))
+}
+
+/// Creates the name of a global value.
+fn global(name: &str) -> String {
+    format!("@{}", identifier(name))
+}
+
+/// Writes a name as LLVM expects it, quoting it if it has characters LLVM does not allow in bare names.
+fn identifier(name: &str) -> String {
+    if name
+        .chars()
+        .all(|character| character.is_ascii_alphanumeric() || matches!(character, '_' | '.'))
+    {
+        name.to_string()
+    } else {
+        format!("\"{}\"", escape(name))
+    }
+}
+
+/// Escapes a string for LLVM, writing quotes, backslashes and every byte that is not printable ASCII in hexadecimal.
+fn escape(string: &str) -> String {
+    let mut escaped = String::new();
+
+    for byte in string.bytes() {
+        if matches!(byte, b' '..=b'~') && byte != b'"' && byte != b'\\' {
+            escaped.push(char::from(byte));
+        } else {
+            write!(escaped, "\\{:02X}", byte).expect("Writing to a string cannot fail.");
+        }
+    }
+
+    escaped
+}
+
+/// Creates the error for a token starting something that cannot be compiled yet.
+fn unsupported(token: &Token) -> Error {
+    Error::Unsupported(token.lexeme.to_string(), token.line, token.column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::parse;
+    use crate::lang::span::Span;
+
+    /// Generates the module for the source code.
+    fn generate(source: &str) -> Result<String, Vec<Error>> {
+        let statements = parse(source).expect("Parsing should succeed.");
+
+        LlvmGenerator::new(&statements).generate()
+    }
+
+    /// Generates the module for the source code, without the parts every program has,
+    /// leaving only the body of `main` after its exit status has been allocated.
+    fn body(source: &str) -> Vec<String> {
+        let ir = generate(source).expect("Generating should succeed.");
+        let lines = ir.lines().collect::<Vec<_>>();
+
+        let start = lines
+            .iter()
+            .position(|line| *line == "  store i64 0, ptr %status")
+            .expect("The program should start with its default exit status.");
+        let end = lines
+            .iter()
+            .position(|line| line.ends_with("= load i64, ptr %status"))
+            .expect("The program should end by returning its exit status.");
+
+        lines[start + 1..end]
+            .iter()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_generate_skeleton() {
+        assert_eq!(
+            generate(""),
+            Ok(concat!(
+                "declare i32 @printf(ptr, ...)\n",
+                "\n",
+                "define i32 @main() {\n",
+                "entry:\n",
+                "  %status = alloca i64\n",
+                "  store i64 0, ptr %status\n",
+                "  %t0 = load i64, ptr %status\n",
+                "  %t1 = trunc i64 %t0 to i32\n",
+                "  ret i32 %t1\n",
+                "}\n",
+            )
+            .to_string())
+        );
+    }
+
+    #[test]
+    fn test_generate_arithmetic() {
+        assert_eq!(
+            body("1 + 2 * 3;"),
+            [
+                "  %t0 = mul i64 2, 3",
+                "  %t1 = add i64 1, %t0",
+                "  store i64 %t1, ptr %status",
+            ]
+        );
+
+        // Integers are converted when mixed with floats, which are written as their bits.
+        assert_eq!(
+            body("let a = 1; let b = a + 2.5; print b;"),
+            [
+                "  %a.0 = alloca i64",
+                "  %b.4 = alloca double",
+                "  store i64 1, ptr %a.0",
+                "  %t1 = load i64, ptr %a.0",
+                "  %t2 = sitofp i64 %t1 to double",
+                "  %t3 = fadd double %t2, 0x4004000000000000",
+                "  store double %t3, ptr %b.4",
+                "  %t5 = load double, ptr %b.4",
+                "  call void @cpl_print_float(double %t5)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_division_checks() {
+        // Integer divisions stop the program with the interpreter's error before dividing by zero or overflowing.
+        assert_eq!(
+            body("let a = 7; a / 2;"),
+            [
+                "  %a.0 = alloca i64",
+                "  store i64 7, ptr %a.0",
+                "  %t1 = load i64, ptr %a.0",
+                "  %t2 = icmp eq i64 2, 0",
+                "  br i1 %t2, label %fail.3, label %pass.4",
+                "fail.3:",
+                "  call i32 (i32, ptr, ...) @dprintf(i32 2, ptr @.str.0, ptr @.str.1)",
+                "  call void @exit(i32 1)",
+                "  unreachable",
+                "pass.4:",
+                "  %t5 = icmp eq i64 2, -1",
+                "  %t6 = icmp eq i64 %t1, -9223372036854775808",
+                "  %t7 = and i1 %t5, %t6",
+                "  br i1 %t7, label %fail.8, label %pass.9",
+                "fail.8:",
+                "  call i32 (i32, ptr, ...) @dprintf(i32 2, ptr @.str.0, ptr @.str.2)",
+                "  call void @exit(i32 1)",
+                "  unreachable",
+                "pass.9:",
+                "  %t10 = sdiv i64 %t1, 2",
+                "  store i64 %t10, ptr %status",
+            ]
+        );
+
+        let ir = generate("let a = 7; print a % 2;").expect("Generating should succeed.");
+        assert!(ir.contains("c\"1:20: Cannot divide an integer by zero\\00\""));
+        assert!(ir.contains("c\"1:20: Integer overflow in '%'\\00\""));
+        assert!(ir.contains("declare void @exit(i32)\n"));
+
+        // Float divisions need no checks.
+        let ir = generate("let a = 7.0; print a / 0;").expect("Generating should succeed.");
+        assert!(!ir.contains("@dprintf"));
+    }
+
+    #[test]
+    fn test_generate_float_printer() {
+        // The routine printing floats is only defined when a float is printed.
+        let ir = generate("print 1; print 1.5; print 2.5;").expect("Generating should succeed.");
+        assert_eq!(
+            ir.matches("define private void @cpl_print_float(double %x)")
+                .count(),
+            1
+        );
+        assert!(ir.contains("declare double @strtod(ptr, ptr)\n"));
+
+        let ir = generate("print 1; print true;").expect("Generating should succeed.");
+        assert!(!ir.contains("@cpl_print_float"));
+        assert!(!ir.contains("@strtod"));
+    }
+
+    #[test]
+    fn test_generate_branches() {
+        assert_eq!(
+            body("let a = 1; if (a > 0) print true; else a = 2;"),
+            [
+                "  %a.0 = alloca i64",
+                "  store i64 1, ptr %a.0",
+                "  %t1 = load i64, ptr %a.0",
+                "  %t2 = icmp sgt i64 %t1, 0",
+                "  br i1 %t2, label %then.3, label %else.5",
+                "then.3:",
+                "  %t6 = select i1 true, ptr @.str.0, ptr @.str.1",
+                "  call i32 (ptr, ...) @printf(ptr @.str.2, ptr %t6)",
+                "  br label %end.4",
+                "else.5:",
+                "  store i64 2, ptr %a.0",
+                "  store i64 2, ptr %status",
+                "  br label %end.4",
+                "end.4:",
+            ]
+        );
+        assert_eq!(
+            body("let n = 3; while (n > 0) n = n - 1;"),
+            [
+                "  %n.0 = alloca i64",
+                "  store i64 3, ptr %n.0",
+                "  br label %while.1",
+                "while.1:",
+                "  %t4 = load i64, ptr %n.0",
+                "  %t5 = icmp sgt i64 %t4, 0",
+                "  br i1 %t5, label %do.2, label %end.3",
+                "do.2:",
+                "  %t6 = load i64, ptr %n.0",
+                "  %t7 = sub i64 %t6, 1",
+                "  store i64 %t7, ptr %n.0",
+                "  store i64 %t7, ptr %status",
+                "  br label %while.1",
+                "end.3:",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_functions() {
+        let ir = generate("fn add(a: int, b: int) -> int { return a + b; } print add(1, 2);")
+            .expect("Generating should succeed.");
+
+        assert!(
+            ir.starts_with("@.str.0 = private unnamed_addr constant [5 x i8] c\"%ld\\0A\\00\"\n")
+        );
+        assert!(ir.contains("  %t0 = call i64 @cpl_add_0(i64 1, i64 2)\n"));
+        assert!(ir.ends_with(concat!(
+            "define i64 @cpl_add_0(i64 %p0, i64 %p1) {\n",
+            "entry:\n",
+            "  %a.0 = alloca i64\n",
+            "  %b.1 = alloca i64\n",
+            "  store i64 %p0, ptr %a.0\n",
+            "  store i64 %p1, ptr %b.1\n",
+            "  %t2 = load i64, ptr %a.0\n",
+            "  %t3 = load i64, ptr %b.1\n",
+            "  %t4 = add i64 %t2, %t3\n",
+            "  ret i64 %t4\n",
+            "}\n",
+        )));
+    }
+
+    #[test]
+    fn test_generate_strings() {
+        // Equal strings share a constant.
+        let ir = generate("print \"hi\"; print \"hi\";").expect("Generating should succeed.");
+
+        assert!(ir.starts_with(concat!(
+            "@.str.0 = private unnamed_addr constant [3 x i8] c\"hi\\00\"\n",
+            "@.str.1 = private unnamed_addr constant [4 x i8] c\"%s\\0A\\00\"\n",
+        )));
+        assert_eq!(
+            body("print \"hi\"; print \"hi\";"),
+            [
+                "  call i32 (ptr, ...) @printf(ptr @.str.1, ptr @.str.0)",
+                "  call i32 (ptr, ...) @printf(ptr @.str.1, ptr @.str.0)",
+            ]
+        );
+        assert_eq!(escape("a \"b\"\n\u{e9}"), "a \\22b\\22\\0A\\C3\\A9");
+    }
+
+    #[test]
+    fn test_generate_unsupported() {
+        assert_eq!(
+            generate("assert(true);"),
+            Err(vec![Error::UnsupportedConstruct(
+                "Assertions",
+                Span {
+                    start: 0,
+                    end: 13,
+                    line: 1,
+                    column: 1
+                }
+            )])
+        );
+        assert_eq!(
+            generate("\"a\" + \"b\";"),
+            Err(vec![Error::Unsupported("\"a\"".to_string(), 1, 1)])
+        );
+        assert_eq!(
+            generate("print 1 && 2;"),
+            Err(vec![Error::Unsupported("&&".to_string(), 1, 9)])
+        );
+        assert_eq!(
+            generate("print clock();"),
+            Err(vec![Error::Unsupported("clock".to_string(), 1, 7)])
+        );
+        assert_eq!(
+            generate("let a = 1; fn f() -> int { return a; }"),
+            Err(vec![Error::Captured("a".to_string(), 1, 35)])
+        );
+    }
+}
@@ -1,33 +1,300 @@
 use std::io::Write;
+use std::path::PathBuf;
 
+use crate::lang::dump::Dump;
+use crate::lang::errors::{report, warn, Error, Severity};
+use crate::lang::lexer::Token;
+use crate::lang::parser::Statement;
+use crate::lang::semantic_analyzer::AnalyzerOptions;
+use crate::lang::symbols::SymbolTable;
+use crate::lang::target::Target;
 use crate::util::timer::{format_time, Timer};
 
+pub mod bytecode;
+pub mod diagnostics;
+pub mod dump;
 pub mod errors;
+pub mod formatter;
 pub mod generator;
+pub mod includes;
+pub mod interner;
+pub mod interpreter;
 pub mod lexer;
+pub mod llvm;
+pub mod natives;
+pub mod optimizer;
 pub mod parser;
+pub mod peephole;
+pub mod printer;
+pub mod resolver;
+pub mod semantic_analyzer;
+pub mod span;
+pub mod symbols;
+pub mod target;
+pub mod visitor;
 
 /// The maximum number of parameters a function can have.
 pub const MAX_PARAMETERS: usize = 255;
 /// The maximum number of arguments a function can take.
 pub const MAX_ARGUMENTS: usize = 255;
+/// The default maximum depth of nested expressions and statements.
+/// Every stage walks the syntax tree recursively, so this bounds how much of the stack they use, see `STACK_SIZE`.
+pub const MAX_EXPRESSION_DEPTH: usize = 1000;
+/// The default maximum number of nested function calls at runtime.
+pub const MAX_CALL_DEPTH: usize = 1000;
+/// The default maximum number of statements and expressions, of every function being called,
+/// that a call can be nested in at runtime, so functions with deeply nested bodies cannot overflow the stack.
+pub const MAX_NESTING_DEPTH: usize = 10_000;
+/// The default maximum number of errors reported for a run, so a badly broken file does not flood the output.
+pub const MAX_ERRORS: usize = 20;
+/// The size of the stack programs are run with by `with_stack`, in bytes.
+/// Enough for every stage to walk a syntax tree nested `MAX_EXPRESSION_DEPTH` deep,
+/// and for the interpreter to run calls nested `MAX_NESTING_DEPTH` deep, in debug builds.
+pub const STACK_SIZE: usize = 128 << 20;
+
+/// Tokenizes the given source code.
+///
+/// # Arguments
+/// * `source` - The source code to tokenize.
+///
+/// # Returns
+/// The tokens, or every error found while scanning.
+pub fn lex(source: &str) -> Result<Vec<Token>, Vec<Error>> {
+    let mut scanner = lexer::Scanner::new(source);
+    let tokens = scanner.scan_tokens();
+
+    if scanner.had_error() {
+        Err(scanner.errors().to_vec())
+    } else {
+        Ok(tokens)
+    }
+}
+
+/// Tokenizes and parses the given source code.
+///
+/// # Arguments
+/// * `source` - The source code to parse.
+///
+/// # Returns
+/// The syntax tree, or the errors found while scanning or parsing.
+///
+/// # Examples
+/// ```
+/// use cpl::lang::parse;
+/// use cpl::lang::parser::Statement;
+///
+/// let statements = parse("let a = 1 + 2; print a;").unwrap();
+///
+/// assert_eq!(statements.len(), 2);
+/// assert!(matches!(statements[0], Statement::Variable { .. }));
+/// assert_eq!(statements[1].to_string(), "(print a)");
+/// ```
+pub fn parse(source: &str) -> Result<Vec<Statement>, Vec<Error>> {
+    let tokens = lex(source)?;
+
+    parser::Parser::new(&tokens).parse()
+}
+
+/// Runs a function on a new thread with a stack of `STACK_SIZE` bytes, waiting for it to finish.
+/// The main thread's stack may be too small for deeply nested programs, depending on the platform.
+///
+/// Symbols are only valid on the thread that interned them, so the result should not contain any.
+///
+/// # Arguments
+/// * `f` - The function to run.
+///
+/// # Returns
+/// The result of the function.
+///
+/// # Examples
+/// ```
+/// use cpl::lang::{parse, with_stack};
+///
+/// let source = format!("print {}1;", "- ".repeat(500));
+///
+/// assert!(with_stack(|| parse(&source).is_ok()));
+/// ```
+pub fn with_stack<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    std::thread::scope(|scope| {
+        let thread = std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, f)
+            .expect("The thread should spawn.");
+
+        // A panic on the thread is a panic of the caller.
+        thread
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// The results of running a CPL program, with how long each stage took in nanoseconds.
+/// Stages after a failing one are not run, so they have no time.
+#[derive(Debug, Default)]
+pub struct RunReport {
+    pub tokenize_time: Option<u128>,
+    pub parse_time: Option<u128>,
+    pub analyze_time: Option<u128>,
+    pub optimize_time: Option<u128>,
+    pub interpret_time: Option<u128>,
+    pub generate_time: Option<u128>,
+    pub peephole_time: Option<u128>,
+    /// The tokens, if scanning succeeded.
+    pub tokens: Vec<Token>,
+    /// The syntax tree, if parsing succeeded, optimized if analysis succeeded as well.
+    pub syntax_tree: Option<Vec<Statement>>,
+    /// The names declared in the program, if parsing succeeded.
+    pub symbols: Option<SymbolTable>,
+    /// The generated assembly or LLVM IR, if it was asked for and generating it succeeded.
+    pub assembly: Option<String>,
+    /// The number of instructions the peephole optimizer removed from the assembly, if it ran.
+    pub removed_instructions: Option<usize>,
+    pub warnings: Vec<Error>,
+    /// The errors of the stage that failed, if any, up to the maximum number of errors.
+    pub errors: Vec<Error>,
+    /// The number of errors found after the maximum number of errors was reached.
+    pub omitted_errors: usize,
+}
+
+impl RunReport {
+    /// Gets the time taken by all stages that ran.
+    pub fn total_time(&self) -> u128 {
+        [
+            self.tokenize_time,
+            self.parse_time,
+            self.analyze_time,
+            self.optimize_time,
+            self.interpret_time,
+            self.generate_time,
+            self.peephole_time,
+        ]
+        .into_iter()
+        .flatten()
+        .sum()
+    }
+}
 
 /// A struct representing a CPL program.
 pub struct Cpl {
+    /// Whether the last program run, or file formatted, failed.
     pub had_error: bool,
+    /// Which warnings are reported, and whether they fail the program like errors do.
+    pub analyzer_options: AnalyzerOptions,
+    /// Whether to print the symbol table as JSON after analysis, by itself, instead of running the program.
+    pub print_symbols: bool,
+    /// Whether to generate code for the program instead of running it.
+    pub emit_assembly: bool,
+    /// What code to generate, x86_64 assembly unless chosen otherwise.
+    pub target: Target,
+    /// Whether to remove obvious junk from the generated x86_64 assembly, see `peephole::optimize`.
+    pub peephole: bool,
+    /// The file to write the generated code to.
+    /// Without one, it is written next to the file being run, with the target's extension,
+    /// and only printed if no file is being run.
+    pub output: Option<PathBuf>,
+    /// Whether to leave out the tokens, syntax tree and timings when running,
+    /// printing only the program's own output and the diagnostics.
+    pub quiet: bool,
+    /// Whether to stop after analysis, reporting the diagnostics without running the program or generating code.
+    pub check: bool,
+    /// Whether the program must declare a `main` function without parameters.
+    pub require_entry_point: bool,
+    /// The most errors reported for a run, the rest are only counted.
+    pub max_errors: usize,
+    /// The file being run or formatted, which diagnostics are prefixed with and included paths are relative to.
+    /// Without one, diagnostics are prefixed with `<repl>` and included paths are relative to the current directory.
+    pub file: Option<PathBuf>,
 }
 
 impl Cpl {
     /// Creates a new CPL program.
     pub fn new() -> Self {
-        Self { had_error: false }
+        Self {
+            had_error: false,
+            analyzer_options: AnalyzerOptions::default(),
+            print_symbols: false,
+            emit_assembly: false,
+            target: Target::default(),
+            peephole: false,
+            output: None,
+            quiet: false,
+            check: false,
+            require_entry_point: false,
+            max_errors: MAX_ERRORS,
+            file: None,
+        }
+    }
+
+    /// Runs the CPL program in a file.
+    ///
+    /// # Returns
+    /// The same as `run`, or an error if the file cannot be read.
+    pub fn run_file(&mut self, file_path: &str) -> Result<Vec<Statement>, Vec<Error>> {
+        self.file = Some(PathBuf::from(file_path));
+
+        // The file may have been removed, or become unreadable, since it was checked.
+        let source = match std::fs::read_to_string(file_path) {
+            Ok(source) => source,
+            Err(error) => {
+                let errors = vec![file_error("read", file_path, error)];
+                self.report_errors(&errors);
+
+                return Err(errors);
+            }
+        };
+
+        self.run(source)
+    }
+
+    /// Formats a CPL source file.
+    ///
+    /// # Arguments
+    /// * `file_path` - The path to the file to format.
+    /// * `write` - Whether to write the result back to the file instead of printing it.
+    pub fn format_file(&mut self, file_path: &str, write: bool) {
+        self.file = Some(PathBuf::from(file_path));
+        self.had_error = false;
+
+        let source = match std::fs::read_to_string(file_path) {
+            Ok(source) => source,
+            Err(error) => return self.report_errors(&[file_error("read", file_path, error)]),
+        };
+
+        let formatted = match formatter::format(&source) {
+            Ok(formatted) => formatted,
+            Err(errors) => {
+                self.report_errors(&errors);
+                return;
+            }
+        };
+
+        if write {
+            if let Err(error) = std::fs::write(file_path, formatted) {
+                self.report_errors(&[file_error("write", file_path, error)]);
+            }
+        } else {
+            print!("{}", formatted);
+        }
     }
 
-    /// Runs the CPL program.
-    pub fn run_file(&mut self, file_path: &str) {
-        let source = std::fs::read_to_string(file_path).expect("Failed to read file!");
+    /// Prints a CPL source file for external tools or debugging, instead of running it.
+    ///
+    /// # Arguments
+    /// * `file_path` - The path to the file to dump.
+    /// * `dump` - Whether to dump the tokens, the syntax tree or the bytecode.
+    pub fn dump_file(&mut self, file_path: &str, dump: Dump) {
+        self.file = Some(PathBuf::from(file_path));
+        self.had_error = false;
+
+        let source = match std::fs::read_to_string(file_path) {
+            Ok(source) => source,
+            Err(error) => return self.report_errors(&[file_error("read", file_path, error)]),
+        };
 
-        self.run(source);
+        match dump.dump(&source) {
+            Ok(dump) => print!("{}", dump),
+            Err(errors) => self.report_errors(&errors),
+        }
     }
 
     /// Runs the CPL program in REPL mode.
@@ -49,45 +316,310 @@ impl Cpl {
                 break;
             }
 
-            self.run(input);
+            // The errors have already been reported, and a failing line does not end the REPL.
+            let _ = self.run(input);
         }
     }
 
-    /// Runs the CPL program.
+    /// Runs the CPL program, reporting its warnings and errors.
+    /// Unless quiet, the intermediate results and how long each stage took are printed as well.
     ///
     /// # Arguments
     /// * `source` - The source code to run.
-    pub fn run(&mut self, source: String) {
+    ///
+    /// # Returns
+    /// The syntax tree of the program, or the errors of the stage that failed.
+    ///
+    /// # Examples
+    /// ```
+    /// use cpl::lang::Cpl;
+    ///
+    /// let mut cpl = Cpl::new();
+    /// cpl.quiet = true;
+    ///
+    /// assert_eq!(cpl.run("let a = 1;".to_string()).unwrap().len(), 1);
+    /// assert!(cpl.run("let a = ;".to_string()).is_err());
+    /// ```
+    pub fn run(&mut self, source: String) -> Result<Vec<Statement>, Vec<Error>> {
+        let run = self.run_timed(source);
+
+        // The symbol table is printed by itself, so it can be read by other tools.
+        if self.print_symbols {
+            if let Some(symbols) = &run.symbols {
+                println!("{}", symbols.to_json());
+            }
+        } else if !self.quiet {
+            Self::print_report(&run);
+        }
+
+        for warning in &run.warnings {
+            warn(
+                self.file.as_deref(),
+                warning.line,
+                warning.column,
+                &warning.message,
+            );
+        }
+        for error in &run.errors {
+            report(
+                self.file.as_deref(),
+                error.line,
+                error.column,
+                &error.message,
+            );
+        }
+        match run.omitted_errors {
+            0 => {}
+            1 => eprintln!("… and 1 more error"),
+            omitted => eprintln!("… and {} more errors", omitted),
+        }
+
+        if !run.errors.is_empty() {
+            return Err(run.errors);
+        }
+
+        if let Some(assembly) = &run.assembly {
+            if let Err(error) = self.write_assembly(assembly) {
+                self.report_errors(std::slice::from_ref(&error));
+
+                return Err(vec![error]);
+            }
+        }
+
+        if !self.quiet && !self.print_symbols {
+            println!("Total time: {}.", format_time(run.total_time()));
+        }
+
+        Ok(run.syntax_tree.unwrap_or_default())
+    }
+
+    /// Writes generated code to the output file, creating the directories it is in if needed.
+    ///
+    /// # Returns
+    /// Nothing, or the error of the file that could not be created or written.
+    fn write_assembly(&self, assembly: &str) -> Result<(), Error> {
+        let path = match (&self.output, &self.file) {
+            (Some(output), _) => output.clone(),
+            (None, Some(file)) => file.with_extension(self.target.extension()),
+            (None, None) => return Ok(()),
+        };
+
+        if let Some(directory) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(directory)
+                .map_err(|error| file_error("create", &directory.to_string_lossy(), error))?;
+        }
+
+        std::fs::write(&path, assembly)
+            .map_err(|error| file_error("write", &path.to_string_lossy(), error))
+    }
+
+    /// Prints the intermediate results of a run and how long each stage took.
+    ///
+    /// # Arguments
+    /// * `run` - The results of the run.
+    fn print_report(run: &RunReport) {
+        println!("Tokens:");
+        for token in &run.tokens {
+            println!("{}", token);
+        }
+        if let Some(time) = run.tokenize_time {
+            println!("Tokenization took {}.", format_time(time));
+        }
+
+        if let Some(syntax_tree) = &run.syntax_tree {
+            println!("Syntax tree:\n{:#?}", syntax_tree);
+        }
+        if let Some(time) = run.parse_time {
+            println!("Parsing took {}.", format_time(time));
+        }
+
+        if let Some(time) = run.analyze_time {
+            println!("Analysis took {}.", format_time(time));
+        }
+        if let Some(time) = run.optimize_time {
+            println!("Optimization took {}.", format_time(time));
+        }
+        if let Some(time) = run.interpret_time {
+            println!("Interpretation took {}.", format_time(time));
+        }
+
+        if let Some(assembly) = &run.assembly {
+            println!("Assembly:\n{}", assembly);
+        }
+        if let Some(time) = run.generate_time {
+            println!("Code generation took {}.", format_time(time));
+        }
+        if let (Some(time), Some(removed)) = (run.peephole_time, run.removed_instructions) {
+            println!(
+                "Peephole optimization took {}, removing {} instructions.",
+                format_time(time),
+                removed
+            );
+        }
+    }
+
+    /// Runs the CPL program without printing anything but the program's own output.
+    ///
+    /// # Arguments
+    /// * `source` - The source code to run.
+    ///
+    /// # Returns
+    /// The results of each stage and how long they took.
+    pub fn run_timed(&mut self, source: String) -> RunReport {
+        // Every run starts afresh, so a failing line in the REPL does not fail the ones after it.
+        self.had_error = false;
+
         let mut timer = Timer::new();
+        let mut report = RunReport::default();
 
         // Tokenize the source code.
-        println!("Tokenizing...");
-        let (time, tokens) = timer.time(|| lexer::Scanner::new(&source).scan_tokens());
+        let (time, tokens) = timer.time(|| lex(&source));
+        report.tokenize_time = Some(time);
 
-        println!("Tokens:\n{:#?}", tokens);
-        println!("Tokenization took {}.", format_time(time));
+        let tokens = match tokens {
+            Ok(tokens) => tokens,
+            Err(errors) => return self.fail(report, errors),
+        };
 
-        // Parse the tokens.
-        println!("Parsing...");
-        let (time, syntax_tree) = timer.time(|| parser::Parser::new(&tokens).parse());
+        // Parse the tokens, along with the files they include.
+        let mut parser = parser::Parser::new(&tokens).with_max_errors(self.max_errors);
+        let (time, syntax_tree) = timer.time(|| {
+            parser
+                .parse()
+                .and_then(|statements| includes::resolve(statements, self.file.as_deref()))
+        });
+        report.omitted_errors = parser.omitted_errors();
+        report.tokens = tokens;
+        report.parse_time = Some(time);
 
-        println!("Syntax tree:\n{:#?}", syntax_tree);
-        println!("Parsing took {}.", format_time(time));
+        // The errors in included files are reported with their positions in those files.
+        let (syntax_tree, sources) = match syntax_tree {
+            Ok((syntax_tree, sources)) => (report.syntax_tree.insert(syntax_tree), sources),
+            Err(errors) => return self.fail(report, errors),
+        };
 
-        if syntax_tree.is_err() {
-            self.had_error = true;
-            return;
+        // Analyze the syntax tree.
+        let mut analyzer = semantic_analyzer::Analyzer::new()
+            .with_require_entry_point(self.require_entry_point)
+            .with_options(self.analyzer_options.clone())
+            .with_max_errors(self.max_errors);
+        let (time, diagnostics) = timer.time(|| analyzer.analyze(syntax_tree));
+        report.analyze_time = Some(time);
+        report.omitted_errors = analyzer.omitted_errors();
+        report.symbols = Some(analyzer.into_symbols());
+
+        // Warnings already have the severity of errors if they are to be treated as such.
+        let (errors, warnings): (Vec<_>, Vec<_>) = diagnostics
+            .into_iter()
+            .partition(|diagnostic| diagnostic.severity == Severity::Error);
+        report.warnings = sources.locate(warnings);
+
+        if !errors.is_empty() {
+            return self.fail(report, sources.locate(errors));
         }
 
-        // Generate the assembly code.
-        //println!("Generating code...");
-        //let (time, assembly) =
-        //    timer.time(|| generator::Generator::new(syntax_tree.unwrap()).generate());
+        if self.check || self.print_symbols {
+            return report;
+        }
 
-        //println!("Assembly:\n{}", assembly);
-        //println!("Code generation took {}.", format_time(time));
+        // Replace the constants by their values and remove the code that can never run.
+        let (time, optimized) = timer.time(|| optimizer::optimize(syntax_tree));
+        report.optimize_time = Some(time);
+
+        if let Err(errors) = optimized {
+            let errors = errors.into_iter().map(Error::from).collect();
+
+            return self.fail(report, sources.locate(errors));
+        }
+
+        if !self.emit_assembly {
+            // Run the syntax tree.
+            let (time, result) =
+                timer.time(|| interpreter::Interpreter::new().interpret(syntax_tree));
+            report.interpret_time = Some(time);
+
+            if let Err(error) = result {
+                return self.fail(report, sources.locate(vec![error.into()]));
+            }
+
+            return report;
+        }
+
+        // Generate the code for the target.
+        let target = self.target;
+        let (time, assembly) = timer.time(|| target.generate(syntax_tree));
+        report.generate_time = Some(time);
+
+        let assembly = match assembly {
+            Ok(assembly) => report.assembly.insert(assembly),
+            Err(errors) => {
+                let errors = errors.into_iter().map(Error::from).collect();
+
+                return self.fail(report, sources.locate(errors));
+            }
+        };
+
+        if !self.peephole || target != Target::Assembly {
+            return report;
+        }
+
+        // Remove the instructions that do nothing from the assembly.
+        let (time, (optimized, removed)) = timer.time(|| peephole::optimize(assembly));
+        report.peephole_time = Some(time);
+        report.removed_instructions = Some(removed);
+        *assembly = optimized;
+
+        report
+    }
+
+    /// Marks the program as failed and records the errors in the report,
+    /// counting the ones beyond the maximum number of errors instead.
+    fn fail(&mut self, mut report: RunReport, mut errors: Vec<Error>) -> RunReport {
+        self.had_error = true;
+
+        let max_errors = self.max_errors.max(1);
+        if errors.len() > max_errors {
+            report.omitted_errors += errors.len() - max_errors;
+            errors.truncate(max_errors);
+        }
+        report.errors = errors;
+
+        report
+    }
+
+    /// Reports the given errors and marks the program as failed.
+    ///
+    /// # Arguments
+    /// * `errors` - The errors to report.
+    fn report_errors(&mut self, errors: &[Error]) {
+        for error in errors {
+            report(
+                self.file.as_deref(),
+                error.line,
+                error.column,
+                &error.message,
+            );
+        }
+
+        self.had_error = true;
+    }
+}
 
-        println!("Total time: {}.", format_time(timer.total_time()));
+/// Creates the error for a file that could not be read or written.
+///
+/// # Arguments
+/// * `action` - What was done to the file, such as "read".
+/// * `file_path` - The path to the file.
+/// * `error` - The reason it failed.
+fn file_error(action: &str, file_path: &str, error: std::io::Error) -> Error {
+    Error {
+        line: 1,
+        column: 1,
+        message: format!("Cannot {} '{}': {}", action, file_path, error),
+        severity: Severity::Error,
     }
 }
 
@@ -0,0 +1,217 @@
+use std::fmt::{Debug, Formatter};
+use std::time::Instant;
+
+use crate::lang::interpreter::Value;
+use crate::lang::semantic_analyzer::Type;
+
+/// The signature of a native function's implementation.
+/// An error is returned as a message, which the interpreter attaches the call's position to.
+pub type NativeImplementation = dyn Fn(&[Value]) -> Result<Value, String>;
+
+/// A function implemented in Rust that can be called from CPL.
+pub struct NativeFunction {
+    pub name: String,
+    /// The number of arguments the function takes.
+    pub arity: usize,
+    /// The type of the values the function returns, as far as the analyzer can tell.
+    pub return_type: Type,
+    pub function: Box<NativeImplementation>,
+}
+
+impl NativeFunction {
+    /// Creates a new native function.
+    ///
+    /// # Arguments
+    /// * `name` - The name the function is called by.
+    /// * `arity` - The number of arguments the function takes.
+    /// * `function` - The implementation, which is only called with `arity` arguments.
+    pub fn new<F>(name: &str, arity: usize, function: F) -> Self
+    where
+        F: Fn(&[Value]) -> Result<Value, String> + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            arity,
+            return_type: Type::Unknown,
+            function: Box::new(function),
+        }
+    }
+
+    /// Sets the type of the values the function returns, which is unknown by default.
+    ///
+    /// # Arguments
+    /// * `return_type` - The type of the function's results.
+    pub fn returning(mut self, return_type: Type) -> Self {
+        self.return_type = return_type;
+
+        self
+    }
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .field("return_type", &self.return_type)
+            .finish()
+    }
+}
+
+/// Creates the native functions every program can call.
+///
+/// * `clock()` - The number of seconds since the functions were created.
+/// * `len(x)` - The number of characters in a string.
+/// * `str(x)` - Converts a value to a string.
+/// * `num(x)` - Converts a string or number to a number, which is an integer if the string holds one.
+/// * `int(x)` - Converts a number, string, boolean or character to an integer, truncating floats.
+/// * `float(x)` - Converts a number or string to a float.
+/// * `bool(x)` - Converts a value to a boolean, by whether it is truthy.
+pub fn standard_library() -> Vec<NativeFunction> {
+    let start = Instant::now();
+
+    vec![
+        NativeFunction::new("clock", 0, move |_| {
+            Ok(Value::Float(start.elapsed().as_secs_f64()))
+        })
+        .returning(Type::Float),
+        NativeFunction::new("len", 1, |arguments| match &arguments[0] {
+            Value::String(string) => Ok(Value::Int(string.chars().count() as i64)),
+            value => Err(format!("Cannot get the length of '{}'", value)),
+        })
+        .returning(Type::Int),
+        NativeFunction::new("str", 1, |arguments| {
+            Ok(Value::String(arguments[0].to_string()))
+        })
+        .returning(Type::String),
+        NativeFunction::new("int", 1, |arguments| match &arguments[0] {
+            Value::Int(int) => Ok(Value::Int(*int)),
+            // Floats are truncated towards zero, as long as the result fits in an integer.
+            Value::Float(float)
+                if float.is_finite() && (i64::MIN as f64..=i64::MAX as f64).contains(float) =>
+            {
+                Ok(Value::Int(*float as i64))
+            }
+            Value::String(string) => string
+                .trim()
+                .parse()
+                .map(Value::Int)
+                .map_err(|_| format!("Cannot convert '{}' to an int", string)),
+            Value::Boolean(boolean) => Ok(Value::Int(i64::from(*boolean))),
+            Value::Char(c) => Ok(Value::Int(i64::from(u32::from(*c)))),
+            value => Err(format!("Cannot convert '{}' to an int", value)),
+        })
+        .returning(Type::Int),
+        NativeFunction::new("float", 1, |arguments| match &arguments[0] {
+            Value::String(string) => string
+                .trim()
+                .parse()
+                .map(Value::Float)
+                .map_err(|_| format!("Cannot convert '{}' to a float", string)),
+            value => value
+                .as_float()
+                .map(Value::Float)
+                .ok_or_else(|| format!("Cannot convert '{}' to a float", value)),
+        })
+        .returning(Type::Float),
+        NativeFunction::new("bool", 1, |arguments| {
+            Ok(Value::Boolean(arguments[0].is_truthy()))
+        })
+        .returning(Type::Boolean),
+        NativeFunction::new("num", 1, |arguments| match &arguments[0] {
+            number @ (Value::Int(_) | Value::Float(_)) => Ok(number.clone()),
+            // Strings holding a whole number become integers, like literals do.
+            Value::String(string) => {
+                let string = string.trim();
+
+                string
+                    .parse()
+                    .map(Value::Int)
+                    .or_else(|_| string.parse().map(Value::Float))
+                    .map_err(|_| format!("Cannot convert '{}' to a number", string))
+            }
+            value => Err(format!("Cannot convert '{}' to a number", value)),
+        }),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lang::interpreter::{Error, Interpreter};
+    use crate::lang::parse;
+
+    /// Runs the source code and returns what it printed.
+    fn run(source: &str) -> String {
+        let statements = parse(source).expect("Parsing should succeed.");
+        let mut interpreter = Interpreter::with_output(Vec::new());
+        interpreter
+            .interpret(&statements)
+            .expect("Interpreting should succeed.");
+
+        String::from_utf8(interpreter.output().clone()).expect("Output should be UTF-8.")
+    }
+
+    #[test]
+    fn test_standard_library() {
+        assert_eq!(run("print len(\"abc\");"), "3\n");
+        assert_eq!(run("print str(1 + 2) + \"!\";"), "3!\n");
+        assert_eq!(run("print num(\" 2.5 \") * 2;"), "5.0\n");
+        assert_eq!(run("print num(\"7\") / 2;"), "3\n");
+        assert_eq!(
+            run("let start = clock(); print clock() >= start;"),
+            "true\n"
+        );
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(
+            run("print int(3.9); print int(-3.9); print int(\" 42 \"); print int(true); print int('a');"),
+            "3\n-3\n42\n1\n97\n"
+        );
+        assert_eq!(
+            run("print float(2); print float(\"2.5\"); print float(1e300);"),
+            "2.0\n2.5\n1e300\n"
+        );
+        assert_eq!(
+            run("print bool(0); print bool(none); print bool(\"\"); print str(1.5) + str(false);"),
+            "true\nfalse\ntrue\n1.5false\n"
+        );
+
+        let error = |source: &str| {
+            let statements = parse(source).expect("Parsing should succeed.");
+            let mut interpreter = Interpreter::with_output(Vec::new());
+
+            interpreter
+                .interpret(&statements)
+                .expect_err("Interpreting should fail.")
+        };
+        assert_eq!(
+            error("int(\"abc\");"),
+            Error::NativeFunction(
+                "int".to_string(),
+                "Cannot convert 'abc' to an int".to_string(),
+                1,
+                10
+            )
+        );
+        assert_eq!(
+            error("int(1e300);"),
+            Error::NativeFunction(
+                "int".to_string(),
+                "Cannot convert '1e300' to an int".to_string(),
+                1,
+                10
+            )
+        );
+        assert_eq!(
+            error("float(true);"),
+            Error::NativeFunction(
+                "float".to_string(),
+                "Cannot convert 'true' to a float".to_string(),
+                1,
+                11
+            )
+        );
+    }
+}
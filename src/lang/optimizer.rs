@@ -0,0 +1,525 @@
+use std::collections::HashMap;
+
+use crate::lang::interner::Symbol;
+use crate::lang::lexer::{Literal, Token, TokenType};
+use crate::lang::parser::{Expression, Statement};
+use crate::lang::semantic_analyzer::{fold_constant, Constant, Error};
+
+/// Simplifies a program after it has been analyzed,
+/// replacing constants by their values and removing the parts that can never run.
+///
+/// Replacing constants can reveal integers divided by zero, such as `1 / ZERO` or `1 % ZERO`,
+/// which are errors like the ones the analyzer finds in literals.
+///
+/// # Arguments
+/// * `statements` - The program to optimize, which behaves the same as before afterwards.
+///
+/// # Returns
+/// Nothing, or the divisions by zero found, leaving the constants replaced but the code that can never run in place.
+///
+/// # Examples
+/// ```
+/// use cpl::lang::optimizer::optimize;
+/// use cpl::lang::parse;
+///
+/// let mut statements = parse("fn f() { return 1; print 2; }").unwrap();
+/// optimize(&mut statements).unwrap();
+///
+/// assert_eq!(statements[0].to_string(), "(fn f() (block (ret return 1)))");
+///
+/// let mut statements = parse("const ZERO = 0; print 1 / ZERO;").unwrap();
+/// assert!(optimize(&mut statements).is_err());
+/// ```
+pub fn optimize(statements: &mut Vec<Statement>) -> Result<(), Vec<Error>> {
+    let mut propagator = ConstantPropagator::new();
+    propagator.propagate_statements(statements);
+    if !propagator.errors.is_empty() {
+        return Err(propagator.errors);
+    }
+
+    *statements = eliminate_dead_code(std::mem::take(statements));
+
+    Ok(())
+}
+
+/// Gets the statements that can never run, as they follow an unconditional `return`, `break` or `continue`.
+/// Functions are defined before the statements around them run, so they are never unreachable.
+///
+/// # Arguments
+/// * `statements` - The statements of a block.
+pub fn unreachable(statements: &[Statement]) -> impl Iterator<Item = &Statement> {
+    statements
+        .iter()
+        .skip_while(|statement| !is_jump(statement))
+        .skip(1)
+        .filter(|statement| !matches!(statement, Statement::Function { .. }))
+}
+
+/// Drops the statements that can never run, from the given statements and every block within them.
+fn eliminate_dead_code(statements: Vec<Statement>) -> Vec<Statement> {
+    let jump = statements.iter().position(is_jump);
+
+    statements
+        .into_iter()
+        .enumerate()
+        .filter(|(i, statement)| {
+            jump.is_none_or(|jump| *i <= jump) || matches!(statement, Statement::Function { .. })
+        })
+        .map(|(_, statement)| optimize_statement(statement))
+        .collect()
+}
+
+/// Optimizes the blocks within a statement.
+fn optimize_statement(statement: Statement) -> Statement {
+    match statement {
+        Statement::Block(statements, span) => {
+            Statement::Block(eliminate_dead_code(statements), span)
+        }
+        Statement::If {
+            condition,
+            then_branch,
+            else_branch,
+            span,
+        } => Statement::If {
+            condition,
+            then_branch: Box::new(optimize_statement(*then_branch)),
+            else_branch: else_branch.map(|branch| Box::new(optimize_statement(*branch))),
+            span,
+        },
+        Statement::While {
+            condition,
+            body,
+            span,
+        } => Statement::While {
+            condition,
+            body: Box::new(optimize_statement(*body)),
+            span,
+        },
+        Statement::Loop { body, span } => Statement::Loop {
+            body: Box::new(optimize_statement(*body)),
+            span,
+        },
+        Statement::For {
+            initializer,
+            condition,
+            increment,
+            body,
+            span,
+        } => Statement::For {
+            initializer,
+            condition,
+            increment,
+            body: Box::new(optimize_statement(*body)),
+            span,
+        },
+        Statement::Function {
+            name,
+            parameters,
+            return_type,
+            body,
+            span,
+        } => Statement::Function {
+            name,
+            parameters,
+            return_type,
+            body: Box::new(optimize_statement(*body)),
+            span,
+        },
+        Statement::Module {
+            name,
+            statements,
+            span,
+        } => Statement::Module {
+            name,
+            statements: eliminate_dead_code(statements),
+            span,
+        },
+        statement => statement,
+    }
+}
+
+/// Replaces the uses of constants declared with a literal value by the value itself.
+/// Variables declared with `let` can be assigned to, so they are never replaced.
+struct ConstantPropagator {
+    /// The variables declared in every scope, innermost last,
+    /// with the values of the ones that are constants with a literal value, and the tokens they were parsed from.
+    scopes: Vec<HashMap<Symbol, Option<(Literal, Token)>>>,
+    /// The integers divided by zero once the constants are replaced.
+    errors: Vec<Error>,
+}
+
+impl ConstantPropagator {
+    fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+            errors: Vec::new(),
+        }
+    }
+
+    /// Propagates constants into the statements of a scope.
+    /// Every name declared among them is declared first, as not being a constant,
+    /// as the functions declared among them can be called before the rest of the names are,
+    /// and then see them instead of the names of the enclosing scopes.
+    /// Constants only become constants at their declaration.
+    fn propagate_statements(&mut self, statements: &mut [Statement]) {
+        for statement in statements.iter() {
+            match statement {
+                Statement::Variable { name, .. }
+                | Statement::Function { name, .. }
+                | Statement::Module { name, .. } => self.declare(name, None),
+                Statement::Destructure { names, .. } => {
+                    for name in names {
+                        self.declare(name, None);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for statement in statements {
+            self.propagate_statement(statement);
+        }
+    }
+
+    /// Propagates constants into statements in a new scope.
+    fn propagate_scope(&mut self, statements: &mut [Statement]) {
+        self.scopes.push(HashMap::new());
+        self.propagate_statements(statements);
+        self.scopes.pop();
+    }
+
+    fn propagate_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::Expression(expression, _) | Statement::Print(expression, _) => {
+                self.propagate_expression(expression);
+            }
+            Statement::Variable {
+                name,
+                initializer,
+                mutable,
+                ..
+            } => {
+                if let Some(initializer) = initializer {
+                    self.propagate_expression(initializer);
+                }
+
+                let value = match initializer {
+                    Some(Expression::Literal { value, token, .. }) if !*mutable => {
+                        Some((value.clone(), token.clone()))
+                    }
+                    _ => None,
+                };
+                self.declare(name, value);
+            }
+            Statement::Destructure {
+                names, initializer, ..
+            } => {
+                self.propagate_expression(initializer);
+
+                for name in names {
+                    self.declare(name, None);
+                }
+            }
+            Statement::Block(statements, _) => self.propagate_scope(statements),
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                self.propagate_expression(condition);
+                self.propagate_statement(then_branch);
+
+                if let Some(else_branch) = else_branch {
+                    self.propagate_statement(else_branch);
+                }
+            }
+            Statement::While {
+                condition, body, ..
+            } => {
+                self.propagate_expression(condition);
+                self.propagate_statement(body);
+            }
+            Statement::Loop { body, .. } => self.propagate_statement(body),
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            } => {
+                // The initializer's variable is only visible within the loop.
+                self.scopes.push(HashMap::new());
+
+                if let Some(initializer) = initializer {
+                    self.propagate_statement(initializer);
+                }
+                if let Some(condition) = condition {
+                    self.propagate_expression(condition);
+                }
+                if let Some(increment) = increment {
+                    self.propagate_expression(increment);
+                }
+                self.propagate_statement(body);
+
+                self.scopes.pop();
+            }
+            Statement::Function {
+                parameters, body, ..
+            } => {
+                // The parameters share the scope of the body.
+                self.scopes.push(HashMap::new());
+
+                for (parameter, _) in parameters.iter() {
+                    self.declare(parameter, None);
+                }
+
+                match body.as_mut() {
+                    Statement::Block(statements, _) => self.propagate_statements(statements),
+                    body => self.propagate_statement(body),
+                }
+
+                self.scopes.pop();
+            }
+            Statement::Return { value, .. } => {
+                if let Some(value) = value {
+                    self.propagate_expression(value);
+                }
+            }
+            Statement::Assert {
+                condition, message, ..
+            } => {
+                self.propagate_expression(condition);
+
+                if let Some(message) = message {
+                    self.propagate_expression(message);
+                }
+            }
+            Statement::Module {
+                name, statements, ..
+            } => {
+                self.propagate_scope(statements);
+                self.declare(name, None);
+            }
+            Statement::Break { .. }
+            | Statement::Continue { .. }
+            | Statement::Include { .. }
+            | Statement::Comment { .. } => {}
+        }
+    }
+
+    fn propagate_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Variable(name, span) => {
+                if let Some((value, token)) = self.constant(name) {
+                    // The value keeps the position of the variable it replaces.
+                    let span = *span;
+                    let token = Token {
+                        line: name.line,
+                        column: name.column,
+                        start: name.start,
+                        end: name.end,
+                        ..token
+                    };
+
+                    *expression = Expression::Literal { value, token, span };
+                }
+            }
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            } => {
+                self.propagate_expression(left);
+                self.propagate_expression(right);
+
+                // Like in the interpreter, only dividing an integer by zero fails, floats become infinite or NaN.
+                if matches!(operator.token_type, TokenType::Slash | TokenType::Percent)
+                    && matches!(fold_constant(left), Some(Constant::Int(_)))
+                    && fold_constant(right) == Some(Constant::Int(0))
+                {
+                    self.errors
+                        .push(Error::DivisionByZero(operator.line, operator.column));
+                }
+            }
+            Expression::Coalesce { left, right, .. } => {
+                self.propagate_expression(left);
+                self.propagate_expression(right);
+            }
+            Expression::Grouping(expression, _)
+            | Expression::TypeOf(expression, _)
+            | Expression::Unary {
+                right: expression, ..
+            }
+            | Expression::Assign {
+                value: expression, ..
+            } => self.propagate_expression(expression),
+            Expression::Call {
+                callee, arguments, ..
+            } => {
+                self.propagate_expression(callee);
+
+                for argument in arguments {
+                    self.propagate_expression(argument);
+                }
+            }
+            Expression::Tuple(elements, _) => {
+                for element in elements {
+                    self.propagate_expression(element);
+                }
+            }
+            Expression::Literal { .. } | Expression::Qualified { .. } => {}
+        }
+    }
+
+    /// Declares a variable in the innermost scope, hiding the variables of the same name in enclosing scopes.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the variable.
+    /// * `value` - The literal value of the variable and its token, if it is a constant.
+    fn declare(&mut self, name: &Token, value: Option<(Literal, Token)>) {
+        let scope = self.scopes.last_mut().expect("There is always a scope.");
+
+        scope.insert(name.name(), value);
+    }
+
+    /// Gets the value of the innermost variable with a name, if it is a constant with a literal value.
+    ///
+    /// # Returns
+    /// The value and the token it was parsed from.
+    fn constant(&self, name: &Token) -> Option<(Literal, Token)> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(&name.name()))?
+            .clone()
+    }
+}
+
+/// Checks if a statement always leaves the block it is in.
+fn is_jump(statement: &Statement) -> bool {
+    matches!(
+        statement,
+        Statement::Return { .. } | Statement::Break { .. } | Statement::Continue { .. }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::parse;
+
+    /// Optimizes the source code, returning the optimized syntax tree in its textual form.
+    fn optimized(source: &str) -> Vec<String> {
+        let mut statements = parse(source).expect("Parsing should succeed.");
+        optimize(&mut statements).expect("Optimizing should succeed.");
+
+        statements.iter().map(Statement::to_string).collect()
+    }
+
+    #[test]
+    fn test_eliminate_dead_code() {
+        assert_eq!(
+            optimized("fn f() { return 1; print 2; print 3; }"),
+            ["(fn f() (block (ret return 1)))"]
+        );
+        assert_eq!(
+            optimized("while (true) { if (a) { break; print 1; } continue; a = 1; }"),
+            ["(while true (block (if a (block (break break)) ) (continue continue)))"]
+        );
+        // Functions are defined before anything runs, so they can still be called.
+        assert_eq!(
+            optimized("fn f() { return g(); fn g() { return 1; } }"),
+            ["(fn f() (block (ret return (g())) (fn g() (block (ret return 1)))))"]
+        );
+    }
+
+    #[test]
+    fn test_propagate_constants() {
+        assert_eq!(
+            optimized("const SIZE = 4; const NAME = \"a\"; print SIZE * 2 + len(NAME);"),
+            [
+                "(const SIZE 4)",
+                "(const NAME a)",
+                "(print (+ (* 4 2) (len(a))))"
+            ]
+        );
+        // Constants initialized with other constants are propagated as well.
+        assert_eq!(
+            optimized("const A = 1; const B = A; fn f() { return B; }")[2],
+            "(fn f() (block (ret return 1)))"
+        );
+        // Variables declared with 'let' can change, and constants without a literal value are not known.
+        assert_eq!(
+            optimized("let a = 1; const b = a + 1; print a + b;")[2],
+            "(print (+ a b))"
+        );
+    }
+
+    #[test]
+    fn test_propagate_constants_shadowing() {
+        assert_eq!(
+            optimized("const X = 1; { let X = 2; print X; } print X;"),
+            ["(const X 1)", "(block (var X 2) (print X))", "(print 1)"]
+        );
+        // Parameters and functions hide constants as well, even when declared after the use.
+        assert_eq!(
+            optimized(
+                "const n = 1; const g = 2; fn f(n: int) { return n; } { print g; fn g() {} }"
+            )[2..],
+            [
+                "(fn f(n) (block (ret return n)))",
+                "(block (print g) (fn g() (block )))"
+            ]
+        );
+        // Functions can be called after the names declared after them, which they then see.
+        assert_eq!(
+            optimized("const X = 1; { fn f() { print X; } let X = 2; f(); }")[1],
+            "(block (fn f() (block (print X))) (var X 2) (f()))"
+        );
+        assert_eq!(
+            optimized("{ fn f() { print X; } const X = 2; f(); print X; }")[0],
+            "(block (fn f() (block (print X))) (const X 2) (f()) (print 2))"
+        );
+    }
+
+    #[test]
+    fn test_keep_reachable_code() {
+        let source =
+            "fn f(n: int) { if (n > 0) { return 1; } print 2; while (true) break; print 3; }";
+
+        assert_eq!(
+            optimized(source),
+            parse(source)
+                .expect("Parsing should succeed.")
+                .iter()
+                .map(Statement::to_string)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        let errors = |source: &str| {
+            let mut statements = parse(source).expect("Parsing should succeed.");
+
+            optimize(&mut statements).err().unwrap_or_default()
+        };
+
+        assert_eq!(
+            errors("const ZERO = 0; print 1 / ZERO; print 2 / (ZERO * 3);"),
+            [Error::DivisionByZero(1, 25), Error::DivisionByZero(1, 41)]
+        );
+        assert_eq!(errors("print 1 / 0;"), [Error::DivisionByZero(1, 9)]);
+        assert_eq!(
+            errors("const ZERO = 0; print 7 % ZERO; print 7 % 2;"),
+            [Error::DivisionByZero(1, 25)]
+        );
+        // Variables can change, and floats divided by zero become infinite instead of failing.
+        assert_eq!(
+            errors("let zero = 0; print 1 / zero; const Z = 0; print 1.5 / Z; print 1 / 0.0; print 1.5 % Z;"),
+            []
+        );
+    }
+}
@@ -1,33 +1,64 @@
 use std::fmt::{Display, Formatter};
 
-use crate::lang::errors::{report, Error};
+use serde::Serialize;
+
+use crate::lang::errors::{Error, Severity};
 use crate::lang::lexer::{Literal, Token, TokenType};
-use crate::lang::{MAX_ARGUMENTS, MAX_PARAMETERS};
+use crate::lang::span::Span;
+use crate::lang::{MAX_ARGUMENTS, MAX_ERRORS, MAX_EXPRESSION_DEPTH, MAX_PARAMETERS};
 
 /// An expression is a piece of code that evaluates to a value.
-#[derive(Debug, Clone)]
+/// Every variant ends with the span of the source code it was parsed from, see `Expression::span`.
+/// Expressions are equal if their tokens and spans are, see `Expression::eq_ignoring_spans` to only compare their code.
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum Expression {
     Binary {
         left: Box<Expression>,
         operator: Token,
         right: Box<Expression>,
+        span: Span,
+    },
+    Grouping(Box<Expression>, Span),
+    Literal {
+        value: Literal,
+        token: Token,
+        span: Span,
     },
-    Grouping(Box<Expression>),
-    Literal(Literal),
     Unary {
         operator: Token,
         right: Box<Expression>,
+        span: Span,
     },
-    Variable(Token),
+    Variable(Token, Span),
     Assign {
         name: Token,
         value: Box<Expression>,
+        span: Span,
     },
     Call {
         callee: Box<Expression>,
         parenthesis: Token,
         arguments: Vec<Expression>,
+        span: Span,
+    },
+    /// A null-coalescing expression, the right side is only evaluated if the left side is none.
+    Coalesce {
+        left: Box<Expression>,
+        operator: Token,
+        right: Box<Expression>,
+        span: Span,
     },
+    /// A member of a module, such as `math.add`.
+    /// The member's name is boxed to keep expressions small, as they are kept on the parser's stack.
+    Qualified {
+        module: Token,
+        name: Box<Token>,
+        span: Span,
+    },
+    /// A fixed number of values, written as a parenthesized list with at least one comma, such as `(1, 2)`.
+    Tuple(Vec<Expression>, Span),
+    /// The name of the type of a value, such as `typeof 1` being `"int"`.
+    TypeOf(Box<Expression>, Span),
 }
 
 impl Display for Expression {
@@ -37,106 +68,190 @@ impl Display for Expression {
                 left,
                 operator,
                 right,
+                ..
             } => {
                 write!(f, "({} {} {})", operator.lexeme, left, right)
             }
-            Expression::Grouping(expression) => write!(f, "(group {})", expression),
-            Expression::Literal(value) => write!(f, "{}", value),
-            Expression::Unary { operator, right } => write!(f, "({} {})", operator.lexeme, right),
-            Expression::Variable(name) => write!(f, "{}", name.lexeme),
-            Expression::Assign { name, value } => write!(f, "(= {} {})", name.lexeme, value),
+            Expression::Grouping(expression, _) => write!(f, "(group {})", expression),
+            Expression::Literal { value, .. } => write!(f, "{}", value),
+            Expression::Unary {
+                operator, right, ..
+            } => write!(f, "({} {})", operator.lexeme, right),
+            Expression::Variable(name, _) => write!(f, "{}", name.lexeme),
+            Expression::Assign { name, value, .. } => {
+                write!(f, "(= {} {})", name.lexeme, value)
+            }
             Expression::Call {
-                callee,
-                parenthesis: _parenthesis,
-                arguments,
+                callee, arguments, ..
             } => {
                 write!(f, "({}(", callee)?;
-
-                for (i, argument) in arguments.iter().enumerate() {
-                    write!(f, "{}", argument)?;
-
-                    if i != arguments.len() - 1 {
-                        write!(f, ", ")?;
-                    }
-                }
-
+                write_separated(f, arguments, ", ")?;
                 write!(f, "))")
             }
+            Expression::Coalesce {
+                left,
+                operator,
+                right,
+                ..
+            } => write!(f, "({} {} {})", operator.lexeme, left, right),
+            Expression::Qualified { module, name, .. } => {
+                write!(f, "{}.{}", module.lexeme, name.lexeme)
+            }
+            Expression::Tuple(elements, _) => {
+                write!(f, "(tuple ")?;
+                write_separated(f, elements, " ")?;
+                write!(f, ")")
+            }
+            Expression::TypeOf(expression, _) => write!(f, "(typeof {})", expression),
         }
     }
 }
 
 /// A statement is a piece of code that does not evaluate to a value.
-#[derive(Debug, Clone)]
+/// Every variant ends with the span of the source code it was parsed from, see `Statement::span`.
+/// Statements are equal if their tokens and spans are, see `Statement::eq_ignoring_spans` to only compare their code.
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum Statement {
-    Expression(Expression),
-    Print(Expression),
+    Expression(Expression, Span),
+    Print(Expression, Span),
     Variable {
         name: Token,
+        /// The type name, if the variable is annotated with one.
+        declared_type: Option<Token>,
         initializer: Option<Expression>,
+        /// Whether the variable is declared with 'let' rather than 'const', so it can be assigned to.
+        mutable: bool,
+        span: Span,
+    },
+    /// Declares a variable for each value of a tuple, such as `let (a, b) = (1, 2);`.
+    Destructure {
+        names: Vec<Token>,
+        initializer: Expression,
+        /// Whether the variables are declared with 'let' rather than 'const'.
+        mutable: bool,
+        span: Span,
     },
-    Block(Vec<Statement>),
+    Block(Vec<Statement>, Span),
     If {
         condition: Expression,
         then_branch: Box<Statement>,
         else_branch: Option<Box<Statement>>,
+        span: Span,
     },
     While {
         condition: Expression,
         body: Box<Statement>,
+        span: Span,
+    },
+    Loop {
+        body: Box<Statement>,
+        span: Span,
     },
     For {
         initializer: Option<Box<Statement>>,
         condition: Option<Expression>,
         increment: Option<Expression>,
         body: Box<Statement>,
+        span: Span,
     },
     Function {
         name: Token,
         parameters: Vec<(Token, Token)>,
+        /// The return type name, if the function is annotated with one.
+        return_type: Option<Token>,
         body: Box<Statement>,
+        span: Span,
     },
     Return {
         keyword: Token,
         value: Option<Expression>,
+        span: Span,
     },
     Break {
         keyword: Token,
+        span: Span,
     },
     Continue {
         keyword: Token,
+        span: Span,
+    },
+    /// Stops the program with an error if the condition is falsy, including the message if there is one.
+    Assert {
+        keyword: Token,
+        condition: Expression,
+        message: Option<Expression>,
+        span: Span,
+    },
+    /// Splices the top-level statements of the file at the path, relative to the including file,
+    /// or places them in a module if it is given a name with 'as'.
+    /// Replaced by the included statements before analysis, see `includes::resolve`.
+    Include {
+        keyword: Token,
+        path: Token,
+        alias: Option<Token>,
+        span: Span,
+    },
+    /// A namespace whose declarations are only reachable through its name, such as `math.add`.
+    Module {
+        name: Token,
+        statements: Vec<Statement>,
+        span: Span,
+    },
+    /// A single-line comment, only present when parsing tokens scanned with comments.
+    /// A trailing comment follows other code on the same line.
+    Comment {
+        token: Token,
+        trailing: bool,
+        span: Span,
     },
 }
 
 impl Display for Statement {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            Statement::Expression(expression) => write!(f, "{}", expression),
-            Statement::Print(expression) => write!(f, "(print {})", expression),
-            Statement::Variable { name, initializer } => {
-                if let Some(initializer) = initializer {
-                    write!(f, "(var {} {})", name.lexeme, initializer)
-                } else {
-                    write!(f, "(var {})", name.lexeme)
-                }
-            }
-            Statement::Block(statements) => {
-                write!(f, "(block ")?;
+            Statement::Expression(expression, _) => write!(f, "{}", expression),
+            Statement::Print(expression, _) => write!(f, "(print {})", expression),
+            Statement::Variable {
+                name,
+                declared_type,
+                initializer,
+                mutable,
+                ..
+            } => {
+                let keyword = if *mutable { "var" } else { "const" };
+                write!(f, "({} {}", keyword, name.lexeme)?;
 
-                for (i, statement) in statements.iter().enumerate() {
-                    write!(f, "{}", statement)?;
+                if let Some(declared_type) = declared_type {
+                    write!(f, ": {}", declared_type.lexeme)?;
+                }
 
-                    if i != statements.len() - 1 {
-                        write!(f, " ")?;
-                    }
+                if let Some(initializer) = initializer {
+                    write!(f, " {}", initializer)?;
                 }
 
                 write!(f, ")")
             }
+            Statement::Destructure {
+                names,
+                initializer,
+                mutable,
+                ..
+            } => {
+                let keyword = if *mutable { "var" } else { "const" };
+                write!(f, "({} (", keyword)?;
+                write_separated(f, names.iter().map(|name| &name.lexeme), ", ")?;
+                write!(f, ") {})", initializer)
+            }
+            Statement::Block(statements, _) => {
+                write!(f, "(block ")?;
+                write_separated(f, statements, " ")?;
+                write!(f, ")")
+            }
             Statement::If {
                 condition,
                 then_branch,
                 else_branch,
+                ..
             } => {
                 write!(f, "(if {} {} ", condition, then_branch)?;
 
@@ -146,12 +261,16 @@ impl Display for Statement {
 
                 write!(f, ")")
             }
-            Statement::While { condition, body } => write!(f, "(while {} {})", condition, body),
+            Statement::While {
+                condition, body, ..
+            } => write!(f, "(while {} {})", condition, body),
+            Statement::Loop { body, .. } => write!(f, "(loop {})", body),
             Statement::For {
                 initializer,
                 condition,
                 increment,
                 body,
+                ..
             } => {
                 write!(f, "(for ")?;
 
@@ -174,70 +293,193 @@ impl Display for Statement {
             Statement::Function {
                 name,
                 parameters,
+                return_type,
                 body,
+                ..
             } => {
                 write!(f, "(fn {}(", name.lexeme)?;
-
-                for (i, (parameter, _)) in parameters.iter().enumerate() {
-                    write!(f, "{}", parameter.lexeme)?;
-
-                    if i != parameters.len() - 1 {
-                        write!(f, ", ")?;
-                    }
+                write_separated(
+                    f,
+                    parameters.iter().map(|(parameter, _)| &parameter.lexeme),
+                    ", ",
+                )?;
+                write!(f, ")")?;
+
+                if let Some(return_type) = return_type {
+                    write!(f, " -> {}", return_type.lexeme)?;
                 }
 
-                write!(f, ") {})", body)
+                write!(f, " {})", body)
             }
-            Statement::Return { keyword, value } => {
+            Statement::Return { keyword, value, .. } => {
                 if let Some(value) = value {
                     write!(f, "(ret {} {})", keyword.lexeme, value)
                 } else {
                     write!(f, "(ret {})", keyword.lexeme)
                 }
             }
-            Statement::Break { keyword } => write!(f, "(break {})", keyword.lexeme),
-            Statement::Continue { keyword } => write!(f, "(continue {})", keyword.lexeme),
+            Statement::Break { keyword, .. } => write!(f, "(break {})", keyword.lexeme),
+            Statement::Continue { keyword, .. } => write!(f, "(continue {})", keyword.lexeme),
+            Statement::Assert {
+                condition, message, ..
+            } => match message {
+                Some(message) => write!(f, "(assert {} {})", condition, message),
+                None => write!(f, "(assert {})", condition),
+            },
+            Statement::Include { path, alias, .. } => match alias {
+                Some(alias) => write!(f, "(include {} as {})", path.lexeme, alias.lexeme),
+                None => write!(f, "(include {})", path.lexeme),
+            },
+            Statement::Module {
+                name, statements, ..
+            } => {
+                write!(f, "(module {} ", name.lexeme)?;
+                write_separated(f, statements, " ")?;
+                write!(f, ")")
+            }
+            Statement::Comment { token, .. } => write!(f, "(comment {})", token.lexeme),
+        }
+    }
+}
+
+/// Writes the items with a separator between each of them, and none for fewer than two items.
+fn write_separated<T: Display>(
+    f: &mut Formatter<'_>,
+    items: impl IntoIterator<Item = T>,
+    separator: &str,
+) -> std::fmt::Result {
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            f.write_str(separator)?;
         }
+
+        write!(f, "{}", item)?;
     }
+
+    Ok(())
 }
 
-impl Iterator for Box<Statement> {
-    type Item = Statement;
+impl Statement {
+    /// Gets the statements directly nested in this one, in source order.
+    /// Statements nested deeper are not included, such as the contents of a block in a loop's body.
+    ///
+    /// # Examples
+    /// ```
+    /// use cpl::lang::parse;
+    ///
+    /// let statements = parse("if (a) { print 1; print 2; } else print 3;").unwrap();
+    ///
+    /// assert_eq!(statements[0].children().count(), 2);
+    /// ```
+    pub fn children(&self) -> impl Iterator<Item = &Statement> {
+        let children: Vec<&Statement> = match self {
+            Statement::Block(statements, _) | Statement::Module { statements, .. } => {
+                statements.iter().collect()
+            }
+            Statement::If {
+                then_branch,
+                else_branch,
+                ..
+            } => std::iter::once(then_branch.as_ref())
+                .chain(else_branch.as_deref())
+                .collect(),
+            Statement::While { body, .. }
+            | Statement::Loop { body, .. }
+            | Statement::Function { body, .. } => vec![body],
+            Statement::For {
+                initializer, body, ..
+            } => initializer
+                .as_deref()
+                .into_iter()
+                .chain(std::iter::once(body.as_ref()))
+                .collect(),
+            Statement::Expression(..)
+            | Statement::Print(..)
+            | Statement::Variable { .. }
+            | Statement::Destructure { .. }
+            | Statement::Return { .. }
+            | Statement::Break { .. }
+            | Statement::Continue { .. }
+            | Statement::Assert { .. }
+            | Statement::Include { .. }
+            | Statement::Comment { .. } => Vec::new(),
+        };
 
-    fn next(&mut self) -> Option<Self::Item> {
-        Some(*self.clone())
+        children.into_iter()
     }
 }
 
 /// A parser for the CPL language.
 #[derive(Debug)]
-pub struct Parser {
-    tokens: Vec<Token>,
+pub struct Parser<'a> {
+    tokens: &'a [Token],
     current: usize,
 
+    /// How deeply the code being parsed is nested, which is the height of the tree above it.
+    depth: usize,
+    /// The deepest the code has been nested since the current chain of operators started, see `start_chain`.
+    peak: usize,
+    max_depth: usize,
+    block_depth: usize,
+
     errors: Vec<Error>,
+    /// The most errors to record, the ones after them are only counted.
+    max_errors: usize,
+    /// The number of errors found after the maximum was reached.
+    omitted_errors: usize,
     had_error: bool,
+    panic_mode: bool,
 }
 
-impl Parser {
-    pub fn new(tokens: &[Token]) -> Self {
+impl<'a> Parser<'a> {
+    pub fn new(tokens: &'a [Token]) -> Self {
         Self {
-            tokens: tokens.to_vec(),
+            tokens,
             current: 0,
 
+            depth: 0,
+            peak: 0,
+            max_depth: MAX_EXPRESSION_DEPTH,
+            block_depth: 0,
+
             errors: Vec::new(),
+            max_errors: MAX_ERRORS,
+            omitted_errors: 0,
             had_error: false,
+            panic_mode: false,
         }
     }
 
+    /// Sets the maximum depth of nested expressions and statements.
+    ///
+    /// # Arguments
+    /// * `max_depth` - The maximum depth before parsing is aborted.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+
+        self
+    }
+
+    /// Sets the most errors to record, which defaults to `MAX_ERRORS`.
+    /// At least one error is always recorded.
+    ///
+    /// # Arguments
+    /// * `max_errors` - The maximum number of errors.
+    pub fn with_max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = max_errors.max(1);
+
+        self
+    }
+
+    /// Gets the number of errors found after the maximum number of errors was reached.
+    pub fn omitted_errors(&self) -> usize {
+        self.omitted_errors
+    }
+
     pub fn parse(&mut self) -> Result<Vec<Statement>, Vec<Error>> {
         let mut statements = Vec::new();
 
         while !self.is_at_end() {
-            if self.had_error {
-                break;
-            }
-
             statements.push(self.declaration());
         }
 
@@ -249,13 +491,48 @@ impl Parser {
     }
 
     fn declaration(&mut self) -> Statement {
-        if self.matches(&[TokenType::Variable]) {
-            self.variable_declaration()
+        if self.matches(&[TokenType::Comment]) {
+            let token = self.previous().clone();
+            let trailing = self.current >= 2 && {
+                let before = &self.tokens[self.current - 2];
+                before.token_type != TokenType::Comment && before.line == token.line
+            };
+
+            let span = token.span();
+
+            return Statement::Comment {
+                token,
+                trailing,
+                span,
+            };
+        }
+
+        let start = self.current;
+
+        let statement = if self.matches(&[TokenType::Variable]) {
+            self.variable_declaration(true)
+        } else if self.matches(&[TokenType::Constant]) {
+            self.variable_declaration(false)
         } else if self.matches(&[TokenType::Function]) {
             self.function_declaration()
+        } else if self.matches(&[TokenType::Include]) {
+            self.include_declaration()
+        } else if self.matches(&[TokenType::Module]) {
+            self.module_declaration()
         } else {
             *self.statement()
+        };
+
+        if self.panic_mode {
+            // Always make progress, so a token that cannot start a statement is skipped.
+            if self.current == start {
+                self.advance();
+            }
+
+            self.synchronize();
         }
+
+        statement
     }
 
     fn expression(&mut self) -> Expression {
@@ -263,21 +540,35 @@ impl Parser {
     }
 
     fn assignment(&mut self) -> Expression {
-        let expression = self.or();
+        if !self.enter("Expression") {
+            return self.placeholder();
+        }
+
+        let expression = self.assignment_inner();
+        self.depth -= 1;
+
+        expression
+    }
+
+    fn assignment_inner(&mut self) -> Expression {
+        let expression = self.coalesce();
 
         if self.matches(&[TokenType::Equal]) {
             let equals = self.previous().clone();
             let value = self.assignment();
 
             match expression {
-                Expression::Variable(name) => {
+                Expression::Variable(name, span) => {
+                    let span = span.to(value.span());
+
                     return Expression::Assign {
                         name,
                         value: Box::new(value),
+                        span,
                     };
                 }
                 _ => {
-                    self.error(&equals, "Invalid assignment target!");
+                    self.error(&equals, "Invalid assignment target");
                 }
             }
         }
@@ -285,58 +576,53 @@ impl Parser {
         expression
     }
 
-    fn or(&mut self) -> Expression {
-        let mut expression = self.and();
+    fn coalesce(&mut self) -> Expression {
+        let chain = self.start_chain();
+        let expression = self.or();
 
-        while self.matches(&[TokenType::LogicalOr]) {
+        if self.matches(&[TokenType::QuestionQuestion]) {
             let operator = self.previous().clone();
-            let right = self.and();
-
-            expression = Expression::Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            };
-        }
-
-        expression
-    }
+            if !self.link_chain() {
+                self.end_chain(chain);
+                return expression;
+            }
 
-    fn and(&mut self) -> Expression {
-        let mut expression = self.equality();
+            // Right-associative, so 'a ?? b ?? c' is 'a ?? (b ?? c)'.
+            let right = self.coalesce();
+            self.end_chain(chain);
 
-        while self.matches(&[TokenType::LogicalAnd]) {
-            let operator = self.previous().clone();
-            let right = self.equality();
+            let span = expression.span().to(right.span());
 
-            expression = Expression::Binary {
+            return Expression::Coalesce {
                 left: Box::new(expression),
                 operator,
                 right: Box::new(right),
+                span,
             };
         }
 
+        self.end_chain(chain);
+
         expression
     }
 
-    fn equality(&mut self) -> Expression {
-        let mut expression = self.comparison();
-
-        while self.matches(&[TokenType::BangEqual, TokenType::EqualEqual]) {
-            let operator = self.previous().clone();
-            let right = self.comparison();
+    fn or(&mut self) -> Expression {
+        self.binary_chain(&[TokenType::LogicalOr], Self::and)
+    }
 
-            expression = Expression::Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            };
-        }
+    fn and(&mut self) -> Expression {
+        self.binary_chain(&[TokenType::LogicalAnd], Self::equality)
+    }
 
-        expression
+    fn equality(&mut self) -> Expression {
+        self.binary_chain(
+            &[TokenType::BangEqual, TokenType::EqualEqual],
+            Self::comparison,
+        )
     }
 
     fn comparison(&mut self) -> Expression {
+        let chain = self.start_chain();
         let mut expression = self.term();
 
         while self.matches(&[
@@ -346,107 +632,242 @@ impl Parser {
             TokenType::LessThanOrEqual,
         ]) {
             let operator = self.previous().clone();
+            if !self.link_chain() {
+                break;
+            }
+
             let right = self.term();
 
-            expression = Expression::Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            };
+            // 'a < b < c' would compare the result of 'a < b' with 'c', which is rarely intended.
+            if let Expression::Binary {
+                left: first,
+                operator: first_operator,
+                right: middle,
+                ..
+            } = &expression
+            {
+                if is_comparison(&first_operator.token_type) {
+                    let message = format!(
+                        "Comparisons cannot be chained, use '{} {} {} && {} {} {}' instead",
+                        first.to_source(),
+                        first_operator.lexeme,
+                        middle.to_source(),
+                        middle.to_source(),
+                        operator.lexeme,
+                        right.to_source()
+                    );
+                    self.error(&operator, &message);
+                }
+            }
+
+            expression = binary(expression, operator, right);
         }
 
+        self.end_chain(chain);
+
         expression
     }
 
     fn term(&mut self) -> Expression {
-        let mut expression = self.factor();
-
-        while self.matches(&[TokenType::Minus, TokenType::Plus]) {
-            let operator = self.previous().clone();
-            let right = self.factor();
-
-            expression = Expression::Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            };
-        }
-
-        expression
+        self.binary_chain(&[TokenType::Minus, TokenType::Plus], Self::factor)
     }
 
     fn factor(&mut self) -> Expression {
-        let mut expression = self.unary();
+        self.binary_chain(
+            &[TokenType::Slash, TokenType::Star, TokenType::Percent],
+            Self::unary,
+        )
+    }
 
-        while self.matches(&[TokenType::Slash, TokenType::Star]) {
+    /// Parses a chain of left-associative binary operators, such as `a + b - c`.
+    ///
+    /// # Arguments
+    /// * `operators` - The operators of the chain.
+    /// * `operand` - Parses an operand, which binds tighter than the operators.
+    fn binary_chain(
+        &mut self,
+        operators: &[TokenType],
+        operand: fn(&mut Self) -> Expression,
+    ) -> Expression {
+        let chain = self.start_chain();
+        let mut expression = operand(self);
+
+        while self.matches(operators) {
             let operator = self.previous().clone();
-            let right = self.unary();
+            if !self.link_chain() {
+                break;
+            }
 
-            expression = Expression::Binary {
-                left: Box::new(expression),
-                operator,
-                right: Box::new(right),
-            };
+            let right = operand(self);
+
+            expression = binary(expression, operator, right);
         }
 
+        self.end_chain(chain);
+
         expression
     }
 
     fn unary(&mut self) -> Expression {
         if self.matches(&[TokenType::Bang, TokenType::Minus]) {
             let operator = self.previous().clone();
+            if !self.enter("Expression") {
+                return self.placeholder();
+            }
+
             let right = self.unary();
+            self.depth -= 1;
+            let span = operator.span().to(right.span());
 
             Expression::Unary {
                 operator,
                 right: Box::new(right),
+                span,
             }
+        } else if self.matches(&[TokenType::TypeOf]) {
+            let keyword = self.previous().span();
+            if !self.enter("Expression") {
+                return self.placeholder();
+            }
+
+            let expression = self.unary();
+            self.depth -= 1;
+            let span = keyword.to(expression.span());
+
+            Expression::TypeOf(Box::new(expression), span)
         } else {
-            self.call()
+            self.power()
+        }
+    }
+
+    /// Parses a power, which binds tighter than the unary operators on its left and is right-associative,
+    /// so `-2 ** 2` is `-(2 ** 2)` and `2 ** 3 ** 2` is `2 ** (3 ** 2)`.
+    fn power(&mut self) -> Expression {
+        let chain = self.start_chain();
+        let expression = self.call();
+
+        if !self.matches(&[TokenType::StarStar]) {
+            self.end_chain(chain);
+            return expression;
+        }
+
+        let operator = self.previous().clone();
+        if !self.link_chain() {
+            self.end_chain(chain);
+            return self.placeholder();
         }
+
+        // The exponent may be negated, such as in `2 ** -1`.
+        let right = self.unary();
+        self.end_chain(chain);
+
+        binary(expression, operator, right)
     }
 
     fn call(&mut self) -> Expression {
+        let chain = self.start_chain();
         let mut expression = self.primary();
 
-        loop {
-            if self.matches(&[TokenType::LeftParenthesis]) {
-                expression = self.finish_call(expression);
-            } else {
+        // Every call nests the callee, such as `f()()` calling the result of `f()`.
+        while self.matches(&[TokenType::LeftParenthesis]) {
+            if !self.link_chain() {
                 break;
             }
+
+            expression = self.finish_call(expression);
         }
 
+        self.end_chain(chain);
+
         expression
     }
 
     fn primary(&mut self) -> Expression {
-        if self.matches(&[TokenType::False]) {
-            Expression::Literal(Literal::Boolean(false))
-        } else if self.matches(&[TokenType::True]) {
-            Expression::Literal(Literal::Boolean(true))
-        } else if self.matches(&[TokenType::None]) {
-            Expression::Literal(Literal::None)
-        } else if self.matches(&[TokenType::Number, TokenType::String]) {
-            let previous = self.previous().clone();
-            let literal = previous.literal.clone();
-            if literal.is_none() {
-                self.error(&previous, "Expected literal!");
-            }
-
-            Expression::Literal(literal.unwrap())
+        if self.matches(&[
+            TokenType::False,
+            TokenType::True,
+            TokenType::None,
+            TokenType::Number,
+            TokenType::String,
+            TokenType::Char,
+        ]) {
+            let token = self.previous().clone();
+            let value = token
+                .literal
+                .clone()
+                .expect("The scanner gives every literal token a value.");
+
+            let span = token.span();
+
+            Expression::Literal { value, token, span }
         } else if self.matches(&[TokenType::Identifier]) {
-            Expression::Variable(self.previous().clone())
+            self.variable()
         } else if self.matches(&[TokenType::LeftParenthesis]) {
+            let start = self.current - 1;
             let expression = self.expression();
-            self.consume(
-                TokenType::RightParenthesis,
-                "Expected ')' after expression!",
-            );
-            Expression::Grouping(Box::new(expression))
+            if self.matches(&[TokenType::Comma]) {
+                return self.tuple(start, expression);
+            }
+
+            self.consume(TokenType::RightParenthesis, "after expression");
+            Expression::Grouping(Box::new(expression), self.span_since(start))
         } else {
-            self.error(&self.peek().clone(), "Expected expression!");
-            Expression::Literal(Literal::None)
+            self.error_expected(&self.peek().clone(), "Expected expression");
+            self.placeholder()
+        }
+    }
+
+    /// Parses the rest of a tuple, after its first element and comma.
+    /// Kept out of `primary` for the same reason as `variable`.
+    ///
+    /// # Arguments
+    /// * `start` - The index of the opening parenthesis.
+    /// * `first` - The first element.
+    fn tuple(&mut self, start: usize, first: Expression) -> Expression {
+        let mut elements = vec![first];
+
+        // A trailing comma may come before the closing parenthesis, which is how `(1,)` holds one value.
+        while !self.check(&TokenType::RightParenthesis) && !self.is_at_end() {
+            elements.push(self.expression());
+
+            if !self.matches(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(TokenType::RightParenthesis, "after tuple");
+
+        Expression::Tuple(elements, self.span_since(start))
+    }
+
+    /// Parses a variable, or a member of a module if the name is followed by a dot.
+    /// Kept out of `primary` so its frame stays small, as that recurses for every level of nesting.
+    fn variable(&mut self) -> Expression {
+        let name = self.previous().clone();
+        if !self.matches(&[TokenType::Dot]) {
+            let span = name.span();
+
+            return Expression::Variable(name, span);
+        }
+
+        let member = self.consume(TokenType::Identifier, "as member name");
+        let span = name.span().to(member.span());
+
+        Expression::Qualified {
+            module: name,
+            name: Box::new(member),
+            span,
+        }
+    }
+
+    /// Creates an expression to stand in for one that could not be parsed.
+    fn placeholder(&self) -> Expression {
+        let token = self.peek().clone();
+
+        Expression::Literal {
+            value: Literal::None,
+            span: token.span(),
+            token,
         }
     }
 
@@ -458,62 +879,123 @@ impl Parser {
                 if arguments.len() >= MAX_ARGUMENTS {
                     self.error(
                         &self.peek().clone(),
-                        &format!("Cannot have more than {} arguments.", MAX_ARGUMENTS),
+                        &format!("Cannot have more than {} arguments", MAX_ARGUMENTS),
                     );
                 }
 
                 arguments.push(self.expression());
 
-                if !self.matches(&[TokenType::Comma]) {
+                // A trailing comma may come before the closing parenthesis.
+                if !self.matches(&[TokenType::Comma]) || self.check(&TokenType::RightParenthesis) {
                     break;
                 }
             }
         }
 
-        let parenthesis =
-            self.consume(TokenType::RightParenthesis, "Expected ')' after arguments.");
+        let parenthesis = self.consume(TokenType::RightParenthesis, "after arguments");
+        let span = callee.span().to(parenthesis.span());
 
         Expression::Call {
             callee: Box::new(callee),
             parenthesis,
             arguments,
+            span,
         }
     }
 
-    fn variable_declaration(&mut self) -> Statement {
-        let name = self.consume(TokenType::Identifier, "Expected variable name.");
+    /// Parses a variable declaration, after its 'let' or 'const' keyword.
+    /// Constants can never be assigned to, so they must be given a value.
+    ///
+    /// # Arguments
+    /// * `mutable` - Whether the variable is declared with 'let'.
+    fn variable_declaration(&mut self, mutable: bool) -> Statement {
+        let start = self.current - 1;
+        if self.matches(&[TokenType::LeftParenthesis]) {
+            return self.destructure_declaration(start, mutable);
+        }
+
+        let name = self.consume(TokenType::Identifier, "as variable name");
+
+        let declared_type = if self.matches(&[TokenType::Colon]) {
+            Some(self.consume(TokenType::Identifier, "as type name"))
+        } else {
+            None
+        };
 
         let initializer = if self.matches(&[TokenType::Equal]) {
             Some(self.expression())
         } else {
+            if !mutable {
+                self.consume(TokenType::Equal, "after constant name");
+            }
+
             None
         };
 
-        self.consume(
-            TokenType::Semicolon,
-            "Expected ';' after variable declaration.",
-        );
+        self.consume(TokenType::Semicolon, "after variable declaration");
+
+        Statement::Variable {
+            name,
+            declared_type,
+            initializer,
+            mutable,
+            span: self.span_since(start),
+        }
+    }
+
+    /// Parses the declaration of the variables a tuple is destructured into, after the opening parenthesis.
+    /// The tuple must be given right away, so the variables always have values.
+    ///
+    /// # Arguments
+    /// * `start` - The index of the 'let' or 'const' keyword.
+    /// * `mutable` - Whether the variables are declared with 'let'.
+    fn destructure_declaration(&mut self, start: usize, mutable: bool) -> Statement {
+        let mut names = Vec::new();
+
+        loop {
+            names.push(self.consume(TokenType::Identifier, "as variable name"));
+
+            // A trailing comma may come before the closing parenthesis.
+            if !self.matches(&[TokenType::Comma]) || self.check(&TokenType::RightParenthesis) {
+                break;
+            }
+        }
+
+        self.consume(TokenType::RightParenthesis, "after variable names");
+        self.consume(TokenType::Equal, "after variable names");
+        let initializer = self.expression();
+        self.consume(TokenType::Semicolon, "after variable declaration");
 
-        Statement::Variable { name, initializer }
+        Statement::Destructure {
+            names,
+            initializer,
+            mutable,
+            span: self.span_since(start),
+        }
     }
 
     fn function_declaration(&mut self) -> Statement {
-        let name = self.consume(TokenType::Identifier, "Expected function name.");
+        let start = self.current - 1;
+        let name = self.consume(TokenType::Identifier, "as function name");
         let parameters = self.function_parameters();
+        let return_type = if self.matches(&[TokenType::Arrow]) {
+            Some(self.consume(TokenType::Identifier, "as return type"))
+        } else {
+            None
+        };
         let body = self.block();
 
         Statement::Function {
             name,
             parameters,
+            return_type,
             body,
+            span: self.span_since(start),
         }
     }
 
     fn function_parameters(&mut self) -> Vec<(Token, Token)> {
-        self.consume(
-            TokenType::LeftParenthesis,
-            "Expected '(' after function name.",
-        );
+        self.consume(TokenType::LeftParenthesis, "after function name");
 
         let mut parameters = Vec::new();
 
@@ -522,169 +1004,462 @@ impl Parser {
                 if parameters.len() >= MAX_PARAMETERS {
                     self.error(
                         &self.peek().clone(),
-                        &format!("Cannot have more than {} parameters.", MAX_PARAMETERS),
+                        &format!("Cannot have more than {} parameters", MAX_PARAMETERS),
                     );
                 }
 
-                let identifier = self.consume(TokenType::Identifier, "Expected parameter name.");
-                let r#type = if self.matches(&[TokenType::Colon]) {
-                    Some(self.consume(TokenType::Identifier, "Expected type name."))
-                } else {
-                    None
-                };
-                if r#type.is_none() {
-                    self.error(&self.peek().clone(), "Expected type name.");
-                }
+                let identifier = self.consume(TokenType::Identifier, "as parameter name");
+                self.consume(TokenType::Colon, "after parameter name");
+                let r#type = self.consume(TokenType::Identifier, "as type name");
 
-                parameters.push((identifier, r#type.unwrap()));
+                parameters.push((identifier, r#type));
 
-                if !self.matches(&[TokenType::Comma]) {
+                // A trailing comma may come before the closing parenthesis.
+                if !self.matches(&[TokenType::Comma]) || self.check(&TokenType::RightParenthesis) {
                     break;
                 }
             }
         }
 
-        self.consume(
-            TokenType::RightParenthesis,
-            "Expected ')' after parameters.",
-        );
+        self.consume(TokenType::RightParenthesis, "after parameters");
 
         parameters
     }
 
     fn block(&mut self) -> Box<Statement> {
+        let start = self.current;
+        if !self.enter("Statement") {
+            return Box::new(Statement::Block(Vec::new(), self.peek().span()));
+        }
+
+        let block = self.block_inner(start);
+        self.depth -= 1;
+
+        block
+    }
+
+    fn block_inner(&mut self, start: usize) -> Box<Statement> {
         let mut statements = Vec::new();
 
-        self.consume(TokenType::LeftCurlyBrace, "Expected '{' before block.");
+        self.consume(TokenType::LeftCurlyBrace, "before block");
+        self.block_depth += 1;
         while !self.check(&TokenType::RightCurlyBrace) && !self.is_at_end() {
             statements.push(self.declaration());
         }
-        self.consume(TokenType::RightCurlyBrace, "Expected '}' after block.");
+        self.block_depth -= 1;
+        self.consume(TokenType::RightCurlyBrace, "after block");
 
-        Box::new(Statement::Block(statements))
+        Box::new(Statement::Block(statements, self.span_since(start)))
     }
 
     fn statement(&mut self) -> Box<Statement> {
+        // Blocks count their own nesting, as functions and modules have them too.
+        if self.check(&TokenType::LeftCurlyBrace) {
+            return self.block();
+        }
+
+        if !self.enter("Statement") {
+            let placeholder = self.placeholder();
+            let span = placeholder.span();
+
+            return Box::new(Statement::Expression(placeholder, span));
+        }
+
+        let statement = self.statement_inner();
+        self.depth -= 1;
+
+        statement
+    }
+
+    fn statement_inner(&mut self) -> Box<Statement> {
         if self.matches(&[TokenType::Print]) {
             self.print_statement()
         } else if self.matches(&[TokenType::Return]) {
             self.return_statement()
+        } else if self.matches(&[TokenType::Assert]) {
+            self.assert_statement()
         } else if self.matches(&[TokenType::If]) {
             self.if_statement()
         } else if self.matches(&[TokenType::Switch]) {
             self.switch_statement()
         } else if self.matches(&[TokenType::While]) {
             self.while_statement()
+        } else if self.matches(&[TokenType::Loop]) {
+            self.loop_statement()
         } else if self.matches(&[TokenType::For]) {
             self.for_statement()
         } else if self.matches(&[TokenType::Break]) {
             self.break_statement()
         } else if self.matches(&[TokenType::Continue]) {
             self.continue_statement()
-        } else if self.matches(&[TokenType::LeftCurlyBrace]) {
-            Box::new(*self.block())
         } else {
             self.expression_statement()
         }
     }
 
     fn print_statement(&mut self) -> Box<Statement> {
+        let start = self.current - 1;
         let value = self.expression();
-        self.consume(TokenType::Semicolon, "Expected ';' after value.");
+        self.consume(TokenType::Semicolon, "after value");
 
-        Box::new(Statement::Print(value))
+        Box::new(Statement::Print(value, self.span_since(start)))
     }
 
     fn return_statement(&mut self) -> Box<Statement> {
+        let start = self.current - 1;
         let keyword = self.previous().clone();
         let value = if !self.check(&TokenType::Semicolon) {
-            self.expression()
+            Some(self.expression())
         } else {
-            Expression::Literal(Literal::None)
+            None
         };
-        self.consume(TokenType::Semicolon, "Expected ';' after return value.");
+        self.consume(TokenType::Semicolon, "after return value");
 
         Box::new(Statement::Return {
             keyword,
-            value: Some(value),
+            value,
+            span: self.span_since(start),
         })
     }
 
-    fn if_statement(&mut self) -> Box<Statement> {
-        self.consume(TokenType::LeftParenthesis, "Expected '(' after 'if'.");
+    fn assert_statement(&mut self) -> Box<Statement> {
+        let start = self.current - 1;
+        let keyword = self.previous().clone();
+        self.consume(TokenType::LeftParenthesis, "after 'assert'");
         let condition = self.expression();
-        self.consume(
-            TokenType::RightParenthesis,
-            "Expected ')' after if condition.",
-        );
-
-        let then_branch = self.statement();
-        let else_branch = if self.matches(&[TokenType::Else]) {
-            Some(self.statement())
+        let message = if self.matches(&[TokenType::Comma]) {
+            Some(self.expression())
         } else {
             None
         };
+        self.consume(TokenType::RightParenthesis, "after assertion");
+        self.consume(TokenType::Semicolon, "after assertion");
 
-        Box::new(Statement::If {
+        Box::new(Statement::Assert {
+            keyword,
             condition,
-            then_branch,
+            message,
+            span: self.span_since(start),
+        })
+    }
+
+    fn include_declaration(&mut self) -> Statement {
+        let start = self.current - 1;
+        let keyword = self.previous().clone();
+        if self.block_depth > 0 {
+            self.error(&keyword, "Files can only be included at the top level");
+        }
+
+        let path = self.consume(TokenType::String, "after 'include'");
+        let alias = if self.matches(&[TokenType::As]) {
+            Some(self.consume(TokenType::Identifier, "as module name"))
+        } else {
+            None
+        };
+        self.consume(TokenType::Semicolon, "after included path");
+
+        Statement::Include {
+            keyword,
+            path,
+            alias,
+            span: self.span_since(start),
+        }
+    }
+
+    fn module_declaration(&mut self) -> Statement {
+        let start = self.current - 1;
+        let keyword = self.previous().clone();
+        if self.block_depth > 0 {
+            self.error(&keyword, "Modules can only be declared at the top level");
+        }
+
+        let name = self.consume(TokenType::Identifier, "as module name");
+        let Statement::Block(statements, _) = *self.block() else {
+            unreachable!("Blocks are parsed into block statements.");
+        };
+
+        Statement::Module {
+            name,
+            statements,
+            span: self.span_since(start),
+        }
+    }
+
+    fn if_statement(&mut self) -> Box<Statement> {
+        let start = self.current - 1;
+        self.consume(TokenType::LeftParenthesis, "after 'if'");
+        let condition = self.expression();
+        self.consume(TokenType::RightParenthesis, "after if condition");
+
+        let then_branch = self.statement();
+        let else_branch = if self.matches(&[TokenType::Else]) {
+            Some(self.statement())
+        } else if self.matches(&[TokenType::Elif]) {
+            // 'elif' is short for 'else if', so it nests another if statement.
+            if self.enter("Statement") {
+                let branch = self.if_statement();
+                self.depth -= 1;
+
+                Some(branch)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        Box::new(Statement::If {
+            condition,
+            then_branch,
             else_branch,
+            span: self.span_since(start),
         })
     }
 
+    /// Reports a switch statement as unsupported, skipping it up to and including its closing brace,
+    /// so parsing carries on after it.
     fn switch_statement(&mut self) -> Box<Statement> {
-        unimplemented!("Switch statements are not yet implemented!")
+        let start = self.current - 1;
+        let keyword = self.previous().clone();
+        self.error(&keyword, "Switch statements are not supported");
+
+        while !self.check(&TokenType::LeftCurlyBrace) && !self.is_at_end() {
+            self.advance();
+        }
+
+        let mut braces = 0;
+        while !self.is_at_end() {
+            match self.advance().token_type {
+                TokenType::LeftCurlyBrace => braces += 1,
+                TokenType::RightCurlyBrace => braces -= 1,
+                _ => {}
+            }
+
+            if braces == 0 {
+                // The whole statement was skipped, so there is nothing left to recover from.
+                self.panic_mode = false;
+                break;
+            }
+        }
+
+        Box::new(Statement::Block(Vec::new(), self.span_since(start)))
     }
 
     fn while_statement(&mut self) -> Box<Statement> {
-        self.consume(TokenType::LeftParenthesis, "Expected '(' after 'while'.");
+        let start = self.current - 1;
+        self.consume(TokenType::LeftParenthesis, "after 'while'");
         let condition = self.expression();
-        self.consume(
-            TokenType::RightParenthesis,
-            "Expected ')' after while condition.",
-        );
+        self.consume(TokenType::RightParenthesis, "after while condition");
 
         let body = self.statement();
 
-        Box::new(Statement::While { condition, body })
+        Box::new(Statement::While {
+            condition,
+            body,
+            span: self.span_since(start),
+        })
+    }
+
+    fn loop_statement(&mut self) -> Box<Statement> {
+        let start = self.current - 1;
+        let body = if self.check(&TokenType::LeftCurlyBrace) {
+            self.block()
+        } else {
+            self.error_expected(&self.peek().clone(), "Expected '{' after 'loop'");
+            self.statement()
+        };
+
+        Box::new(Statement::Loop {
+            body,
+            span: self.span_since(start),
+        })
     }
 
+    /// Parses a C-style 'for' loop.
+    /// The increment is kept apart from the body, so a 'continue' in the body still runs it.
     fn for_statement(&mut self) -> Box<Statement> {
-        unimplemented!("For statements are not yet implemented!")
+        let start = self.current - 1;
+        self.consume(TokenType::LeftParenthesis, "after 'for'");
+
+        let initializer = if self.matches(&[TokenType::Semicolon]) {
+            None
+        } else if self.matches(&[TokenType::Variable]) {
+            Some(Box::new(self.variable_declaration(true)))
+        } else {
+            Some(self.expression_statement())
+        };
+
+        let condition = if !self.check(&TokenType::Semicolon) {
+            Some(self.expression())
+        } else {
+            None
+        };
+        self.consume(TokenType::Semicolon, "after loop condition");
+
+        let increment = if !self.check(&TokenType::RightParenthesis) {
+            Some(self.expression())
+        } else {
+            None
+        };
+        self.consume(TokenType::RightParenthesis, "after for clauses");
+
+        let body = self.statement();
+
+        Box::new(Statement::For {
+            initializer,
+            condition,
+            increment,
+            body,
+            span: self.span_since(start),
+        })
     }
 
     fn break_statement(&mut self) -> Box<Statement> {
+        let start = self.current - 1;
         let keyword = self.previous().clone();
-        self.consume(TokenType::Semicolon, "Expected ';' after 'break'.");
+        self.consume(TokenType::Semicolon, "after 'break'");
+        let span = self.span_since(start);
 
-        Box::new(Statement::Break { keyword })
+        Box::new(Statement::Break { keyword, span })
     }
 
     fn continue_statement(&mut self) -> Box<Statement> {
+        let start = self.current - 1;
         let keyword = self.previous().clone();
-        self.consume(TokenType::Semicolon, "Expected ';' after 'continue'.");
+        self.consume(TokenType::Semicolon, "after 'continue'");
+        let span = self.span_since(start);
 
-        Box::new(Statement::Continue { keyword })
+        Box::new(Statement::Continue { keyword, span })
     }
 
     fn expression_statement(&mut self) -> Box<Statement> {
+        let start = self.current;
         let value = self.expression();
-        self.consume(TokenType::Semicolon, "Expected ';' after expression.");
+        self.consume(TokenType::Semicolon, "after expression");
+
+        Box::new(Statement::Expression(value, self.span_since(start)))
+    }
+
+    /// Discards tokens until the start of the next statement, so parsing can continue after an error.
+    /// Inside a block, the closing brace is left for the block to consume.
+    fn synchronize(&mut self) {
+        self.panic_mode = false;
+
+        while !self.is_at_end() {
+            if self.current > 0 && self.previous().token_type == TokenType::Semicolon {
+                return;
+            }
+
+            match self.peek().token_type {
+                TokenType::RightCurlyBrace if self.block_depth > 0 => return,
+                TokenType::Function
+                | TokenType::Variable
+                | TokenType::Constant
+                | TokenType::If
+                | TokenType::Switch
+                | TokenType::While
+                | TokenType::Loop
+                | TokenType::For
+                | TokenType::Print
+                | TokenType::Assert
+                | TokenType::Include
+                | TokenType::Module
+                | TokenType::Return
+                | TokenType::Break
+                | TokenType::Continue => return,
+                _ => {}
+            }
+
+            self.advance();
+        }
+    }
+
+    /// Enters a nested expression or statement, reporting an error if it is nested too deeply.
+    ///
+    /// # Arguments
+    /// * `kind` - What is nested, such as "Expression", for the error.
+    ///
+    /// # Returns
+    /// True if the expression or statement may be parsed, false otherwise.
+    fn enter(&mut self, kind: &str) -> bool {
+        if self.depth >= self.max_depth {
+            let token = self.peek().clone();
+            self.error(&token, &format!("{} is nested too deeply", kind));
+
+            return false;
+        }
+
+        self.depth += 1;
+        self.peak = self.peak.max(self.depth);
+
+        true
+    }
 
-        Box::new(Statement::Expression(value))
+    /// Starts a chain of operators, such as `a + b + c`, where every operator nests the ones before it.
+    /// The operators nest on top of the most deeply nested operand so far, see `link_chain`.
+    ///
+    /// # Returns
+    /// The depths to restore with `end_chain`.
+    fn start_chain(&mut self) -> (usize, usize) {
+        let depths = (self.depth, self.peak);
+        self.peak = self.depth;
+
+        depths
     }
 
-    fn consume(&mut self, token_type: TokenType, message: &str) -> Token {
+    /// Enters the next operator of a chain, which nests everything parsed since the chain started.
+    ///
+    /// # Returns
+    /// True if the operator's operand may be parsed, false otherwise.
+    fn link_chain(&mut self) -> bool {
+        self.depth = self.peak;
+
+        self.enter("Expression")
+    }
+
+    /// Ends a chain of operators, going back to the depth it started at.
+    ///
+    /// # Arguments
+    /// * `(depth, peak)` - The depths returned by `start_chain`.
+    fn end_chain(&mut self, (depth, peak): (usize, usize)) {
+        self.depth = depth;
+        self.peak = self.peak.max(peak);
+    }
+
+    /// Consumes the next token if it has the given type, reporting an error otherwise.
+    ///
+    /// # Arguments
+    /// * `token_type` - The type of the expected token.
+    /// * `context` - Where the token is expected, e.g. "after value" for "Expected ';' after value".
+    ///
+    /// # Returns
+    /// The consumed token, or the unexpected token in its place.
+    fn consume(&mut self, token_type: TokenType, context: &str) -> Token {
         if self.check(&token_type) {
             self.advance().clone()
         } else {
             let token = self.peek().clone();
-            self.error(&token, message);
+            let expected = match token_type.spelling() {
+                Some(spelling) => format!("'{}'", spelling),
+                None => token_type.to_string(),
+            };
+            self.error_expected(&token, &format!("Expected {} {}", expected, context));
 
             token
         }
     }
 
+    /// Gets the span from the start of a token to the end of the last consumed token.
+    /// If the token has not been consumed, which happens after an error, only the token itself is covered.
+    ///
+    /// # Arguments
+    /// * `start` - The index of the node's first token.
+    fn span_since(&self, start: usize) -> Span {
+        let end = self.current.max(start + 1) - 1;
+
+        self.tokens[start].span().to(self.tokens[end].span())
+    }
+
     fn peek(&self) -> &Token {
         &self.tokens[self.current]
     }
@@ -724,21 +1499,755 @@ impl Parser {
         false
     }
 
-    fn error(&mut self, token: &Token, message: &str) {
-        if token.token_type == TokenType::EndOfFile {
-            report(token.line, token.column, &format!("{} at end", message));
+    /// Reports an error for an unexpected token, describing the token that was found.
+    ///
+    /// # Arguments
+    /// * `token` - The token that was found instead.
+    /// * `expected` - What was expected, e.g. "after value".
+    fn error_expected(&mut self, token: &Token, expected: &str) {
+        let found = if token.token_type == TokenType::EndOfFile {
+            "end of file".to_string()
         } else {
-            report(
-                token.line,
-                token.column,
-                &format!("{} at '{}'", token.lexeme, message),
+            format!(
+                "'{}' ({}, line {}, column {})",
+                token.lexeme, token.token_type, token.line, token.column
+            )
+        };
+
+        self.error(token, &format!("{}, found {}", expected, found));
+    }
+
+    fn error(&mut self, token: &Token, message: &str) {
+        self.had_error = true;
+
+        // Only report the first error until the parser has recovered.
+        if self.panic_mode {
+            return;
+        }
+        self.panic_mode = true;
+
+        // Parsing carries on past the maximum number of errors, but only to count them.
+        if self.errors.len() >= self.max_errors {
+            self.omitted_errors += 1;
+            return;
+        }
+
+        self.errors.push(Error {
+            line: token.line,
+            column: token.column,
+            message: message.to_string(),
+            severity: Severity::Error,
+        });
+    }
+}
+
+/// Creates a binary expression, spanning both of its operands.
+fn binary(left: Expression, operator: Token, right: Expression) -> Expression {
+    let span = left.span().to(right.span());
+
+    Expression::Binary {
+        left: Box::new(left),
+        operator,
+        right: Box::new(right),
+        span,
+    }
+}
+
+/// Checks if the given token type is a comparison operator.
+fn is_comparison(token_type: &TokenType) -> bool {
+    matches!(
+        token_type,
+        TokenType::GreaterThan
+            | TokenType::GreaterThanOrEqual
+            | TokenType::LessThan
+            | TokenType::LessThanOrEqual
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::lexer::Scanner;
+    use crate::lang::with_stack;
+
+    /// Parses the source code on a thread with the stack programs are run with,
+    /// returning the messages of the errors found.
+    fn nesting_errors(source: &str) -> Vec<String> {
+        with_stack(|| {
+            let tokens = Scanner::new(source).scan_tokens();
+
+            match Parser::new(&tokens).parse() {
+                Ok(_) => Vec::new(),
+                Err(errors) => errors.into_iter().map(|error| error.message).collect(),
+            }
+        })
+    }
+
+    #[test]
+    fn test_deeply_nested_expression() {
+        let source = format!("{}1{};", "(".repeat(5000), ")".repeat(5000));
+        let errors = nesting_errors(&source);
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0], "Expression is nested too deeply");
+    }
+
+    #[test]
+    fn test_nesting_within_limit() {
+        let source = format!("{}1{};", "(".repeat(50), ")".repeat(50));
+        let tokens = Scanner::new(&source).scan_tokens();
+
+        assert!(Parser::new(&tokens).parse().is_ok());
+        assert!(Parser::new(&tokens).with_max_depth(10).parse().is_err());
+
+        let source = format!("print {}1;", "- ".repeat(MAX_EXPRESSION_DEPTH - 10));
+        assert_eq!(nesting_errors(&source), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_deeply_nested_unary() {
+        let source = format!("{}1;", "- ".repeat(5000));
+
+        assert_eq!(nesting_errors(&source), ["Expression is nested too deeply"]);
+    }
+
+    #[test]
+    fn test_deeply_chained_operators() {
+        // Every operator nests the ones before it, so long chains are as deep as they are long.
+        for chain in [" + 1", " * 1", " && 1", " == 1", " ?? 1", " ** 1", "()"] {
+            let source = format!("print 1{};", chain.repeat(20000));
+
+            assert_eq!(
+                nesting_errors(&source),
+                ["Expression is nested too deeply"],
+                "{}",
+                chain
             );
         }
 
-        if !self.had_error {
-            self.had_error = true;
-        } else {
-            panic!("Too many errors!");
+        // Chains nest on top of their most deeply nested operand.
+        let operand = format!("{}1{}", "(".repeat(600), ")".repeat(600));
+        let source = format!("print {}{};", operand, " + 1".repeat(600));
+        assert_eq!(nesting_errors(&source), ["Expression is nested too deeply"]);
+    }
+
+    #[test]
+    fn test_deeply_nested_statements() {
+        for (open, close) in [
+            ("{", "}"),
+            ("if (true) ", ""),
+            ("while (true) ", ""),
+            ("fn f() {", "}"),
+        ] {
+            let source = format!("{}print 1;{}", open.repeat(20000), close.repeat(20000));
+            let errors = nesting_errors(&source);
+
+            // The conditions of the innermost statements may be the first to nest too deeply.
+            assert!(errors[0].ends_with(" is nested too deeply"), "{}", open);
+        }
+
+        let source = format!(
+            "if (false) print 0; {}",
+            "elif (false) print 0; ".repeat(20000)
+        );
+        assert!(nesting_errors(&source)[0].ends_with(" is nested too deeply"));
+    }
+
+    /// Parses a single statement.
+    fn parse_statement(source: &str) -> Statement {
+        let tokens = Scanner::new(source).scan_tokens();
+        let mut statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+        assert_eq!(statements.len(), 1);
+
+        statements.remove(0)
+    }
+
+    /// Creates a token without a position, as the expected trees are compared ignoring spans.
+    fn token(token_type: TokenType, lexeme: &str) -> Token {
+        Token::new(token_type, lexeme, None, 0, 0)
+    }
+
+    fn number(value: i64) -> Expression {
+        let literal = Literal::Int(value);
+
+        Expression::Literal {
+            token: Token::new(
+                TokenType::Number,
+                &value.to_string(),
+                Some(literal.clone()),
+                0,
+                0,
+            ),
+            value: literal,
+            span: Span::default(),
+        }
+    }
+
+    fn variable(name: &str) -> Expression {
+        Expression::Variable(token(TokenType::Identifier, name), Span::default())
+    }
+
+    fn binary(left: Expression, operator: Token, right: Expression) -> Expression {
+        Expression::Binary {
+            left: Box::new(left),
+            operator,
+            right: Box::new(right),
+            span: Span::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_precedence() {
+        let expected = Statement::Expression(
+            binary(
+                number(1),
+                token(TokenType::Plus, "+"),
+                binary(number(2), token(TokenType::Star, "*"), number(3)),
+            ),
+            Span::default(),
+        );
+
+        assert!(parse_statement("1 + 2 * 3;").eq_ignoring_spans(&expected));
+        assert!(!parse_statement("(1 + 2) * 3;").eq_ignoring_spans(&expected));
+
+        // Left-associative operators nest to the left.
+        let expected = Statement::Expression(
+            binary(
+                binary(number(1), token(TokenType::Minus, "-"), number(2)),
+                token(TokenType::Minus, "-"),
+                number(3),
+            ),
+            Span::default(),
+        );
+        assert!(parse_statement("1 - 2 - 3;").eq_ignoring_spans(&expected));
+    }
+
+    #[test]
+    fn test_parse_power() {
+        let power = |left, right| binary(left, token(TokenType::StarStar, "**"), right);
+
+        // Powers group from the right.
+        let expected = Statement::Expression(
+            power(number(2), power(number(3), number(2))),
+            Span::default(),
+        );
+        assert!(parse_statement("2 ** 3 ** 2;").eq_ignoring_spans(&expected));
+
+        // They bind tighter than negation on their left and multiplication, and their exponent may be negated.
+        let negate = |right| Expression::Unary {
+            operator: token(TokenType::Minus, "-"),
+            right: Box::new(right),
+            span: Span::default(),
+        };
+        let expected = Statement::Expression(
+            binary(
+                negate(power(number(2), negate(number(1)))),
+                token(TokenType::Star, "*"),
+                number(3),
+            ),
+            Span::default(),
+        );
+        assert!(parse_statement("-2 ** -1 * 3;").eq_ignoring_spans(&expected));
+    }
+
+    #[test]
+    fn test_parse_declaration() {
+        let expected = Statement::Variable {
+            name: token(TokenType::Identifier, "a"),
+            declared_type: Some(token(TokenType::Identifier, "i32")),
+            initializer: Some(Expression::Unary {
+                operator: token(TokenType::Minus, "-"),
+                right: Box::new(variable("b")),
+                span: Span::default(),
+            }),
+            mutable: true,
+            span: Span::default(),
+        };
+
+        assert!(parse_statement("let a: i32 = -b;").eq_ignoring_spans(&expected));
+        assert!(!parse_statement("const a: i32 = -b;").eq_ignoring_spans(&expected));
+    }
+
+    #[test]
+    fn test_parse_if_with_call() {
+        let expected = Statement::If {
+            condition: variable("a"),
+            then_branch: Box::new(Statement::Print(
+                Expression::Call {
+                    callee: Box::new(variable("f")),
+                    parenthesis: token(TokenType::RightParenthesis, ")"),
+                    arguments: vec![number(1), number(2)],
+                    span: Span::default(),
+                },
+                Span::default(),
+            )),
+            else_branch: Some(Box::new(Statement::Block(Vec::new(), Span::default()))),
+            span: Span::default(),
+        };
+
+        assert!(parse_statement("if (a)\n    print f(1, 2);\nelse {}").eq_ignoring_spans(&expected));
+    }
+
+    #[test]
+    fn test_parsed_trees_compare_spans() {
+        assert_eq!(parse_statement("print 1;"), parse_statement("print 1;"));
+        assert_ne!(parse_statement("print 1;"), parse_statement(" print 1;"));
+        assert!(parse_statement("print 1;").eq_ignoring_spans(&parse_statement(" print  1 ;")));
+    }
+
+    fn parse_errors(source: &str) -> Vec<String> {
+        let tokens = Scanner::new(source).scan_tokens();
+
+        Parser::new(&tokens)
+            .parse()
+            .expect_err("Parsing should fail.")
+            .into_iter()
+            .map(|error| error.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_switch_is_unsupported() {
+        assert_eq!(
+            parse_errors("switch (1) {}"),
+            ["Switch statements are not supported"]
+        );
+
+        // Parsing carries on after the switch statement.
+        assert_eq!(
+            parse_errors("switch (a) { case 1 => { print 1; } _ => print 2; }\nprint ;"),
+            [
+                "Switch statements are not supported",
+                "Expected expression, found ';' (semicolon, line 2, column 7)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expected_found_messages() {
+        assert_eq!(
+            parse_errors("if (true {\n}"),
+            vec![
+                "Expected ')' after if condition, found '{' (left curly brace, line 1, column 10)"
+            ]
+        );
+        assert_eq!(
+            parse_errors("let a = 1\nlet b = 2;"),
+            vec![
+                "Expected ';' after variable declaration, found 'let' (let keyword, line 2, column 1)"
+            ]
+        );
+        assert_eq!(
+            parse_errors("print 1 + 2"),
+            vec!["Expected ';' after value, found end of file"]
+        );
+        assert_eq!(
+            parse_errors("return 1 2;"),
+            vec!["Expected ';' after return value, found '2' (number, line 1, column 10)"]
+        );
+        assert_eq!(
+            parse_errors("fn add(a, b: i32) {}"),
+            vec!["Expected ':' after parameter name, found ',' (comma, line 1, column 9)"]
+        );
+        assert_eq!(
+            parse_errors("let a = ;"),
+            vec!["Expected expression, found ';' (semicolon, line 1, column 9)"]
+        );
+    }
+
+    #[test]
+    fn test_consume_names_expected_token() {
+        let expected = |source: &str| {
+            parse_errors(source)
+                .into_iter()
+                .map(|message| message.split(", found").next().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(expected("print 1"), ["Expected ';' after value"]);
+        assert_eq!(
+            expected("let 5 = 1;"),
+            ["Expected identifier as variable name"]
+        );
+        assert_eq!(expected("let a: 5;"), ["Expected identifier as type name"]);
+        assert_eq!(
+            expected("fn f(a: i32 {}"),
+            ["Expected ')' after parameters"]
+        );
+        assert_eq!(expected("while true {}"), ["Expected '(' after 'while'"]);
+        assert_eq!(expected("const a;"), ["Expected '=' after constant name"]);
+        assert_eq!(TokenType::BitwiseLeftShiftEqual.spelling(), Some("<<="));
+        assert_eq!(TokenType::Identifier.spelling(), None);
+    }
+
+    #[test]
+    fn test_annotated_declarations() {
+        let tokens =
+            Scanner::new("let a: int = 1; let b: str; const c: float = 2.5;").scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+
+        let Statement::Variable {
+            declared_type: Some(declared_type),
+            ..
+        } = &statements[0]
+        else {
+            panic!("The declaration should be annotated.");
+        };
+        assert_eq!(&*declared_type.lexeme, "int");
+        assert_eq!(
+            statements
+                .iter()
+                .map(Statement::to_string)
+                .collect::<Vec<_>>(),
+            ["(var a: int 1)", "(var b: str)", "(const c: float 2.5)"]
+        );
+    }
+
+    #[test]
+    fn test_tuples() {
+        let tokens = Scanner::new("print (1, \"a\"); print (1); print (1,); let (a, b) = (2, 3);")
+            .scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+
+        // Only a comma makes a parenthesized expression a tuple.
+        assert_eq!(
+            statements
+                .iter()
+                .map(Statement::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "(print (tuple 1 a))",
+                "(print (group 1))",
+                "(print (tuple 1))",
+                "(var (a, b) (tuple 2 3))"
+            ]
+        );
+
+        let Statement::Destructure { names, .. } = &statements[3] else {
+            panic!("The declaration should destructure a tuple.");
+        };
+        assert_eq!(names.len(), 2);
+
+        assert_eq!(
+            parse_errors("let (a, b);"),
+            ["Expected '=' after variable names, found ';' (semicolon, line 1, column 11)"]
+        );
+    }
+
+    #[test]
+    fn test_elif_is_else_if() {
+        let tree = |source: &str| {
+            let tokens = Scanner::new(source).scan_tokens();
+            Parser::new(&tokens)
+                .parse()
+                .expect("Parsing should succeed.")
+                .iter()
+                .map(Statement::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        let expected = tree(
+            "if (a) { print 1; } else if (b) { print 2; } else if (c) print 3; else { print 4; }",
+        );
+        assert_eq!(
+            tree("if (a) { print 1; } elif (b) { print 2; } elif (c) print 3; else { print 4; }"),
+            expected
+        );
+        assert_eq!(
+            tree(
+                "if (a) { print 1; } elif (b) { print 2; } else if (c) print 3; else { print 4; }"
+            ),
+            expected
+        );
+        assert_eq!(
+            tree(
+                "if (a) { print 1; } else if (b) { print 2; } elif (c) print 3; else { print 4; }"
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_coalesce_is_right_associative() {
+        let tokens = Scanner::new("a ?? b ?? c || d;").scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+
+        assert_eq!(statements[0].to_string(), "(?? a (?? b (|| c d)))");
+    }
+
+    #[test]
+    fn test_parse_typeof() {
+        let tokens = Scanner::new("typeof -a ** 2 + typeof typeof b;").scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+
+        // 'typeof' binds like the other unary operators.
+        assert_eq!(
+            statements[0].to_string(),
+            "(+ (typeof (- (** a 2))) (typeof (typeof b)))"
+        );
+        assert_eq!(
+            statements[0].span(),
+            Span {
+                start: 0,
+                end: 33,
+                line: 1,
+                column: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_recover_inside_blocks() {
+        let errors = parse_errors(
+            r#"
+            fn first() {
+                let a = 1;
+                let b = ;
+                print a;
+            }
+
+            fn second() {
+                print 1 +;
+            }
+            "#,
+        );
+
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].starts_with("Expected expression, found ';'"));
+        assert!(errors[0].contains("line 4"));
+        assert!(errors[1].starts_with("Expected expression, found ';'"));
+        assert!(errors[1].contains("line 9"));
+    }
+
+    #[test]
+    fn test_recover_keeps_closing_brace() {
+        let errors = parse_errors("fn first() { print 1 }\nfn second() { print 2; }\n}");
+
+        assert_eq!(
+            errors,
+            vec![
+                "Expected ';' after value, found '}' (right curly brace, line 1, column 22)",
+                "Expected expression, found '}' (right curly brace, line 3, column 1)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_loop_statement() {
+        let tokens = Scanner::new("loop { if (done) break; continue; }").scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+
+        assert!(matches!(statements[0], Statement::Loop { .. }));
+        assert_eq!(
+            statements[0].to_string(),
+            "(loop (block (if done (break break) ) (continue continue)))"
+        );
+    }
+
+    #[test]
+    fn test_break_and_continue_without_semicolon() {
+        for keyword in ["break", "continue"] {
+            let message = format!("Expected ';' after '{}', found end of file", keyword);
+
+            assert_eq!(parse_errors(keyword), vec![message.clone()]);
+            assert_eq!(
+                parse_errors(&format!("print 1; {}", keyword)),
+                vec![message]
+            );
         }
     }
+
+    #[test]
+    fn test_for_statement() {
+        let tokens = Scanner::new("for (let i = 0; i < 3; i = i + 1) print i; for (;;) break;")
+            .scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+
+        // The loop is kept as a 'For' node, with the increment apart from the body.
+        let Statement::For {
+            initializer: Some(initializer),
+            condition: Some(_),
+            increment: Some(increment),
+            body,
+            ..
+        } = &statements[0]
+        else {
+            panic!("Expected a for statement, found {}", statements[0]);
+        };
+        assert!(matches!(**initializer, Statement::Variable { .. }));
+        assert_eq!(increment.to_string(), "(= i (+ i 1))");
+        assert!(matches!(**body, Statement::Print(_, _)));
+        assert_eq!(
+            statements[0].to_string(),
+            "(for (var i 0) (< i 3) (= i (+ i 1)) (print i))"
+        );
+        assert_eq!(statements[1].to_string(), "(for (break break))");
+    }
+
+    #[test]
+    fn test_trailing_commas() {
+        let tokens = Scanner::new("f(a, b,); fn g(x: int, y: int,) {}").scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+
+        assert_eq!(statements[0].to_string(), "(f(a, b))");
+        assert_eq!(statements[1].to_string(), "(fn g(x, y) (block ))");
+
+        // A comma still needs something before it.
+        let tokens = Scanner::new("f(,);").scan_tokens();
+        assert!(Parser::new(&tokens).parse().is_err());
+        let tokens = Scanner::new("fn g(,) {}").scan_tokens();
+        assert!(Parser::new(&tokens).parse().is_err());
+    }
+
+    #[test]
+    fn test_assert_statement() {
+        let tokens = Scanner::new("assert(a == 1); assert(b, \"b is false\");").scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+
+        assert_eq!(statements[0].to_string(), "(assert (== a 1))");
+        assert_eq!(statements[1].to_string(), "(assert b b is false)");
+
+        let tokens = Scanner::new("assert(a, b, c);").scan_tokens();
+        assert!(Parser::new(&tokens).parse().is_err());
+    }
+
+    #[test]
+    fn test_include() {
+        let tokens = Scanner::new("include \"utils.cpl\"; print 1;").scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+
+        assert_eq!(statements[0].to_string(), "(include \"utils.cpl\")");
+        assert_eq!(
+            parse_errors("fn f() { include \"utils.cpl\"; }"),
+            vec!["Files can only be included at the top level"]
+        );
+        assert_eq!(parse_errors("include utils;").len(), 1);
+
+        let tokens = Scanner::new("include \"math.cpl\" as math;").scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+        assert_eq!(statements[0].to_string(), "(include \"math.cpl\" as math)");
+    }
+
+    #[test]
+    fn test_module() {
+        let tokens =
+            Scanner::new("module math { const pi = 3; fn half(x: int) -> int { return x / 2; } } print math.half(math.pi);")
+                .scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+
+        assert_eq!(
+            statements[0].to_string(),
+            "(module math (const pi 3) (fn half(x) -> int (block (ret return (/ x 2)))))"
+        );
+        assert_eq!(statements[1].to_string(), "(print (math.half(math.pi)))");
+        assert_eq!(
+            parse_errors("{ module inner {} }"),
+            vec!["Modules can only be declared at the top level"]
+        );
+        assert_eq!(parse_errors("print math.;").len(), 1);
+    }
+
+    #[test]
+    fn test_loop_requires_block() {
+        assert_eq!(
+            parse_errors("loop print 1;"),
+            vec!["Expected '{' after 'loop', found 'print' (print keyword, line 1, column 6)"]
+        );
+    }
+
+    #[test]
+    fn test_chained_comparison() {
+        assert_eq!(
+            parse_errors("if (0 < x < 10) print x;"),
+            vec!["Comparisons cannot be chained, use '0 < x && x < 10' instead"]
+        );
+        assert_eq!(parse_errors("print a >= b + 1 > c;").len(), 1);
+    }
+
+    #[test]
+    fn test_grouped_comparison() {
+        let tokens = Scanner::new("if ((0 < x) < 10) print x;").scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+
+        assert_eq!(
+            statements[0].to_string(),
+            "(if (< (group (< 0 x)) 10) (print x) )"
+        );
+    }
+
+    #[test]
+    fn test_parser_borrows_tokens() {
+        let tokens = Scanner::new("let a = 1; print a;").scan_tokens();
+        let parser = Parser::new(&tokens);
+
+        assert!(std::ptr::eq(parser.tokens, tokens.as_slice()));
+    }
+
+    #[test]
+    fn test_display_lists() {
+        let tokens = Scanner::new(
+            "f(); f(a); f(a, b); fn g() {} fn h(x: int) { x; } fn i(x: int, y: int) { x; y; }",
+        )
+        .scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+        let rendered = statements
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            rendered,
+            [
+                "(f())",
+                "(f(a))",
+                "(f(a, b))",
+                "(fn g() (block ))",
+                "(fn h(x) (block x))",
+                "(fn i(x, y) (block x y))",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_statement_children() {
+        let tokens = Scanner::new("{ let a = 1; { print a; } loop { break; } }").scan_tokens();
+        let statements = Parser::new(&tokens)
+            .parse()
+            .expect("Parsing should succeed.");
+
+        let children = statements[0].children().collect::<Vec<_>>();
+        assert_eq!(children.len(), 3);
+        assert!(matches!(children[0], Statement::Variable { .. }));
+        assert_eq!(children[1].children().count(), 1);
+        assert_eq!(children[2].children().count(), 1);
+        assert_eq!(children[0].children().count(), 0);
+    }
 }
@@ -0,0 +1,297 @@
+use std::collections::HashSet;
+
+/// Removes obvious junk from generated x86_64 assembly, looking at a few lines at a time,
+/// without changing what the program does.
+///
+/// Until there is nothing left to remove, these are removed:
+/// - a push directly followed by a pop of the same register,
+/// - two negations of the same register in a row,
+/// - adding 0 to a register, or multiplying it by 1,
+/// - jumps to the label right after them,
+/// - local labels, starting with `.L`, that nothing refers to.
+///
+/// The flags set by the removed instructions are never relied upon,
+/// as the generator compares right before every conditional jump.
+///
+/// # Arguments
+/// * `assembly` - The generated assembly.
+///
+/// # Returns
+/// The assembly, and the number of instructions removed from it.
+///
+/// # Examples
+/// ```
+/// use cpl::lang::peephole::optimize;
+///
+/// let (assembly, removed) = optimize("    push %rax\n    pop %rax\n    ret\n");
+///
+/// assert_eq!(assembly, "    ret\n");
+/// assert_eq!(removed, 2);
+/// ```
+pub fn optimize(assembly: &str) -> (String, usize) {
+    let mut lines = assembly.lines().collect::<Vec<_>>();
+    let mut removed = 0;
+
+    // Removing a jump can leave its label dead, and removing a dead label can leave instructions
+    // that cancel each other next to each other.
+    loop {
+        let count = lines.len();
+
+        let (kept, instructions) = remove_junk(&lines);
+        lines = remove_dead_labels(kept);
+        removed += instructions;
+
+        if lines.len() == count {
+            break;
+        }
+    }
+
+    let assembly = lines.iter().map(|line| format!("{}\n", line)).collect();
+
+    (assembly, removed)
+}
+
+/// Removes the instructions that do nothing, comparing each line with the lines kept before it,
+/// so removing a pair of instructions can make another pair of the ones around it.
+///
+/// # Returns
+/// The lines kept, and the number of instructions removed.
+fn remove_junk<'a>(lines: &[&'a str]) -> (Vec<&'a str>, usize) {
+    let mut kept: Vec<&str> = Vec::with_capacity(lines.len());
+    let mut removed = 0;
+
+    for &line in lines {
+        if let Some(label) = label(line) {
+            // Other labels and empty lines may be in between, as they are not run.
+            let previous = kept
+                .iter()
+                .rposition(|line| self::label(line).is_none() && !line.trim().is_empty());
+            if let Some(previous) = previous {
+                if instruction(kept[previous]) == Some(("jmp", vec![label])) {
+                    kept.remove(previous);
+                    removed += 1;
+                }
+            }
+        } else if let Some(current) = instruction(line) {
+            match (current.0, current.1.as_slice()) {
+                ("add", ["$0", _]) | ("imul", ["$1", _]) => {
+                    removed += 1;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let previous = kept.last().and_then(|line| instruction(line));
+            let cancels = match (previous, current) {
+                (Some((first, first_operands)), (second, second_operands)) => {
+                    first_operands == second_operands
+                        && matches!((first, second), ("push", "pop") | ("neg", "neg"))
+                }
+                _ => false,
+            };
+            if cancels {
+                kept.pop();
+                removed += 2;
+                continue;
+            }
+        }
+
+        kept.push(line);
+    }
+
+    (kept, removed)
+}
+
+/// Removes the local labels that no instruction or directive refers to.
+fn remove_dead_labels(lines: Vec<&str>) -> Vec<&str> {
+    let referenced = lines
+        .iter()
+        .filter(|line| label(line).is_none())
+        .flat_map(|line| line.split(|c: char| !is_label_character(c)))
+        .filter(|name| name.starts_with(".L"))
+        .collect::<HashSet<_>>();
+
+    lines
+        .into_iter()
+        .filter(|line| {
+            label(line).is_none_or(|label| !label.starts_with(".L") || referenced.contains(label))
+        })
+        .collect()
+}
+
+/// Gets the name of the label a line defines, such as `.L1` for `.L1:`.
+fn label(line: &str) -> Option<&str> {
+    line.strip_suffix(':')
+        .filter(|label| !label.is_empty() && label.chars().all(is_label_character))
+}
+
+/// Checks if a character can be part of the name of a label.
+fn is_label_character(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '$')
+}
+
+/// Splits an instruction into its mnemonic and operands, such as `add` and `$0` and `%rax` for `add $0, %rax`.
+///
+/// # Returns
+/// The mnemonic and operands, or `None` if the line is a label, a directive or empty.
+fn instruction(line: &str) -> Option<(&str, Vec<&str>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('.') || label(line).is_some() {
+        return None;
+    }
+
+    let (mnemonic, operands) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+
+    // Memory operands can have commas of their own, within their parentheses.
+    let mut depth = 0;
+    let operands = operands
+        .split(|c| {
+            match c {
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+
+            c == ',' && depth == 0
+        })
+        .map(str::trim)
+        .filter(|operand| !operand.is_empty())
+        .collect();
+
+    Some((mnemonic, operands))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Optimizes the lines of assembly, indenting the instructions.
+    fn optimize_lines(lines: &[&str]) -> (Vec<String>, usize) {
+        let assembly = lines
+            .iter()
+            .map(|line| match label(line) {
+                Some(_) => format!("{}\n", line),
+                None => format!("    {}\n", line),
+            })
+            .collect::<String>();
+        let (assembly, removed) = optimize(&assembly);
+
+        (
+            assembly
+                .lines()
+                .map(|line| line.trim().to_string())
+                .collect(),
+            removed,
+        )
+    }
+
+    #[test]
+    fn test_remove_cancelling_instructions() {
+        assert_eq!(
+            optimize_lines(&["push %rax", "pop %rax", "neg %rcx", "neg %rcx", "ret"]),
+            (vec!["ret".to_string()], 4)
+        );
+        assert_eq!(
+            optimize_lines(&["add $0, %rax", "imul $1, %rcx", "ret"]),
+            (vec!["ret".to_string()], 2)
+        );
+
+        // Removing the negations makes a push and pop of the same register.
+        assert_eq!(
+            optimize_lines(&["push %rax", "neg %rax", "neg %rax", "pop %rax"]),
+            (Vec::new(), 4)
+        );
+    }
+
+    #[test]
+    fn test_keep_instructions_that_do_something() {
+        let lines = [
+            "push %rax",
+            "pop %rcx",
+            "neg %rax",
+            "neg %rcx",
+            "add $1, %rax",
+            "imul $1, %rax, %rcx",
+            "pop %rax",
+            "push %rax",
+            "mov (%rax,%rcx,8), %rax",
+        ];
+
+        assert_eq!(
+            optimize_lines(&lines),
+            (lines.iter().map(|line| line.to_string()).collect(), 0)
+        );
+    }
+
+    #[test]
+    fn test_remove_jumps_to_next_label() {
+        assert_eq!(
+            optimize_lines(&["jmp .L1", ".L2:", "", ".L1:", "ret", "jmp .L2"]),
+            (
+                vec![
+                    ".L2:".to_string(),
+                    String::new(),
+                    "ret".to_string(),
+                    "jmp .L2".to_string()
+                ],
+                1
+            )
+        );
+        assert_eq!(
+            optimize_lines(&["jge .L1", ".L1:", "jmp .L2", "ret", ".L2:"]),
+            (
+                vec![
+                    "jge .L1".to_string(),
+                    ".L1:".to_string(),
+                    "jmp .L2".to_string(),
+                    "ret".to_string(),
+                    ".L2:".to_string()
+                ],
+                0
+            )
+        );
+    }
+
+    #[test]
+    fn test_remove_dead_labels() {
+        assert_eq!(
+            optimize_lines(&[
+                "main:",
+                "lea .L3(%rip), %rdi",
+                "call cpl_f_0",
+                ".L1:",
+                "ret",
+                "cpl_f_0:",
+                ".L3:",
+                ".string \"%ld\\012\"",
+            ]),
+            (
+                vec![
+                    "main:".to_string(),
+                    "lea .L3(%rip), %rdi".to_string(),
+                    "call cpl_f_0".to_string(),
+                    "ret".to_string(),
+                    "cpl_f_0:".to_string(),
+                    ".L3:".to_string(),
+                    ".string \"%ld\\012\"".to_string(),
+                ],
+                0
+            )
+        );
+
+        // Once the jumps to them are removed, the labels are dead,
+        // which leaves a push and a pop of the same register next to each other.
+        assert_eq!(
+            optimize_lines(&[
+                "jmp .L1",
+                ".L1:",
+                "push %rax",
+                "jmp .L2",
+                ".L2:",
+                "pop %rax",
+                "ret"
+            ]),
+            (vec!["ret".to_string()], 4)
+        );
+    }
+}
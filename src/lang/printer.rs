@@ -0,0 +1,381 @@
+use crate::lang::lexer::Literal;
+use crate::lang::parser::{Expression, Statement};
+
+/// The string used for one level of indentation.
+const INDENT: &str = "    ";
+
+impl Expression {
+    /// Renders the expression as CPL source code.
+    ///
+    /// # Returns
+    /// Source code that parses back into an equivalent expression.
+    pub fn to_source(&self) -> String {
+        match self {
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                ..
+            }
+            | Expression::Coalesce {
+                left,
+                operator,
+                right,
+                ..
+            } => format!(
+                "{} {} {}",
+                left.to_source(),
+                operator.lexeme,
+                right.to_source()
+            ),
+            Expression::Grouping(expression, _) => format!("({})", expression.to_source()),
+            Expression::Literal { value, .. } => literal_to_source(value),
+            Expression::Unary {
+                operator, right, ..
+            } => {
+                let right = right.to_source();
+
+                // Keep '- -a' from being read back as a decrement.
+                if right.starts_with(operator.lexeme.as_ref()) {
+                    format!("{} {}", operator.lexeme, right)
+                } else {
+                    format!("{}{}", operator.lexeme, right)
+                }
+            }
+            Expression::Variable(name, _) => name.lexeme.to_string(),
+            Expression::Assign { name, value, .. } => {
+                format!("{} = {}", name.lexeme, value.to_source())
+            }
+            Expression::Call {
+                callee, arguments, ..
+            } => {
+                let arguments = arguments
+                    .iter()
+                    .map(Expression::to_source)
+                    .collect::<Vec<_>>();
+
+                format!("{}({})", callee.to_source(), arguments.join(", "))
+            }
+            Expression::Qualified { module, name, .. } => {
+                format!("{}.{}", module.lexeme, name.lexeme)
+            }
+            // A tuple of one element needs a trailing comma to not be read back as a grouping.
+            Expression::Tuple(elements, _) => match elements.as_slice() {
+                [element] => format!("({},)", element.to_source()),
+                elements => {
+                    let elements = elements
+                        .iter()
+                        .map(Expression::to_source)
+                        .collect::<Vec<_>>();
+
+                    format!("({})", elements.join(", "))
+                }
+            },
+            Expression::TypeOf(expression, _) => format!("typeof {}", expression.to_source()),
+        }
+    }
+}
+
+impl Statement {
+    /// Renders the statement as CPL source code, indented with four spaces per level.
+    ///
+    /// # Returns
+    /// Source code that parses back into an equivalent statement.
+    pub fn to_source(&self) -> String {
+        let mut source = String::new();
+        self.write_source(&mut source, 0);
+
+        source
+    }
+
+    /// Checks if the statement is a comment following other code on the same line.
+    pub fn is_trailing_comment(&self) -> bool {
+        matches!(self, Statement::Comment { trailing: true, .. })
+    }
+
+    /// Writes the statement to the given string.
+    /// The first line is not indented, as it may follow other code on the same line.
+    ///
+    /// # Arguments
+    /// * `source` - The string to write to.
+    /// * `indent` - The current indentation level.
+    fn write_source(&self, source: &mut String, indent: usize) {
+        match self {
+            Statement::Expression(expression, _) => {
+                source.push_str(&format!("{};", expression.to_source()));
+            }
+            Statement::Print(expression, _) => {
+                source.push_str(&format!("print {};", expression.to_source()));
+            }
+            Statement::Variable {
+                name,
+                declared_type,
+                initializer,
+                mutable,
+                ..
+            } => {
+                let keyword = if *mutable { "let" } else { "const" };
+                source.push_str(&format!("{} {}", keyword, name.lexeme));
+
+                if let Some(declared_type) = declared_type {
+                    source.push_str(&format!(": {}", declared_type.lexeme));
+                }
+
+                if let Some(initializer) = initializer {
+                    source.push_str(&format!(" = {}", initializer.to_source()));
+                }
+
+                source.push(';');
+            }
+            Statement::Destructure {
+                names,
+                initializer,
+                mutable,
+                ..
+            } => {
+                let keyword = if *mutable { "let" } else { "const" };
+                let names = names
+                    .iter()
+                    .map(|name| name.lexeme.as_ref())
+                    .collect::<Vec<_>>();
+
+                source.push_str(&format!(
+                    "{} ({}) = {};",
+                    keyword,
+                    names.join(", "),
+                    initializer.to_source()
+                ));
+            }
+            Statement::Block(statements, _) => write_block(statements, source, indent),
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                ..
+            } => {
+                source.push_str(&format!("if ({}) ", condition.to_source()));
+                then_branch.write_source(source, indent);
+
+                if let Some(else_branch) = else_branch {
+                    source.push_str(" else ");
+                    else_branch.write_source(source, indent);
+                }
+            }
+            Statement::While {
+                condition, body, ..
+            } => {
+                source.push_str(&format!("while ({}) ", condition.to_source()));
+                body.write_source(source, indent);
+            }
+            Statement::Loop { body, .. } => {
+                source.push_str("loop ");
+                body.write_source(source, indent);
+            }
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                ..
+            } => {
+                source.push_str("for (");
+                match initializer {
+                    Some(initializer) => initializer.write_source(source, indent),
+                    None => source.push(';'),
+                }
+                if let Some(condition) = condition {
+                    source.push_str(&format!(" {}", condition.to_source()));
+                }
+                source.push(';');
+                if let Some(increment) = increment {
+                    source.push_str(&format!(" {}", increment.to_source()));
+                }
+                source.push_str(") ");
+                body.write_source(source, indent);
+            }
+            Statement::Function {
+                name,
+                parameters,
+                return_type,
+                body,
+                ..
+            } => {
+                let parameters = parameters
+                    .iter()
+                    .map(|(parameter, r#type)| format!("{}: {}", parameter.lexeme, r#type.lexeme))
+                    .collect::<Vec<_>>();
+
+                source.push_str(&format!("fn {}({}) ", name.lexeme, parameters.join(", ")));
+                if let Some(return_type) = return_type {
+                    source.push_str(&format!("-> {} ", return_type.lexeme));
+                }
+                body.write_source(source, indent);
+            }
+            Statement::Return { value, .. } => match value {
+                Some(value) => source.push_str(&format!("return {};", value.to_source())),
+                None => source.push_str("return;"),
+            },
+            Statement::Break { .. } => source.push_str("break;"),
+            Statement::Continue { .. } => source.push_str("continue;"),
+            Statement::Assert {
+                condition, message, ..
+            } => match message {
+                Some(message) => source.push_str(&format!(
+                    "assert({}, {});",
+                    condition.to_source(),
+                    message.to_source()
+                )),
+                None => source.push_str(&format!("assert({});", condition.to_source())),
+            },
+            Statement::Include { path, alias, .. } => match alias {
+                Some(alias) => {
+                    source.push_str(&format!("include {} as {};", path.lexeme, alias.lexeme));
+                }
+                None => source.push_str(&format!("include {};", path.lexeme)),
+            },
+            Statement::Module {
+                name, statements, ..
+            } => {
+                source.push_str(&format!("module {} ", name.lexeme));
+                write_block(statements, source, indent);
+            }
+            Statement::Comment { token, .. } => source.push_str(&token.lexeme),
+        }
+    }
+}
+
+/// Writes statements between braces, one per line.
+///
+/// # Arguments
+/// * `statements` - The statements of the block.
+/// * `source` - The string to write to.
+/// * `indent` - The indentation level of the braces.
+fn write_block(statements: &[Statement], source: &mut String, indent: usize) {
+    if statements.is_empty() {
+        source.push_str("{}");
+        return;
+    }
+
+    source.push_str("{\n");
+    for (i, statement) in statements.iter().enumerate() {
+        if i > 0 && statement.is_trailing_comment() {
+            // Keep the comment on the line of the statement it follows.
+            source.pop();
+            source.push(' ');
+        } else {
+            source.push_str(&INDENT.repeat(indent + 1));
+        }
+
+        statement.write_source(source, indent + 1);
+        source.push('\n');
+    }
+    source.push_str(&INDENT.repeat(indent));
+    source.push('}');
+}
+
+/// Renders a literal as CPL source code.
+///
+/// # Arguments
+/// * `value` - The literal to render.
+fn literal_to_source(value: &Literal) -> String {
+    match value {
+        Literal::String(string) => format!("\"{}\"", escape(string, '"')),
+        Literal::Char(c) => format!("'{}'", escape(&c.to_string(), '\'')),
+        Literal::Int(int) => int.to_string(),
+        // Keep a decimal point, so the literal is read back as a float.
+        Literal::Float(float) if float.fract() == 0.0 => format!("{:.1}", float),
+        Literal::Float(float) => float.to_string(),
+        Literal::Boolean(boolean) => boolean.to_string(),
+        Literal::None => "none".to_string(),
+    }
+}
+
+/// Escapes the characters that cannot appear as they are in a string or character literal.
+///
+/// # Arguments
+/// * `string` - The contents of the literal.
+/// * `quote` - The quote the literal is delimited by.
+fn escape(string: &str, quote: char) -> String {
+    let mut escaped = String::with_capacity(string.len());
+
+    for c in string.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            '\0' => escaped.push_str("\\0"),
+            '\\' => escaped.push_str("\\\\"),
+            c if c == quote => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lang::parse;
+    use crate::lang::parser::Statement;
+
+    /// Renders the statements back to source, one per line.
+    fn render(statements: &[Statement]) -> String {
+        statements
+            .iter()
+            .map(Statement::to_source)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Renders the statements as S-expressions, which capture the tree's structure.
+    fn structure(statements: &[Statement]) -> Vec<String> {
+        statements.iter().map(Statement::to_string).collect()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let programs = [
+            "let a = 1 + 2 * (3 - 4) / 5.0 + 0.5 - 100000000000000000000.0;",
+            "print !true == false;",
+            "let a; a = b = -(-1); print a ?? b ?? none;",
+            "fn add(a: i32, b: i32) -> i32 { return a + b; } print add(1, add(2, 3));",
+            "if (a < 1) { print \"small\"; } else if (a < 10) print \"medium\"; else { print \"large\"; }",
+            "while (a >= 0) { a = a - 1; if (a == 5) { break; } else { continue; } }",
+            "{ let a = 1; { let b = 2; } {} }",
+            "loop { if (a) { break; } continue; }",
+            "assert(a == 1); assert(!b, \"b\" + \"!\");",
+            "include \"utils.cpl\"; include \"../lib/a.cpl\" as a;",
+            "module math { const pi = 3.14; fn half(x: float) -> float { return x / 2; } } print math.half(math.pi);",
+            "for (let i = 0; i < 10; i = i + 1) { continue; } for (;;) break; for (a = 1; ; ) {}",
+            "fn nothing() { return; }",
+            "let a: i32 = 5; let b: str; const c = a;",
+            "let (a, b) = (1, (2,)); const (c, d) = ((a), b);",
+            r#"print "tab\t \"quoted\" \\ \u{1F600}\n";"#,
+            r#"print 'a' == '\'' ?? '"' ?? '\n';"#,
+            "print typeof -a == typeof typeof (1 + 2);",
+        ];
+
+        for program in programs {
+            let original = parse(program).expect("Parsing should succeed.");
+            let source = render(&original);
+            let reparsed = parse(&source).expect("Rendered source should parse.");
+
+            assert_eq!(structure(&original), structure(&reparsed), "{}", source);
+            assert_eq!(source, render(&reparsed));
+        }
+    }
+
+    #[test]
+    fn test_to_source_layout() {
+        let statements = parse("fn main() { if (a) { print 1; } else print -a; }")
+            .expect("Parsing should succeed.");
+
+        assert_eq!(
+            statements[0].to_source(),
+            "fn main() {\n    if (a) {\n        print 1;\n    } else print -a;\n}"
+        );
+    }
+}
@@ -0,0 +1,408 @@
+use std::collections::HashMap;
+
+use crate::lang::interner::Symbol;
+use crate::lang::lexer::Token;
+use crate::lang::natives;
+use crate::lang::parser::{Expression, Statement};
+use crate::lang::semantic_analyzer::Error;
+use crate::lang::visitor::{walk_expression, Visitor};
+
+/// Where a variable is declared, as seen from a reference to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Slot {
+    /// The number of scopes between the reference and the declaration, 0 being the reference's own scope.
+    pub depth: usize,
+    /// The position of the declaration among the variables of its scope.
+    pub index: usize,
+}
+
+/// The slots the variable references of a program resolve to.
+#[derive(Debug, Default)]
+pub struct Resolution {
+    /// The slots, keyed by the byte offset of the referring name.
+    slots: HashMap<usize, Slot>,
+}
+
+impl Resolution {
+    /// Gets the slot a variable reference resolves to.
+    ///
+    /// # Arguments
+    /// * `name` - The name of a variable, assignment or call target in the resolved program.
+    ///
+    /// # Returns
+    /// The slot, or `None` if the token is not a resolved reference, such as the name of a declaration.
+    pub fn get(&self, name: &Token) -> Option<Slot> {
+        self.slots.get(&name.start).copied()
+    }
+}
+
+/// A variable declared in a scope.
+#[derive(Debug, Clone, Copy)]
+struct Variable {
+    index: usize,
+    is_initialized: bool,
+}
+
+/// Resolves every variable reference to the scope and slot of its declaration,
+/// with the same scopes as the interpreter's environments.
+/// The native functions live in a scope of their own, enclosing the global scope.
+#[derive(Debug)]
+pub struct Resolver {
+    scopes: Vec<HashMap<Symbol, Variable>>,
+    slots: HashMap<usize, Slot>,
+    errors: Vec<Error>,
+}
+
+impl Resolver {
+    /// Resolves the variable references of a program.
+    ///
+    /// # Arguments
+    /// * `statements` - The program to resolve.
+    ///
+    /// # Returns
+    /// The slot of every reference, or the undefined and uninitialized variables found, in source order.
+    ///
+    /// # Examples
+    /// ```
+    /// use cpl::lang::parse;
+    /// use cpl::lang::resolver::{Resolver, Slot};
+    /// use cpl::lang::parser::{Expression, Statement};
+    ///
+    /// let statements = parse("let a = 1; { print a; }").unwrap();
+    /// let resolution = Resolver::resolve(&statements).unwrap();
+    ///
+    /// let Statement::Block(block, _) = &statements[1] else { unreachable!() };
+    /// let Statement::Print(Expression::Variable(name, _), _) = &block[0] else { unreachable!() };
+    /// assert_eq!(resolution.get(name), Some(Slot { depth: 1, index: 0 }));
+    /// ```
+    pub fn resolve(statements: &[Statement]) -> Result<Resolution, Vec<Error>> {
+        let natives = natives::standard_library()
+            .iter()
+            .enumerate()
+            .map(|(index, function)| {
+                let variable = Variable {
+                    index,
+                    is_initialized: true,
+                };
+
+                (Symbol::intern(&function.name), variable)
+            })
+            .collect();
+
+        let mut resolver = Self {
+            scopes: vec![natives, HashMap::new()],
+            slots: HashMap::new(),
+            errors: Vec::new(),
+        };
+        resolver.resolve_statements(statements);
+
+        if resolver.errors.is_empty() {
+            Ok(Resolution {
+                slots: resolver.slots,
+            })
+        } else {
+            resolver.errors.sort_by_key(Error::position);
+
+            Err(resolver.errors)
+        }
+    }
+
+    /// Resolves the statements of a scope.
+    /// The functions declared among them are declared first, so they can refer to each other in any order.
+    fn resolve_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            if let Statement::Function { name, .. } = statement {
+                self.declare(name, true);
+            }
+        }
+
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+    }
+
+    /// Resolves statements in a new scope.
+    fn resolve_scope(&mut self, statements: &[Statement]) {
+        self.scopes.push(HashMap::new());
+        self.resolve_statements(statements);
+        self.scopes.pop();
+    }
+
+    /// Declares a variable in the innermost scope, giving it the next slot.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the variable.
+    /// * `is_initialized` - Whether the variable has been given a value.
+    fn declare(&mut self, name: &Token, is_initialized: bool) {
+        let scope = self.scopes.last_mut().expect("There is always a scope.");
+
+        if scope.contains_key(&name.name()) {
+            self.errors.push(Error::VariableRedeclaration(
+                name.lexeme.to_string(),
+                name.line,
+                name.column,
+            ));
+            return;
+        }
+
+        let variable = Variable {
+            index: scope.len(),
+            is_initialized,
+        };
+        scope.insert(name.name(), variable);
+    }
+
+    /// Finds the innermost declaration of a name, recording the slot it resolves to.
+    ///
+    /// # Returns
+    /// The declaration, or `None` if the name is not declared.
+    fn lookup(&mut self, name: &Token) -> Option<&mut Variable> {
+        let (depth, variable) = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .enumerate()
+            .find_map(|(depth, scope)| Some((depth, scope.get_mut(&name.name())?)))?;
+
+        self.slots.insert(
+            name.start,
+            Slot {
+                depth,
+                index: variable.index,
+            },
+        );
+
+        Some(variable)
+    }
+}
+
+impl Visitor for Resolver {
+    fn visit_variable_declaration(
+        &mut self,
+        name: &Token,
+        _declared_type: Option<&Token>,
+        initializer: Option<&Expression>,
+        _mutable: bool,
+    ) {
+        // The initializer is resolved first, so 'let a = a;' cannot refer to itself.
+        if let Some(initializer) = initializer {
+            self.visit_expression(initializer);
+        }
+
+        self.declare(name, initializer.is_some());
+    }
+
+    fn visit_destructure(&mut self, names: &[Token], initializer: &Expression, _mutable: bool) {
+        self.visit_expression(initializer);
+
+        for name in names {
+            self.declare(name, true);
+        }
+    }
+
+    fn visit_block(&mut self, statements: &[Statement]) {
+        self.resolve_scope(statements);
+    }
+
+    fn visit_for(
+        &mut self,
+        initializer: Option<&Statement>,
+        condition: Option<&Expression>,
+        increment: Option<&Expression>,
+        body: &Statement,
+    ) {
+        // The initializer's variable is only visible within the loop.
+        self.scopes.push(HashMap::new());
+
+        if let Some(initializer) = initializer {
+            self.visit_statement(initializer);
+        }
+
+        if let Some(condition) = condition {
+            self.visit_expression(condition);
+        }
+
+        if let Some(increment) = increment {
+            self.visit_expression(increment);
+        }
+
+        self.visit_statement(body);
+        self.scopes.pop();
+    }
+
+    fn visit_function(
+        &mut self,
+        _name: &Token,
+        parameters: &[(Token, Token)],
+        _return_type: Option<&Token>,
+        body: &Statement,
+    ) {
+        // The function's name has already been declared by `resolve_statements`,
+        // and the parameters share the scope of the body.
+        self.scopes.push(HashMap::new());
+
+        for (parameter, _) in parameters {
+            self.declare(parameter, true);
+        }
+
+        match body {
+            Statement::Block(statements, _) => self.resolve_statements(statements),
+            body => self.resolve_statements(std::slice::from_ref(body)),
+        }
+
+        self.scopes.pop();
+    }
+
+    fn visit_module(&mut self, name: &Token, statements: &[Statement]) {
+        // The module's name is declared after its statements have run, like the interpreter does.
+        self.resolve_scope(statements);
+        self.declare(name, true);
+    }
+
+    fn visit_qualified(&mut self, module: &Token, _name: &Token) {
+        // Members are looked up in the module's variables at runtime.
+        self.visit_variable(module);
+    }
+
+    fn visit_variable(&mut self, name: &Token) {
+        let error = match self.lookup(name) {
+            Some(variable) if variable.is_initialized => return,
+            Some(_) => {
+                Error::VariableNotInitialized(name.lexeme.to_string(), name.line, name.column)
+            }
+            None => Error::VariableNotFound(name.lexeme.to_string(), None, name.line, name.column),
+        };
+
+        self.errors.push(error);
+    }
+
+    fn visit_assign(&mut self, name: &Token, value: &Expression) {
+        self.visit_expression(value);
+
+        match self.lookup(name) {
+            // Assigning is how a variable declared without a value becomes initialized.
+            Some(variable) => variable.is_initialized = true,
+            None => self.errors.push(Error::VariableNotFound(
+                name.lexeme.to_string(),
+                None,
+                name.line,
+                name.column,
+            )),
+        }
+    }
+
+    fn visit_call(&mut self, callee: &Expression, _parenthesis: &Token, arguments: &[Expression]) {
+        match callee {
+            Expression::Variable(name, _) if self.lookup(name).is_none() => {
+                self.errors.push(Error::FunctionNotFound(
+                    name.lexeme.to_string(),
+                    None,
+                    name.line,
+                    name.column,
+                ));
+            }
+            callee => walk_expression(self, callee),
+        }
+
+        for argument in arguments {
+            self.visit_expression(argument);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::lexer::TokenType;
+    use crate::lang::{lex, parse};
+
+    /// Resolves the source code, returning the slot of every identifier in order.
+    /// Declarations have no slot.
+    fn slots(source: &str) -> Vec<(String, Option<Slot>)> {
+        let statements = parse(source).expect("Parsing should succeed.");
+        let resolution = Resolver::resolve(&statements).expect("Resolving should succeed.");
+
+        lex(source)
+            .expect("Scanning should succeed.")
+            .iter()
+            .filter(|token| token.token_type == TokenType::Identifier)
+            .map(|token| (token.lexeme.to_string(), resolution.get(token)))
+            .collect()
+    }
+
+    fn slot(depth: usize, index: usize) -> Option<Slot> {
+        Some(Slot { depth, index })
+    }
+
+    #[test]
+    fn test_resolve_shadowing() {
+        assert_eq!(
+            slots("let a = 1; let b = 2; { let c = 3; let a = 4; print a + b + c; } print a;"),
+            [
+                ("a".to_string(), None),
+                ("b".to_string(), None),
+                ("c".to_string(), None),
+                ("a".to_string(), None),
+                ("a".to_string(), slot(0, 1)),
+                ("b".to_string(), slot(1, 1)),
+                ("c".to_string(), slot(0, 0)),
+                ("a".to_string(), slot(0, 0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_closures() {
+        // The parameters come first in a function's scope, then its nested functions, then its locals.
+        assert_eq!(
+            slots("fn outer(n: i32) { let x = n; fn inner() { print x + n; } inner(); } outer(1);"),
+            [
+                ("outer".to_string(), None),
+                ("n".to_string(), None),
+                ("i32".to_string(), None),
+                ("x".to_string(), None),
+                ("n".to_string(), slot(0, 0)),
+                ("inner".to_string(), None),
+                ("x".to_string(), slot(1, 2)),
+                ("n".to_string(), slot(1, 0)),
+                ("inner".to_string(), slot(0, 1)),
+                ("outer".to_string(), slot(0, 0)),
+            ]
+        );
+
+        // Native functions are in the outermost scope.
+        let names = slots("fn f() { print clock(); }");
+        assert_eq!(names[1], ("clock".to_string(), slot(2, 0)));
+    }
+
+    #[test]
+    fn test_resolve_modules() {
+        // Members are looked up at runtime, only the module itself is resolved.
+        assert_eq!(
+            slots("module m { let a = 1; print a; } print m.a;"),
+            [
+                ("m".to_string(), None),
+                ("a".to_string(), None),
+                ("a".to_string(), slot(0, 0)),
+                ("m".to_string(), slot(0, 0)),
+                ("a".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve_errors() {
+        let statements =
+            parse("print a;\nlet b;\nfn f() { print b; missing(); }\nb = 1;\nprint b;")
+                .expect("Parsing should succeed.");
+
+        assert_eq!(
+            Resolver::resolve(&statements).unwrap_err(),
+            [
+                Error::VariableNotFound("a".to_string(), None, 1, 7),
+                Error::VariableNotInitialized("b".to_string(), 3, 16),
+                Error::FunctionNotFound("missing".to_string(), None, 3, 19),
+            ]
+        );
+    }
+}
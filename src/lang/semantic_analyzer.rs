@@ -0,0 +1,3482 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+use crate::lang::errors;
+use crate::lang::interner::Symbol;
+use crate::lang::lexer::{Literal, Token, TokenType};
+use crate::lang::natives;
+use crate::lang::optimizer;
+use crate::lang::parser::{Expression, Statement};
+use crate::lang::symbols::{SymbolEntry, SymbolKind, SymbolTable};
+use crate::lang::visitor::{walk_expression, Visitor};
+use crate::lang::MAX_ERRORS;
+
+/// An error found during semantic analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A variable was used without being declared,
+    /// with its name, a similar name in scope if there is one, line and column.
+    VariableNotFound(String, Option<String>, usize, usize),
+    /// A variable was declared twice in the same scope, with its name, line and column.
+    VariableRedeclaration(String, usize, usize),
+    /// A variable was read before it was given a value, with its name, line and column.
+    VariableNotInitialized(String, usize, usize),
+    /// A 'break' or 'continue' statement outside of a loop, with its keyword, line and column.
+    OutsideLoop(String, usize, usize),
+    /// A function was called with the wrong number of arguments,
+    /// with its name, the expected and given counts, line and column.
+    ArgumentCount(String, usize, usize, usize, usize),
+    /// An argument has a different type than its parameter is annotated with, with the function's name,
+    /// the argument's position starting from 1, its type, the parameter's type, and the argument's line and column.
+    ArgumentMismatch(String, usize, Type, Type, usize, usize),
+    /// A function that is not declared was called,
+    /// with its name, a similar name in scope if there is one, line and column.
+    FunctionNotFound(String, Option<String>, usize, usize),
+    /// A 'return' statement outside of a function, with its line and column.
+    ReturnOutsideFunction(usize, usize),
+    /// A binary operator was applied to operands it does not support,
+    /// with the operator, the types of both operands, line and column.
+    TypeMismatch(String, Type, Type, usize, usize),
+    /// A unary operator was applied to an operand it does not support,
+    /// with the operator, the type of the operand, line and column.
+    InvalidOperand(String, Type, usize, usize),
+    /// The condition of a statement is not a boolean,
+    /// with the statement's keyword, the condition's type, line and column.
+    ConditionType(String, Type, usize, usize),
+    /// A type annotation names a type that does not exist, with the name, line and column.
+    UnknownType(String, usize, usize),
+    /// A value of the wrong type was given to an annotated variable,
+    /// with the variable's name, its type, the value's type, and the line and column of the value.
+    AssignmentMismatch(String, Type, Type, usize, usize),
+    /// A constant was assigned to, with its name and the line and column of the assignment.
+    AssignmentToConst(String, usize, usize),
+    /// A tuple was destructured into a different number of variables than it has values,
+    /// with the number of variables, the number of values, and the line and column of the tuple.
+    DestructureCount(usize, usize, usize, usize),
+    /// A value that is not a tuple was destructured, with its type and line and column.
+    NotATuple(Type, usize, usize),
+    /// A value of the wrong type was returned, with the declared and returned types,
+    /// and the line and column of the 'return' keyword.
+    ReturnTypeMismatch(Type, Type, usize, usize),
+    /// A function with a return type returned without a value,
+    /// with the declared type and the line and column of the 'return' keyword.
+    MissingReturnValue(Type, usize, usize),
+    /// A function without a return type returned a value,
+    /// with the returned type and the line and column of the 'return' keyword.
+    UnexpectedReturnValue(Type, usize, usize),
+    /// A function with a return type can finish without returning,
+    /// with its name, its declared type, and the line and column of its name.
+    MissingReturn(String, Type, usize, usize),
+    /// An integer is divided by a constant zero, with the line and column of the operator.
+    DivisionByZero(usize, usize),
+    /// A built-in function was declared again, with its name and the line and column of the declaration.
+    BuiltinRedefinition(String, usize, usize),
+    /// A module has no member with the name, with the member's name, the module's name, line and column.
+    MemberNotFound(String, String, usize, usize),
+    /// A member was accessed on a variable that is not a module, with the variable's name, line and column.
+    NotAModule(String, usize, usize),
+    /// A member of a module was used without the module's name,
+    /// with the member's name, the module's name, line and column.
+    UnqualifiedMember(String, String, usize, usize),
+    /// The program has no entry point, with its name. Reported at the start of the program.
+    EntryPointNotFound(String),
+    /// The entry point takes parameters, with its name, the number of parameters, and the line and column of its name.
+    EntryPointParameters(String, usize, usize, usize),
+    /// The entry point is declared more than once,
+    /// with its name and the line and column of every declaration after the first.
+    EntryPointRedeclaration(String, usize, usize),
+}
+
+impl Error {
+    /// Gets the line and column the error occurred at.
+    pub fn position(&self) -> (usize, usize) {
+        match self {
+            Error::VariableNotFound(_, _, line, column)
+            | Error::VariableRedeclaration(_, line, column)
+            | Error::VariableNotInitialized(_, line, column)
+            | Error::OutsideLoop(_, line, column)
+            | Error::ArgumentCount(_, _, _, line, column)
+            | Error::ArgumentMismatch(_, _, _, _, line, column)
+            | Error::FunctionNotFound(_, _, line, column)
+            | Error::ReturnOutsideFunction(line, column)
+            | Error::TypeMismatch(_, _, _, line, column)
+            | Error::InvalidOperand(_, _, line, column)
+            | Error::ConditionType(_, _, line, column)
+            | Error::UnknownType(_, line, column)
+            | Error::AssignmentMismatch(_, _, _, line, column)
+            | Error::AssignmentToConst(_, line, column)
+            | Error::DestructureCount(_, _, line, column)
+            | Error::NotATuple(_, line, column)
+            | Error::ReturnTypeMismatch(_, _, line, column)
+            | Error::MissingReturnValue(_, line, column)
+            | Error::UnexpectedReturnValue(_, line, column)
+            | Error::MissingReturn(_, _, line, column)
+            | Error::DivisionByZero(line, column)
+            | Error::BuiltinRedefinition(_, line, column)
+            | Error::MemberNotFound(_, _, line, column)
+            | Error::NotAModule(_, line, column)
+            | Error::UnqualifiedMember(_, _, line, column)
+            | Error::EntryPointParameters(_, _, line, column)
+            | Error::EntryPointRedeclaration(_, line, column) => (*line, *column),
+            Error::EntryPointNotFound(_) => (1, 1),
+        }
+    }
+}
+
+/// Writes the similar name suggested for an undefined one, if there is one.
+fn write_suggestion(f: &mut Formatter<'_>, suggestion: &Option<String>) -> std::fmt::Result {
+    match suggestion {
+        Some(suggestion) => write!(f, ", did you mean '{}'?", suggestion),
+        None => Ok(()),
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::VariableNotFound(name, suggestion, ..) => {
+                write!(f, "Variable '{}' is not defined", name)?;
+                write_suggestion(f, suggestion)
+            }
+            Error::VariableRedeclaration(name, ..) => {
+                write!(f, "Variable '{}' is already defined in this scope", name)
+            }
+            Error::VariableNotInitialized(name, ..) => {
+                write!(f, "Variable '{}' is used before being initialized", name)
+            }
+            Error::OutsideLoop(keyword, ..) => {
+                write!(f, "Cannot use '{}' outside of a loop", keyword)
+            }
+            Error::FunctionNotFound(name, suggestion, ..) => {
+                write!(f, "Function '{}' is not defined", name)?;
+                write_suggestion(f, suggestion)
+            }
+            Error::ReturnOutsideFunction(..) => {
+                write!(f, "Cannot use 'return' outside of a function")
+            }
+            Error::ArgumentCount(name, expected, found, ..) => write!(
+                f,
+                "Function '{}' takes {} argument{} but {} {} given",
+                name,
+                expected,
+                if *expected == 1 { "" } else { "s" },
+                found,
+                if *found == 1 { "was" } else { "were" }
+            ),
+            Error::ArgumentMismatch(name, position, found, expected, ..) => write!(
+                f,
+                "Argument {} of function '{}' must be {}, found {}",
+                position, name, expected, found
+            ),
+            Error::TypeMismatch(operator, left, right, ..) => {
+                write!(
+                    f,
+                    "Operator '{}' cannot be applied to {} and {}",
+                    operator, left, right
+                )?;
+
+                match (operator.as_str(), left, right) {
+                    ("+", Type::String, other) | ("+", other, Type::String) => {
+                        write!(f, ", convert the {} with 'str' first", other)
+                    }
+                    ("==" | "!=", ..) => write!(f, ", only values of the same type can be compared"),
+                    (">" | ">=" | "<" | "<=", ..) => write!(
+                        f,
+                        ", only two numbers or two strings (compared lexicographically) can be ordered"
+                    ),
+                    _ => Ok(()),
+                }
+            }
+            Error::InvalidOperand(operator, right, ..) => {
+                write!(f, "Operator '{}' cannot be applied to {}", operator, right)
+            }
+            Error::ConditionType(keyword, found, ..) => write!(
+                f,
+                "The condition of '{}' must be boolean, found {}",
+                keyword, found
+            ),
+            Error::UnknownType(name, ..) => write!(f, "Unknown type '{}'", name),
+            Error::AssignmentMismatch(name, expected, found, ..) => write!(
+                f,
+                "Cannot assign {} to variable '{}' of type {}",
+                found, name, expected
+            ),
+            Error::AssignmentToConst(name, ..) => {
+                write!(f, "Cannot assign to constant '{}'", name)
+            }
+            Error::DestructureCount(variables, values, ..) => write!(
+                f,
+                "Cannot destructure a tuple of {} values into {} variables",
+                values, variables
+            ),
+            Error::NotATuple(found, ..) => {
+                write!(
+                    f,
+                    "Cannot destructure {}, only tuples can be destructured",
+                    found
+                )
+            }
+            Error::ReturnTypeMismatch(declared, found, ..) => write!(
+                f,
+                "Cannot return {} from a function that returns {}",
+                found, declared
+            ),
+            Error::MissingReturnValue(declared, ..) => {
+                write!(f, "Expected a return value of type {}", declared)
+            }
+            Error::UnexpectedReturnValue(found, ..) => write!(
+                f,
+                "Cannot return {} from a function without a return type",
+                found
+            ),
+            Error::MissingReturn(name, declared, ..) => write!(
+                f,
+                "Function '{}' does not return {} on every path",
+                name, declared
+            ),
+            Error::DivisionByZero(..) => write!(f, "Cannot divide an integer by zero"),
+            Error::BuiltinRedefinition(name, ..) => {
+                write!(f, "Cannot redefine the built-in function '{}'", name)
+            }
+            Error::MemberNotFound(name, module, ..) => {
+                write!(f, "Module '{}' has no member '{}'", module, name)
+            }
+            Error::NotAModule(name, ..) => write!(f, "'{}' is not a module", name),
+            Error::UnqualifiedMember(name, module, ..) => write!(
+                f,
+                "'{}' is a member of module '{}', use '{}.{}'",
+                name, module, module, name
+            ),
+            Error::EntryPointNotFound(name) => write!(f, "No '{}' function found", name),
+            Error::EntryPointParameters(name, found, ..) => write!(
+                f,
+                "Function '{}' must take 0 parameters, found {}",
+                name, found
+            ),
+            Error::EntryPointRedeclaration(name, ..) => {
+                write!(f, "Function '{}' is declared more than once", name)
+            }
+        }
+    }
+}
+
+impl From<Error> for errors::Error {
+    fn from(error: Error) -> Self {
+        let (line, column) = error.position();
+
+        Self {
+            line,
+            column,
+            message: error.to_string(),
+            severity: errors::Severity::Error,
+        }
+    }
+}
+
+/// The type of a value, as far as the analyzer can tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Int,
+    Float,
+    String,
+    Char,
+    Boolean,
+    None,
+    /// A tuple with the number of values it holds. The types of the values are not tracked.
+    Tuple(usize),
+    /// The type is only known at runtime, such as the result of a call.
+    Unknown,
+}
+
+impl Type {
+    /// Gets the type a type annotation names.
+    ///
+    /// # Returns
+    /// The type, or an error if no type has the annotation's name.
+    pub(crate) fn from_annotation(annotation: &Token) -> Result<Self, Error> {
+        match annotation.lexeme.as_ref() {
+            "i8" | "i16" | "i32" | "i64" | "i128" | "u8" | "u16" | "u32" | "u64" | "u128"
+            | "int" => Ok(Type::Int),
+            "f32" | "f64" | "float" => Ok(Type::Float),
+            "str" | "string" => Ok(Type::String),
+            "char" => Ok(Type::Char),
+            "bool" => Ok(Type::Boolean),
+            // The analyzer does not track which values are functions.
+            "function" => Ok(Type::Unknown),
+            _ => Err(Error::UnknownType(
+                annotation.lexeme.to_string(),
+                annotation.line,
+                annotation.column,
+            )),
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Int => write!(f, "int"),
+            Type::Float => write!(f, "float"),
+            Type::String => write!(f, "string"),
+            Type::Char => write!(f, "char"),
+            Type::Boolean => write!(f, "boolean"),
+            Type::None => write!(f, "none"),
+            Type::Tuple(length) => write!(f, "tuple of {}", length),
+            Type::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// A problem found during semantic analysis that does not stop the program from running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// A variable is never read, with its name and the line and column of its declaration.
+    UnusedVariable(String, usize, usize),
+    /// A function is never called, with its name and the line and column of its declaration.
+    UnusedFunction(String, usize, usize),
+    /// A variable hides a variable of an enclosing scope, with its name,
+    /// the line and column of its declaration, and the line and column of the hidden variable's declaration.
+    ShadowedVariable(String, usize, usize, usize, usize),
+    /// A float is divided by a constant zero, which results in infinity or NaN,
+    /// with the line and column of the operator.
+    DivisionByZero(usize, usize),
+    /// A statement follows an unconditional `return`, `break` or `continue`, so it can never run,
+    /// with the line and column of the statement.
+    UnreachableCode(usize, usize),
+    /// The analyzer's options disable a kind of warning that does not exist, with its name.
+    /// Reported at the start of the program.
+    UnknownWarning(String),
+}
+
+impl Warning {
+    /// The names of every kind of warning, as returned by `name`.
+    pub const NAMES: [&'static str; 6] = [
+        "unused-variable",
+        "unused-function",
+        "shadowing",
+        "division-by-zero",
+        "unreachable-code",
+        "unknown-warning",
+    ];
+
+    /// Gets the stable name of the warning's kind, used to disable it.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Warning::UnusedVariable(..) => "unused-variable",
+            Warning::UnusedFunction(..) => "unused-function",
+            Warning::ShadowedVariable(..) => "shadowing",
+            Warning::DivisionByZero(..) => "division-by-zero",
+            Warning::UnreachableCode(..) => "unreachable-code",
+            Warning::UnknownWarning(_) => "unknown-warning",
+        }
+    }
+
+    /// Gets the line and column the warning refers to.
+    pub fn position(&self) -> (usize, usize) {
+        match self {
+            Warning::UnusedVariable(_, line, column)
+            | Warning::UnusedFunction(_, line, column)
+            | Warning::ShadowedVariable(_, line, column, ..)
+            | Warning::DivisionByZero(line, column)
+            | Warning::UnreachableCode(line, column) => (*line, *column),
+            Warning::UnknownWarning(_) => (1, 1),
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::UnusedVariable(name, ..) => write!(f, "Variable '{}' is never used", name),
+            Warning::UnusedFunction(name, ..) => write!(f, "Function '{}' is never called", name),
+            Warning::ShadowedVariable(name, _, _, line, column) => write!(
+                f,
+                "Variable '{}' shadows the variable declared at line {}, column {}",
+                name, line, column
+            ),
+            Warning::DivisionByZero(..) => {
+                write!(f, "Dividing by zero results in infinity or NaN")
+            }
+            Warning::UnreachableCode(..) => write!(f, "Unreachable code"),
+            Warning::UnknownWarning(name) => write!(
+                f,
+                "Unknown warning '{}', expected one of: {}",
+                name,
+                Warning::NAMES.join(", ")
+            ),
+        }
+    }
+}
+
+impl From<Warning> for errors::Error {
+    fn from(warning: Warning) -> Self {
+        let (line, column) = warning.position();
+
+        Self {
+            line,
+            column,
+            message: warning.to_string(),
+            severity: errors::Severity::Warning,
+        }
+    }
+}
+
+/// Which diagnostics the analyzer reports, and how.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnalyzerOptions {
+    /// The names of the kinds of warnings not to report, see `Warning::name`.
+    pub disabled_warnings: HashSet<String>,
+    /// Whether to report warnings as errors, so they stop the program from running.
+    pub warnings_as_errors: bool,
+}
+
+/// A variable known to the analyzer.
+#[derive(Debug)]
+struct VariableEntry {
+    name: Symbol,
+    is_initialized: bool,
+    /// The type of the variable's current value.
+    r#type: Type,
+    /// The type the variable is annotated with, which every value given to it must have.
+    declared: Option<Type>,
+    /// The types of the parameters, if the variable is a function.
+    /// Parameters of native functions, and parameters annotated with 'function', have unknown types.
+    parameters: Option<Vec<Type>>,
+    /// The type of the values calling the variable results in, which is only known for native functions.
+    returns: Type,
+    /// The index of the function in the analyzer's usage table, if the variable is a declared function.
+    function: Option<usize>,
+    /// The variables declared in the module, if the variable is a module.
+    members: Option<HashMap<Symbol, VariableEntry>>,
+    /// Whether the variable can be assigned to, which constants cannot.
+    mutable: bool,
+    /// The scope and index of the variable in the symbol table, if it is declared in the program.
+    symbol: Option<(usize, usize)>,
+    /// The line and column of the declaration.
+    line: usize,
+    column: usize,
+    /// Whether the variable has been read.
+    used: bool,
+    /// Whether to warn if the variable is never read.
+    warn_unused: bool,
+}
+
+impl VariableEntry {
+    /// Creates an entry for an initialized variable of an unknown type, which is never reported as unused.
+    fn new(name: &Token) -> Self {
+        Self {
+            name: name.name(),
+            is_initialized: true,
+            r#type: Type::Unknown,
+            declared: None,
+            parameters: None,
+            returns: Type::Unknown,
+            function: None,
+            members: None,
+            mutable: true,
+            symbol: None,
+            line: name.line,
+            column: name.column,
+            used: false,
+            warn_unused: false,
+        }
+    }
+
+    /// Checks if the variable is a native function, which has no declaration in the source code.
+    fn is_native(&self) -> bool {
+        self.parameters.is_some() && self.function.is_none()
+    }
+}
+
+/// The most characters a name in scope may differ by from an undefined one to be suggested instead.
+const MAX_SUGGESTION_DISTANCE: usize = 2;
+/// The most names in scope compared against an undefined one, so suggesting stays fast in large programs.
+const MAX_SUGGESTION_CANDIDATES: usize = 1000;
+
+/// The variables in scope, from the outermost scope to the innermost one.
+#[derive(Debug)]
+struct Environment {
+    scopes: Vec<HashMap<Symbol, VariableEntry>>,
+}
+
+impl Environment {
+    /// Creates a new environment containing only the global scope.
+    fn new() -> Self {
+        Self {
+            scopes: vec![HashMap::new()],
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    /// Closes the innermost scope.
+    ///
+    /// # Returns
+    /// The variables that were defined in the scope, in the order they were declared.
+    fn end_scope(&mut self) -> Vec<VariableEntry> {
+        let mut entries = self
+            .scopes
+            .pop()
+            .unwrap_or_default()
+            .into_values()
+            .collect::<Vec<_>>();
+        entries.sort_by_key(|entry| (entry.line, entry.column));
+
+        entries
+    }
+
+    /// Defines a variable in the innermost scope.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the variable.
+    /// * `is_initialized` - Whether the variable has been given a value.
+    /// * `type` - The type of the variable's value.
+    /// * `declared` - The type the variable is annotated with, if any.
+    /// * `mutable` - Whether the variable can be assigned to.
+    ///
+    /// # Returns
+    /// An error if the variable is already defined in the innermost scope.
+    fn define(
+        &mut self,
+        name: &Token,
+        is_initialized: bool,
+        r#type: Type,
+        declared: Option<Type>,
+        mutable: bool,
+    ) -> Result<(), Error> {
+        self.insert(VariableEntry {
+            is_initialized,
+            r#type,
+            declared,
+            mutable,
+            // Names starting with an underscore are deliberately unused.
+            warn_unused: !name.lexeme.starts_with('_'),
+            ..VariableEntry::new(name)
+        })
+    }
+
+    /// Defines a function parameter in the innermost scope.
+    /// Parameters are required by the function's signature, so they are never reported as unused.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the parameter.
+    /// * `type` - The type the parameter is annotated with.
+    ///
+    /// # Returns
+    /// An error if the name is already defined in the innermost scope.
+    fn define_parameter(&mut self, name: &Token, r#type: Type) -> Result<(), Error> {
+        self.insert(VariableEntry {
+            r#type,
+            declared: Some(r#type),
+            ..VariableEntry::new(name)
+        })
+    }
+
+    /// Defines a function in the innermost scope.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the function.
+    /// * `parameters` - The types of the function's parameters.
+    /// * `function` - The index of the function in the usage table, if it is declared in the program.
+    /// * `returns` - The type of the values the function returns.
+    ///
+    /// # Returns
+    /// An error if the name is already defined in the innermost scope.
+    fn define_function(
+        &mut self,
+        name: &Token,
+        parameters: Vec<Type>,
+        function: Option<usize>,
+        returns: Type,
+    ) -> Result<(), Error> {
+        self.insert(VariableEntry {
+            parameters: Some(parameters),
+            returns,
+            function,
+            ..VariableEntry::new(name)
+        })
+    }
+
+    /// Defines a module in the innermost scope.
+    /// Modules cannot be assigned to, and are never reported as unused.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the module.
+    /// * `members` - The variables declared in the module.
+    ///
+    /// # Returns
+    /// An error if the name is already defined in the innermost scope.
+    fn define_module(
+        &mut self,
+        name: &Token,
+        members: HashMap<Symbol, VariableEntry>,
+    ) -> Result<(), Error> {
+        self.insert(VariableEntry {
+            members: Some(members),
+            mutable: false,
+            ..VariableEntry::new(name)
+        })
+    }
+
+    fn insert(&mut self, entry: VariableEntry) -> Result<(), Error> {
+        let scope = self
+            .scopes
+            .last_mut()
+            .expect("The global scope is never closed.");
+
+        if let Some(existing) = scope.get(&entry.name) {
+            let error = if existing.is_native() {
+                Error::BuiltinRedefinition
+            } else {
+                Error::VariableRedeclaration
+            };
+
+            return Err(error(entry.name.to_string(), entry.line, entry.column));
+        }
+
+        scope.insert(entry.name, entry);
+
+        Ok(())
+    }
+
+    /// Looks up a variable in the scopes enclosing the innermost one, starting from the closest.
+    ///
+    /// # Returns
+    /// The variable and the index of its scope, where the global scope is 0.
+    fn get_enclosing(&self, name: Symbol) -> Option<(usize, &VariableEntry)> {
+        let enclosing = self.scopes.len().saturating_sub(1);
+
+        self.scopes[..enclosing]
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(index, scope)| scope.get(&name).map(|entry| (index, entry)))
+    }
+
+    /// Looks up a variable, starting from the innermost scope.
+    fn get(&self, name: Symbol) -> Option<&VariableEntry> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(&name))
+    }
+
+    /// Looks up the variable an expression names, either directly or as a member of a module.
+    ///
+    /// # Returns
+    /// The variable, or `None` if the expression does not name one that is in scope.
+    fn lookup(&self, expression: &Expression) -> Option<&VariableEntry> {
+        match expression {
+            Expression::Variable(name, _) => self.get(name.name()),
+            Expression::Qualified { module, name, .. } => {
+                self.get(module.name())?.members.as_ref()?.get(&name.name())
+            }
+            _ => None,
+        }
+    }
+
+    /// Finds the module in scope that has a member with the given name.
+    ///
+    /// # Returns
+    /// The name of the module, starting the search from the innermost scope.
+    fn find_member(&self, name: Symbol) -> Option<Symbol> {
+        self.scopes.iter().rev().find_map(|scope| {
+            scope
+                .values()
+                .find(|entry| {
+                    entry
+                        .members
+                        .as_ref()
+                        .is_some_and(|members| members.contains_key(&name))
+                })
+                .map(|entry| entry.name)
+        })
+    }
+
+    /// Finds a name in scope that is similar to one that is not, for suggesting it instead.
+    /// Names differing only in case are always similar, others may differ by up to two characters,
+    /// but by no more than half of the name, so 'cuont' suggests 'count' while 'i' does not suggest 'int'.
+    /// Only a limited number of names are compared, innermost scope first and then alphabetically.
+    ///
+    /// # Arguments
+    /// * `name` - The name that was not found.
+    ///
+    /// # Returns
+    /// The most similar name, or `None` if none are similar enough.
+    fn suggest(&self, name: &str) -> Option<Symbol> {
+        let length = name.chars().count();
+        let max_distance = (length / 2).min(MAX_SUGGESTION_DISTANCE);
+
+        // Names whose lengths differ too much cannot be similar, so they are skipped before comparing.
+        let mut candidates = self
+            .scopes
+            .iter()
+            .rev()
+            .enumerate()
+            .flat_map(|(depth, scope)| {
+                scope
+                    .keys()
+                    .map(move |&candidate| (depth, candidate.as_str(), candidate))
+            })
+            .filter(|(_, candidate_name, _)| {
+                candidate_name.chars().count().abs_diff(length) <= max_distance
+            })
+            .collect::<Vec<_>>();
+        // Scopes have no order of their own, so the same names are compared every time.
+        candidates.sort_unstable_by(|(a_depth, a_name, _), (b_depth, b_name, _)| {
+            a_depth.cmp(b_depth).then_with(|| a_name.cmp(b_name))
+        });
+
+        candidates
+            .into_iter()
+            .take(MAX_SUGGESTION_CANDIDATES)
+            .filter_map(|(_, candidate_name, candidate)| {
+                // A difference in case alone counts as a single character.
+                let distance = match edit_distance(name, &candidate_name) {
+                    0 => 1,
+                    distance if distance <= max_distance => distance,
+                    _ => return None,
+                };
+
+                Some((distance, candidate_name, candidate))
+            })
+            .min_by(|(a, a_name, _), (b, b_name, _)| a.cmp(b).then_with(|| a_name.cmp(b_name)))
+            .map(|(.., candidate)| candidate)
+    }
+
+    /// Looks up a variable for modification, starting from the innermost scope.
+    fn get_mut(&mut self, name: Symbol) -> Option<&mut VariableEntry> {
+        self.scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.get_mut(&name))
+    }
+}
+
+/// A function declared in the program, for finding the ones that are never called.
+#[derive(Debug)]
+struct FunctionUsage {
+    name: String,
+    /// The number of parameters the function takes.
+    arity: usize,
+    /// The line and column of the declaration.
+    line: usize,
+    column: usize,
+    /// The functions referred to from within this function's body.
+    references: Vec<usize>,
+    /// Whether the function has already been reported as unused.
+    warned: bool,
+}
+
+/// Checks that a syntax tree makes sense beyond its syntax, such as variables being declared before use.
+#[derive(Debug)]
+pub struct Analyzer {
+    environment: Environment,
+    /// The number of loops enclosing the current statement within the current function.
+    loop_depth: usize,
+    /// The number of functions enclosing the current statement.
+    function_depth: usize,
+    errors: Vec<Error>,
+    /// The most errors to record, the ones after them are only counted.
+    max_errors: usize,
+    /// The number of errors found after the maximum was reached.
+    omitted_errors: usize,
+    warnings: Vec<Warning>,
+    /// Every function declared in the program, so calls can be tracked across scopes.
+    functions: Vec<FunctionUsage>,
+    /// The function whose body is being analyzed, if any.
+    current_function: Option<usize>,
+    /// The type the function being analyzed is annotated to return, if any.
+    return_type: Option<Type>,
+    /// The functions referred to from outside of any function.
+    top_level_references: Vec<usize>,
+    /// The name of the function the program starts at, which is never reported as unused.
+    entry_point: String,
+    /// Whether to warn about variables that shadow variables of enclosing scopes.
+    warn_shadowing: bool,
+    /// Whether the program must declare its entry point exactly once, without parameters.
+    require_entry_point: bool,
+    /// Which diagnostics to report, and how.
+    options: AnalyzerOptions,
+    /// Every name declared in the program, by scope.
+    symbols: SymbolTable,
+    /// The index of the innermost scope in the symbol table.
+    scope: usize,
+}
+
+impl Analyzer {
+    /// Creates a new analyzer that knows about the native functions.
+    pub fn new() -> Self {
+        let mut environment = Environment::new();
+        for function in natives::standard_library() {
+            let name = Token::new(TokenType::Identifier, &function.name, None, 0, 0);
+
+            environment
+                .define_function(
+                    &name,
+                    vec![Type::Unknown; function.arity],
+                    None,
+                    function.return_type,
+                )
+                .expect("Native functions have unique names.");
+        }
+
+        Self {
+            environment,
+            loop_depth: 0,
+            function_depth: 0,
+            errors: Vec::new(),
+            max_errors: MAX_ERRORS,
+            omitted_errors: 0,
+            warnings: Vec::new(),
+            functions: Vec::new(),
+            current_function: None,
+            return_type: None,
+            top_level_references: Vec::new(),
+            entry_point: "main".to_string(),
+            warn_shadowing: false,
+            require_entry_point: false,
+            options: AnalyzerOptions::default(),
+            symbols: SymbolTable::new(),
+            scope: 0,
+        }
+    }
+
+    /// Sets the name of the function the program starts at, which defaults to `main`.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the entry point.
+    pub fn with_entry_point(mut self, name: &str) -> Self {
+        self.entry_point = name.to_string();
+
+        self
+    }
+
+    /// Sets whether to warn about variables that shadow variables of enclosing scopes, which is off by default.
+    /// Parameters shadowing global variables are never reported.
+    ///
+    /// # Arguments
+    /// * `warn_shadowing` - Whether to warn about shadowing.
+    pub fn with_warn_shadowing(mut self, warn_shadowing: bool) -> Self {
+        self.warn_shadowing = warn_shadowing;
+
+        self
+    }
+
+    /// Sets whether the program must declare its entry point exactly once, without parameters,
+    /// which is off by default so programs can also be run from the top down.
+    ///
+    /// # Arguments
+    /// * `require_entry_point` - Whether to require the entry point.
+    pub fn with_require_entry_point(mut self, require_entry_point: bool) -> Self {
+        self.require_entry_point = require_entry_point;
+
+        self
+    }
+
+    /// Sets which diagnostics to report, and how. Every warning is reported by default.
+    ///
+    /// # Arguments
+    /// * `options` - The options.
+    pub fn with_options(mut self, options: AnalyzerOptions) -> Self {
+        self.options = options;
+
+        self
+    }
+
+    /// Sets the most errors to record, which defaults to `MAX_ERRORS`.
+    /// At least one error is always recorded.
+    ///
+    /// # Arguments
+    /// * `max_errors` - The maximum number of errors.
+    pub fn with_max_errors(mut self, max_errors: usize) -> Self {
+        self.max_errors = max_errors.max(1);
+
+        self
+    }
+
+    /// Gets the errors found so far, up to the maximum number of errors.
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Gets the number of errors found after the maximum number of errors was reached.
+    pub fn omitted_errors(&self) -> usize {
+        self.omitted_errors
+    }
+
+    /// Gets the warnings found so far, without the disabled ones.
+    /// Variables in the global scope are not reported as unused, as that scope never ends.
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Gets the names declared in the program so far.
+    pub fn symbols(&self) -> &SymbolTable {
+        &self.symbols
+    }
+
+    /// Consumes the analyzer, keeping only the names declared in the program.
+    pub fn into_symbols(self) -> SymbolTable {
+        self.symbols
+    }
+
+    /// Analyzes the given program, then warns about the functions that can never be called.
+    /// A function only counts as called if it is reachable from the top level or the entry point,
+    /// so functions that only call each other are still reported.
+    ///
+    /// # Arguments
+    /// * `statements` - The statements to analyze.
+    ///
+    /// # Returns
+    /// The errors and warnings found so far, in source order.
+    /// Warnings are reported with the severity of errors if the options say so.
+    pub fn analyze(&mut self, statements: &[Statement]) -> Vec<errors::Error> {
+        let mut unknown = self
+            .options
+            .disabled_warnings
+            .iter()
+            .filter(|name| !Warning::NAMES.contains(&name.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        unknown.sort();
+        for name in unknown {
+            self.warn(Warning::UnknownWarning(name));
+        }
+
+        self.analyze_statements(statements);
+        self.warn_unused_functions();
+        if self.require_entry_point {
+            self.check_entry_point();
+        }
+
+        // Errors are found while walking the tree, which does not always visit it in source order.
+        self.errors.sort_by_key(Error::position);
+
+        let mut diagnostics = self
+            .errors
+            .iter()
+            .cloned()
+            .map(errors::Error::from)
+            .chain(self.warnings.iter().cloned().map(|warning| {
+                let mut diagnostic = errors::Error::from(warning);
+                if self.options.warnings_as_errors {
+                    diagnostic.severity = errors::Severity::Error;
+                }
+
+                diagnostic
+            }))
+            .collect::<Vec<_>>();
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+
+        diagnostics
+    }
+
+    /// Records an error, so analysis can carry on to find the next one.
+    /// Once the maximum number of errors is reached, errors are only counted.
+    fn error(&mut self, error: Error) {
+        if self.errors.len() < self.max_errors {
+            self.errors.push(error);
+        } else {
+            self.omitted_errors += 1;
+        }
+    }
+
+    /// Records a warning, unless its kind is disabled.
+    fn warn(&mut self, warning: Warning) {
+        if !self.options.disabled_warnings.contains(warning.name()) {
+            self.warnings.push(warning);
+        }
+    }
+
+    /// Records the error of a result, if it has one.
+    ///
+    /// # Returns
+    /// The value of the result, if it has one.
+    fn check<T>(&mut self, result: Result<T, Error>) -> Option<T> {
+        result.map_err(|error| self.error(error)).ok()
+    }
+
+    /// Analyzes the given statements.
+    /// The functions declared among them are defined first, so they can refer to each other in any order.
+    fn analyze_statements(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            if let Statement::Function {
+                name,
+                parameters,
+                return_type,
+                ..
+            } = statement
+            {
+                self.functions.push(FunctionUsage {
+                    name: name.lexeme.to_string(),
+                    arity: parameters.len(),
+                    line: name.line,
+                    column: name.column,
+                    references: Vec::new(),
+                    warned: false,
+                });
+                // Unknown parameter types are reported when the function itself is analyzed.
+                let parameters = parameters
+                    .iter()
+                    .map(|(_, annotation)| {
+                        Type::from_annotation(annotation).unwrap_or(Type::Unknown)
+                    })
+                    .collect();
+                let result = self.environment.define_function(
+                    name,
+                    parameters,
+                    Some(self.functions.len() - 1),
+                    Type::Unknown,
+                );
+
+                if self.check(result).is_some() {
+                    // Unknown return types are reported when the function itself is analyzed.
+                    let return_type = match return_type {
+                        Some(annotation) => {
+                            Type::from_annotation(annotation).unwrap_or(Type::Unknown)
+                        }
+                        None => Type::None,
+                    };
+                    self.declare_symbol(name, SymbolKind::Function, return_type);
+                }
+            }
+        }
+
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+
+        // Only the first unreachable statement is reported, the rest are unreachable for the same reason.
+        let unreachable = optimizer::unreachable(statements)
+            .find(|statement| !matches!(statement, Statement::Comment { .. }));
+        if let Some(statement) = unreachable {
+            let span = statement.span();
+            self.warn(Warning::UnreachableCode(span.line, span.column));
+        }
+    }
+
+    /// Checks that the entry point is declared exactly once, and takes no parameters.
+    fn check_entry_point(&mut self) {
+        let mut declarations = self
+            .functions
+            .iter()
+            .filter(|function| function.name == self.entry_point);
+
+        let Some(first) = declarations.next() else {
+            self.error(Error::EntryPointNotFound(self.entry_point.clone()));
+            return;
+        };
+
+        let mut errors = Vec::new();
+        if first.arity != 0 {
+            errors.push(Error::EntryPointParameters(
+                first.name.clone(),
+                first.arity,
+                first.line,
+                first.column,
+            ));
+        }
+        errors.extend(declarations.map(|function| {
+            Error::EntryPointRedeclaration(function.name.clone(), function.line, function.column)
+        }));
+
+        for error in errors {
+            self.error(error);
+        }
+    }
+
+    /// Warns about the functions that are not reachable from the top level or the entry point.
+    fn warn_unused_functions(&mut self) {
+        let mut reachable = vec![false; self.functions.len()];
+        let mut pending: Vec<usize> = self
+            .functions
+            .iter()
+            .enumerate()
+            .filter(|(_, function)| function.name == self.entry_point)
+            .map(|(index, _)| index)
+            .chain(self.top_level_references.iter().copied())
+            .collect();
+
+        while let Some(index) = pending.pop() {
+            if !std::mem::replace(&mut reachable[index], true) {
+                pending.extend(&self.functions[index].references);
+            }
+        }
+
+        let mut unused = Vec::new();
+        for (function, reachable) in self.functions.iter_mut().zip(reachable) {
+            if !reachable && !function.warned {
+                function.warned = true;
+                unused.push(Warning::UnusedFunction(
+                    function.name.clone(),
+                    function.line,
+                    function.column,
+                ));
+            }
+        }
+
+        for warning in unused {
+            self.warn(warning);
+        }
+    }
+
+    fn begin_scope(&mut self) {
+        self.environment.begin_scope();
+        self.scope = self.symbols.add_scope(self.scope);
+    }
+
+    /// Closes the innermost scope.
+    ///
+    /// # Returns
+    /// The variables that were defined in the scope, in the order they were declared.
+    fn close_scope(&mut self) -> Vec<VariableEntry> {
+        self.scope = self.symbols.scopes[self.scope]
+            .parent
+            .expect("The global scope is never closed.");
+
+        self.environment.end_scope()
+    }
+
+    /// Closes the innermost scope, warning about the variables in it that were never read.
+    fn end_scope(&mut self) {
+        for entry in self.close_scope() {
+            if entry.warn_unused && !entry.used {
+                self.warn(Warning::UnusedVariable(
+                    entry.name.to_string(),
+                    entry.line,
+                    entry.column,
+                ));
+            }
+        }
+    }
+
+    /// Defines a variable or constant in the innermost scope, recording it in the symbol table.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the variable.
+    /// * `is_initialized` - Whether the variable has been given a value.
+    /// * `type` - The type of the variable's value.
+    /// * `declared` - The type the variable is annotated with, if any.
+    /// * `mutable` - Whether the variable is declared with 'let'.
+    fn define_variable(
+        &mut self,
+        name: &Token,
+        is_initialized: bool,
+        r#type: Type,
+        declared: Option<Type>,
+        mutable: bool,
+    ) {
+        self.check_shadowing(name, false);
+        let result = self
+            .environment
+            .define(name, is_initialized, r#type, declared, mutable);
+
+        if self.check(result).is_some() {
+            let kind = if mutable {
+                SymbolKind::Variable
+            } else {
+                SymbolKind::Constant
+            };
+            self.declare_symbol(name, kind, r#type);
+        }
+    }
+
+    /// Warns about a declaration that shadows a variable of an enclosing scope, if enabled.
+    ///
+    /// # Arguments
+    /// * `name` - The name being declared.
+    /// * `is_parameter` - Whether the declaration is a function parameter.
+    fn check_shadowing(&mut self, name: &Token, is_parameter: bool) {
+        if !self.warn_shadowing {
+            return;
+        }
+
+        let Some((scope, shadowed)) = self.environment.get_enclosing(name.name()) else {
+            return;
+        };
+
+        // Native functions have no declaration to point to.
+        if shadowed.is_native() || (is_parameter && scope == 0) {
+            return;
+        }
+
+        self.warn(Warning::ShadowedVariable(
+            name.lexeme.to_string(),
+            name.line,
+            name.column,
+            shadowed.line,
+            shadowed.column,
+        ));
+    }
+
+    fn analyze_loop_body(&mut self, body: &Statement) {
+        self.loop_depth += 1;
+        self.visit_statement(body);
+        self.loop_depth -= 1;
+    }
+
+    /// Reports a 'break' or 'continue' that is not within a loop.
+    fn check_in_loop(&mut self, keyword: &Token) {
+        if self.loop_depth == 0 {
+            self.error(Error::OutsideLoop(
+                keyword.lexeme.to_string(),
+                keyword.line,
+                keyword.column,
+            ));
+        }
+    }
+
+    /// Analyzes a function's parameters and body in the current scope.
+    /// The body shares the scope of the parameters, so a local cannot redeclare a parameter.
+    fn analyze_function(&mut self, parameters: &[(Token, Token)], body: &Statement) {
+        for (parameter, r#type) in parameters {
+            self.check_shadowing(parameter, true);
+            let r#type = self
+                .check(Type::from_annotation(r#type))
+                .unwrap_or(Type::Unknown);
+            let result = self.environment.define_parameter(parameter, r#type);
+
+            if self.check(result).is_some() {
+                self.declare_symbol(parameter, SymbolKind::Parameter, r#type);
+            }
+        }
+
+        match body {
+            Statement::Block(statements, _) => self.analyze_statements(statements),
+            body => self.visit_statement(body),
+        }
+    }
+
+    /// Records a declaration in the symbol table, once it has been defined in the innermost scope.
+    ///
+    /// # Arguments
+    /// * `name` - The name being declared.
+    /// * `kind` - What the name is declared as.
+    /// * `type` - The type of the variable, or the type the function returns.
+    fn declare_symbol(&mut self, name: &Token, kind: SymbolKind, r#type: Type) {
+        let symbol = SymbolEntry {
+            name: name.lexeme.to_string(),
+            kind,
+            span: name.span().range(),
+            r#type: known(r#type),
+            usages: Vec::new(),
+        };
+        let symbol = self.symbols.add_symbol(self.scope, symbol);
+
+        if let Some(entry) = self.environment.get_mut(name.name()) {
+            entry.symbol = Some(symbol);
+        }
+    }
+
+    /// Records a reference to a name in the symbol table, if the name is declared in the program.
+    fn add_usage(&mut self, name: &Token) {
+        let symbol = self
+            .environment
+            .get(name.name())
+            .and_then(|entry| entry.symbol);
+
+        if let Some(symbol) = symbol {
+            self.symbols
+                .get_mut(symbol)
+                .usages
+                .push(name.span().range());
+        }
+    }
+
+    /// Records a reference to a function from the function being analyzed, or from the top level.
+    fn reference_function(&mut self, function: usize) {
+        match self.current_function {
+            Some(caller) => self.functions[caller].references.push(function),
+            None => self.top_level_references.push(function),
+        }
+    }
+
+    /// Reports a name that is not in scope, pointing to the module it is a member of if there is one.
+    ///
+    /// # Arguments
+    /// * `name` - The name that was not found.
+    /// * `error` - Creates the error to report if no module has a member with the name,
+    ///   given the name and a similar name in scope if there is one.
+    fn report_not_found(
+        &mut self,
+        name: &Token,
+        error: fn(String, Option<String>, usize, usize) -> Error,
+    ) {
+        let error = match self.environment.find_member(name.name()) {
+            Some(module) => Error::UnqualifiedMember(
+                name.lexeme.to_string(),
+                module.to_string(),
+                name.line,
+                name.column,
+            ),
+            None => error(
+                name.lexeme.to_string(),
+                self.environment
+                    .suggest(&name.lexeme)
+                    .map(|suggestion| suggestion.to_string()),
+                name.line,
+                name.column,
+            ),
+        };
+
+        self.error(error);
+    }
+
+    /// Reports a division or remainder by a denominator that is always zero,
+    /// which is an error for integers and a warning for floats.
+    fn check_division(&mut self, left: &Expression, operator: &Token, right: &Expression) {
+        let is_integer = match fold_constant(right) {
+            Some(Constant::Int(0)) => true,
+            Some(Constant::Float(0.0)) => false,
+            _ => return,
+        };
+
+        let left = infer_type(left, &self.environment).unwrap_or(Type::Unknown);
+        if is_integer && left == Type::Int {
+            self.error(Error::DivisionByZero(operator.line, operator.column));
+        } else {
+            self.warn(Warning::DivisionByZero(operator.line, operator.column));
+        }
+    }
+
+    /// Analyzes an expression, then checks the types of its operands.
+    ///
+    /// # Returns
+    /// The type of the expression, which is unknown if its operands' types are not supported.
+    fn check_expression(&mut self, expression: &Expression) -> Type {
+        self.visit_expression(expression);
+
+        self.check(infer_type(expression, &self.environment))
+            .unwrap_or(Type::Unknown)
+    }
+
+    /// Analyzes the condition of a statement, checking that it is a boolean.
+    /// Like with '!', `none` counts as false, but other values must be compared to get a boolean.
+    ///
+    /// # Arguments
+    /// * `keyword` - The keyword of the statement, such as "while".
+    /// * `condition` - The condition.
+    fn check_condition(&mut self, keyword: &str, condition: &Expression) {
+        let found = self.check_expression(condition);
+
+        if !matches!(found, Type::Boolean | Type::None | Type::Unknown) {
+            let token = condition.first_token();
+
+            self.error(Error::ConditionType(
+                keyword.to_string(),
+                found,
+                token.line,
+                token.column,
+            ));
+        }
+    }
+}
+
+impl Visitor for Analyzer {
+    fn visit_expression_statement(&mut self, expression: &Expression) {
+        self.check_expression(expression);
+    }
+
+    fn visit_print(&mut self, expression: &Expression) {
+        self.check_expression(expression);
+    }
+
+    fn visit_variable_declaration(
+        &mut self,
+        name: &Token,
+        declared_type: Option<&Token>,
+        initializer: Option<&Expression>,
+        mutable: bool,
+    ) {
+        // The initializer is analyzed first, so 'let a = a;' cannot refer to itself.
+        let initializer_type = match initializer {
+            Some(initializer) => self.check_expression(initializer),
+            None => Type::Unknown,
+        };
+        // An unknown annotation accepts any value, so it is only reported once.
+        let declared = declared_type.map(|annotation| {
+            self.check(Type::from_annotation(annotation))
+                .unwrap_or(Type::Unknown)
+        });
+
+        if let (Some(declared), Some(initializer)) = (declared, initializer) {
+            self.check(check_assignable(
+                name,
+                declared,
+                initializer_type,
+                initializer,
+            ));
+        }
+
+        // An annotation takes precedence over the type of the initializer.
+        let r#type = declared.unwrap_or(initializer_type);
+        self.define_variable(name, initializer.is_some(), r#type, declared, mutable);
+    }
+
+    fn visit_destructure(&mut self, names: &[Token], initializer: &Expression, mutable: bool) {
+        let initializer_type = self.check_expression(initializer);
+        let position = initializer.first_token();
+
+        match initializer_type {
+            Type::Tuple(length) if length != names.len() => {
+                self.error(Error::DestructureCount(
+                    names.len(),
+                    length,
+                    position.line,
+                    position.column,
+                ));
+            }
+            Type::Tuple(_) | Type::Unknown => {}
+            found => {
+                self.error(Error::NotATuple(found, position.line, position.column));
+            }
+        }
+
+        // The types of the values are only known when the tuple is written out.
+        let types = match initializer {
+            Expression::Tuple(elements, _) if elements.len() == names.len() => elements
+                .iter()
+                .map(|element| infer_type(element, &self.environment).unwrap_or(Type::Unknown))
+                .collect(),
+            _ => vec![Type::Unknown; names.len()],
+        };
+
+        for (name, r#type) in names.iter().zip(types) {
+            self.define_variable(name, true, r#type, None, mutable);
+        }
+    }
+
+    fn visit_block(&mut self, statements: &[Statement]) {
+        self.begin_scope();
+        self.analyze_statements(statements);
+        self.end_scope();
+    }
+
+    fn visit_module(&mut self, name: &Token, statements: &[Statement]) {
+        self.begin_scope();
+        self.analyze_statements(statements);
+        // Members can be used from outside the module, so they are not reported as unused.
+        let members = self
+            .close_scope()
+            .into_iter()
+            .map(|entry| (entry.name, entry))
+            .collect();
+
+        let result = self.environment.define_module(name, members);
+        if self.check(result).is_some() {
+            self.declare_symbol(name, SymbolKind::Module, Type::Unknown);
+        }
+    }
+
+    fn visit_if(
+        &mut self,
+        condition: &Expression,
+        then_branch: &Statement,
+        else_branch: Option<&Statement>,
+    ) {
+        self.check_condition("if", condition);
+        self.visit_statement(then_branch);
+
+        if let Some(else_branch) = else_branch {
+            self.visit_statement(else_branch);
+        }
+    }
+
+    fn visit_while(&mut self, condition: &Expression, body: &Statement) {
+        self.check_condition("while", condition);
+        self.analyze_loop_body(body);
+    }
+
+    fn visit_loop(&mut self, body: &Statement) {
+        self.analyze_loop_body(body);
+    }
+
+    fn visit_for(
+        &mut self,
+        initializer: Option<&Statement>,
+        condition: Option<&Expression>,
+        increment: Option<&Expression>,
+        body: &Statement,
+    ) {
+        // The initializer's variable is only visible within the loop.
+        self.begin_scope();
+        if let Some(initializer) = initializer {
+            self.visit_statement(initializer);
+        }
+        if let Some(condition) = condition {
+            self.check_condition("for", condition);
+        }
+        if let Some(increment) = increment {
+            self.check_expression(increment);
+        }
+        self.analyze_loop_body(body);
+        self.end_scope();
+    }
+
+    fn visit_function(
+        &mut self,
+        name: &Token,
+        parameters: &[(Token, Token)],
+        return_type: Option<&Token>,
+        body: &Statement,
+    ) {
+        let return_type = return_type.map(|annotation| {
+            self.check(Type::from_annotation(annotation))
+                .unwrap_or(Type::Unknown)
+        });
+        let enclosing_return_type = std::mem::replace(&mut self.return_type, return_type);
+        // The function's name has already been defined by `analyze_statements`.
+        let function = self
+            .environment
+            .get(name.name())
+            .and_then(|entry| entry.function);
+        let current_function = std::mem::replace(&mut self.current_function, function);
+        // Loops outside of the function cannot be broken out of from within it.
+        let loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        self.function_depth += 1;
+        self.begin_scope();
+        self.analyze_function(parameters, body);
+        self.end_scope();
+        self.function_depth -= 1;
+        self.loop_depth = loop_depth;
+        self.current_function = current_function;
+        self.return_type = enclosing_return_type;
+
+        if let Some(return_type) = return_type.filter(|_| !always_returns(body)) {
+            self.error(Error::MissingReturn(
+                name.lexeme.to_string(),
+                return_type,
+                name.line,
+                name.column,
+            ));
+        }
+    }
+
+    fn visit_return(&mut self, keyword: &Token, value: Option<&Expression>) {
+        let found = value.map(|value| self.check_expression(value));
+
+        if self.function_depth == 0 {
+            self.error(Error::ReturnOutsideFunction(keyword.line, keyword.column));
+            return;
+        }
+
+        // Functions without a return type return none, so they can only return without a value.
+        let error = match (self.return_type, found) {
+            (Some(declared), Some(found)) if !is_assignable(declared, found) => Some(
+                Error::ReturnTypeMismatch(declared, found, keyword.line, keyword.column),
+            ),
+            (Some(declared), None) => Some(Error::MissingReturnValue(
+                declared,
+                keyword.line,
+                keyword.column,
+            )),
+            (None, Some(found)) if found != Type::None => Some(Error::UnexpectedReturnValue(
+                found,
+                keyword.line,
+                keyword.column,
+            )),
+            _ => None,
+        };
+
+        if let Some(error) = error {
+            self.error(error);
+        }
+    }
+
+    fn visit_break(&mut self, keyword: &Token) {
+        self.check_in_loop(keyword);
+    }
+
+    fn visit_continue(&mut self, keyword: &Token) {
+        self.check_in_loop(keyword);
+    }
+
+    fn visit_assert(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expression,
+        message: Option<&Expression>,
+    ) {
+        self.check_condition("assert", condition);
+
+        if let Some(message) = message {
+            self.check_expression(message);
+        }
+    }
+
+    fn visit_binary(&mut self, left: &Expression, operator: &Token, right: &Expression) {
+        self.visit_expression(left);
+        self.visit_expression(right);
+
+        if matches!(operator.token_type, TokenType::Slash | TokenType::Percent) {
+            self.check_division(left, operator, right);
+        }
+    }
+
+    fn visit_variable(&mut self, name: &Token) {
+        self.add_usage(name);
+
+        match self.environment.get_mut(name.name()) {
+            Some(entry) if entry.is_initialized => {
+                entry.used = true;
+
+                if let Some(function) = entry.function {
+                    self.reference_function(function);
+                }
+            }
+            Some(_) => self.error(Error::VariableNotInitialized(
+                name.lexeme.to_string(),
+                name.line,
+                name.column,
+            )),
+            None => self.report_not_found(name, Error::VariableNotFound),
+        }
+    }
+
+    fn visit_qualified(&mut self, module: &Token, name: &Token) {
+        self.add_usage(module);
+
+        let member = match self.environment.get_mut(module.name()) {
+            Some(entry) => {
+                entry.used = true;
+
+                match entry.members.as_mut() {
+                    Some(members) => match members.get_mut(&name.name()) {
+                        Some(member) => {
+                            member.used = true;
+                            Ok((member.function, member.symbol))
+                        }
+                        None => Err(Error::MemberNotFound(
+                            name.lexeme.to_string(),
+                            module.lexeme.to_string(),
+                            name.line,
+                            name.column,
+                        )),
+                    },
+                    None => Err(Error::NotAModule(
+                        module.lexeme.to_string(),
+                        module.line,
+                        module.column,
+                    )),
+                }
+            }
+            None => Err(Error::VariableNotFound(
+                module.lexeme.to_string(),
+                self.environment
+                    .suggest(&module.lexeme)
+                    .map(|suggestion| suggestion.to_string()),
+                module.line,
+                module.column,
+            )),
+        };
+
+        match member {
+            Ok((function, symbol)) => {
+                if let Some(symbol) = symbol {
+                    self.symbols
+                        .get_mut(symbol)
+                        .usages
+                        .push(name.span().range());
+                }
+                if let Some(function) = function {
+                    self.reference_function(function);
+                }
+            }
+            Err(error) => self.error(error),
+        }
+    }
+
+    fn visit_assign(&mut self, name: &Token, value: &Expression) {
+        let r#type = self.check_expression(value);
+        self.add_usage(name);
+
+        let Some(entry) = self.environment.get_mut(name.name()) else {
+            self.report_not_found(name, Error::VariableNotFound);
+            return;
+        };
+
+        if !entry.mutable {
+            self.error(Error::AssignmentToConst(
+                name.lexeme.to_string(),
+                name.line,
+                name.column,
+            ));
+            return;
+        }
+
+        let mismatch = match entry.declared {
+            Some(declared) => check_assignable(name, declared, r#type, value).err(),
+            // The first value of a variable declared without one decides its type.
+            None if !entry.is_initialized => {
+                entry.r#type = r#type;
+                None
+            }
+            // Without an annotation, values of another type may be assigned,
+            // after which the variable's type is no longer known.
+            None if entry.r#type != r#type => {
+                entry.r#type = Type::Unknown;
+                None
+            }
+            None => None,
+        };
+
+        // Assigning is how a variable declared without a value becomes initialized.
+        entry.is_initialized = true;
+
+        if let Some(symbol) = entry.symbol {
+            self.symbols.get_mut(symbol).r#type = known(entry.r#type);
+        }
+
+        if let Some(mismatch) = mismatch {
+            self.error(mismatch);
+        }
+    }
+
+    fn visit_call(&mut self, callee: &Expression, parenthesis: &Token, arguments: &[Expression]) {
+        match callee {
+            Expression::Variable(name, _) if self.environment.get(name.name()).is_none() => {
+                self.report_not_found(name, Error::FunctionNotFound);
+            }
+            callee => walk_expression(self, callee),
+        }
+
+        for argument in arguments {
+            self.visit_expression(argument);
+        }
+
+        // Only calls naming a known function directly, or as a member of a module, can be checked.
+        let Some(parameters) = self
+            .environment
+            .lookup(callee)
+            .and_then(|entry| entry.parameters.clone())
+        else {
+            return;
+        };
+
+        if parameters.len() != arguments.len() {
+            self.error(Error::ArgumentCount(
+                callee.to_string(),
+                parameters.len(),
+                arguments.len(),
+                parenthesis.line,
+                parenthesis.column,
+            ));
+            return;
+        }
+
+        for (index, (expected, argument)) in parameters.into_iter().zip(arguments).enumerate() {
+            // Errors within the argument are reported along with the rest of the expression.
+            let found = infer_type(argument, &self.environment).unwrap_or(Type::Unknown);
+
+            if !is_assignable(expected, found) {
+                let position = argument.first_token();
+                self.error(Error::ArgumentMismatch(
+                    callee.to_string(),
+                    index + 1,
+                    found,
+                    expected,
+                    position.line,
+                    position.column,
+                ));
+            }
+        }
+    }
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Infers the type of an expression, checking that its operators support the types of their operands.
+/// Operands of an unknown type are assumed to be supported, so they do not cause further errors.
+///
+/// # Arguments
+/// * `expression` - The expression, which must already have been analyzed.
+/// * `environment` - The variables in scope of the expression.
+///
+/// # Returns
+/// The type of the expression, or the first error found.
+fn infer_type(expression: &Expression, environment: &Environment) -> Result<Type, Error> {
+    match expression {
+        Expression::Binary {
+            left,
+            operator,
+            right,
+            ..
+        } => {
+            let left = infer_type(left, environment)?;
+            let right = infer_type(right, environment)?;
+
+            binary_type(operator, left, right)
+        }
+        Expression::Grouping(expression, _) => infer_type(expression, environment),
+        Expression::Tuple(elements, _) => {
+            for element in elements {
+                infer_type(element, environment)?;
+            }
+
+            Ok(Type::Tuple(elements.len()))
+        }
+        // Every value has a type, whose name is a string.
+        Expression::TypeOf(expression, _) => {
+            infer_type(expression, environment)?;
+
+            Ok(Type::String)
+        }
+        Expression::Literal { value, .. } => Ok(match value {
+            Literal::Int(_) => Type::Int,
+            Literal::Float(_) => Type::Float,
+            Literal::String(_) => Type::String,
+            Literal::Char(_) => Type::Char,
+            Literal::Boolean(_) => Type::Boolean,
+            Literal::None => Type::None,
+        }),
+        Expression::Unary {
+            operator, right, ..
+        } => {
+            let right = infer_type(right, environment)?;
+
+            match (&operator.token_type, right) {
+                (TokenType::Minus, Type::Int | Type::Float | Type::Unknown) => Ok(right),
+                (TokenType::Bang, Type::Boolean | Type::None | Type::Unknown) => Ok(Type::Boolean),
+                (_, right) => Err(Error::InvalidOperand(
+                    operator.lexeme.to_string(),
+                    right,
+                    operator.line,
+                    operator.column,
+                )),
+            }
+        }
+        Expression::Variable(_, _) | Expression::Qualified { .. } => Ok(environment
+            .lookup(expression)
+            .map_or(Type::Unknown, |entry| entry.r#type)),
+        Expression::Assign { value, .. } => infer_type(value, environment),
+        Expression::Call {
+            callee, arguments, ..
+        } => {
+            infer_type(callee, environment)?;
+            for argument in arguments {
+                infer_type(argument, environment)?;
+            }
+
+            // Only the results of native functions called by name are known.
+            Ok(environment
+                .lookup(callee)
+                .map_or(Type::Unknown, |entry| entry.returns))
+        }
+        Expression::Coalesce { left, right, .. } => {
+            let left = infer_type(left, environment)?;
+            let right = infer_type(right, environment)?;
+
+            // The right side is only the result if the left side is none.
+            Ok(match left {
+                Type::None => right,
+                left => left,
+            })
+        }
+    }
+}
+
+/// Gets a type, or `None` if it is unknown.
+fn known(r#type: Type) -> Option<Type> {
+    Some(r#type).filter(|r#type| *r#type != Type::Unknown)
+}
+
+/// A number known before the program runs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Constant {
+    Int(i64),
+    Float(f64),
+}
+
+impl Constant {
+    fn as_float(self) -> f64 {
+        match self {
+            Constant::Int(int) => int as f64,
+            Constant::Float(float) => float,
+        }
+    }
+}
+
+/// Folds an expression made of number literals into its value, with the interpreter's arithmetic.
+///
+/// # Returns
+/// The value, or `None` if the expression is not constant or evaluating it fails,
+/// such as when dividing an integer by zero or overflowing.
+pub(crate) fn fold_constant(expression: &Expression) -> Option<Constant> {
+    match expression {
+        Expression::Literal {
+            value: Literal::Int(int),
+            ..
+        } => Some(Constant::Int(*int)),
+        Expression::Literal {
+            value: Literal::Float(float),
+            ..
+        } => Some(Constant::Float(*float)),
+        Expression::Grouping(expression, _) => fold_constant(expression),
+        Expression::Unary {
+            operator, right, ..
+        } if operator.token_type == TokenType::Minus => match fold_constant(right)? {
+            Constant::Int(int) => int.checked_neg().map(Constant::Int),
+            Constant::Float(float) => Some(Constant::Float(-float)),
+        },
+        Expression::Binary {
+            left,
+            operator,
+            right,
+            ..
+        } => match (fold_constant(left)?, fold_constant(right)?) {
+            (left, right) if operator.token_type == TokenType::StarStar => {
+                Some(Constant::Float(left.as_float().powf(right.as_float())))
+            }
+            (Constant::Int(left), Constant::Int(right)) => match operator.token_type {
+                TokenType::Plus => left.checked_add(right),
+                TokenType::Minus => left.checked_sub(right),
+                TokenType::Star => left.checked_mul(right),
+                TokenType::Slash => left.checked_div(right),
+                TokenType::Percent => left.checked_rem(right),
+                _ => None,
+            }
+            .map(Constant::Int),
+            (left, right) => {
+                let (left, right) = (left.as_float(), right.as_float());
+
+                match operator.token_type {
+                    TokenType::Plus => Some(left + right),
+                    TokenType::Minus => Some(left - right),
+                    TokenType::Star => Some(left * right),
+                    TokenType::Slash => Some(left / right),
+                    TokenType::Percent => Some(left % right),
+                    _ => None,
+                }
+                .map(Constant::Float)
+            }
+        },
+        _ => None,
+    }
+}
+
+/// Checks that a value can be given to a variable annotated with a type.
+///
+/// # Arguments
+/// * `name` - The name of the variable.
+/// * `declared` - The type the variable is annotated with.
+/// * `found` - The type of the value.
+/// * `value` - The value, whose position errors are reported at.
+fn check_assignable(
+    name: &Token,
+    declared: Type,
+    found: Type,
+    value: &Expression,
+) -> Result<(), Error> {
+    if is_assignable(declared, found) {
+        return Ok(());
+    }
+
+    let position = value.first_token();
+
+    Err(Error::AssignmentMismatch(
+        name.lexeme.to_string(),
+        declared,
+        found,
+        position.line,
+        position.column,
+    ))
+}
+
+/// Counts the characters that must be inserted, removed or replaced to turn one name into another.
+/// Case is ignored, so names differing only in case are 0 apart.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.to_lowercase().chars().collect::<Vec<_>>();
+    let b = b.to_lowercase().chars().collect::<Vec<_>>();
+
+    // The distances between the prefixes of 'a' and the previous and current prefixes of 'b'.
+    let mut previous = (0..=a.len()).collect::<Vec<_>>();
+    let mut current = vec![0; a.len() + 1];
+
+    for (j, b_char) in b.iter().enumerate() {
+        current[0] = j + 1;
+
+        for (i, a_char) in a.iter().enumerate() {
+            let replace = previous[i] + usize::from(a_char != b_char);
+
+            current[i + 1] = replace.min(previous[i + 1] + 1).min(current[i] + 1);
+        }
+
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[a.len()]
+}
+
+/// Checks if a value of one type can be used where another type is expected.
+/// Values of an unknown type are assumed to have the expected type.
+fn is_assignable(expected: Type, found: Type) -> bool {
+    // Integers are converted to floats where floats are expected, but not the other way around.
+    expected == found
+        || expected == Type::Unknown
+        || found == Type::Unknown
+        || (expected == Type::Float && found == Type::Int)
+}
+
+/// Checks if a statement always returns, so execution cannot continue past it.
+/// A loop that is never broken out of, and whose condition is always true, never finishes, so it counts as returning.
+/// Other loops are assumed to finish, so returning from within them does not count.
+fn always_returns(statement: &Statement) -> bool {
+    match statement {
+        Statement::Return { .. } => true,
+        Statement::Block(statements, _) => statements.iter().any(always_returns),
+        Statement::If {
+            then_branch,
+            else_branch: Some(else_branch),
+            ..
+        } => always_returns(then_branch) && always_returns(else_branch),
+        Statement::Loop { body, .. }
+        | Statement::For {
+            condition: None,
+            body,
+            ..
+        } => !breaks_out(body),
+        Statement::While {
+            condition, body, ..
+        } => is_true(condition) && !breaks_out(body),
+        _ => false,
+    }
+}
+
+/// Checks if the body of a loop contains a 'break' that targets the loop, rather than a loop within it.
+fn breaks_out(statement: &Statement) -> bool {
+    match statement {
+        Statement::Break { .. } => true,
+        Statement::Block(statements, _) => statements.iter().any(breaks_out),
+        Statement::If {
+            then_branch,
+            else_branch,
+            ..
+        } => breaks_out(then_branch) || else_branch.as_deref().is_some_and(breaks_out),
+        _ => false,
+    }
+}
+
+/// Checks if an expression is the literal 'true', possibly within parentheses.
+fn is_true(expression: &Expression) -> bool {
+    match expression {
+        Expression::Literal {
+            value: Literal::Boolean(true),
+            ..
+        } => true,
+        Expression::Grouping(expression, _) => is_true(expression),
+        _ => false,
+    }
+}
+
+/// Gets the type of a binary operation, checking that the operator supports the types of its operands.
+fn binary_type(operator: &Token, left: Type, right: Type) -> Result<Type, Error> {
+    let numbers = matches!(
+        (left, right),
+        (
+            Type::Int | Type::Float | Type::Unknown,
+            Type::Int | Type::Float | Type::Unknown
+        )
+    );
+    // Mixing integers and floats converts the integers to floats.
+    let number = match (left, right) {
+        (Type::Int, Type::Int) => Type::Int,
+        (Type::Float, _) | (_, Type::Float) => Type::Float,
+        _ => Type::Unknown,
+    };
+
+    let r#type = match operator.token_type {
+        TokenType::LogicalAnd | TokenType::LogicalOr => Some(Type::Boolean),
+        // Powers are always floats, like in the interpreter.
+        TokenType::StarStar => numbers.then_some(Type::Float),
+        // Any value can be compared with none, to check if it has a value.
+        TokenType::EqualEqual | TokenType::BangEqual => (numbers
+            || left == right
+            || matches!(left, Type::None | Type::Unknown)
+            || matches!(right, Type::None | Type::Unknown))
+        .then_some(Type::Boolean),
+        TokenType::Plus => match (left, right) {
+            (Type::String, Type::String | Type::Unknown) | (Type::Unknown, Type::String) => {
+                Some(Type::String)
+            }
+            _ => numbers.then_some(number),
+        },
+        TokenType::GreaterThan
+        | TokenType::GreaterThanOrEqual
+        | TokenType::LessThan
+        | TokenType::LessThanOrEqual => (numbers
+            || matches!(
+                (left, right),
+                (Type::String, Type::String | Type::Unknown) | (Type::Unknown, Type::String)
+            ))
+        .then_some(Type::Boolean),
+        _ => numbers.then_some(number),
+    };
+
+    r#type.ok_or_else(|| {
+        Error::TypeMismatch(
+            operator.lexeme.to_string(),
+            left,
+            right,
+            operator.line,
+            operator.column,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::parse;
+    use crate::lang::symbols::Scope;
+
+    fn analyze(source: &str) -> Result<(), Error> {
+        let statements = parse(source).expect("Parsing should succeed.");
+        let mut analyzer = Analyzer::new();
+        analyzer.analyze(&statements);
+
+        match analyzer.errors() {
+            [] => Ok(()),
+            [error, ..] => Err(error.clone()),
+        }
+    }
+
+    #[test]
+    fn test_parameter_local_collision() {
+        assert_eq!(
+            analyze("fn f(x: i32) { let x = 1; }"),
+            Err(Error::VariableRedeclaration("x".to_string(), 1, 20))
+        );
+        assert_eq!(
+            analyze("fn f(x: i32, x: i32) {}"),
+            Err(Error::VariableRedeclaration("x".to_string(), 1, 14))
+        );
+    }
+
+    #[test]
+    fn test_function_scope() {
+        assert_eq!(
+            analyze("fn f(x: i32) -> int { let y = x; { let x = y; } return x; }"),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("fn f(x: i32) { let y = x; }\nprint x;"),
+            Err(Error::VariableNotFound("x".to_string(), None, 2, 7))
+        );
+        assert_eq!(
+            analyze("fn f(x: i32) { let y = x; }\nprint y;"),
+            Err(Error::VariableNotFound("y".to_string(), None, 2, 7))
+        );
+    }
+
+    #[test]
+    fn test_print() {
+        assert_eq!(analyze("let a = 1; print a + 1;"), Ok(()));
+        assert_eq!(
+            analyze("print a;"),
+            Err(Error::VariableNotFound("a".to_string(), None, 1, 7))
+        );
+    }
+
+    #[test]
+    fn test_return() {
+        assert_eq!(analyze("fn f() { return; }"), Ok(()));
+        assert_eq!(
+            analyze("fn f() -> int { return a; }"),
+            Err(Error::VariableNotFound("a".to_string(), None, 1, 24))
+        );
+    }
+
+    #[test]
+    fn test_for() {
+        assert_eq!(
+            analyze("for (let i = 0; i < 10; i = i + 1) { print i; break; }"),
+            Ok(())
+        );
+
+        // The loop variable does not outlive the loop.
+        assert_eq!(
+            analyze("for (let i = 0; i < 10; i = i + 1) {} print i;"),
+            Err(Error::VariableNotFound("i".to_string(), None, 1, 45))
+        );
+    }
+
+    #[test]
+    fn test_break_and_continue() {
+        assert_eq!(
+            analyze("while (true) { if (true) break; continue; } loop { break; }"),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("break;"),
+            Err(Error::OutsideLoop("break".to_string(), 1, 1))
+        );
+        assert_eq!(
+            analyze("if (true) { continue; }"),
+            Err(Error::OutsideLoop("continue".to_string(), 1, 13))
+        );
+    }
+
+    #[test]
+    fn test_suggestions() {
+        assert_eq!(
+            analyze("let counter = 0; print countr;"),
+            Err(Error::VariableNotFound(
+                "countr".to_string(),
+                Some("counter".to_string()),
+                1,
+                24
+            ))
+        );
+        assert_eq!(
+            analyze("fn greet() {} { let Greet = 1; print Greet; } gret();"),
+            Err(Error::FunctionNotFound(
+                "gret".to_string(),
+                Some("greet".to_string()),
+                1,
+                47
+            ))
+        );
+
+        // A difference in case is always close enough, even for short names.
+        let error = analyze("let a = 1; print A;").unwrap_err();
+        assert_eq!(
+            error,
+            Error::VariableNotFound("A".to_string(), Some("a".to_string()), 1, 18)
+        );
+        assert_eq!(
+            error.to_string(),
+            "Variable 'A' is not defined, did you mean 'a'?"
+        );
+
+        assert_eq!(
+            analyze("let counter = 0; let b = 1; print total + c;"),
+            Err(Error::VariableNotFound("total".to_string(), None, 1, 35))
+        );
+
+        // Names may differ by two characters, but by no more than half of the name.
+        assert_eq!(
+            analyze("let count = 0; print cuont;"),
+            Err(Error::VariableNotFound(
+                "cuont".to_string(),
+                Some("count".to_string()),
+                1,
+                22
+            ))
+        );
+        assert_eq!(
+            analyze("let b = 1; print c + i;"),
+            Err(Error::VariableNotFound("c".to_string(), None, 1, 18))
+        );
+        assert_eq!(
+            analyze("print foo();"),
+            Err(Error::FunctionNotFound("foo".to_string(), None, 1, 7))
+        );
+    }
+
+    #[test]
+    fn test_suggestions_are_deterministic() {
+        // Equally similar names are suggested alphabetically, however many there are.
+        let names = (0..2000)
+            .map(|i| format!("let v{:04} = {};", i, i))
+            .collect::<String>();
+
+        for _ in 0..3 {
+            assert_eq!(
+                analyze(&format!("{} print v000;", names)),
+                Err(Error::VariableNotFound(
+                    "v000".to_string(),
+                    Some("v0000".to_string()),
+                    1,
+                    names.len() + 8
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn test_mutual_recursion() {
+        assert_eq!(
+            analyze(
+                r#"
+                fn is_even(n: i32) -> bool {
+                    if (n == 0) return true;
+                    return is_odd(n - 1);
+                }
+
+                fn is_odd(n: i32) -> bool {
+                    if (n == 0) return false;
+                    return is_even(n - 1);
+                }
+
+                print is_even(10);
+                "#
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("fn f() { fn g() -> int { return h(); } fn h() -> int { return g(); } }"),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("fn f() -> int { return g(); } { fn g() {} }"),
+            Err(Error::FunctionNotFound("g".to_string(), None, 1, 24))
+        );
+    }
+
+    #[test]
+    fn test_argument_count() {
+        let add = "fn add(a: i32, b: i32) -> int { return a + b; }\n";
+
+        assert_eq!(analyze(&format!("{}print add(1, 2);", add)), Ok(()));
+        assert_eq!(
+            analyze(&format!("{}print add(1);", add)),
+            Err(Error::ArgumentCount("add".to_string(), 2, 1, 2, 12))
+        );
+        assert_eq!(
+            analyze(&format!("{}print add(1, 2, 3);", add)),
+            Err(Error::ArgumentCount("add".to_string(), 2, 3, 2, 18))
+        );
+    }
+
+    #[test]
+    fn test_argument_count_message() {
+        let message = |expected, found| {
+            Error::ArgumentCount("f".to_string(), expected, found, 1, 1).to_string()
+        };
+
+        assert_eq!(
+            message(2, 1),
+            "Function 'f' takes 2 arguments but 1 was given"
+        );
+        assert_eq!(
+            message(1, 2),
+            "Function 'f' takes 1 argument but 2 were given"
+        );
+        assert_eq!(
+            message(1, 0),
+            "Function 'f' takes 1 argument but 0 were given"
+        );
+    }
+
+    #[test]
+    fn test_argument_count_of_unknown_functions() {
+        // Functions declared later are known before their body is analyzed.
+        assert_eq!(
+            analyze(
+                "fn main() -> int { return twice(); } fn twice(x: i32) -> int { return x * 2; }"
+            ),
+            Err(Error::ArgumentCount("twice".to_string(), 1, 0, 1, 33))
+        );
+        // Parameters are not known to be functions, so calls through them are not checked.
+        assert_eq!(
+            analyze("fn apply(f: function, x: i32) -> int { return f(x, x); }"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_native_functions() {
+        assert_eq!(analyze("print len(\"abc\") + clock();"), Ok(()));
+        assert_eq!(
+            analyze("print len();"),
+            Err(Error::ArgumentCount("len".to_string(), 1, 0, 1, 11))
+        );
+    }
+
+    #[test]
+    fn test_conversion_functions() {
+        assert_eq!(
+            analyze("let a: int = int(\"1\"); let b: float = float(a); let c: str = str(b); let d: bool = bool(c);"),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("let a: int = float(1);"),
+            Err(Error::AssignmentMismatch(
+                "a".to_string(),
+                Type::Int,
+                Type::Float,
+                1,
+                14
+            ))
+        );
+        assert_eq!(
+            analyze("print str(1) + 1;"),
+            Err(Error::TypeMismatch(
+                "+".to_string(),
+                Type::String,
+                Type::Int,
+                1,
+                14
+            ))
+        );
+        assert_eq!(
+            analyze("print bool(1, 2);"),
+            Err(Error::ArgumentCount("bool".to_string(), 1, 2, 1, 16))
+        );
+        assert_eq!(
+            analyze("fn int(x: str) -> int { return 0; }"),
+            Err(Error::BuiltinRedefinition("int".to_string(), 1, 4))
+        );
+        assert_eq!(
+            Error::BuiltinRedefinition("int".to_string(), 1, 4).to_string(),
+            "Cannot redefine the built-in function 'int'"
+        );
+        // Built-in functions can still be shadowed in inner scopes.
+        assert_eq!(analyze("{ let str = 1; print str + 1; }"), Ok(()));
+    }
+
+    #[test]
+    fn test_condition_types() {
+        assert_eq!(
+            analyze(
+                "let a = 1; let b = none; if (a > 0 && true) {} while (!false) { break; } \
+                 for (let i = 0; i < a; i = i + 1) {} assert(b); if (b ?? true) {}"
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("let a = 5;\nwhile (a) {\n    a = a - 1;\n}"),
+            Err(Error::ConditionType("while".to_string(), Type::Int, 2, 8))
+        );
+        assert_eq!(
+            analyze("if (\"yes\") {}").map_err(|error| error.to_string()),
+            Err("The condition of 'if' must be boolean, found string".to_string())
+        );
+        assert_eq!(
+            analyze("for (let i = 3; i; i = i - 1) {}"),
+            Err(Error::ConditionType("for".to_string(), Type::Int, 1, 17))
+        );
+        assert_eq!(
+            analyze("assert(1.5, \"positive\");"),
+            Err(Error::ConditionType(
+                "assert".to_string(),
+                Type::Float,
+                1,
+                8
+            ))
+        );
+    }
+
+    #[test]
+    fn test_argument_types() {
+        let add = "fn add(a: int, b: float) -> float { return a + b; }\n";
+
+        // Integers are accepted where floats are expected.
+        assert_eq!(analyze(&format!("{}print add(1, 2);", add)), Ok(()));
+        assert_eq!(
+            analyze(&format!("{}print add(\"a\", 2.5);", add)),
+            Err(Error::ArgumentMismatch(
+                "add".to_string(),
+                1,
+                Type::String,
+                Type::Int,
+                2,
+                11
+            ))
+        );
+        assert_eq!(
+            analyze(&format!("{}print add(1, 2 > 1);", add)),
+            Err(Error::ArgumentMismatch(
+                "add".to_string(),
+                2,
+                Type::Boolean,
+                Type::Float,
+                2,
+                14
+            ))
+        );
+        assert_eq!(
+            Error::ArgumentMismatch("add".to_string(), 2, Type::Boolean, Type::Float, 2, 14)
+                .to_string(),
+            "Argument 2 of function 'add' must be float, found boolean"
+        );
+
+        // Arguments of unknown types, and parameters annotated with 'function', are not checked.
+        assert_eq!(
+            analyze(&format!(
+                "{}fn apply(f: function, x: int) -> float {{ return add(x, f(x)); }}\nprint apply(add, num(\"1\"));",
+                add
+            )),
+            Ok(())
+        );
+        assert_eq!(
+            analyze(&format!("{}print add(num(\"1\"), clock);", add)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_function_not_found() {
+        assert_eq!(
+            analyze("fn main() { helper(1, 2); }\nfn helper(a: i32, b: i32) {}"),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("fn main() {\n    foo(1, 2);\n}"),
+            Err(Error::FunctionNotFound("foo".to_string(), None, 2, 5))
+        );
+        // Variables may hold functions, so calling one is left to the interpreter.
+        assert_eq!(analyze("let f = len; let x = 1; f(\"a\"); x();"), Ok(()));
+    }
+
+    #[test]
+    fn test_break_in_function_inside_loop() {
+        assert_eq!(
+            analyze("while (true) {\n    fn stop() {\n        break;\n    }\n}"),
+            Err(Error::OutsideLoop("break".to_string(), 3, 9))
+        );
+        assert_eq!(
+            analyze("fn f() { loop { fn g() { while (true) continue; } break; } }"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_return_outside_function() {
+        assert_eq!(
+            analyze("return 5;"),
+            Err(Error::ReturnOutsideFunction(1, 1))
+        );
+        assert_eq!(
+            analyze("while (true) {\n    if (true) { return; }\n}"),
+            Err(Error::ReturnOutsideFunction(2, 17))
+        );
+        assert_eq!(
+            analyze("fn f() -> int { if (true) { return 1; } { return 2; } }"),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("fn f() -> int { fn g() -> int { return 1; } return g(); }"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_division_by_zero() {
+        let diagnostics = |source: &str| {
+            let statements = parse(source).expect("Parsing should succeed.");
+            let mut analyzer = Analyzer::new();
+            analyzer.analyze(&statements);
+
+            (analyzer.errors().to_vec(), analyzer.warnings().to_vec())
+        };
+
+        assert_eq!(
+            diagnostics("let x = 5; print x / 0; print x / (2 - 2);"),
+            (
+                vec![Error::DivisionByZero(1, 20), Error::DivisionByZero(1, 33)],
+                vec![]
+            )
+        );
+        assert_eq!(
+            diagnostics("let x = 5; print x % 0; print x % (4 % 2); print 1.5 % 0;"),
+            (
+                vec![Error::DivisionByZero(1, 20), Error::DivisionByZero(1, 33)],
+                vec![Warning::DivisionByZero(1, 54)]
+            )
+        );
+        // Dividing floats by zero is allowed, but never useful.
+        assert_eq!(
+            diagnostics("print 1.5 / 0; print 1 / -0.0; print 1 / (1 - 1.0);"),
+            (
+                vec![],
+                vec![
+                    Warning::DivisionByZero(1, 11),
+                    Warning::DivisionByZero(1, 24),
+                    Warning::DivisionByZero(1, 40),
+                ]
+            )
+        );
+        // Denominators that cannot be folded are not checked, including ones that would fail to fold.
+        assert_eq!(
+            diagnostics("fn f(y: int) -> int { return 10 / y; } print f(1) / (1 - 1 + f(0));"),
+            (vec![], vec![])
+        );
+        assert_eq!(
+            diagnostics("print 1 / (1 / 0); print 1 / (9223372036854775807 + 1);"),
+            (vec![Error::DivisionByZero(1, 14)], vec![])
+        );
+    }
+
+    #[test]
+    fn test_unreachable_code() {
+        let warnings = |source: &str| {
+            let statements = parse(source).expect("Parsing should succeed.");
+            let mut analyzer = Analyzer::new();
+            analyzer.analyze(&statements);
+
+            analyzer.warnings().to_vec()
+        };
+
+        assert_eq!(
+            warnings("fn f() -> int {\n    return 1;\n    print 2;\n    print 3;\n}\nprint f();"),
+            [Warning::UnreachableCode(3, 5)]
+        );
+        assert_eq!(
+            warnings("while (true) { break; // Done.\n { print 1; } }"),
+            [Warning::UnreachableCode(2, 2)]
+        );
+        // Code after a return that only happens under a condition can still run.
+        assert_eq!(
+            warnings(
+                "fn f(n: int) -> int { if (n > 0) { return 1; } print n; return 0; } print f(1);"
+            ),
+            []
+        );
+        // Functions declared after a return are defined before it runs.
+        assert_eq!(
+            warnings("fn f() -> int { return g(); fn g() -> int { return 1; } } print f();"),
+            []
+        );
+    }
+
+    #[test]
+    fn test_require_entry_point() {
+        let errors = |source: &str| {
+            let statements = parse(source).expect("Parsing should succeed.");
+            let mut analyzer = Analyzer::new().with_require_entry_point(true);
+            analyzer.analyze(&statements);
+
+            analyzer.errors().to_vec()
+        };
+
+        assert_eq!(errors("fn main() { print 1; }"), []);
+        assert_eq!(
+            errors("fn start() {}"),
+            [Error::EntryPointNotFound("main".to_string())]
+        );
+        assert_eq!(
+            errors("fn main(a: int, b: int) {}"),
+            [Error::EntryPointParameters("main".to_string(), 2, 1, 4)]
+        );
+        assert_eq!(
+            errors("fn main() {}\n{ fn main() {} }"),
+            [Error::EntryPointRedeclaration("main".to_string(), 2, 6)]
+        );
+        assert_eq!(
+            Error::EntryPointParameters("main".to_string(), 2, 1, 4).to_string(),
+            "Function 'main' must take 0 parameters, found 2"
+        );
+
+        // The entry point is only required when asked for.
+        assert_eq!(analyze("print 1;"), Ok(()));
+        let statements = parse("fn begin() {}").expect("Parsing should succeed.");
+        let mut analyzer = Analyzer::new()
+            .with_entry_point("begin")
+            .with_require_entry_point(true);
+        analyzer.analyze(&statements);
+        assert_eq!(analyzer.errors(), []);
+    }
+
+    #[test]
+    fn test_unused_variables() {
+        let statements = parse(
+            r#"
+            let global = 1;
+            fn f(parameter: i32) {
+                let read = 1;
+                let written = 2;
+                let _ignored = 3;
+                written = read;
+                {
+                    let inner = 4;
+                }
+            }
+            "#,
+        )
+        .expect("Parsing should succeed.");
+
+        let mut analyzer = Analyzer::new();
+        analyzer.analyze(&statements);
+        assert_eq!(analyzer.errors(), []);
+        assert_eq!(
+            analyzer.warnings(),
+            [
+                Warning::UnusedVariable("inner".to_string(), 9, 25),
+                Warning::UnusedVariable("written".to_string(), 5, 21),
+                Warning::UnusedFunction("f".to_string(), 3, 16),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unused_functions() {
+        let statements = parse(
+            r#"
+            fn main() {
+                print used(1);
+            }
+
+            fn used(x: i32) -> int {
+                return x;
+            }
+
+            fn unused() {}
+
+            fn ping(n: i32) -> int {
+                return pong(n - 1);
+            }
+
+            fn pong(n: i32) -> int {
+                return ping(n - 1);
+            }
+
+            fn called_from_top_level() {}
+            called_from_top_level();
+            "#,
+        )
+        .expect("Parsing should succeed.");
+
+        let mut analyzer = Analyzer::new();
+        analyzer.analyze(&statements);
+        assert_eq!(analyzer.errors(), []);
+        assert_eq!(
+            analyzer.warnings(),
+            [
+                Warning::UnusedFunction("unused".to_string(), 10, 16),
+                Warning::UnusedFunction("ping".to_string(), 12, 16),
+                Warning::UnusedFunction("pong".to_string(), 16, 16),
+            ]
+        );
+
+        // Without 'main' as the entry point, nothing calls it or the helper it uses.
+        let mut analyzer = Analyzer::new().with_entry_point("start");
+        analyzer.analyze(&statements);
+        assert_eq!(analyzer.errors(), []);
+        assert_eq!(
+            analyzer.warnings()[..2],
+            [
+                Warning::UnusedFunction("main".to_string(), 2, 16),
+                Warning::UnusedFunction("used".to_string(), 6, 16),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        let mismatch = |operator: &str, left, right, column| {
+            Err(Error::TypeMismatch(
+                operator.to_string(),
+                left,
+                right,
+                1,
+                column,
+            ))
+        };
+
+        assert_eq!(
+            analyze("print \"hello\" - 5;"),
+            mismatch("-", Type::String, Type::Int, 15)
+        );
+        assert_eq!(
+            analyze("print true * 3;"),
+            mismatch("*", Type::Boolean, Type::Int, 12)
+        );
+        assert_eq!(
+            analyze("print \"a\" ** 2;"),
+            mismatch("**", Type::String, Type::Int, 11)
+        );
+        // The names of types are strings.
+        assert_eq!(
+            analyze("print typeof 1 - 1;"),
+            mismatch("-", Type::String, Type::Int, 16)
+        );
+        assert_eq!(analyze("let a: string = typeof 1;"), Ok(()));
+        // Powers are floats, even of integers.
+        assert_eq!(analyze("let a: float = 2 ** 3;"), Ok(()));
+        assert!(analyze("let a: int = 2 ** 3;").is_err());
+        assert_eq!(
+            analyze("let a = \"a\"; print a < 1;"),
+            mismatch("<", Type::String, Type::Int, 22)
+        );
+        assert_eq!(
+            analyze("fn f(a: i32) { print \"b\" + a; }"),
+            mismatch("+", Type::String, Type::Int, 26)
+        );
+        assert_eq!(
+            analyze("print !5;"),
+            Err(Error::InvalidOperand("!".to_string(), Type::Int, 1, 7))
+        );
+        assert_eq!(
+            analyze("fn f(s: str) -> int { return -s; }"),
+            Err(Error::InvalidOperand("-".to_string(), Type::String, 1, 30))
+        );
+    }
+
+    #[test]
+    fn test_string_operators() {
+        // Each operator, and whether it works on two strings. None of them work on a string and a number.
+        let operators = [
+            ("+", true),
+            ("-", false),
+            ("*", false),
+            ("/", false),
+            ("==", true),
+            ("!=", true),
+            ("<", true),
+            ("<=", true),
+            (">", true),
+            (">=", true),
+        ];
+
+        for (operator, on_strings) in operators {
+            let mismatch = |right| {
+                Err(Error::TypeMismatch(
+                    operator.to_string(),
+                    Type::String,
+                    right,
+                    1,
+                    11,
+                ))
+            };
+
+            let expected = if on_strings {
+                Ok(())
+            } else {
+                mismatch(Type::String)
+            };
+            assert_eq!(
+                analyze(&format!("print \"a\" {} \"b\";", operator)),
+                expected
+            );
+            assert_eq!(
+                analyze(&format!("print \"a\" {} 1;", operator)),
+                mismatch(Type::Int)
+            );
+        }
+
+        // Anything can be compared with none.
+        assert_eq!(
+            analyze("let a = \"a\"; print a == none; print none != a;"),
+            Ok(())
+        );
+        assert_eq!(analyze("print 1 == 1.0; print 'a' == 'b';"), Ok(()));
+        assert_eq!(
+            analyze("print 'a' == \"a\";"),
+            Err(Error::TypeMismatch(
+                "==".to_string(),
+                Type::Char,
+                Type::String,
+                1,
+                11
+            ))
+        );
+
+        let message = |operator: &str, left, right| {
+            Error::TypeMismatch(operator.to_string(), left, right, 1, 1).to_string()
+        };
+        assert_eq!(
+            message("+", Type::Int, Type::String),
+            "Operator '+' cannot be applied to int and string, convert the int with 'str' first"
+        );
+        assert_eq!(
+            message("!=", Type::String, Type::Boolean),
+            "Operator '!=' cannot be applied to string and boolean, only values of the same type can be compared"
+        );
+        assert_eq!(
+            message("<", Type::String, Type::Int),
+            "Operator '<' cannot be applied to string and int, \
+             only two numbers or two strings (compared lexicographically) can be ordered"
+        );
+        assert_eq!(
+            message("-", Type::String, Type::String),
+            "Operator '-' cannot be applied to string and string"
+        );
+    }
+
+    #[test]
+    fn test_type_inference() {
+        assert_eq!(
+            analyze("let a = 1 + 2.5; let b = \"a\" + \"b\"; print -a < 3 == !(b == \"ab\");"),
+            Ok(())
+        );
+        // Nothing is known about the results of declared functions, parameters of unknown types or reassigned variables.
+        assert_eq!(
+            analyze(
+                "fn f(x: i32, g: function) -> float { return clock() - g() + x; } print len(\"a\") + f(1, clock);"
+            ),
+            Ok(())
+        );
+        assert_eq!(analyze("let a = 1; a = \"a\"; print a + \"b\";"), Ok(()));
+        assert_eq!(
+            analyze("let a = none ?? 1; print (a ?? \"b\") - 1;"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_destructuring() {
+        assert_eq!(
+            analyze("let (a, b) = (1, \"b\"); print a + 1; print b + \"c\";"),
+            Ok(())
+        );
+        // The values of a destructured tuple literal keep their types.
+        assert_eq!(
+            analyze("let (a, b) = (1, \"b\"); print a + b;"),
+            Err(Error::TypeMismatch(
+                "+".to_string(),
+                Type::Int,
+                Type::String,
+                1,
+                32
+            ))
+        );
+        assert_eq!(
+            analyze("const (a, b) = (1, 2); a = 3; print b;"),
+            Err(Error::AssignmentToConst("a".to_string(), 1, 24))
+        );
+        // The results of declared functions are only checked when the program runs.
+        assert_eq!(analyze("fn f() {} let (a, b) = f(); print a + b;"), Ok(()));
+    }
+
+    #[test]
+    fn test_destructuring_arity() {
+        assert_eq!(
+            analyze("let (a, b, c) = (1, 2); print a + b + c;"),
+            Err(Error::DestructureCount(3, 2, 1, 18))
+        );
+        assert_eq!(
+            analyze("let t = (1, 2, 3);\nlet (a, b) = t; print a + b;"),
+            Err(Error::DestructureCount(2, 3, 2, 14))
+        );
+        assert_eq!(
+            analyze("let (a, b) = 1; print a + b;"),
+            Err(Error::NotATuple(Type::Int, 1, 14))
+        );
+        assert_eq!(
+            Error::DestructureCount(3, 2, 1, 18).to_string(),
+            "Cannot destructure a tuple of 2 values into 3 variables"
+        );
+    }
+
+    #[test]
+    fn test_int_and_float() {
+        assert_eq!(
+            analyze("let a: int = 5 / 2; let b: float = 1; let c: float = a * 0.5; b = a;"),
+            Ok(())
+        );
+        assert_eq!(analyze("fn f() -> float { return 1; }"), Ok(()));
+        // Integers become floats when mixed with them, so the result is not an integer.
+        assert_eq!(
+            analyze("let a: int = 1 + 2.0;"),
+            Err(Error::AssignmentMismatch(
+                "a".to_string(),
+                Type::Int,
+                Type::Float,
+                1,
+                14
+            ))
+        );
+        assert_eq!(
+            analyze("let a: int = -1e3;"),
+            Err(Error::AssignmentMismatch(
+                "a".to_string(),
+                Type::Int,
+                Type::Float,
+                1,
+                14
+            ))
+        );
+        assert_eq!(
+            analyze("fn f() -> int { return 0.5; }"),
+            Err(Error::ReturnTypeMismatch(Type::Int, Type::Float, 1, 17))
+        );
+    }
+
+    #[test]
+    fn test_type_annotations() {
+        assert_eq!(
+            analyze("let a: int = 1 + 2; let b: string = \"b\"; let c: bool = a < 5; a = 4;"),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("let x: int = \"hi\";"),
+            Err(Error::AssignmentMismatch(
+                "x".to_string(),
+                Type::Int,
+                Type::String,
+                1,
+                14
+            ))
+        );
+        assert_eq!(
+            analyze("let x: int;\nx = (\"s\");"),
+            Err(Error::AssignmentMismatch(
+                "x".to_string(),
+                Type::Int,
+                Type::String,
+                2,
+                6
+            ))
+        );
+        assert_eq!(
+            analyze("fn f(flag: bool) { flag = 1; }"),
+            Err(Error::AssignmentMismatch(
+                "flag".to_string(),
+                Type::Boolean,
+                Type::Int,
+                1,
+                27
+            ))
+        );
+        assert_eq!(analyze("let c: char = 'c'; c = '\\n';"), Ok(()));
+        assert_eq!(
+            analyze("let s: string = 'c';"),
+            Err(Error::AssignmentMismatch(
+                "s".to_string(),
+                Type::String,
+                Type::Char,
+                1,
+                17
+            ))
+        );
+        // Without an annotation, any value can be assigned.
+        assert_eq!(analyze("let x = 1; x = \"s\"; x = true;"), Ok(()));
+        assert_eq!(
+            analyze("let x: integer = 1;"),
+            Err(Error::UnknownType("integer".to_string(), 1, 8))
+        );
+        assert_eq!(
+            analyze("fn f(x: number) {}"),
+            Err(Error::UnknownType("number".to_string(), 1, 9))
+        );
+    }
+
+    #[test]
+    fn test_modules() {
+        // A module's members do not collide with the names outside of it.
+        assert_eq!(
+            analyze(
+                "module math { const pi = 3.14; fn add(a: int, b: int) -> int { return a + b; } }\nfn add(a: str, b: str) -> str { return a + b; }\nlet x: float = math.pi;\nprint math.add(1, 2) + add(\"a\", \"b\");"
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("module math { const pi = 3.14; }\nlet x: int = math.pi;"),
+            Err(Error::AssignmentMismatch(
+                "x".to_string(),
+                Type::Int,
+                Type::Float,
+                2,
+                14
+            ))
+        );
+        assert_eq!(
+            analyze("module math { fn add(a: int, b: int) -> int { return a + b; } }\nprint math.add(1);"),
+            Err(Error::ArgumentCount("math.add".to_string(), 2, 1, 2, 17))
+        );
+
+        // Members are only reachable through the module's name.
+        assert_eq!(
+            analyze("module math { const pi = 3.14; }\nprint pi;"),
+            Err(Error::UnqualifiedMember(
+                "pi".to_string(),
+                "math".to_string(),
+                2,
+                7
+            ))
+        );
+        assert_eq!(
+            analyze("module math { fn f() {} }\nf();"),
+            Err(Error::UnqualifiedMember(
+                "f".to_string(),
+                "math".to_string(),
+                2,
+                1
+            ))
+        );
+        assert_eq!(
+            Error::UnqualifiedMember("pi".to_string(), "math".to_string(), 2, 7).to_string(),
+            "'pi' is a member of module 'math', use 'math.pi'"
+        );
+        assert_eq!(
+            analyze("module math {}\nprint math.pi;"),
+            Err(Error::MemberNotFound(
+                "pi".to_string(),
+                "math".to_string(),
+                2,
+                12
+            ))
+        );
+        assert_eq!(
+            analyze("let a = 1;\nprint a.b;"),
+            Err(Error::NotAModule("a".to_string(), 2, 7))
+        );
+        assert_eq!(
+            analyze("module a {}\nmodule a {}"),
+            Err(Error::VariableRedeclaration("a".to_string(), 2, 8))
+        );
+    }
+
+    #[test]
+    fn test_scope_lookups() {
+        let statements = parse(
+            "fn main() { let a = 1; let b = a; { let a = b; let c; } let d = 2; let e = 3; let f = 4; }",
+        )
+        .expect("Parsing should succeed.");
+
+        let mut analyzer = Analyzer::new();
+        analyzer.analyze(&statements);
+        assert_eq!(analyzer.errors(), []);
+        // Unused variables are reported in the order they were declared, one scope at a time.
+        assert_eq!(
+            analyzer
+                .warnings()
+                .iter()
+                .map(Warning::to_string)
+                .collect::<Vec<_>>(),
+            [
+                "Variable 'a' is never used",
+                "Variable 'c' is never used",
+                "Variable 'd' is never used",
+                "Variable 'e' is never used",
+                "Variable 'f' is never used",
+            ]
+        );
+        assert_eq!(
+            analyze("fn main() { let a = 1; { let b = a; } let b = 2; let a = b; }"),
+            Err(Error::VariableRedeclaration("a".to_string(), 1, 54))
+        );
+    }
+
+    #[test]
+    fn test_shadowing_warnings() {
+        let source = "let x = 1;\nfn f(x: i32) -> int {\n    let y = x;\n    {\n        let y = 2;\n        print y;\n    }\n    let len = y;\n    return len;\n}\n{\n    let x = 3;\n    print x + f(x);\n}";
+        let statements = parse(source).expect("Parsing should succeed.");
+
+        let mut analyzer = Analyzer::new();
+        analyzer.analyze(&statements);
+        assert_eq!(analyzer.errors(), []);
+        assert_eq!(analyzer.warnings(), []);
+
+        // The parameter shadowing the global and the local shadowing a native function are not reported.
+        let mut analyzer = Analyzer::new().with_warn_shadowing(true);
+        analyzer.analyze(&statements);
+        assert_eq!(analyzer.errors(), []);
+        assert_eq!(
+            analyzer.warnings(),
+            [
+                Warning::ShadowedVariable("y".to_string(), 5, 13, 3, 9),
+                Warning::ShadowedVariable("x".to_string(), 12, 9, 1, 5),
+            ]
+        );
+        assert_eq!(
+            analyzer.warnings()[1].to_string(),
+            "Variable 'x' shadows the variable declared at line 1, column 5"
+        );
+
+        // Parameters of nested functions shadowing locals are reported.
+        let mut analyzer = Analyzer::new().with_warn_shadowing(true);
+        analyzer.analyze(
+            &parse("fn main(a: i32) -> int { fn g(a: i32) -> int { return a; } return g(a); }")
+                .unwrap(),
+        );
+        assert_eq!(analyzer.errors(), []);
+        assert_eq!(
+            analyzer.warnings(),
+            [Warning::ShadowedVariable("a".to_string(), 1, 31, 1, 9)]
+        );
+    }
+
+    #[test]
+    fn test_disabled_warnings() {
+        let source = "fn unused() {}\nfn main() {\n    let a = 1;\n    { let a = 2; print a; }\n    print 1.0 / 0;\n}";
+        let statements = parse(source).expect("Parsing should succeed.");
+        let analyze = |options: AnalyzerOptions| {
+            let mut analyzer = Analyzer::new()
+                .with_warn_shadowing(true)
+                .with_options(options);
+            let diagnostics = analyzer.analyze(&statements);
+            let mut names = analyzer
+                .warnings()
+                .iter()
+                .map(Warning::name)
+                .collect::<Vec<_>>();
+            names.sort();
+
+            (names, diagnostics)
+        };
+        let disabled = |names: &[&str]| AnalyzerOptions {
+            disabled_warnings: names.iter().map(|name| name.to_string()).collect(),
+            warnings_as_errors: false,
+        };
+
+        let all = [
+            "division-by-zero",
+            "shadowing",
+            "unused-function",
+            "unused-variable",
+        ];
+        assert_eq!(analyze(AnalyzerOptions::default()).0, all);
+        for name in all {
+            let expected = all
+                .into_iter()
+                .filter(|other| *other != name)
+                .collect::<Vec<_>>();
+            assert_eq!(analyze(disabled(&[name])).0, expected);
+        }
+        assert_eq!(analyze(disabled(&all)).0, Vec::<&str>::new());
+
+        // Unknown names are reported, and can be disabled like any other warning.
+        let (names, diagnostics) = analyze(disabled(&["unused-variables"]));
+        assert_eq!(
+            names,
+            [
+                "division-by-zero",
+                "shadowing",
+                "unknown-warning",
+                "unused-function",
+                "unused-variable"
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].message,
+            "Unknown warning 'unused-variables', expected one of: unused-variable, unused-function, shadowing, division-by-zero, unreachable-code, unknown-warning"
+        );
+        assert_eq!(analyze(disabled(&["typo", "unknown-warning"])).0, all);
+
+        // Warnings can be reported as errors.
+        let (_, diagnostics) = analyze(AnalyzerOptions {
+            warnings_as_errors: true,
+            ..disabled(&["shadowing"])
+        });
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == errors::Severity::Error));
+    }
+
+    #[test]
+    fn test_assignment_initializes() {
+        assert_eq!(analyze("let x; x = 5; print x;"), Ok(()));
+        assert_eq!(
+            analyze("let x; print x; x = 5;"),
+            Err(Error::VariableNotInitialized("x".to_string(), 1, 14))
+        );
+        assert_eq!(
+            analyze("x = 5;"),
+            Err(Error::VariableNotFound("x".to_string(), None, 1, 1))
+        );
+        // The first value gives the variable its type.
+        assert_eq!(
+            analyze("let x; x = \"a\"; print x - 1;"),
+            Err(Error::TypeMismatch(
+                "-".to_string(),
+                Type::String,
+                Type::Int,
+                1,
+                25
+            ))
+        );
+    }
+
+    #[test]
+    fn test_forward_declarations() {
+        assert_eq!(
+            analyze(
+                r#"
+                fn main() {
+                    print helper(1) + is_odd(3);
+                }
+
+                main();
+
+                fn is_even(n: i32) -> bool {
+                    return n == 0 || is_odd(n - 1);
+                }
+
+                fn is_odd(n: i32) -> bool {
+                    return n != 0 && is_even(n - 1);
+                }
+
+                fn helper(x: i32) -> int {
+                    return x * 2;
+                }
+                "#
+            ),
+            Ok(())
+        );
+        // Only functions are declared ahead of time.
+        assert_eq!(
+            analyze("print value;\nlet value = 1;"),
+            Err(Error::VariableNotFound("value".to_string(), None, 1, 7))
+        );
+        assert_eq!(
+            analyze("print twice(1);\nlet twice = len;"),
+            Err(Error::FunctionNotFound("twice".to_string(), None, 1, 7))
+        );
+        assert_eq!(
+            analyze("fn main() -> int { return missing(); }"),
+            Err(Error::FunctionNotFound("missing".to_string(), None, 1, 27))
+        );
+    }
+
+    #[test]
+    fn test_reports_all_errors() {
+        let statements = parse(
+            "fn main() {\n    let a = 1;\n    let a = 2;\n    print a;\n}\nlet b;\nprint c + b;",
+        )
+        .expect("Parsing should succeed.");
+
+        let mut analyzer = Analyzer::new();
+        let diagnostics = analyzer.analyze(&statements);
+
+        assert_eq!(
+            analyzer.errors(),
+            [
+                Error::VariableRedeclaration("a".to_string(), 3, 9),
+                Error::VariableNotFound("c".to_string(), None, 7, 7),
+                Error::VariableNotInitialized("b".to_string(), 7, 11),
+            ]
+        );
+        assert_eq!(
+            diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.line, diagnostic.column))
+                .collect::<Vec<_>>(),
+            [(3, 9), (7, 7), (7, 11)]
+        );
+    }
+
+    #[test]
+    fn test_assignment_to_const() {
+        assert_eq!(
+            analyze("const a = 1;\na = 2;"),
+            Err(Error::AssignmentToConst("a".to_string(), 2, 1))
+        );
+        assert_eq!(
+            analyze("const a = 1;\n{ a = 2; }"),
+            Err(Error::AssignmentToConst("a".to_string(), 2, 3))
+        );
+        // A variable shadowing a constant is a new binding, so it can be assigned to.
+        assert_eq!(
+            analyze("const a = 1;\n{ let a = 2; a = 3; print a; }"),
+            Ok(())
+        );
+        assert_eq!(
+            Error::AssignmentToConst("a".to_string(), 2, 1).to_string(),
+            "Cannot assign to constant 'a'"
+        );
+    }
+
+    #[test]
+    fn test_return_types() {
+        assert_eq!(
+            analyze("fn f() -> int { return \"a\"; }"),
+            Err(Error::ReturnTypeMismatch(Type::Int, Type::String, 1, 17))
+        );
+        assert_eq!(
+            analyze("fn f() -> str { return; }"),
+            Err(Error::MissingReturnValue(Type::String, 1, 17))
+        );
+        // Functions without a return type return none.
+        assert_eq!(
+            analyze("fn f() { return 5; }"),
+            Err(Error::UnexpectedReturnValue(Type::Int, 1, 10))
+        );
+        assert_eq!(
+            analyze("fn f() { return; } fn g() { return none; }"),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("fn f(a: bool) -> int { if (a) return 1; }"),
+            Err(Error::MissingReturn("f".to_string(), Type::Int, 1, 4))
+        );
+        assert_eq!(
+            analyze("fn f(a: bool) -> int { if (a) { return 1; } else return 2; }"),
+            Ok(())
+        );
+        // Loops that never finish cannot fall off the end of the function.
+        assert_eq!(analyze("fn f() -> int { loop { return 1; } }"), Ok(()));
+        assert_eq!(
+            analyze("fn f(a: int) -> int { while (true) { if (a > 1) return a; } }"),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("fn f() -> int { for (;;) { loop { break; } return 1; } }"),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("fn f(a: bool) -> int { loop { if (a) break; return 1; } }"),
+            Err(Error::MissingReturn("f".to_string(), Type::Int, 1, 4))
+        );
+        assert_eq!(
+            analyze("fn f(a: bool) -> int { while (a) { return 1; } }"),
+            Err(Error::MissingReturn("f".to_string(), Type::Int, 1, 4))
+        );
+        // Nested functions have their own return type.
+        assert_eq!(
+            analyze("fn f() -> str { fn g() -> bool { return true; } g(); return \"s\"; }"),
+            Ok(())
+        );
+        assert_eq!(
+            analyze("fn f() -> integer { return 1; }"),
+            Err(Error::UnknownType("integer".to_string(), 1, 11))
+        );
+        assert_eq!(
+            Error::ReturnTypeMismatch(Type::Int, Type::String, 1, 17).to_string(),
+            "Cannot return string from a function that returns int"
+        );
+    }
+
+    #[test]
+    fn test_symbol_table() {
+        let statements = parse(
+            "fn add(a: int, b: int) -> int { let sum = a + b; return sum; } \
+             { const x = add(1, 2); let y; y = 'c'; print x; print y; }",
+        )
+        .expect("Parsing should succeed.");
+        let mut analyzer = Analyzer::new();
+        analyzer.analyze(&statements);
+        assert_eq!(analyzer.errors(), []);
+
+        // Every name is written the same way throughout, so only the start of each span is given.
+        let symbol = |name: &str, kind, start: usize, r#type, usages: &[usize]| SymbolEntry {
+            name: name.to_string(),
+            kind,
+            span: start..start + name.len(),
+            r#type,
+            usages: usages
+                .iter()
+                .map(|start| *start..start + name.len())
+                .collect(),
+        };
+
+        assert_eq!(
+            analyzer.into_symbols().scopes,
+            [
+                Scope {
+                    parent: None,
+                    symbols: vec![symbol(
+                        "add",
+                        SymbolKind::Function,
+                        3,
+                        Some(Type::Int),
+                        &[75]
+                    )],
+                },
+                Scope {
+                    parent: Some(0),
+                    symbols: vec![
+                        symbol("a", SymbolKind::Parameter, 7, Some(Type::Int), &[42]),
+                        symbol("b", SymbolKind::Parameter, 15, Some(Type::Int), &[46]),
+                        symbol("sum", SymbolKind::Variable, 36, Some(Type::Int), &[56]),
+                    ],
+                },
+                Scope {
+                    parent: Some(0),
+                    symbols: vec![
+                        // The result of a call is only known at runtime.
+                        symbol("x", SymbolKind::Constant, 71, None, &[108]),
+                        // The first value given to a variable decides its type.
+                        symbol("y", SymbolKind::Variable, 90, Some(Type::Char), &[93, 117]),
+                    ],
+                },
+            ]
+        );
+    }
+}
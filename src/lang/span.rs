@@ -0,0 +1,479 @@
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::lang::lexer::Token;
+use crate::lang::parser::{Expression, Statement};
+
+/// A piece of the source code, such as the text a token or a syntax tree node was parsed from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Span {
+    /// The byte offset of the first character.
+    pub start: usize,
+    /// The byte offset just past the last character.
+    pub end: usize,
+    /// The line of the first character.
+    pub line: usize,
+    /// The column of the first character.
+    pub column: usize,
+}
+
+impl Span {
+    /// Creates a span from the start of this one to the end of another.
+    ///
+    /// # Arguments
+    /// * `other` - The span to extend to, which must not start before this one.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            end: self.end.max(other.end),
+            ..self
+        }
+    }
+
+    /// Gets the byte range the span covers, for slicing the source code.
+    pub fn range(self) -> Range<usize> {
+        self.start..self.end
+    }
+}
+
+impl Token {
+    /// Gets the span of the source code the token was scanned from.
+    pub fn span(&self) -> Span {
+        Span {
+            start: self.start,
+            end: self.end,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+impl Expression {
+    /// Gets the span of the source code the expression was parsed from.
+    /// The parentheses around a grouping or a tuple are part of it.
+    pub fn span(&self) -> Span {
+        match self {
+            Expression::Binary { span, .. }
+            | Expression::Coalesce { span, .. }
+            | Expression::Unary { span, .. }
+            | Expression::Literal { span, .. }
+            | Expression::Assign { span, .. }
+            | Expression::Call { span, .. }
+            | Expression::Qualified { span, .. }
+            | Expression::Grouping(_, span)
+            | Expression::Variable(_, span)
+            | Expression::Tuple(_, span)
+            | Expression::TypeOf(_, span) => *span,
+        }
+    }
+
+    /// Gets the first token of the expression, which is where errors about the whole expression are reported.
+    pub fn first_token(&self) -> &Token {
+        match self {
+            Expression::Binary { left, .. }
+            | Expression::Coalesce { left, .. }
+            | Expression::Grouping(left, _)
+            | Expression::TypeOf(left, _)
+            | Expression::Call { callee: left, .. } => left.first_token(),
+            Expression::Literal { token, .. }
+            | Expression::Variable(token, _)
+            | Expression::Unary {
+                operator: token, ..
+            }
+            | Expression::Assign { name: token, .. }
+            | Expression::Qualified { module: token, .. } => token,
+            Expression::Tuple(elements, _) => elements[0].first_token(),
+        }
+    }
+}
+
+impl Statement {
+    /// Gets the span of the source code the statement was parsed from,
+    /// from its first keyword up to and including its closing brace or semicolon.
+    pub fn span(&self) -> Span {
+        match self {
+            Statement::Variable { span, .. }
+            | Statement::Destructure { span, .. }
+            | Statement::If { span, .. }
+            | Statement::While { span, .. }
+            | Statement::Loop { span, .. }
+            | Statement::For { span, .. }
+            | Statement::Function { span, .. }
+            | Statement::Return { span, .. }
+            | Statement::Break { span, .. }
+            | Statement::Continue { span, .. }
+            | Statement::Assert { span, .. }
+            | Statement::Include { span, .. }
+            | Statement::Module { span, .. }
+            | Statement::Comment { span, .. }
+            | Statement::Expression(_, span)
+            | Statement::Print(_, span)
+            | Statement::Block(_, span) => *span,
+        }
+    }
+}
+
+impl Expression {
+    /// Checks whether two expressions have the same code, no matter where they were parsed from,
+    /// comparing their tokens by type, lexeme and literal.
+    ///
+    /// # Examples
+    /// ```
+    /// use cpl::lang::parse;
+    /// use cpl::lang::parser::Statement;
+    ///
+    /// let (a, b) = (parse("1 + 2;").unwrap(), parse("\n\n  1+2 ;").unwrap());
+    /// let (Statement::Expression(a, _), Statement::Expression(b, _)) = (&a[0], &b[0]) else { unreachable!() };
+    ///
+    /// assert_ne!(a, b);
+    /// assert!(a.eq_ignoring_spans(b));
+    /// ```
+    pub fn eq_ignoring_spans(&self, other: &Expression) -> bool {
+        let (mut this, mut other) = (self.clone(), other.clone());
+        this.clear_spans();
+        other.clear_spans();
+
+        this == other
+    }
+
+    /// Clears the positions of the expression and its tokens.
+    fn clear_spans(&mut self) {
+        self.update_positions(&mut Token::clear_position, &mut |span| {
+            *span = Span::default()
+        });
+    }
+
+    /// Updates the positions of the expression and its tokens.
+    ///
+    /// # Arguments
+    /// * `on_token` - Updates the position of a token.
+    /// * `on_span` - Updates a span.
+    fn update_positions(
+        &mut self,
+        on_token: &mut dyn FnMut(&mut Token),
+        on_span: &mut dyn FnMut(&mut Span),
+    ) {
+        match self {
+            Expression::Binary {
+                left,
+                operator,
+                right,
+                span,
+            }
+            | Expression::Coalesce {
+                left,
+                operator,
+                right,
+                span,
+            } => {
+                left.update_positions(on_token, on_span);
+                on_token(operator);
+                right.update_positions(on_token, on_span);
+                on_span(span);
+            }
+            Expression::Grouping(expression, span) | Expression::TypeOf(expression, span) => {
+                expression.update_positions(on_token, on_span);
+                on_span(span);
+            }
+            Expression::Literal { token, span, .. } | Expression::Variable(token, span) => {
+                on_token(token);
+                on_span(span);
+            }
+            Expression::Unary {
+                operator,
+                right,
+                span,
+            } => {
+                on_token(operator);
+                right.update_positions(on_token, on_span);
+                on_span(span);
+            }
+            Expression::Assign { name, value, span } => {
+                on_token(name);
+                value.update_positions(on_token, on_span);
+                on_span(span);
+            }
+            Expression::Call {
+                callee,
+                parenthesis,
+                arguments,
+                span,
+            } => {
+                callee.update_positions(on_token, on_span);
+                on_token(parenthesis);
+                arguments
+                    .iter_mut()
+                    .for_each(|expression| expression.update_positions(on_token, on_span));
+                on_span(span);
+            }
+            Expression::Qualified { module, name, span } => {
+                on_token(module);
+                on_token(name);
+                on_span(span);
+            }
+            Expression::Tuple(elements, span) => {
+                elements
+                    .iter_mut()
+                    .for_each(|expression| expression.update_positions(on_token, on_span));
+                on_span(span);
+            }
+        }
+    }
+}
+
+impl Statement {
+    /// Checks whether two statements have the same code, no matter where they were parsed from,
+    /// comparing their tokens by type, lexeme and literal.
+    pub fn eq_ignoring_spans(&self, other: &Statement) -> bool {
+        let (mut this, mut other) = (self.clone(), other.clone());
+        this.clear_spans();
+        other.clear_spans();
+
+        this == other
+    }
+
+    /// Clears the positions of the statement, its tokens and everything nested in it.
+    fn clear_spans(&mut self) {
+        self.update_positions(&mut Token::clear_position, &mut |span| {
+            *span = Span::default()
+        });
+    }
+
+    /// Updates the positions of the statement, its tokens and everything nested in it.
+    ///
+    /// # Arguments
+    /// * `on_token` - Updates the position of a token.
+    /// * `on_span` - Updates a span.
+    pub(crate) fn update_positions(
+        &mut self,
+        on_token: &mut dyn FnMut(&mut Token),
+        on_span: &mut dyn FnMut(&mut Span),
+    ) {
+        let span = match self {
+            Statement::Expression(expression, span) | Statement::Print(expression, span) => {
+                expression.update_positions(on_token, on_span);
+                span
+            }
+            Statement::Variable {
+                name,
+                declared_type,
+                initializer,
+                span,
+                ..
+            } => {
+                on_token(name);
+                declared_type.iter_mut().for_each(&mut *on_token);
+                initializer
+                    .iter_mut()
+                    .for_each(|expression| expression.update_positions(on_token, on_span));
+                span
+            }
+            Statement::Destructure {
+                names,
+                initializer,
+                span,
+                ..
+            } => {
+                names.iter_mut().for_each(&mut *on_token);
+                initializer.update_positions(on_token, on_span);
+                span
+            }
+            Statement::Block(statements, span) => {
+                statements
+                    .iter_mut()
+                    .for_each(|statement| statement.update_positions(on_token, on_span));
+                span
+            }
+            Statement::If {
+                condition,
+                then_branch,
+                else_branch,
+                span,
+            } => {
+                condition.update_positions(on_token, on_span);
+                then_branch.update_positions(on_token, on_span);
+                else_branch
+                    .iter_mut()
+                    .for_each(|branch| branch.update_positions(on_token, on_span));
+                span
+            }
+            Statement::While {
+                condition,
+                body,
+                span,
+            } => {
+                condition.update_positions(on_token, on_span);
+                body.update_positions(on_token, on_span);
+                span
+            }
+            Statement::Loop { body, span } => {
+                body.update_positions(on_token, on_span);
+                span
+            }
+            Statement::For {
+                initializer,
+                condition,
+                increment,
+                body,
+                span,
+            } => {
+                initializer
+                    .iter_mut()
+                    .for_each(|initializer| initializer.update_positions(on_token, on_span));
+                condition
+                    .iter_mut()
+                    .for_each(|expression| expression.update_positions(on_token, on_span));
+                increment
+                    .iter_mut()
+                    .for_each(|expression| expression.update_positions(on_token, on_span));
+                body.update_positions(on_token, on_span);
+                span
+            }
+            Statement::Function {
+                name,
+                parameters,
+                return_type,
+                body,
+                span,
+            } => {
+                on_token(name);
+                for (parameter, r#type) in parameters {
+                    on_token(parameter);
+                    on_token(r#type);
+                }
+                return_type.iter_mut().for_each(&mut *on_token);
+                body.update_positions(on_token, on_span);
+                span
+            }
+            Statement::Return {
+                keyword,
+                value,
+                span,
+            } => {
+                on_token(keyword);
+                value
+                    .iter_mut()
+                    .for_each(|expression| expression.update_positions(on_token, on_span));
+                span
+            }
+            Statement::Break { keyword, span } | Statement::Continue { keyword, span } => {
+                on_token(keyword);
+                span
+            }
+            Statement::Assert {
+                keyword,
+                condition,
+                message,
+                span,
+            } => {
+                on_token(keyword);
+                condition.update_positions(on_token, on_span);
+                message
+                    .iter_mut()
+                    .for_each(|expression| expression.update_positions(on_token, on_span));
+                span
+            }
+            Statement::Include {
+                keyword,
+                path,
+                alias,
+                span,
+            } => {
+                on_token(keyword);
+                on_token(path);
+                alias.iter_mut().for_each(&mut *on_token);
+                span
+            }
+            Statement::Module {
+                name,
+                statements,
+                span,
+            } => {
+                on_token(name);
+                statements
+                    .iter_mut()
+                    .for_each(|statement| statement.update_positions(on_token, on_span));
+                span
+            }
+            Statement::Comment { token, span, .. } => {
+                on_token(token);
+                span
+            }
+        };
+
+        on_span(span);
+    }
+}
+
+impl Token {
+    /// Clears where the token was scanned from, along with its interned lexeme,
+    /// which tokens created by hand do not have.
+    fn clear_position(&mut self) {
+        self.symbol = None;
+        self.line = 0;
+        self.column = 0;
+        self.start = 0;
+        self.end = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::lex;
+    use crate::lang::parse;
+
+    #[test]
+    fn test_token_span() {
+        let source = "let größe = \"äöü\";";
+        let tokens = lex(source).expect("Scanning should succeed.");
+
+        assert_eq!(&*tokens[1].lexeme, "größe");
+        assert_eq!(tokens[1].span().range(), 4..11);
+        assert_eq!(&source[tokens[3].span().range()], "\"äöü\"");
+        assert_eq!(
+            tokens[4].span(),
+            Span {
+                start: 22,
+                end: 23,
+                line: 1,
+                column: 18,
+            }
+        );
+    }
+
+    #[test]
+    fn test_node_span() {
+        let source = "fn f(a: i32) {\n    return -a + g(1, \"x\");\n}\nprint (1 + 2) * 3;";
+        let statements = parse(source).expect("Parsing should succeed.");
+        let text = |span: Span| &source[span.range()];
+
+        assert_eq!(text(statements[0].span()), &source[..43]);
+        assert_eq!(text(statements[1].span()), "print (1 + 2) * 3;");
+
+        let Statement::Function { body, .. } = &statements[0] else {
+            panic!("The statement should be a function.");
+        };
+        let Statement::Block(body, _) = body.as_ref() else {
+            panic!("The body should be a block.");
+        };
+        assert_eq!(text(body[0].span()), "return -a + g(1, \"x\");");
+        let Statement::Return {
+            value: Some(value), ..
+        } = &body[0]
+        else {
+            panic!("The body should return a value.");
+        };
+        let span = value.span();
+        assert_eq!(text(span), "-a + g(1, \"x\")");
+        assert_eq!((span.line, span.column), (2, 12));
+        let first = value.first_token();
+        assert_eq!((&*first.lexeme, first.line, first.column), ("-", 2, 12));
+
+        let Statement::Print(Expression::Binary { left, .. }, _) = &statements[1] else {
+            panic!("The statement should print a product.");
+        };
+        assert_eq!(text(left.span()), "(1 + 2)");
+        assert_eq!((left.span().line, left.span().column), (4, 7));
+    }
+}
@@ -0,0 +1,227 @@
+use std::fmt::Write;
+use std::ops::Range;
+
+use crate::lang::semantic_analyzer::Type;
+
+/// What a name was declared as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    Variable,
+    Constant,
+    Function,
+    Parameter,
+    Module,
+}
+
+impl SymbolKind {
+    fn name(self) -> &'static str {
+        match self {
+            SymbolKind::Variable => "variable",
+            SymbolKind::Constant => "constant",
+            SymbolKind::Function => "function",
+            SymbolKind::Parameter => "parameter",
+            SymbolKind::Module => "module",
+        }
+    }
+}
+
+/// A name declared in the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolEntry {
+    pub name: String,
+    pub kind: SymbolKind,
+    /// The byte range of the name in its declaration.
+    pub span: Range<usize>,
+    /// The type of the variable, or the type a function returns, if known.
+    pub r#type: Option<Type>,
+    /// The byte ranges of every reference to the name, including assignments and calls.
+    pub usages: Vec<Range<usize>>,
+}
+
+/// The names declared directly within a scope, in the order they were declared.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scope {
+    /// The index of the enclosing scope, or `None` for the global scope.
+    pub parent: Option<usize>,
+    pub symbols: Vec<SymbolEntry>,
+}
+
+/// Every name declared in a program, by scope.
+/// The native functions have no declaration, so they are not included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolTable {
+    /// The scopes in the order they were opened, starting with the global scope.
+    pub scopes: Vec<Scope>,
+}
+
+impl SymbolTable {
+    /// Creates a new symbol table containing only the global scope.
+    pub fn new() -> Self {
+        Self {
+            scopes: vec![Scope::default()],
+        }
+    }
+
+    /// Opens a scope within another one.
+    ///
+    /// # Arguments
+    /// * `parent` - The index of the enclosing scope.
+    ///
+    /// # Returns
+    /// The index of the new scope.
+    pub(crate) fn add_scope(&mut self, parent: usize) -> usize {
+        self.scopes.push(Scope {
+            parent: Some(parent),
+            symbols: Vec::new(),
+        });
+
+        self.scopes.len() - 1
+    }
+
+    /// Adds a declaration to a scope.
+    ///
+    /// # Returns
+    /// The index of the scope and the index of the symbol within it.
+    pub(crate) fn add_symbol(&mut self, scope: usize, symbol: SymbolEntry) -> (usize, usize) {
+        let symbols = &mut self.scopes[scope].symbols;
+        symbols.push(symbol);
+
+        (scope, symbols.len() - 1)
+    }
+
+    /// Gets a declaration by the indices `add_symbol` returned.
+    pub(crate) fn get_mut(&mut self, (scope, index): (usize, usize)) -> &mut SymbolEntry {
+        &mut self.scopes[scope].symbols[index]
+    }
+
+    /// Serializes the table to JSON, for inspection by external tools.
+    /// Unknown types are written as `null`.
+    ///
+    /// # Examples
+    /// ```
+    /// use cpl::lang::parse;
+    /// use cpl::lang::semantic_analyzer::Analyzer;
+    ///
+    /// let statements = parse("let a = 1; print a;").unwrap();
+    /// let mut analyzer = Analyzer::new();
+    /// analyzer.analyze(&statements);
+    ///
+    /// assert_eq!(
+    ///     analyzer.symbols().to_json(),
+    ///     r#"{"scopes":[{"parent":null,"symbols":[{"name":"a","kind":"variable","span":[4,5],"type":"int","usages":[[17,18]]}]}]}"#
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut json = String::from(r#"{"scopes":["#);
+
+        for (i, scope) in self.scopes.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+
+            match scope.parent {
+                Some(parent) => write!(json, r#"{{"parent":{},"symbols":["#, parent),
+                None => write!(json, r#"{{"parent":null,"symbols":["#),
+            }
+            .expect("Writing to a string cannot fail.");
+
+            for (i, symbol) in scope.symbols.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+
+                let r#type = match symbol.r#type {
+                    Some(r#type) => json_string(&r#type.to_string()),
+                    None => "null".to_string(),
+                };
+                let usages = symbol
+                    .usages
+                    .iter()
+                    .map(|usage| format!("[{},{}]", usage.start, usage.end))
+                    .collect::<Vec<_>>();
+
+                write!(
+                    json,
+                    r#"{{"name":{},"kind":"{}","span":[{},{}],"type":{},"usages":[{}]}}"#,
+                    json_string(&symbol.name),
+                    symbol.kind.name(),
+                    symbol.span.start,
+                    symbol.span.end,
+                    r#type,
+                    usages.join(",")
+                )
+                .expect("Writing to a string cannot fail.");
+            }
+
+            json.push_str("]}");
+        }
+
+        json.push_str("]}");
+
+        json
+    }
+}
+
+impl Default for SymbolTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Quotes a string for JSON, escaping the characters JSON does not allow in strings.
+fn json_string(string: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for character in string.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            character if character.is_control() => {
+                write!(quoted, "\\u{:04x}", character as u32)
+                    .expect("Writing to a string cannot fail.");
+            }
+            character => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_json() {
+        let mut table = SymbolTable::new();
+        let function = SymbolEntry {
+            name: "f".to_string(),
+            kind: SymbolKind::Function,
+            span: 3..4,
+            r#type: Some(Type::None),
+            usages: vec![20..21, 30..31],
+        };
+        table.add_symbol(0, function);
+        let scope = table.add_scope(0);
+        let parameter = SymbolEntry {
+            name: "a\"b".to_string(),
+            kind: SymbolKind::Parameter,
+            span: 5..6,
+            r#type: None,
+            usages: Vec::new(),
+        };
+        table.add_symbol(scope, parameter);
+
+        assert_eq!(
+            table.to_json(),
+            concat!(
+                r#"{"scopes":["#,
+                r#"{"parent":null,"symbols":[{"name":"f","kind":"function","span":[3,4],"type":"none","usages":[[20,21],[30,31]]}]},"#,
+                r#"{"parent":0,"symbols":[{"name":"a\"b","kind":"parameter","span":[5,6],"type":null,"usages":[]}]}"#,
+                "]}"
+            )
+        );
+    }
+}
@@ -0,0 +1,75 @@
+use crate::lang::generator::{Error, Generator};
+use crate::lang::llvm::LlvmGenerator;
+use crate::lang::parser::Statement;
+
+/// What code is generated for a program.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Target {
+    /// x86_64 assembly for the GNU assembler, see `Generator`.
+    #[default]
+    Assembly,
+    /// Textual LLVM IR, see `LlvmGenerator`.
+    LlvmIr,
+}
+
+impl Target {
+    /// The names of every target, as returned by `name`.
+    pub const NAMES: [&'static str; 2] = ["asm", "llvm-ir"];
+
+    /// Gets a target by its name, as given to `--emit`.
+    ///
+    /// # Returns
+    /// The target, or `None` if there is no target with the name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "asm" => Some(Target::Assembly),
+            "llvm-ir" => Some(Target::LlvmIr),
+            _ => None,
+        }
+    }
+
+    /// Gets the stable name of the target.
+    pub fn name(self) -> &'static str {
+        match self {
+            Target::Assembly => "asm",
+            Target::LlvmIr => "llvm-ir",
+        }
+    }
+
+    /// Gets the extension of the files the target's code is written to.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Target::Assembly => "s",
+            Target::LlvmIr => "ll",
+        }
+    }
+
+    /// Generates the code for a program.
+    ///
+    /// # Arguments
+    /// * `statements` - The program to generate code for, which must already have been analyzed.
+    ///
+    /// # Returns
+    /// The code, or an error for every statement that cannot be compiled for the target.
+    pub fn generate(self, statements: &[Statement]) -> Result<String, Vec<Error>> {
+        match self {
+            Target::Assembly => Generator::new(statements).generate(),
+            Target::LlvmIr => LlvmGenerator::new(statements).generate(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_names() {
+        for name in Target::NAMES {
+            let target = Target::from_name(name).expect("Every name should have a target.");
+
+            assert_eq!(target.name(), name);
+        }
+        assert_eq!(Target::from_name("wasm"), None);
+    }
+}
@@ -0,0 +1,344 @@
+use crate::lang::lexer::{Literal, Token};
+use crate::lang::parser::{Expression, Statement};
+
+/// A visitor over the syntax tree.
+///
+/// Every method has a default implementation that recurses into the children of the node,
+/// so a pass only has to override the nodes it cares about.
+/// The resolver and the semantic analyzer both walk the tree this way.
+pub trait Visitor: Sized {
+    /// Visits a statement, dispatching to the method for its variant.
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement);
+    }
+
+    /// Visits an expression, dispatching to the method for its variant.
+    fn visit_expression(&mut self, expression: &Expression) {
+        walk_expression(self, expression);
+    }
+
+    fn visit_expression_statement(&mut self, expression: &Expression) {
+        self.visit_expression(expression);
+    }
+
+    fn visit_print(&mut self, expression: &Expression) {
+        self.visit_expression(expression);
+    }
+
+    fn visit_variable_declaration(
+        &mut self,
+        _name: &Token,
+        _declared_type: Option<&Token>,
+        initializer: Option<&Expression>,
+        _mutable: bool,
+    ) {
+        if let Some(initializer) = initializer {
+            self.visit_expression(initializer);
+        }
+    }
+
+    fn visit_destructure(&mut self, _names: &[Token], initializer: &Expression, _mutable: bool) {
+        self.visit_expression(initializer);
+    }
+
+    fn visit_block(&mut self, statements: &[Statement]) {
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+    }
+
+    fn visit_if(
+        &mut self,
+        condition: &Expression,
+        then_branch: &Statement,
+        else_branch: Option<&Statement>,
+    ) {
+        self.visit_expression(condition);
+        self.visit_statement(then_branch);
+
+        if let Some(else_branch) = else_branch {
+            self.visit_statement(else_branch);
+        }
+    }
+
+    fn visit_while(&mut self, condition: &Expression, body: &Statement) {
+        self.visit_expression(condition);
+        self.visit_statement(body);
+    }
+
+    fn visit_loop(&mut self, body: &Statement) {
+        self.visit_statement(body);
+    }
+
+    fn visit_for(
+        &mut self,
+        initializer: Option<&Statement>,
+        condition: Option<&Expression>,
+        increment: Option<&Expression>,
+        body: &Statement,
+    ) {
+        if let Some(initializer) = initializer {
+            self.visit_statement(initializer);
+        }
+
+        if let Some(condition) = condition {
+            self.visit_expression(condition);
+        }
+
+        if let Some(increment) = increment {
+            self.visit_expression(increment);
+        }
+
+        self.visit_statement(body);
+    }
+
+    fn visit_function(
+        &mut self,
+        _name: &Token,
+        _parameters: &[(Token, Token)],
+        _return_type: Option<&Token>,
+        body: &Statement,
+    ) {
+        self.visit_statement(body);
+    }
+
+    fn visit_return(&mut self, _keyword: &Token, value: Option<&Expression>) {
+        if let Some(value) = value {
+            self.visit_expression(value);
+        }
+    }
+
+    fn visit_break(&mut self, _keyword: &Token) {}
+
+    fn visit_continue(&mut self, _keyword: &Token) {}
+
+    fn visit_assert(
+        &mut self,
+        _keyword: &Token,
+        condition: &Expression,
+        message: Option<&Expression>,
+    ) {
+        self.visit_expression(condition);
+
+        if let Some(message) = message {
+            self.visit_expression(message);
+        }
+    }
+
+    fn visit_include(&mut self, _keyword: &Token, _path: &Token, _alias: Option<&Token>) {}
+
+    fn visit_module(&mut self, _name: &Token, statements: &[Statement]) {
+        for statement in statements {
+            self.visit_statement(statement);
+        }
+    }
+
+    fn visit_comment(&mut self, _token: &Token) {}
+
+    fn visit_binary(&mut self, left: &Expression, _operator: &Token, right: &Expression) {
+        self.visit_expression(left);
+        self.visit_expression(right);
+    }
+
+    fn visit_grouping(&mut self, expression: &Expression) {
+        self.visit_expression(expression);
+    }
+
+    fn visit_literal(&mut self, _value: &Literal) {}
+
+    fn visit_unary(&mut self, _operator: &Token, right: &Expression) {
+        self.visit_expression(right);
+    }
+
+    fn visit_variable(&mut self, _name: &Token) {}
+
+    fn visit_assign(&mut self, _name: &Token, value: &Expression) {
+        self.visit_expression(value);
+    }
+
+    fn visit_call(&mut self, callee: &Expression, _parenthesis: &Token, arguments: &[Expression]) {
+        self.visit_expression(callee);
+
+        for argument in arguments {
+            self.visit_expression(argument);
+        }
+    }
+
+    fn visit_coalesce(&mut self, left: &Expression, _operator: &Token, right: &Expression) {
+        self.visit_expression(left);
+        self.visit_expression(right);
+    }
+
+    fn visit_qualified(&mut self, _module: &Token, _name: &Token) {}
+
+    fn visit_tuple(&mut self, elements: &[Expression]) {
+        for element in elements {
+            self.visit_expression(element);
+        }
+    }
+
+    fn visit_typeof(&mut self, expression: &Expression) {
+        self.visit_expression(expression);
+    }
+}
+
+/// Dispatches a statement to the visitor method for its variant.
+///
+/// # Arguments
+/// * `visitor` - The visitor to dispatch to.
+/// * `statement` - The statement to visit.
+pub fn walk_statement<V: Visitor>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Expression(expression, _) => visitor.visit_expression_statement(expression),
+        Statement::Print(expression, _) => visitor.visit_print(expression),
+        Statement::Variable {
+            name,
+            declared_type,
+            initializer,
+            mutable,
+            ..
+        } => visitor.visit_variable_declaration(
+            name,
+            declared_type.as_ref(),
+            initializer.as_ref(),
+            *mutable,
+        ),
+        Statement::Destructure {
+            names,
+            initializer,
+            mutable,
+            ..
+        } => visitor.visit_destructure(names, initializer, *mutable),
+        Statement::Block(statements, _) => visitor.visit_block(statements),
+        Statement::If {
+            condition,
+            then_branch,
+            else_branch,
+            ..
+        } => visitor.visit_if(condition, then_branch, else_branch.as_deref()),
+        Statement::While {
+            condition, body, ..
+        } => visitor.visit_while(condition, body),
+        Statement::Loop { body, .. } => visitor.visit_loop(body),
+        Statement::For {
+            initializer,
+            condition,
+            increment,
+            body,
+            ..
+        } => visitor.visit_for(
+            initializer.as_deref(),
+            condition.as_ref(),
+            increment.as_ref(),
+            body,
+        ),
+        Statement::Function {
+            name,
+            parameters,
+            return_type,
+            body,
+            ..
+        } => visitor.visit_function(name, parameters, return_type.as_ref(), body),
+        Statement::Return { keyword, value, .. } => visitor.visit_return(keyword, value.as_ref()),
+        Statement::Break { keyword, .. } => visitor.visit_break(keyword),
+        Statement::Continue { keyword, .. } => visitor.visit_continue(keyword),
+        Statement::Assert {
+            keyword,
+            condition,
+            message,
+            ..
+        } => visitor.visit_assert(keyword, condition, message.as_ref()),
+        Statement::Include {
+            keyword,
+            path,
+            alias,
+            ..
+        } => visitor.visit_include(keyword, path, alias.as_ref()),
+        Statement::Module {
+            name, statements, ..
+        } => visitor.visit_module(name, statements),
+        Statement::Comment { token, .. } => visitor.visit_comment(token),
+    }
+}
+
+/// Dispatches an expression to the visitor method for its variant.
+///
+/// # Arguments
+/// * `visitor` - The visitor to dispatch to.
+/// * `expression` - The expression to visit.
+pub fn walk_expression<V: Visitor>(visitor: &mut V, expression: &Expression) {
+    match expression {
+        Expression::Binary {
+            left,
+            operator,
+            right,
+            ..
+        } => visitor.visit_binary(left, operator, right),
+        Expression::Grouping(expression, _) => visitor.visit_grouping(expression),
+        Expression::Literal { value, .. } => visitor.visit_literal(value),
+        Expression::Unary {
+            operator, right, ..
+        } => visitor.visit_unary(operator, right),
+        Expression::Variable(name, _) => visitor.visit_variable(name),
+        Expression::Assign { name, value, .. } => visitor.visit_assign(name, value),
+        Expression::Call {
+            callee,
+            parenthesis,
+            arguments,
+            ..
+        } => visitor.visit_call(callee, parenthesis, arguments),
+        Expression::Coalesce {
+            left,
+            operator,
+            right,
+            ..
+        } => visitor.visit_coalesce(left, operator, right),
+        Expression::Qualified { module, name, .. } => visitor.visit_qualified(module, name),
+        Expression::Tuple(elements, _) => visitor.visit_tuple(elements),
+        Expression::TypeOf(expression, _) => visitor.visit_typeof(expression),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::parse;
+
+    /// Counts the binary expressions in a tree.
+    #[derive(Default)]
+    struct BinaryCounter {
+        count: usize,
+    }
+
+    impl Visitor for BinaryCounter {
+        fn visit_binary(&mut self, left: &Expression, _operator: &Token, right: &Expression) {
+            self.count += 1;
+
+            self.visit_expression(left);
+            self.visit_expression(right);
+        }
+    }
+
+    #[test]
+    fn test_count_binary_expressions() {
+        let statements = parse(
+            r#"
+            let a = 1 + 2 * 3;
+            fn add(x: i32, y: i32) {
+                return x + y;
+            }
+            if (a > 2) {
+                print add(a, (a - 1) / 2);
+            }
+            "#,
+        )
+        .expect("Parsing should succeed.");
+
+        let mut counter = BinaryCounter::default();
+        for statement in &statements {
+            counter.visit_statement(statement);
+        }
+
+        assert_eq!(counter.count, 6);
+    }
+}
@@ -1,24 +1,70 @@
-use crate::util::files;
+use std::path::PathBuf;
 
-pub mod lang;
-mod util;
+use cpl::lang;
+use cpl::lang::semantic_analyzer::AnalyzerOptions;
+use cpl::util::files;
+use cpl::util::options::Options;
 
 fn main() {
+    // Deeply nested programs need more stack than the main thread may have.
+    lang::with_stack(run);
+}
+
+fn run() {
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(1);
+        }
+    };
+
     let mut cpl = lang::Cpl::new();
+    cpl.analyzer_options = AnalyzerOptions {
+        disabled_warnings: options.allowed_warnings.into_iter().collect(),
+        warnings_as_errors: options.warnings_as_errors,
+    };
+    cpl.print_symbols = options.symbols;
+    cpl.emit_assembly = options.assembly;
+    cpl.target = options.target;
+    cpl.peephole = options.peephole;
+    cpl.output = options.output.map(PathBuf::from);
+    cpl.quiet = options.quiet || options.check;
+    cpl.check = options.check;
+    cpl.require_entry_point = options.require_entry_point;
+    cpl.max_errors = options.max_errors;
 
-    // Get the file passed as the first argument.
-    let is_source_code_provided = std::env::args().nth(1).is_some();
-    if !is_source_code_provided {
+    if options.files.is_empty() {
         println!("No file specified, starting REPL...");
         cpl.run_repl();
 
         return;
     }
 
-    let file_path = std::env::args().nth(1).unwrap();
-    if !files::is_valid_file(&file_path) {
-        return;
+    // Each file is run as a program of its own, one after the other,
+    // so a failing file does not stop the rest unless asked to.
+    let mut had_error = false;
+    for file_path in &options.files {
+        if files::is_valid_file(file_path) {
+            if let Some(dump) = options.dump {
+                cpl.dump_file(file_path, dump);
+            } else if options.format {
+                cpl.format_file(file_path, options.write);
+            } else {
+                // The errors have already been reported.
+                let _ = cpl.run_file(file_path);
+            }
+        } else {
+            cpl.had_error = true;
+        }
+
+        had_error |= cpl.had_error;
+        if had_error && options.fail_fast {
+            break;
+        }
     }
 
-    cpl.run_file(&file_path);
+    if had_error {
+        std::process::exit(1);
+    }
 }
@@ -24,7 +24,10 @@ pub fn is_valid_file(file: &str) -> bool {
         return false;
     }
 
-    if path.extension().unwrap() != FILE_EXTENSION {
+    if path
+        .extension()
+        .is_none_or(|extension| extension != FILE_EXTENSION)
+    {
         eprintln!("File '{}' must have '.{}' extension!", file, FILE_EXTENSION);
 
         return false;
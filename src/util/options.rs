@@ -0,0 +1,357 @@
+use crate::lang::dump::Dump;
+use crate::lang::target::Target;
+use crate::lang::MAX_ERRORS;
+
+/// The command line options.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The source files to run, one after the other as programs of their own.
+    /// Without any, the REPL is started.
+    pub files: Vec<String>,
+    /// Stop at the first file that fails, instead of running the rest.
+    pub fail_fast: bool,
+    /// Only report the errors and warnings in the source files, without running them or generating code.
+    /// Implies `--quiet`.
+    pub check: bool,
+    /// Format the source files instead of running them.
+    pub format: bool,
+    /// Write the formatted source back to the file instead of printing it.
+    pub write: bool,
+    /// Fail the program when there are warnings, as if they were errors.
+    pub warnings_as_errors: bool,
+    /// The names of the kinds of warnings not to report, given with `--allow <name>`.
+    pub allowed_warnings: Vec<String>,
+    /// Print the symbol table as JSON after analysis, instead of running the source files.
+    pub symbols: bool,
+    /// Generate x86_64 assembly for the program instead of running it.
+    pub assembly: bool,
+    /// What code to generate, given with `--emit <target>`, which implies `--asm`.
+    pub target: Target,
+    /// Remove obvious junk from the generated x86_64 assembly.
+    pub peephole: bool,
+    /// Print the tokens, syntax tree or bytecode instead of running the files, given with `--emit <dump>`.
+    pub dump: Option<Dump>,
+    /// The file to write the generated code to, given with `-o <path>`, which implies `--asm`.
+    pub output: Option<String>,
+    /// Print only the program's output and diagnostics, not the tokens, syntax tree and timings.
+    pub quiet: bool,
+    /// Require the program to declare a `main` function without parameters.
+    pub require_entry_point: bool,
+    /// The most errors to report, given with `--max-errors <count>`.
+    pub max_errors: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            files: Vec::new(),
+            fail_fast: false,
+            check: false,
+            format: false,
+            write: false,
+            warnings_as_errors: false,
+            allowed_warnings: Vec::new(),
+            symbols: false,
+            assembly: false,
+            target: Target::Assembly,
+            peephole: false,
+            dump: None,
+            output: None,
+            quiet: false,
+            require_entry_point: false,
+            max_errors: MAX_ERRORS,
+        }
+    }
+}
+
+impl Options {
+    /// Parses the command line options.
+    ///
+    /// # Arguments
+    /// * `arguments` - The arguments, without the program name.
+    ///
+    /// # Returns
+    /// The parsed options, or a message describing the invalid argument.
+    pub fn parse<I>(arguments: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut options = Self::default();
+
+        let mut arguments = arguments.into_iter();
+        while let Some(argument) = arguments.next() {
+            match argument.as_str() {
+                "--check" | "--no-run" => options.check = true,
+                "--fmt" => options.format = true,
+                "--write" => options.write = true,
+                "--warnings-as-errors" => options.warnings_as_errors = true,
+                "--symbols" => options.symbols = true,
+                "--quiet" => options.quiet = true,
+                "--fail-fast" => options.fail_fast = true,
+                "--asm" => options.assembly = true,
+                "--peephole" => options.peephole = true,
+                "-o" => match arguments.next() {
+                    Some(path) => {
+                        options.assembly = true;
+                        options.output = Some(path);
+                    }
+                    None => return Err("'-o' must be followed by a path!".to_string()),
+                },
+                "--require-entry-point" => options.require_entry_point = true,
+                "--max-errors" => match arguments.next().and_then(|count| count.parse().ok()) {
+                    Some(count) if count > 0 => options.max_errors = count,
+                    _ => {
+                        return Err(
+                            "'--max-errors' must be followed by a positive number!".to_string()
+                        )
+                    }
+                },
+                "--allow" => match arguments.next() {
+                    Some(name) => options.allowed_warnings.push(name),
+                    None => return Err("'--allow' must be followed by a warning name!".to_string()),
+                },
+                "--emit" => match arguments.next() {
+                    Some(name) => options.emit(&name)?,
+                    None => return Err("'--emit' must be followed by what to emit!".to_string()),
+                },
+                _ if argument.starts_with("--emit=") => {
+                    options.emit(&argument["--emit=".len()..])?
+                }
+                _ if argument.starts_with('-') => {
+                    return Err(format!("Unknown option '{}'!", argument));
+                }
+                _ => options.files.push(argument),
+            }
+        }
+
+        if options.write && !options.format {
+            return Err("'--write' can only be used with '--fmt'!".to_string());
+        }
+        if options.peephole && (!options.assembly || options.target != Target::Assembly) {
+            return Err(
+                "'--peephole' can only be used when generating x86_64 assembly!".to_string(),
+            );
+        }
+        if options.output.is_some() && options.format {
+            return Err("'-o' cannot be used with '--fmt'!".to_string());
+        }
+        if let Some(dump) = options.dump {
+            if options.format || options.assembly {
+                return Err(format!(
+                    "'--emit {}' cannot be used with '--fmt', '--asm' or '-o'!",
+                    dump.name()
+                ));
+            }
+        }
+        if options.check && (options.format || options.assembly || options.dump.is_some()) {
+            return Err(
+                "'--check' cannot be used with '--fmt', '--asm', '--emit' or '-o'!".to_string(),
+            );
+        }
+        if options.output.is_some() && options.files.len() > 1 {
+            return Err("'-o' cannot be used with more than one file!".to_string());
+        }
+
+        Ok(options)
+    }
+
+    /// Chooses what to emit, given with `--emit <name>` or `--emit=<name>`,
+    /// either a target to generate code for or a dump of the program.
+    fn emit(&mut self, name: &str) -> Result<(), String> {
+        if let Some(target) = Target::from_name(name) {
+            self.assembly = true;
+            self.target = target;
+        } else if let Some(dump) = Dump::from_name(name) {
+            self.dump = Some(dump);
+        } else {
+            return Err(format!(
+                "Unknown output '{}', expected one of: {}!",
+                name,
+                Target::NAMES
+                    .iter()
+                    .chain(&Dump::NAMES)
+                    .copied()
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Result<Options, String> {
+        Options::parse(arguments.iter().map(|argument| argument.to_string()))
+    }
+
+    #[test]
+    fn test_parse_options() {
+        assert_eq!(parse(&[]), Ok(Options::default()));
+        assert_eq!(
+            parse(&["--fmt", "main.cpl", "--write"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                fail_fast: false,
+                check: false,
+                format: true,
+                write: true,
+                warnings_as_errors: false,
+                allowed_warnings: Vec::new(),
+                symbols: false,
+                assembly: false,
+                target: Target::Assembly,
+                peephole: false,
+                dump: None,
+                output: None,
+                quiet: false,
+                require_entry_point: false,
+                max_errors: MAX_ERRORS,
+            })
+        );
+        assert_eq!(
+            parse(&["--warnings-as-errors", "main.cpl"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                warnings_as_errors: true,
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&["main.cpl", "--symbols"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                symbols: true,
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&["main.cpl", "--asm"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                assembly: true,
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&["main.cpl", "-o", "build/main.s"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                assembly: true,
+                output: Some("build/main.s".to_string()),
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&["--emit=llvm-ir", "main.cpl"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                assembly: true,
+                target: Target::LlvmIr,
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&["--peephole", "-o", "main.s", "main.cpl"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                assembly: true,
+                peephole: true,
+                output: Some("main.s".to_string()),
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&["--emit", "ast-json", "main.cpl"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                dump: Some(Dump::Ast),
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&["--emit=bytecode", "main.cpl"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                dump: Some(Dump::Bytecode),
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&["a.cpl", "--fail-fast", "b.cpl"]),
+            Ok(Options {
+                files: vec!["a.cpl".to_string(), "b.cpl".to_string()],
+                fail_fast: true,
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&["--check", "main.cpl"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                check: true,
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&["--no-run", "main.cpl"]),
+            parse(&["--check", "main.cpl"])
+        );
+        assert_eq!(
+            parse(&["--quiet", "main.cpl"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                quiet: true,
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&["--require-entry-point", "main.cpl"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                require_entry_point: true,
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&[
+                "--allow",
+                "unused-variable",
+                "main.cpl",
+                "--allow",
+                "shadowing"
+            ]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                allowed_warnings: vec!["unused-variable".to_string(), "shadowing".to_string()],
+                ..Options::default()
+            })
+        );
+        assert_eq!(
+            parse(&["--max-errors", "5", "main.cpl"]),
+            Ok(Options {
+                files: vec!["main.cpl".to_string()],
+                max_errors: 5,
+                ..Options::default()
+            })
+        );
+        assert!(parse(&["main.cpl", "--max-errors", "0"]).is_err());
+        assert!(parse(&["main.cpl", "--max-errors", "many"]).is_err());
+        assert!(parse(&["main.cpl", "--allow"]).is_err());
+        assert!(parse(&["main.cpl", "-o"]).is_err());
+        assert!(parse(&["main.cpl", "--emit=wasm"]).is_err());
+        assert!(parse(&["main.cpl", "--emit"]).is_err());
+        assert!(parse(&["--fmt", "main.cpl", "--emit", "tokens-json"]).is_err());
+        assert!(parse(&["--fmt", "main.cpl", "-o", "main.s"]).is_err());
+        assert!(parse(&["--write", "main.cpl"]).is_err());
+        assert!(parse(&["--peephole", "main.cpl"]).is_err());
+        assert!(parse(&["--peephole", "--emit=llvm-ir", "main.cpl"]).is_err());
+        assert!(parse(&["--check", "main.cpl", "--emit=llvm-ir"]).is_err());
+        assert!(parse(&["--check", "main.cpl", "--emit=ast-json"]).is_err());
+        assert!(parse(&["--unknown"]).is_err());
+        assert!(parse(&["a.cpl", "b.cpl", "-o", "main.s"]).is_err());
+    }
+}
@@ -17,6 +17,8 @@ impl Timer {
     ///
     /// # Examples
     /// ```
+    /// use cpl::util::timer::Timer;
+    ///
     /// let mut timer = Timer::new();
     ///
     /// let (time, result) = timer.time(|| {
@@ -48,6 +50,8 @@ impl Timer {
     ///
     /// # Examples
     /// ```
+    /// use cpl::util::timer::Timer;
+    ///
     /// let mut timer = Timer::new();
     ///
     /// let (time, result) = timer.time(|| {
@@ -72,11 +76,18 @@ impl Timer {
     ///
     /// // Prints the time it took for both functions to run in nanoseconds.
     /// println!("Total Time: {} nanoseconds.", timer.total_time());
+    /// ```
     pub fn total_time(&self) -> u128 {
         self.times.iter().sum()
     }
 }
 
+impl Default for Timer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Format a time in nanoseconds into a human-readable string.
 ///
 /// # Arguments
@@ -84,6 +95,8 @@ impl Timer {
 ///
 /// # Examples
 /// ```
+/// use cpl::util::timer::format_time;
+///
 /// let nanos = 1_000_000_000;
 ///
 /// println!("{} nanoseconds is {}.", nanos, format_time(nanos));
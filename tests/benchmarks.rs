@@ -0,0 +1,74 @@
+//! Timings of the compiler's stages on large generated programs.
+//! These are ignored by default, run them with `cargo test --release --test benchmarks -- --ignored --nocapture`.
+
+use cpl::lang::parser::Parser;
+use cpl::lang::semantic_analyzer::Analyzer;
+use cpl::lang::{lex, parse};
+use cpl::util::timer::{format_time, Timer};
+
+/// The number of times each stage is run, the fastest run is reported.
+const RUNS: usize = 10;
+
+/// Generates a program with the given number of lines, using every kind of statement.
+fn generate_program(lines: usize) -> String {
+    (0..lines / 5)
+        .map(|i| {
+            format!(
+                "fn add_{i}(a: i32, b: i32) -> int {{\n\
+                 \x20   let sum = a + b * 2 - (a / 3);\n\
+                 \x20   if (sum > 10 && a != b) {{ return sum; }} else {{ return none ?? a; }}\n\
+                 }}\n\
+                 let value_{i} = add_{i}({i}, {i} + 1);\n"
+            )
+        })
+        .collect()
+}
+
+/// Runs the function several times, printing the fastest time.
+fn bench<R>(name: &str, mut function: impl FnMut() -> R) {
+    let mut timer = Timer::new();
+    let fastest = (0..RUNS)
+        .map(|_| timer.time(&mut function).0)
+        .min()
+        .unwrap_or_default();
+
+    println!("{}: {}", name, format_time(fastest));
+}
+
+#[test]
+#[ignore]
+fn bench_parse_generated_file() {
+    let source = generate_program(10_000);
+    let tokens = lex(&source).expect("Scanning should succeed.");
+
+    bench("Scanning 10k lines", || lex(&source));
+    bench("Parsing 10k lines", || Parser::new(&tokens).parse());
+}
+
+#[test]
+#[ignore]
+fn bench_repeated_identifiers() {
+    let source = format!(
+        "let count = 0;\n{}",
+        "count = count + count * count - count;\n".repeat(10_000)
+    );
+    let statements = parse(&source).expect("Parsing should succeed.");
+
+    bench("Scanning 50k identifiers", || lex(&source));
+    bench("Analyzing 50k identifiers", || {
+        Analyzer::new().analyze(&statements)
+    });
+}
+
+#[test]
+#[ignore]
+fn bench_large_scope() {
+    let source = (1..5_000)
+        .map(|i| format!("let v{} = v{} + 1;\n", i, i - 1))
+        .collect::<String>();
+    let statements = parse(&format!("let v0 = 0;\n{}", source)).expect("Parsing should succeed.");
+
+    bench("Analyzing 5k variables in one scope", || {
+        Analyzer::new().analyze(&statements)
+    });
+}
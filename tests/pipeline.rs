@@ -0,0 +1,930 @@
+use std::path::PathBuf;
+
+use cpl::lang::errors::Severity;
+use cpl::lang::parser::Statement;
+use cpl::lang::target::Target;
+use cpl::lang::Cpl;
+
+fn run(source: &str) -> bool {
+    let mut cpl = Cpl::new();
+    cpl.quiet = true;
+
+    cpl.run(source.to_string()).is_ok()
+}
+
+#[test]
+fn test_valid_program_runs() {
+    assert!(run(
+        "fn add(a: i32, b: i32) -> int { return a + b; }\nlet c = add(1, 2);\nprint c;"
+    ));
+}
+
+#[test]
+fn test_undefined_variable_fails_analysis() {
+    assert!(!run("let a = 1;\nprint b;"));
+}
+
+#[test]
+fn test_syntax_error_fails() {
+    assert!(!run("let a = ;"));
+}
+
+#[test]
+fn test_run_returns_result() {
+    let mut cpl = Cpl::new();
+    cpl.quiet = true;
+
+    match cpl.run("let a = 1;\nprint a;".to_string()) {
+        Ok(statements) => {
+            assert_eq!(statements.len(), 2);
+            assert!(matches!(statements[0], Statement::Variable { .. }));
+        }
+        Err(errors) => panic!("Running should succeed, but failed with {:?}.", errors),
+    }
+
+    match cpl.run("let a = 1;\nprint b;".to_string()) {
+        Ok(_) => panic!("Running should fail."),
+        Err(errors) => {
+            assert_eq!(errors.len(), 1);
+            assert_eq!((errors[0].line, errors[0].column), (2, 7));
+            assert_eq!(errors[0].message, "Variable 'b' is not defined");
+        }
+    }
+}
+
+#[test]
+fn test_unreadable_file_fails() {
+    let directory = std::env::temp_dir();
+    let missing = directory.join("cpl_missing_file.cpl");
+
+    for path in [missing.as_path(), directory.as_path()] {
+        let path = path.to_str().expect("The path should be valid Unicode.");
+
+        let mut cpl = Cpl::new();
+        cpl.quiet = true;
+        let errors = cpl.run_file(path).unwrap_err();
+
+        assert!(cpl.had_error);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0]
+            .message
+            .starts_with(&format!("Cannot read '{}': ", path)));
+    }
+}
+
+#[test]
+fn test_assembly_output() {
+    let directory = std::env::temp_dir().join("cpl_assembly_output");
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).expect("Creating the directory should succeed.");
+
+    let source = "let a = 6; print a * 7;";
+    let file = directory.join("main.cpl");
+    std::fs::write(&file, source).expect("Writing the file should succeed.");
+    let file = file.to_str().expect("The path should be valid Unicode.");
+
+    let mut cpl = Cpl::new();
+    cpl.quiet = true;
+    cpl.emit_assembly = true;
+    let assembly = cpl
+        .run_timed(source.to_string())
+        .assembly
+        .expect("Assembly should be generated.");
+
+    // Without an output file, the assembly is written next to the source file.
+    cpl.run_file(file).expect("Running should succeed.");
+    assert_eq!(
+        std::fs::read_to_string(directory.join("main.s")).ok(),
+        Some(assembly.clone())
+    );
+
+    // The directories of the output file are created if needed.
+    let output = directory.join("build").join("nested").join("out.s");
+    cpl.output = Some(output.clone());
+    cpl.run_file(file).expect("Running should succeed.");
+    assert_eq!(std::fs::read_to_string(&output).ok(), Some(assembly));
+
+    // Failing to write the file is reported like any other error.
+    cpl.output = Some(directory.clone());
+    let errors = cpl.run_file(file).unwrap_err();
+    assert!(cpl.had_error);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0]
+        .message
+        .starts_with(&format!("Cannot write '{}': ", directory.display())));
+}
+
+#[test]
+fn test_generating_code_does_not_run() {
+    for target in [Target::Assembly, Target::LlvmIr] {
+        let mut cpl = Cpl::new();
+        cpl.emit_assembly = true;
+        cpl.target = target;
+
+        // The program is not interpreted, so its runtime error is left to the generated code.
+        let report = cpl.run_timed("let a = 0;\nprint 1 / a;".to_string());
+        assert!(report.errors.is_empty());
+        assert!(report.optimize_time.is_some());
+        assert!(report.interpret_time.is_none());
+        assert!(report.assembly.is_some());
+    }
+}
+
+#[test]
+fn test_unsupported_constructs_are_reported() {
+    let source = "let t = (1, 2);\nlet (a, b) = t;\nprint a + b;\nassert(true);";
+
+    for target in [Target::Assembly, Target::LlvmIr] {
+        let mut cpl = Cpl::new();
+        cpl.quiet = true;
+        cpl.emit_assembly = true;
+        cpl.target = target;
+
+        // The program is valid, but its code cannot be generated.
+        let errors = cpl.run(source.to_string()).unwrap_err();
+        assert!(cpl.had_error);
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| (error.line, error.column, error.message.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (1, 9, "Tuples are not supported by this backend yet"),
+                (
+                    2,
+                    1,
+                    "Destructuring declarations are not supported by this backend yet"
+                ),
+                (4, 1, "Assertions are not supported by this backend yet"),
+            ]
+        );
+    }
+}
+
+#[test]
+fn test_exit_code() {
+    let directory = std::env::temp_dir().join("cpl_exit_code");
+    std::fs::create_dir_all(&directory).expect("Creating the directory should succeed.");
+
+    for (name, source, code) in [
+        ("valid.cpl", "let a = 1;\nprint a;", 0),
+        ("broken.cpl", "let a = ;", 1),
+        ("undefined.cpl", "print b;", 1),
+    ] {
+        let file = directory.join(name);
+        std::fs::write(&file, source).expect("Writing the file should succeed.");
+
+        let status = std::process::Command::new(env!("CARGO_BIN_EXE_cpl"))
+            .arg("--quiet")
+            .arg(&file)
+            .output()
+            .expect("Running the binary should succeed.")
+            .status;
+
+        assert_eq!(status.code(), Some(code), "'{}' exits with {}.", name, code);
+    }
+}
+
+#[test]
+fn test_multiple_files() {
+    let directory = std::env::temp_dir().join("cpl_multiple_files");
+    std::fs::create_dir_all(&directory).expect("Creating the directory should succeed.");
+
+    let broken = directory.join("broken.cpl");
+    let valid = directory.join("valid.cpl");
+    std::fs::write(&broken, "let a = ;").expect("Writing the file should succeed.");
+    std::fs::write(&valid, "print 42;").expect("Writing the file should succeed.");
+
+    let run = |arguments: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_cpl"))
+            .arg("--quiet")
+            .args(arguments)
+            .arg(&broken)
+            .arg(&valid)
+            .output()
+            .expect("Running the binary should succeed.")
+    };
+
+    // The broken file is reported with its name, and the valid one still runs.
+    let output = run(&[]);
+    let stderr = String::from_utf8(output.stderr).expect("The output should be valid UTF-8.");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"42\n");
+    assert!(stderr.starts_with(&format!("{}:1:", broken.display())));
+
+    let output = run(&["--fail-fast"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn test_check() {
+    let directory = std::env::temp_dir().join("cpl_check");
+    std::fs::create_dir_all(&directory).expect("Creating the directory should succeed.");
+
+    let broken = directory.join("broken.cpl");
+    let valid = directory.join("valid.cpl");
+    std::fs::write(&broken, "let a = 1;\nprint b;").expect("Writing the file should succeed.");
+    std::fs::write(&valid, "print 42;").expect("Writing the file should succeed.");
+
+    let check = |file: &PathBuf| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_cpl"))
+            .arg("--check")
+            .arg(file)
+            .output()
+            .expect("Running the binary should succeed.")
+    };
+
+    // The analyzer's errors are reported, but nothing is run.
+    let output = check(&broken);
+    let stderr = String::from_utf8(output.stderr).expect("The output should be valid UTF-8.");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(stderr.starts_with(&format!("{}:2:7:", broken.display())));
+
+    let output = check(&valid);
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+
+    // The program is not interpreted, so runtime errors go unnoticed.
+    let mut cpl = Cpl::new();
+    cpl.check = true;
+    let report = cpl.run_timed("let a = 0;\nprint 1 / a;".to_string());
+    assert!(report.errors.is_empty());
+    assert!(report.analyze_time.is_some());
+    assert!(report.interpret_time.is_none());
+}
+
+#[test]
+fn test_errors_in_included_files() {
+    let directory = std::env::temp_dir().join("cpl_errors_in_included_files");
+    std::fs::create_dir_all(&directory).expect("Creating the directory should succeed.");
+
+    let main = directory.join("main.cpl");
+    std::fs::write(&main, "include \"lib.cpl\";\nprint 1;\nprint f();")
+        .expect("Writing the file should succeed.");
+    std::fs::write(
+        directory.join("lib.cpl"),
+        "fn f() -> int {\n    let zero = 0;\n    return 1 / zero;\n}",
+    )
+    .expect("Writing the file should succeed.");
+
+    // Runtime errors in an included file are reported at their position in it.
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_cpl"))
+        .arg("--quiet")
+        .arg(&main)
+        .output()
+        .expect("Running the binary should succeed.");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(output.stdout, b"1\n");
+    assert_eq!(
+        String::from_utf8(output.stderr).expect("The output should be valid UTF-8."),
+        format!(
+            "{}:3:14: In 'lib.cpl': Cannot divide an integer by zero\n",
+            main.display()
+        )
+    );
+}
+
+#[test]
+fn test_deep_nesting_is_reported() {
+    let directory = std::env::temp_dir().join("cpl_deep_nesting");
+    std::fs::create_dir_all(&directory).expect("Creating the directory should succeed.");
+
+    let chained = directory.join("chained.cpl");
+    let nested = directory.join("nested.cpl");
+    let within = directory.join("within.cpl");
+    std::fs::write(&chained, format!("print 1{};", " + 1".repeat(20000)))
+        .expect("Writing the file should succeed.");
+    std::fs::write(
+        &nested,
+        format!("{}print 1;{}", "{".repeat(20000), "}".repeat(20000)),
+    )
+    .expect("Writing the file should succeed.");
+    std::fs::write(&within, format!("print 1{};", " + 1".repeat(900)))
+        .expect("Writing the file should succeed.");
+
+    let run = |file: &PathBuf, flags: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_cpl"))
+            .args(flags)
+            .arg(file)
+            .output()
+            .expect("Running the binary should succeed.")
+    };
+
+    // The nesting is reported instead of overflowing the stack.
+    for file in [&chained, &nested] {
+        for flag in ["--check", "--fmt"] {
+            let output = run(file, &[flag]);
+            let stderr =
+                String::from_utf8(output.stderr).expect("The output should be valid UTF-8.");
+
+            assert_eq!(output.status.code(), Some(1), "{}", stderr);
+            assert!(stderr.contains("is nested too deeply"), "{}", stderr);
+        }
+    }
+
+    let output = run(&within, &["--quiet"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "901\n");
+}
+
+#[test]
+fn test_deep_recursion_is_reported() {
+    let directory = std::env::temp_dir().join("cpl_deep_recursion");
+    std::fs::create_dir_all(&directory).expect("Creating the directory should succeed.");
+
+    let programs = [
+        "fn f(n: int) -> int { return f(n + 1); }\nprint f(0);".to_string(),
+        format!(
+            "fn f(n: int) -> int {{ {}return f(n + 1);{} return 0; }}\nprint f(0);",
+            "if (true) { ".repeat(100),
+            " }".repeat(100)
+        ),
+    ];
+
+    // The stack the command line runs programs with is enough to report the overflow instead of crashing.
+    for (index, program) in programs.iter().enumerate() {
+        let file = directory.join(format!("recursion_{}.cpl", index));
+        std::fs::write(&file, program).expect("Writing the file should succeed.");
+
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_cpl"))
+            .arg("--quiet")
+            .arg(&file)
+            .output()
+            .expect("Running the binary should succeed.");
+        let stderr = String::from_utf8(output.stderr).expect("The output should be valid UTF-8.");
+
+        assert_eq!(output.status.code(), Some(1), "{}", stderr);
+        assert!(
+            stderr.contains("Stack overflow while calling 'f'"),
+            "{}",
+            stderr
+        );
+    }
+}
+
+#[test]
+fn test_emit_json() {
+    let file = std::env::temp_dir().join("cpl_emit_json.cpl");
+    std::fs::write(&file, "print 1 + 2;").expect("Writing the file should succeed.");
+
+    let emit = |name: &str| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_cpl"))
+            .arg("--emit")
+            .arg(name)
+            .arg(&file)
+            .output()
+            .expect("Running the binary should succeed.");
+        assert!(output.status.success());
+
+        serde_json::from_slice::<serde_json::Value>(&output.stdout)
+            .expect("The output should be valid JSON.")
+    };
+
+    // The program is not run, so only the JSON is printed.
+    let ast = emit("ast-json");
+    let binary = &ast[0]["Print"][0]["Binary"];
+    assert_eq!(binary["operator"]["lexeme"], "+");
+    assert_eq!(binary["operator"]["column"], 9);
+    assert_eq!(binary["left"]["Literal"]["value"]["Int"], 1);
+    assert_eq!(binary["right"]["Literal"]["token"]["lexeme"], "2");
+
+    let tokens = emit("tokens-json");
+    let types = tokens
+        .as_array()
+        .expect("The tokens should be an array.")
+        .iter()
+        .map(|token| {
+            token["token_type"]
+                .as_str()
+                .expect("Every token has a type.")
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        [
+            "Print",
+            "Number",
+            "Plus",
+            "Number",
+            "Semicolon",
+            "EndOfFile"
+        ]
+    );
+}
+
+#[test]
+fn test_symbols_json() {
+    let file = std::env::temp_dir().join("cpl_symbols_json.cpl");
+    std::fs::write(&file, "let a = 1;\nprint a;").expect("Writing the file should succeed.");
+
+    for flags in [&["--symbols"][..], &["--symbols", "--quiet"]] {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_cpl"))
+            .args(flags)
+            .arg(&file)
+            .output()
+            .expect("Running the binary should succeed.");
+        assert!(output.status.success());
+
+        // The program is not run, so only the JSON is printed.
+        let symbols = serde_json::from_slice::<serde_json::Value>(&output.stdout)
+            .expect("The output should be valid JSON.");
+        let symbol = &symbols["scopes"][0]["symbols"][0];
+        assert_eq!(symbol["name"], "a");
+        assert_eq!(symbol["usages"][0][0], 17);
+    }
+}
+
+#[test]
+fn test_max_errors() {
+    for source in ["let a = ;\n".repeat(100), "print a;\n".repeat(100)] {
+        let mut cpl = Cpl::new();
+        let report = cpl.run_timed(source);
+
+        assert_eq!(report.errors.len(), 20);
+        assert_eq!(report.omitted_errors, 80);
+        assert_eq!(report.errors[19].line, 20);
+    }
+
+    let mut cpl = Cpl::new();
+    cpl.max_errors = 1;
+    let report = cpl.run_timed("print a;".to_string());
+
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.omitted_errors, 0);
+}
+
+#[test]
+fn test_max_errors_summary() {
+    let file = std::env::temp_dir().join("cpl_max_errors.cpl");
+    std::fs::write(&file, "print a;\n".repeat(100)).expect("Writing the file should succeed.");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_cpl"))
+        .arg("--quiet")
+        .arg(&file)
+        .output()
+        .expect("Running the binary should succeed.");
+    let stderr = String::from_utf8(output.stderr).expect("The output should be valid UTF-8.");
+    let lines = stderr.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 21);
+    assert!(lines[..20]
+        .iter()
+        .all(|line| line.ends_with("Variable 'a' is not defined")));
+    assert_eq!(lines[20], "… and 80 more errors");
+}
+
+#[test]
+fn test_error_does_not_carry_over() {
+    let mut cpl = Cpl::new();
+    cpl.quiet = true;
+
+    assert!(cpl.run("let a = ;".to_string()).is_err());
+    assert!(cpl.had_error);
+
+    assert!(cpl.run("let a = 1;".to_string()).is_ok());
+    assert!(!cpl.had_error);
+}
+
+#[test]
+fn test_run_timed_reports_durations() {
+    let source = r#"
+        fn fib(n: i32) -> int {
+            if (n < 2) return n;
+            return fib(n - 1) + fib(n - 2);
+        }
+
+        let result = fib(15);
+    "#;
+
+    let mut cpl = Cpl::new();
+    let report = cpl.run_timed(source.to_string());
+
+    assert!(report.errors.is_empty());
+    assert!(report.syntax_tree.is_some());
+    for time in [
+        report.tokenize_time,
+        report.parse_time,
+        report.analyze_time,
+        report.interpret_time,
+    ] {
+        assert!(time.is_some_and(|time| time > 0));
+    }
+    assert!(report.total_time() >= report.interpret_time.unwrap());
+}
+
+#[test]
+fn test_run_timed_stops_at_failing_stage() {
+    let mut cpl = Cpl::new();
+    let report = cpl.run_timed("let a = ;".to_string());
+
+    assert!(cpl.had_error);
+    assert_eq!(report.errors.len(), 1);
+    assert!(report.syntax_tree.is_none());
+    assert!(report.parse_time.is_some());
+    assert!(report.analyze_time.is_none());
+}
+
+#[test]
+fn test_division_by_constant_zero_fails_before_running() {
+    let mut cpl = Cpl::new();
+    let report = cpl.run_timed("const ZERO = 0;\nprint 2;\nprint 1 / ZERO;".to_string());
+
+    assert!(cpl.had_error);
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!((report.errors[0].line, report.errors[0].column), (3, 9));
+    assert!(report.optimize_time.is_some());
+    assert!(report.interpret_time.is_none());
+
+    assert!(run("let zero = 0;\nif (zero != 0) { print 1 / zero; }"));
+
+    let report = cpl.run_timed("const ZERO = 0;\nprint 7 % ZERO;".to_string());
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!((report.errors[0].line, report.errors[0].column), (2, 9));
+    assert!(report.interpret_time.is_none());
+}
+
+#[test]
+fn test_warnings_do_not_fail() {
+    // The variable 'b' is never used.
+    let source = "fn main() { let a = 1; let b = 2; print a; }\nmain();";
+
+    let mut cpl = Cpl::new();
+    let report = cpl.run_timed(source.to_string());
+
+    assert!(!cpl.had_error);
+    assert!(report.errors.is_empty());
+    assert_eq!(report.warnings.len(), 1);
+    assert!(report.interpret_time.is_some());
+
+    let mut cpl = Cpl::new();
+    cpl.analyzer_options.warnings_as_errors = true;
+    let report = cpl.run_timed(source.to_string());
+
+    assert!(cpl.had_error);
+    assert!(report.warnings.is_empty());
+    assert_eq!(report.errors.len(), 1);
+    assert_eq!(report.errors[0].severity, Severity::Error);
+    assert!(report.interpret_time.is_none());
+}
+
+/// Generates the assembly for a program, then assembles and links it with `gcc`.
+///
+/// # Arguments
+/// * `peephole` - Whether to remove the junk from the assembly first.
+///
+/// # Returns
+/// The path of the executable.
+fn compile(source: &str, name: &str, peephole: bool) -> PathBuf {
+    let directory = std::env::temp_dir().join("cpl_generated_assembly");
+    std::fs::create_dir_all(&directory).expect("Creating the directory should succeed.");
+
+    let mut cpl = Cpl::new();
+    cpl.emit_assembly = true;
+    cpl.peephole = peephole;
+    let report = cpl.run_timed(source.to_string());
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+    let assembly = directory.join(format!("{}.s", name));
+    let executable = directory.join(name);
+    std::fs::write(
+        &assembly,
+        report.assembly.expect("Assembly should be generated."),
+    )
+    .expect("Writing the assembly should succeed.");
+
+    let compiled = std::process::Command::new("gcc")
+        .arg(&assembly)
+        .arg("-o")
+        .arg(&executable)
+        .status()
+        .expect("Running gcc should succeed.");
+    assert!(compiled.success(), "'{}' should assemble.", source);
+
+    executable
+}
+
+/// Needs `gcc` to assemble and link the generated code, run it with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn test_generated_assembly_runs() {
+    // The exit status is the value of the last expression, modulo 256.
+    for (i, (source, status)) in [
+        ("42;", 42),
+        ("1 + 2 * 3;", 7),
+        ("(10 - 4) / 3;", 2),
+        ("-(5 - 8) * 4;", 12),
+        ("7 / -2 + 10;", 7),
+        ("1;\n{ 100 + 100; }", 200),
+        ("-1;", 255),
+        ("let a = 2; let b; { let a = a * 3; b = a; } a + b;", 8),
+        ("let x = 5; x = x * x; x - 1;", 24),
+        ("let a = 5; let r = 0; if (a < 3) r = 1; else if (a == 5) { if (a != 0) r = 2; else r = 3; } else r = 4; r;", 2),
+        ("let b = 2 >= 3; if (b) 7; else 9;", 9),
+        ("let sum = 0; for (let i = 0; i < 10; i = i + 1) sum = sum + i; sum;", 45),
+        ("let i = 0; let odd = 0; while (true) { i = i + 1; if (i > 9) break; if (i / 2 * 2 == i) continue; odd = odd + i; } odd;", 25),
+        ("let n = 0; for (let i = 0; i < 4; i = i + 1) { let j = 0; loop { if (j == i) break; n = n + 1; j = j + 1; } } n;", 6),
+        ("fn fib(n: int) -> int { if (n < 2) return n; return fib(n - 1) + fib(n - 2); } fib(10);", 55),
+        ("fn is_even(n: int) -> bool { if (n == 0) return true; return is_odd(n - 1); } fn is_odd(n: int) -> bool { if (n == 0) return false; return is_even(n - 1); } is_even(7);", 0),
+        ("fn sum(a: int, b: int, c: int, d: int, e: int, f: int) -> int { return a + b * 2 + c * 3 + d * 4 + e * 5 + f * 6; } sum(1, 1, 1, sum(0, 0, 0, 0, 0, 1), 1, 1);", 41),
+        // Statements after the last expression statement do not change the exit status.
+        ("3; let a = 9; if (a > 1) print a;", 3),
+        ("5; for (let i = 0; i < 3; i = i + 1) {} let s = \"x\";", 5),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let executable = compile(source, &format!("program_{}", i), false);
+
+        let run = std::process::Command::new(&executable)
+            .status()
+            .expect("Running the program should succeed.");
+        assert_eq!(
+            run.code(),
+            Some(status),
+            "'{}' exits with its value.",
+            source
+        );
+    }
+}
+
+/// Needs `gcc` to assemble and link the generated code, run it with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn test_generated_print() {
+    let source = "let n = 6; n; print n * 7; print \"Hello, World!\"; print (n - 1) * 2 + 1; print 2.5; print 1e16; print n > 5; print false;";
+    let executable = compile(source, "print", false);
+
+    let output = std::process::Command::new(&executable)
+        .output()
+        .expect("Running the program should succeed.");
+    // Printing keeps the value of the last expression as the exit status.
+    assert_eq!(output.status.code(), Some(6));
+    let printed = String::from_utf8(output.stdout).expect("The output should be UTF-8.");
+    assert_eq!(printed, "42\nHello, World!\n11\n2.5\n1e16\ntrue\nfalse\n");
+    assert_eq!(printed, interpret(source, "print"));
+}
+
+#[test]
+#[ignore]
+fn test_generated_assembly_matches_interpreter() {
+    for (i, source) in [
+        // Operands are evaluated from left to right, so calls print in the same order.
+        "fn sq(x: int) -> int { print x; return x * x; } print sq(1) + sq(2);",
+        "fn sq(x: int) -> int { print x; return x * x; } print sq(3) - sq(2) * sq(1);",
+        "fn id(x: int) -> int { print x; return x; } print id(1) < id(2); print id(4) == id(3);",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let name = format!("assembly_matches_{}", i);
+        let executable = compile(source, &name, false);
+        let output = std::process::Command::new(&executable)
+            .output()
+            .expect("Running the program should succeed.");
+
+        assert_eq!(
+            String::from_utf8(output.stdout).expect("The output should be UTF-8."),
+            interpret(source, &name),
+            "'{}' prints the same when compiled.",
+            source
+        );
+    }
+}
+
+#[test]
+fn test_peephole() {
+    let source = "let a = 2; print -(-a); if (a > 1) { a = 3; } else { a = 4; } a;";
+
+    let mut cpl = Cpl::new();
+    cpl.emit_assembly = true;
+    let assembly = cpl
+        .run_timed(source.to_string())
+        .assembly
+        .expect("Assembly should be generated.");
+
+    cpl.peephole = true;
+    let report = cpl.run_timed(source.to_string());
+    let optimized = report.assembly.expect("Assembly should be generated.");
+    let removed = report
+        .removed_instructions
+        .expect("The peephole optimizer should run.");
+
+    assert!(removed > 0);
+    assert!(!optimized.contains("neg %rax\n    neg %rax"));
+    assert!(optimized.lines().count() < assembly.lines().count());
+
+    // Only x86_64 assembly is optimized.
+    cpl.target = Target::LlvmIr;
+    assert_eq!(cpl.run_timed(source.to_string()).removed_instructions, None);
+}
+
+/// Needs `gcc` to assemble and link the generated code, run it with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn test_peephole_keeps_behavior() {
+    for (i, source) in [
+        "print -(-5); -(-(-7));",
+        "let a = 2; if (a > 1) { a = a * 1 + 0; } else { a = 4; } a;",
+        "let n = 0; while (n < 5) { n = n + 1; if (n == 3) continue; print n; } n;",
+        "let s = 0; for (let i = 0; i < 10; i = i + 1) { if (i > 6) break; s = s + -(-i); } print s; s;",
+        "fn fib(n: int) -> int { if (n < 2) return n; return fib(n - 1) + fib(n - 2); } print fib(12); fib(10);",
+        "let x = 1; { let y = x; loop { y = y * 2; if (y > 100) break; } print y; } x;",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let run = |peephole: bool| {
+            let name = format!("peephole_{}_{}", i, peephole);
+
+            std::process::Command::new(compile(source, &name, peephole))
+                .output()
+                .expect("Running the program should succeed.")
+        };
+
+        let (before, after) = (run(false), run(true));
+        assert_eq!(before.status.code(), after.status.code(), "'{}'", source);
+        assert_eq!(before.stdout, after.stdout, "'{}'", source);
+    }
+}
+
+/// Generates the LLVM IR for a program, then runs it with `lli`.
+/// LLVM 14 only reads the opaque pointers the IR uses when asked to.
+///
+/// # Returns
+/// The output of the program.
+fn interpret_ir(source: &str, name: &str) -> std::process::Output {
+    let directory = std::env::temp_dir().join("cpl_generated_ir");
+    std::fs::create_dir_all(&directory).expect("Creating the directory should succeed.");
+
+    let mut cpl = Cpl::new();
+    cpl.emit_assembly = true;
+    cpl.target = Target::LlvmIr;
+    let report = cpl.run_timed(source.to_string());
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+    let ir = directory.join(format!("{}.ll", name));
+    std::fs::write(&ir, report.assembly.expect("IR should be generated."))
+        .expect("Writing the IR should succeed.");
+
+    let version = std::process::Command::new("lli")
+        .arg("--version")
+        .output()
+        .expect("Running lli should succeed.");
+    let mut lli = std::process::Command::new("lli");
+    if String::from_utf8_lossy(&version.stdout).contains("version 14.") {
+        lli.arg("-opaque-pointers");
+    }
+
+    lli.arg(&ir)
+        .output()
+        .expect("Running the program should succeed.")
+}
+
+/// Needs `lli` from LLVM 14 or later to run the generated IR, run it with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn test_generated_ir_runs() {
+    for (i, (source, status)) in [
+        ("42;", 42),
+        ("(10 - 4) / 3 + -(5 - 8) * 4;", 14),
+        ("let a = 2; let b; { let a = a * 3; b = a; } a + b;", 8),
+        ("let a = 5; let r = 0; if (a < 3) r = 1; else if (a == 5) r = 2; else r = 4; r;", 2),
+        ("let sum = 0; for (let i = 0; i < 10; i = i + 1) sum = sum + i; sum;", 45),
+        ("let i = 0; let odd = 0; while (true) { i = i + 1; if (i > 9) break; if (i / 2 * 2 == i) continue; odd = odd + i; } odd;", 25),
+        ("let n = 0; loop { n = n + 1; if (n == 3) break; } n;", 3),
+        ("fn fib(n: int) -> int { if (n < 2) return n; return fib(n - 1) + fib(n - 2); } fib(10);", 55),
+        ("fn is_even(n: int) -> bool { if (n == 0) return true; return is_odd(n - 1); } fn is_odd(n: int) -> bool { if (n == 0) return false; return is_even(n - 1); } is_even(7);", 0),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let output = interpret_ir(source, &format!("program_{}", i));
+
+        assert_eq!(
+            output.status.code(),
+            Some(status),
+            "'{}' exits with its value.",
+            source
+        );
+    }
+}
+
+/// Needs `lli` from LLVM 14 or later to run the generated IR, run it with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn test_generated_ir_print() {
+    let output = interpret_ir(
+        "fn half(x: float) -> float { return x / 2; } let n = 6; n; print n * 7; print \"Hello, \\\"World\\\"!\"; print half(5); print n > 7;",
+        "print",
+    );
+
+    // Printing keeps the value of the last expression as the exit status.
+    assert_eq!(output.status.code(), Some(6));
+    assert_eq!(
+        String::from_utf8(output.stdout).expect("The output should be UTF-8."),
+        "42\nHello, \"World\"!\n2.5\nfalse\n"
+    );
+}
+
+/// Needs `lli` from LLVM 14 or later to run the generated IR, run it with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn test_generated_ir_division_errors() {
+    for (i, (source, printed, error)) in [
+        (
+            "let z = 0; print 1; print 5 / z; print 2;",
+            "1\n",
+            "1:29: Cannot divide an integer by zero\n",
+        ),
+        (
+            "let z = 0; 5 % z;",
+            "",
+            "1:14: Cannot divide an integer by zero\n",
+        ),
+        (
+            "let m = -9223372036854775807 - 1; let n = -1; print m / n;",
+            "",
+            "1:55: Integer overflow in '/'\n",
+        ),
+        (
+            "let m = -9223372036854775807 - 1; let n = -1; print m % n;",
+            "",
+            "1:55: Integer overflow in '%'\n",
+        ),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let output = interpret_ir(source, &format!("division_{}", i));
+
+        // The program stops where the interpreter does, with the error it reports.
+        assert_eq!(output.status.code(), Some(1), "'{}' fails.", source);
+        assert_eq!(
+            String::from_utf8(output.stdout).expect("The output should be UTF-8."),
+            printed,
+            "'{}' prints until the error.",
+            source
+        );
+        assert_eq!(
+            String::from_utf8(output.stderr).expect("The errors should be UTF-8."),
+            error,
+            "'{}' reports the interpreter's error.",
+            source
+        );
+    }
+}
+
+/// Runs a program with the interpreter.
+///
+/// # Returns
+/// What the program printed.
+fn interpret(source: &str, name: &str) -> String {
+    let directory = std::env::temp_dir().join("cpl_interpreted");
+    std::fs::create_dir_all(&directory).expect("Creating the directory should succeed.");
+
+    let file = directory.join(format!("{}.cpl", name));
+    std::fs::write(&file, source).expect("Writing the file should succeed.");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_cpl"))
+        .arg("--quiet")
+        .arg(&file)
+        .output()
+        .expect("Running the binary should succeed.");
+    assert!(output.status.success(), "'{}' runs.", source);
+
+    String::from_utf8(output.stdout).expect("The output should be UTF-8.")
+}
+
+/// Needs `lli` from LLVM 14 or later to run the generated IR, run it with `cargo test -- --ignored`.
+#[test]
+#[ignore]
+fn test_generated_ir_matches_interpreter() {
+    for (i, source) in [
+        "let x: float = 5; print x / 2;",
+        "fn half(x: float) -> float { return x / 2; } print half(5);",
+        "let x = 1.5; x = 2; print x;",
+        "print 0.1 + 0.2;",
+        // Very large and small floats are printed in scientific notation.
+        "let x = 1.0; print x * 1e15; print x * 1e16; print -x * 1.5e-7; print x * 0.0001; print x / 3;",
+        "let x = 1.0; let zero = 0.0; print x * zero; print -x * zero; print x / zero; print -x / zero; print zero / zero;",
+        "let x = 123456789.125; print x; print x * 1e300 * 1e10; print -x * 1e-300;",
+        // Floats halfway between the two shortest ways to write them are rounded away from zero.
+        "let x = 954816149798655.25; print x; print -x; print x + 0.5; print 2.0 * 0.5e-323;",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let name = format!("matches_{}", i);
+        let output = interpret_ir(source, &name);
+
+        assert_eq!(
+            String::from_utf8(output.stdout).expect("The output should be UTF-8."),
+            interpret(source, &name),
+            "'{}' prints the same when compiled.",
+            source
+        );
+    }
+}